@@ -0,0 +1,226 @@
+//! `#[derive(CLayout)]` for the `data_models` crate.
+//!
+//! The derive implements `data_models::clayout::CLayout` for a struct with
+//! named fields, placing them as a C compiler for the data model given by
+//! `#[c_layout(LP64)]` would, or for the native model without the
+//! attribute. It is re-exported by `data_models` with its `derive` feature.
+//!
+//! Fields of the `std::os::raw` and `libc` types (`c_int`, `c_long`, ...)
+//! take their size from the model, as do pointers, references, function
+//! pointers, `usize` and `isize`. Fixed-width integers and floats keep
+//! their size, arrays multiply their elements, and any other type must
+//! implement `CLayout` itself.
+
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+
+/// derive_c_layout implements `CLayout` for a struct.
+#[proc_macro_derive(CLayout, attributes(c_layout))]
+pub fn derive_c_layout(input: TokenStream) -> TokenStream {
+    let src = match expand(input) {
+        Ok(src) => src,
+        Err(msg) => format!("compile_error!({:?});", msg),
+    };
+    src.parse().unwrap()
+}
+
+/// expand returns the source of the `CLayout` implementation.
+fn expand(input: TokenStream) -> Result<String, String> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let mut model = "NATIVE".to_string();
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            TokenTree::Punct(p) if p.as_char() == '#' => {
+                if let Some(TokenTree::Group(group)) = tokens.get(i + 1) {
+                    if let Some(name) = c_layout_model(group.stream())? {
+                        model = name;
+                    }
+                }
+                i += 2;
+            }
+            TokenTree::Ident(ident) if ident.to_string() == "struct" => break,
+            TokenTree::Ident(ident) if ident.to_string() == "enum" => {
+                return Err("CLayout can only be derived for structs".to_string())
+            }
+            TokenTree::Ident(ident) if ident.to_string() == "union" => {
+                return Err("CLayout can only be derived for structs".to_string())
+            }
+            _ => i += 1,
+        }
+    }
+    let name = match tokens.get(i + 1) {
+        Some(TokenTree::Ident(name)) => name.to_string(),
+        _ => return Err("CLayout can only be derived for structs".to_string()),
+    };
+    let body = match tokens.get(i + 2) {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => {
+            group.stream().into_iter().collect()
+        }
+        Some(TokenTree::Punct(p)) if p.as_char() == ';' => Vec::new(),
+        Some(TokenTree::Punct(p)) if p.as_char() == '<' => {
+            return Err("CLayout cannot be derived for generic structs".to_string())
+        }
+        _ => return Err("CLayout needs a struct with named fields".to_string()),
+    };
+    let model = format!("::data_models::DataModel::{}", model);
+    let mut fields = Vec::new();
+    for field in split(&body, ',') {
+        let (field_name, ty) = field_parts(&field)?;
+        fields.push(field_expr(&field_name, ty, &model));
+    }
+    Ok(format!(
+        "impl ::data_models::clayout::CLayout for {name} {{
+            const LAYOUT: ::data_models::clayout::ConstLayout = {{
+                const FIELDS: [::data_models::clayout::ConstField; {n}] =
+                    ::data_models::clayout::ConstField::place([{fields}]);
+                ::data_models::clayout::ConstLayout::new({name:?}, &FIELDS)
+            }};
+        }}",
+        name = name,
+        n = fields.len(),
+        fields = fields.join(", "),
+    ))
+}
+
+/// c_layout_model returns the model named by a `c_layout(MODEL)`
+/// attribute, or `None` for other attributes.
+fn c_layout_model(attr: TokenStream) -> Result<Option<String>, String> {
+    let tokens: Vec<TokenTree> = attr.into_iter().collect();
+    match tokens.as_slice() {
+        [TokenTree::Ident(name), TokenTree::Group(args)] if name.to_string() == "c_layout" => {
+            let args: Vec<TokenTree> = args.stream().into_iter().collect();
+            match args.as_slice() {
+                [TokenTree::Ident(model)] => Ok(Some(model.to_string())),
+                _ => Err("expected #[c_layout(MODEL)], e.g. #[c_layout(LP64)]".to_string()),
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+/// split splits tokens at the punctuation `sep` outside angle brackets.
+fn split(tokens: &[TokenTree], sep: char) -> Vec<Vec<TokenTree>> {
+    let mut parts = vec![Vec::new()];
+    let mut depth = 0;
+    let mut arrow = false;
+    for token in tokens {
+        if let TokenTree::Punct(p) = token {
+            match p.as_char() {
+                '<' => depth += 1,
+                '>' if !arrow && depth > 0 => depth -= 1,
+                c if c == sep && depth == 0 => {
+                    parts.push(Vec::new());
+                    continue;
+                }
+                _ => {}
+            }
+            arrow = p.as_char() == '-' && p.spacing() == Spacing::Joint;
+        } else {
+            arrow = false;
+        }
+        parts.last_mut().unwrap().push(token.clone());
+    }
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// field_parts returns the name and type of a field declaration, skipping
+/// its attributes and visibility.
+fn field_parts(field: &[TokenTree]) -> Result<(String, &[TokenTree]), String> {
+    let mut i = 0;
+    loop {
+        match field.get(i) {
+            Some(TokenTree::Punct(p)) if p.as_char() == '#' => i += 2,
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "pub" => {
+                i += 1;
+                if let Some(TokenTree::Group(group)) = field.get(i) {
+                    if group.delimiter() == Delimiter::Parenthesis {
+                        i += 1;
+                    }
+                }
+            }
+            _ => break,
+        }
+    }
+    match (field.get(i), field.get(i + 1)) {
+        (Some(TokenTree::Ident(name)), Some(TokenTree::Punct(colon))) if colon.as_char() == ':' => {
+            Ok((name.to_string(), &field[i + 2..]))
+        }
+        _ => Err("CLayout needs a struct with named fields".to_string()),
+    }
+}
+
+/// field_expr returns a `ConstField` expression for a field of type `ty`.
+fn field_expr(name: &str, ty: &[TokenTree], model: &str) -> String {
+    let c_type = |ty: &str| {
+        format!(
+            "::data_models::clayout::ConstField::of({:?}, ::data_models::CType::{}, {})",
+            name, ty, model
+        )
+    };
+    let fixed = |size: usize| {
+        format!(
+            "::data_models::clayout::ConstField::new({:?}, {}, {})",
+            name, size, size
+        )
+    };
+    match ty.first() {
+        Some(TokenTree::Punct(p)) if p.as_char() == '*' || p.as_char() == '&' => {
+            return c_type("Pointer")
+        }
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+            let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+            let parts = split(&inner, ';');
+            if let [element, len] = parts.as_slice() {
+                let len: TokenStream = len.iter().cloned().collect();
+                return format!(
+                    "::data_models::clayout::ConstField::array({:?}, {}, {})",
+                    name,
+                    field_expr(name, element, model),
+                    len
+                );
+            }
+        }
+        _ => {}
+    }
+    let idents: Vec<String> = ty
+        .iter()
+        .take_while(|token| !matches!(token, TokenTree::Punct(p) if p.as_char() == '<'))
+        .filter_map(|token| match token {
+            TokenTree::Ident(ident) => Some(ident.to_string()),
+            _ => None,
+        })
+        .collect();
+    let last = idents.last().map(String::as_str).unwrap_or("");
+    match last {
+        _ if idents.iter().any(|ident| ident == "fn") => c_type("Pointer"),
+        "c_char" => c_type("Char"),
+        "c_schar" => c_type("SChar"),
+        "c_uchar" => c_type("UChar"),
+        "c_short" => c_type("Short"),
+        "c_ushort" => c_type("UShort"),
+        "c_int" => c_type("Int"),
+        "c_uint" => c_type("UInt"),
+        "c_long" => c_type("Long"),
+        "c_ulong" => c_type("ULong"),
+        "c_longlong" => c_type("LongLong"),
+        "c_ulonglong" => c_type("ULongLong"),
+        "c_float" => c_type("Float"),
+        "c_double" => c_type("Double"),
+        "bool" => c_type("Bool"),
+        "usize" | "isize" | "NonNull" | "Option" => c_type("Pointer"),
+        "i8" | "u8" => fixed(1),
+        "i16" | "u16" => fixed(2),
+        "i32" | "u32" | "f32" => fixed(4),
+        "i64" | "u64" | "f64" => fixed(8),
+        _ => {
+            let ty: TokenStream = ty.iter().cloned().collect();
+            format!(
+                "<{} as ::data_models::clayout::CLayout>::LAYOUT.as_field({:?})",
+                ty, name
+            )
+        }
+    }
+}
@@ -0,0 +1,426 @@
+//! Addresses on a target, at the width of its pointers.
+//!
+//! Emulators and memory forensics tools handle addresses of a target whose
+//! pointers may be narrower than the host's. [`TargetAddress`] keeps an
+//! address at the pointer width of a data model, so arithmetic wraps
+//! around the target's address space and addresses print with as many
+//! hexadecimal digits as the target has.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! let top = TargetAddress::new(DataModel::IP16, 0xfffe).unwrap();
+//! assert_eq!(top + 4, TargetAddress::new(DataModel::IP16, 2).unwrap());
+//! assert_eq!(top.to_string(), "0xfffe");
+//! assert_eq!(TargetAddress::new(DataModel::LP64, 0x1000).unwrap().to_string(), "0x0000000000001000");
+//! ```
+//!
+//! [`TargetPtr`] adds the type pointed to, so stepping through an array in
+//! target memory scales by the size of the type on the target rather than
+//! on the host.
+
+use crate::layout::Abi;
+use crate::{CType, CTypeInfo, DataModel};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Sub};
+
+/// TargetAddress is an address of a target with the data model `model`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TargetAddress {
+    model: DataModel,
+    value: u64,
+}
+
+impl TargetAddress {
+    /// new returns the address `value`, keeping the bits that fit the
+    /// pointers of `model`, or `None` if the model has no pointer size.
+    pub fn new(model: DataModel, value: u64) -> Option<TargetAddress> {
+        match model.has(CType::Pointer) {
+            true => Some(TargetAddress { model, value }.wrap(value)),
+            false => None,
+        }
+    }
+
+    /// value returns the address as a number.
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    /// model returns the data model of the target.
+    pub fn model(self) -> DataModel {
+        self.model
+    }
+
+    /// bits returns the width of addresses of the target.
+    pub fn bits(self) -> usize {
+        8 * self.model.size(CType::Pointer)
+    }
+
+    /// wrap returns an address of the same target at `value`, wrapped
+    /// around the address space.
+    fn wrap(self, value: u64) -> TargetAddress {
+        let mask = u64::MAX >> (64 - self.bits());
+        TargetAddress {
+            value: value & mask,
+            ..self
+        }
+    }
+
+    /// wrapping_add returns the address `n` bytes higher, wrapping around
+    /// the address space.
+    pub fn wrapping_add(self, n: u64) -> TargetAddress {
+        self.wrap(self.value.wrapping_add(n))
+    }
+
+    /// wrapping_sub returns the address `n` bytes lower, wrapping around
+    /// the address space.
+    pub fn wrapping_sub(self, n: u64) -> TargetAddress {
+        self.wrap(self.value.wrapping_sub(n))
+    }
+
+    /// wrapping_offset returns the address `n` bytes away, which may be
+    /// negative.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let address = TargetAddress::new(DataModel::ILP32, 0x10).unwrap();
+    /// assert_eq!(address.wrapping_offset(-0x20).value(), 0xffff_fff0);
+    /// ```
+    pub fn wrapping_offset(self, n: i64) -> TargetAddress {
+        self.wrap(self.value.wrapping_add(n as u64))
+    }
+
+    /// offset_from returns how many bytes the address is above `origin`,
+    /// as a `ptrdiff_t` of the target, which is negative if it is below.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let a = TargetAddress::new(DataModel::IP16, 0x10).unwrap();
+    /// let b = TargetAddress::new(DataModel::IP16, 0xfff0).unwrap();
+    /// assert_eq!(a.offset_from(b), 0x20);
+    /// ```
+    pub fn offset_from(self, origin: TargetAddress) -> i64 {
+        let shift = 64 - self.bits();
+        (self.value.wrapping_sub(origin.value) << shift) as i64 >> shift
+    }
+
+    /// is_aligned reports whether the address is a multiple of `align`,
+    /// or returns `None` if `align` is not a power of two.
+    pub fn is_aligned(self, align: u64) -> Option<bool> {
+        align
+            .is_power_of_two()
+            .then(|| self.value & (align - 1) == 0)
+    }
+
+    /// align_down returns the highest address at or below this one that
+    /// is a multiple of `align`, or `None` if `align` is not a power of
+    /// two.
+    pub fn align_down(self, align: u64) -> Option<TargetAddress> {
+        align
+            .is_power_of_two()
+            .then(|| self.wrap(self.value & !(align - 1)))
+    }
+
+    /// align_up returns the lowest address at or above this one that is a
+    /// multiple of `align`, or `None` if `align` is not a power of two. It
+    /// wraps to the bottom of the address space past the top.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let address = TargetAddress::new(DataModel::ILP32, 0x1001).unwrap();
+    /// assert_eq!(address.align_up(16).unwrap().value(), 0x1010);
+    /// assert_eq!(address.align_down(16).unwrap().value(), 0x1000);
+    /// assert_eq!(address.align_up(0), None);
+    /// ```
+    pub fn align_up(self, align: u64) -> Option<TargetAddress> {
+        self.wrapping_add(align.wrapping_sub(1)).align_down(align)
+    }
+}
+
+/// Addresses of the same target compare by value; addresses of different
+/// targets do not compare.
+impl PartialOrd for TargetAddress {
+    fn partial_cmp(&self, other: &TargetAddress) -> Option<Ordering> {
+        match self.model == other.model {
+            true => Some(self.value.cmp(&other.value)),
+            false => None,
+        }
+    }
+}
+
+impl Add<u64> for TargetAddress {
+    type Output = TargetAddress;
+
+    fn add(self, n: u64) -> TargetAddress {
+        self.wrapping_add(n)
+    }
+}
+
+impl Sub<u64> for TargetAddress {
+    type Output = TargetAddress;
+
+    fn sub(self, n: u64) -> TargetAddress {
+        self.wrapping_sub(n)
+    }
+}
+
+/// An address prints in hexadecimal with a digit for every four bits of
+/// the target's addresses.
+impl fmt::Display for TargetAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:01$x}", self.value, self.bits() / 4)
+    }
+}
+
+impl fmt::LowerHex for TargetAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.value, f)
+    }
+}
+
+impl fmt::UpperHex for TargetAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.value, f)
+    }
+}
+
+/// PointerError describes why a pointer cannot be moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PointerError {
+    /// The pointer is not aligned for the type it points to.
+    Misaligned {
+        /// The address of the pointer.
+        address: TargetAddress,
+        /// The alignment of the type.
+        align: usize,
+    },
+    /// The offset leaves the address space of the target.
+    OutOfRange,
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PointerError::Misaligned { address, align } => {
+                write!(f, "{} is not aligned to {} bytes", address, align)
+            }
+            PointerError::OutOfRange => write!(f, "offset leaves the address space"),
+        }
+    }
+}
+
+impl Error for PointerError {}
+
+/// TargetPtr is a pointer into the memory of a target to a value of the C
+/// type `T`, one of the marker types like [`Long`](crate::Long).
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let ptr = TargetPtr::<Long>::new(DataModel::LLP64, 0x1000).unwrap();
+/// assert_eq!(ptr.offset(3).unwrap().address().value(), 0x100c);
+/// let ptr = TargetPtr::<Long>::new(DataModel::LP64, 0x1000).unwrap();
+/// assert_eq!(ptr.offset(3).unwrap().address().value(), 0x1018);
+/// ```
+pub struct TargetPtr<T> {
+    address: TargetAddress,
+    abi: Abi,
+    pointee: PhantomData<fn() -> T>,
+}
+
+impl<T: CTypeInfo> TargetPtr<T> {
+    /// new returns a pointer to `address` on a target with the layout
+    /// rules `abi`, or `None` if its model lacks pointers or `T`.
+    pub fn new(abi: impl Into<Abi>, address: u64) -> Option<TargetPtr<T>> {
+        let abi = abi.into();
+        if !abi.model().has(T::C_TYPE) {
+            return None;
+        }
+        Some(TargetPtr {
+            address: TargetAddress::new(abi.model(), address)?,
+            abi,
+            pointee: PhantomData,
+        })
+    }
+
+    /// address returns the address the pointer holds.
+    pub fn address(&self) -> TargetAddress {
+        self.address
+    }
+
+    /// is_null reports whether the pointer holds address 0.
+    pub fn is_null(&self) -> bool {
+        self.address.value() == 0
+    }
+
+    /// element_size returns the size of `T` on the target, the stride of
+    /// [`TargetPtr::offset`].
+    pub fn element_size(&self) -> usize {
+        self.abi.size(T::C_TYPE)
+    }
+
+    /// is_aligned reports whether the address is aligned for `T` as the
+    /// target aligns it inside records. It is not for an ABI whose
+    /// alignment cap is not a power of two.
+    pub fn is_aligned(&self) -> bool {
+        self.address.is_aligned(self.abi.align(T::C_TYPE) as u64) == Some(true)
+    }
+
+    /// offset returns the pointer `n` elements away, as `ptr + n` does in
+    /// C. It fails if the pointer is misaligned or the result leaves the
+    /// address space.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::address::PointerError;
+    /// let ptr = TargetPtr::<Int>::new(DataModel::IP16, 0xfff0).unwrap();
+    /// assert_eq!(ptr.offset(8).unwrap_err(), PointerError::OutOfRange);
+    /// assert!(TargetPtr::<Int>::new(DataModel::IP16, 0x11).unwrap().offset(1).is_err());
+    /// ```
+    pub fn offset(&self, n: i64) -> Result<TargetPtr<T>, PointerError> {
+        if !self.is_aligned() {
+            return Err(PointerError::Misaligned {
+                address: self.address,
+                align: self.abi.align(T::C_TYPE),
+            });
+        }
+        let bytes = (n as i128) * self.element_size() as i128;
+        let target = self.address.value() as i128 + bytes;
+        let top = 1i128 << self.address.bits();
+        match (0..top).contains(&target) {
+            true => Ok(self.wrapping_offset(n)),
+            false => Err(PointerError::OutOfRange),
+        }
+    }
+
+    /// wrapping_offset returns the pointer `n` elements away, wrapping
+    /// around the address space without checks.
+    pub fn wrapping_offset(&self, n: i64) -> TargetPtr<T> {
+        let bytes = n.wrapping_mul(self.element_size() as i64);
+        TargetPtr {
+            address: self.address.wrapping_offset(bytes),
+            ..*self
+        }
+    }
+
+    /// offset_from returns how many elements the pointer is above
+    /// `origin`, as subtracting pointers does in C, or `None` if they are
+    /// not a whole number of elements apart.
+    pub fn offset_from(&self, origin: &TargetPtr<T>) -> Option<i64> {
+        let bytes = self.address.offset_from(origin.address);
+        let size = self.element_size() as i64;
+        (bytes % size == 0).then_some(bytes / size)
+    }
+
+    /// cast returns a pointer to the same address as a pointer to `U`, or
+    /// `None` if the model lacks `U`.
+    pub fn cast<U: CTypeInfo>(&self) -> Option<TargetPtr<U>> {
+        TargetPtr::new(self.abi, self.address.value())
+    }
+}
+
+impl<T> Clone for TargetPtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TargetPtr<T> {}
+
+impl<T> PartialEq for TargetPtr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address && self.abi == other.abi
+    }
+}
+
+impl<T> Eq for TargetPtr<T> {}
+
+impl<T: CTypeInfo> fmt::Debug for TargetPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({} *) {}", T::C_NAME, self.address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(model: DataModel, value: u64) -> TargetAddress {
+        TargetAddress::new(model, value).unwrap()
+    }
+
+    #[test]
+    fn test_addresses() {
+        assert_eq!(TargetAddress::new(DataModel::Unknown, 0), None);
+        assert_eq!(address(DataModel::LP32, 0x1_2345_6789).value(), 0x2345_6789);
+        assert_eq!(
+            address(DataModel::LP64, u64::MAX) + 1,
+            address(DataModel::LP64, 0)
+        );
+        assert_eq!(
+            address(DataModel::IP16, 0) - 1,
+            address(DataModel::IP16, 0xffff)
+        );
+        assert_eq!(
+            address(DataModel::ILP32, 0xffff_fff9).align_up(8),
+            Some(address(DataModel::ILP32, 0))
+        );
+        assert_eq!(address(DataModel::LLP64, 0x40).is_aligned(64), Some(true));
+        assert_eq!(address(DataModel::LLP64, 0x48).is_aligned(16), Some(false));
+        for align in [0, 3, 24] {
+            let address = address(DataModel::LP64, 0x48);
+            assert_eq!(address.is_aligned(align), None);
+            assert_eq!(address.align_down(align), None);
+            assert_eq!(address.align_up(align), None);
+        }
+        let (low, high) = (address(DataModel::LP64, 8), address(DataModel::LP64, 24));
+        assert_eq!(low.offset_from(high), -16);
+        assert!(low < high);
+        assert_eq!(low.partial_cmp(&address(DataModel::LLP64, 8)), None);
+        assert_eq!(address(DataModel::ILP32, 0xbeef).to_string(), "0x0000beef");
+        assert_eq!(
+            format!("{:#X}", address(DataModel::ILP32, 0xbeef)),
+            "0xBEEF"
+        );
+        assert_eq!(address(DataModel::IP16L32, 1).bits(), 16);
+    }
+
+    #[test]
+    fn test_pointers() {
+        use crate::{Char, Int, LongLong, Short};
+        let i386 = Abi::new(DataModel::ILP32).with_max_align(4);
+        let ptr = TargetPtr::<LongLong>::new(i386, 0x1004).unwrap();
+        assert!(ptr.is_aligned());
+        assert!(!TargetPtr::<LongLong>::new(DataModel::ILP32, 0x1004)
+            .unwrap()
+            .is_aligned());
+        let end = ptr.offset(4).unwrap();
+        assert_eq!(end.address().value(), 0x1024);
+        assert_eq!(end.offset_from(&ptr), Some(4));
+        assert_eq!(ptr.offset(-1).unwrap().address().value(), 0x0ffc);
+        let bytes = ptr.cast::<Char>().unwrap().offset(3).unwrap();
+        assert_eq!(bytes.cast::<LongLong>().unwrap().offset_from(&ptr), None);
+        assert_eq!(
+            bytes.cast::<Short>().unwrap().offset(1),
+            Err(PointerError::Misaligned {
+                address: address(DataModel::ILP32, 0x1007),
+                align: 2
+            })
+        );
+        assert!(TargetPtr::<LongLong>::new(DataModel::IP16, 0).is_none());
+        assert!(TargetPtr::<Int>::new(DataModel::IP16, 0).unwrap().is_null());
+        let wrapped = TargetPtr::<Int>::new(DataModel::IP16, 0)
+            .unwrap()
+            .wrapping_offset(-1);
+        assert_eq!(format!("{:?}", wrapped), "(int *) 0xfffe");
+    }
+}
@@ -0,0 +1,224 @@
+//! Random data models, targets and records for property tests and fuzzing.
+//!
+//! The types implement [`Arbitrary`], so cargo-fuzz and libFuzzer harnesses
+//! can take them as input and proptest can build them with
+//! `proptest-arbitrary-interop`. The same bytes always give the same value
+//! and every byte string gives a valid one. Targets only use the data
+//! models this crate knows, and records only use types every model has,
+//! with bit-fields narrow enough and flexible arrays last, so they always
+//! lay out.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::layout::Record;
+//! use ::arbitrary::{Arbitrary, Unstructured};
+//! let mut u = Unstructured::new(&[3, 1, 0x40, 0xff, 0x12]);
+//! let target = TargetModel::arbitrary(&mut u).unwrap();
+//! let record = Record::arbitrary(&mut u).unwrap();
+//! assert!(record.layout(target.abi).is_ok());
+//! ```
+
+use crate::layout::{Abi, BitFieldRules, Field, FieldType, Record, RecordKind};
+use crate::{
+    CType, DataModel, Endianness, LongDouble, Platform, SignedRepresentation, TargetModel,
+};
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+impl<'a> Arbitrary<'a> for DataModel {
+    /// Only the known models, never [`DataModel::Unknown`].
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<DataModel> {
+        u.choose(DataModel::ALL).copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for CType {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<CType> {
+        u.choose(CType::ALL).copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for Endianness {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Endianness> {
+        use Endianness::*;
+        u.choose(&[Little, Big, PdpMiddle]).copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for SignedRepresentation {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<SignedRepresentation> {
+        use SignedRepresentation::*;
+        u.choose(&[TwosComplement, OnesComplement, SignMagnitude])
+            .copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for LongDouble {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<LongDouble> {
+        use LongDouble::*;
+        u.choose(&[Binary64, X87Extended, Binary128, DoubleDouble])
+            .copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for Platform {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Platform> {
+        u.choose(Platform::ALL).copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for BitFieldRules {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<BitFieldRules> {
+        u.choose(&[BitFieldRules::Itanium, BitFieldRules::Msvc])
+            .copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for Abi {
+    /// Packing and alignment caps are powers of two.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Abi> {
+        let mut abi = Abi::new(DataModel::arbitrary(u)?);
+        abi = abi.with_bitfields(BitFieldRules::arbitrary(u)?);
+        if u.arbitrary()? {
+            abi = abi.with_max_align(*u.choose(&[1, 2, 4, 8])?);
+        }
+        if u.arbitrary()? {
+            abi = abi.with_pack(*u.choose(&[1, 2, 4, 8, 16])?);
+        }
+        if u.arbitrary()? {
+            abi = abi.with_short_enums();
+        }
+        if u.arbitrary()? {
+            abi = abi.with_power_align();
+        }
+        Ok(abi)
+    }
+}
+
+impl<'a> Arbitrary<'a> for TargetModel {
+    /// Null pointers have all bits zero or, now and then, all bits one.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<TargetModel> {
+        let abi = Abi::arbitrary(u)?;
+        let bits = 8 * abi.size(CType::Pointer);
+        let null_pointer = match u.choose_index(8)? {
+            7 => u128::MAX >> (128 - bits),
+            _ => 0,
+        };
+        Ok(TargetModel::new(abi.model())
+            .with_abi(abi)
+            .with_endianness(Endianness::arbitrary(u)?)
+            .with_char_signed(u.arbitrary()?)
+            .with_signed_representation(SignedRepresentation::arbitrary(u)?)
+            .with_null_pointer(null_pointer)
+            .with_long_double(LongDouble::arbitrary(u)?))
+    }
+}
+
+/// FIELD_TYPES are the scalar types every data model has, with `int` as
+/// wide as 16 bits.
+const FIELD_TYPES: [CType; 9] = [
+    CType::Bool,
+    CType::Char,
+    CType::SChar,
+    CType::UChar,
+    CType::Int,
+    CType::UInt,
+    CType::Pointer,
+    CType::Float,
+    CType::Double,
+];
+
+/// arbitrary_record builds a record nested `depth` records deep.
+fn arbitrary_record(u: &mut Unstructured<'_>, depth: usize) -> Result<Record> {
+    let name = format!("r{}_{}", depth, u8::arbitrary(u)?);
+    let mut record = match u.arbitrary()? {
+        true => Record::union(&name),
+        false => Record::new(&name),
+    };
+    for i in 0..u.choose_index(6)? {
+        let name = format!("f{}", i);
+        let field = match u.choose_index(8)? {
+            4 => {
+                let ty = *u.choose(&[CType::Int, CType::UInt, CType::UChar])?;
+                let width = 8 * DataModel::IP16.size(ty);
+                Field::bitfield(&name, ty, 1 + u.choose_index(width)?)
+            }
+            5 => Field::new(
+                &name,
+                FieldType::array(*u.choose(&FIELD_TYPES)?, u.choose_index(4)?),
+            ),
+            6 => Field::new(&name, FieldType::pointer(*u.choose(&FIELD_TYPES)?)),
+            7 if depth < 2 => Field::new(&name, arbitrary_record(u, depth + 1)?),
+            _ => Field::new(&name, *u.choose(&FIELD_TYPES)?),
+        };
+        let field = match u.choose_index(8)? {
+            6 => field.packed(),
+            7 => field.aligned(*u.choose(&[1, 2, 4, 8, 16])?),
+            _ => field,
+        };
+        record = record.with_field(field);
+    }
+    let struct_with_fields = record.kind == RecordKind::Struct && !record.fields.is_empty();
+    if depth == 0 && struct_with_fields && u.choose_index(4)? == 3 {
+        record = record.field("tail", FieldType::flexible(*u.choose(&FIELD_TYPES)?));
+    }
+    Ok(match u.choose_index(8)? {
+        5 => record.packed(),
+        6 => record.pack(*u.choose(&[1, 2, 4, 8])?),
+        7 => record.aligned(*u.choose(&[1, 2, 4, 8, 16, 32, 64])?),
+        _ => record,
+    })
+}
+
+impl<'a> Arbitrary<'a> for Record {
+    /// Structs and unions of up to five fields, nested two deep, that lay
+    /// out under every data model.
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Record> {
+        arbitrary_record(u, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// bytes returns `n` pseudo-random bytes from `seed`.
+    fn bytes(seed: u64, n: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..n)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_records_lay_out() {
+        for seed in 0..500 {
+            let bytes = bytes(seed, 256);
+            let mut u = Unstructured::new(&bytes);
+            let target = TargetModel::arbitrary(&mut u).unwrap();
+            let record = Record::arbitrary(&mut u).unwrap();
+            assert_ne!(target.data_model(), DataModel::Unknown);
+            if let Err(err) = record.layout(target.abi) {
+                panic!("seed {}: {} in {:?}", seed, err, record);
+            }
+        }
+    }
+
+    #[test]
+    fn test_exhausted() {
+        let mut u = Unstructured::new(&[]);
+        assert_eq!(Record::arbitrary(&mut u).unwrap(), Record::new("r0_0"));
+        assert_eq!(
+            TargetModel::arbitrary(&mut u).unwrap(),
+            TargetModel::new(DataModel::IP16).with_char_signed(false)
+        );
+        let mut u = Unstructured::new(&[3]);
+        assert_eq!(DataModel::arbitrary(&mut u).unwrap(), DataModel::ILP32);
+        assert!(u.is_empty());
+    }
+}
@@ -0,0 +1,852 @@
+//! C integer arithmetic at the widths of a data model.
+//!
+//! How C converts operands depends on the sizes of the types: an `unsigned
+//! short` promotes to `int` where `int` is wider than `short` but to
+//! `unsigned int` where they have the same width, as on 16-bit PDP-11s and
+//! Cray's SILP64, and whether assigning a `long` to an `int` loses data
+//! depends on whether `long` is wider. The functions here answer these
+//! questions for a given model, treating plain `char` as signed.
+
+use crate::{CType, DataModel, SignedRepresentation, TargetModel};
+
+/// ConversionKind classifies the conversion of values of one type to
+/// another, from the best to the worst case.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::arith::ConversionKind;
+/// let kind = DataModel::LP64.conversion(CType::Long, CType::Int);
+/// assert_eq!(kind, Some(ConversionKind::MayTruncate));
+/// assert!(ConversionKind::Lossless < ConversionKind::SignChange);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConversionKind {
+    /// Every value of the source type is preserved.
+    Lossless,
+    /// The destination type is as wide but of the other signedness, so
+    /// negative values or values above the signed maximum change sign.
+    SignChange,
+    /// The destination type is narrower, or a floating type with fewer
+    /// significant bits, so values may be truncated or rounded.
+    MayTruncate,
+}
+
+/// CastResult is the outcome of converting a value to another type with a
+/// cast or an assignment, telling what the C standard makes of it along
+/// with the value the target gives.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::arith::CastResult;
+/// let result = DataModel::LP64.cast(-1, CType::Int, CType::UInt);
+/// assert_eq!(result, Some(CastResult::Converted(0xffff_ffff)));
+/// assert_eq!(result.and_then(CastResult::value), Some(0xffff_ffff));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CastResult {
+    /// The destination type represents the value, which is unchanged.
+    Exact(i128),
+    /// The value changed in a way the standard defines: reduced modulo
+    /// `2^N` for an unsigned type, or made 1 for a nonzero `_Bool`.
+    Converted(i128),
+    /// The value does not fit a signed type, or is converted between a
+    /// pointer and an integer, so the result is implementation-defined.
+    /// The value is the low bits of the source read back in the target's
+    /// representation, as every known compiler does.
+    ImplementationDefined(i128),
+    /// The integer was rounded to the nearest value of a floating type.
+    Rounded(i128),
+    /// A floating value outside the range of an integer type, whose
+    /// conversion is undefined behavior.
+    Undefined,
+}
+
+impl CastResult {
+    /// value returns the value after the conversion, or `None` if the
+    /// behavior is undefined.
+    pub fn value(self) -> Option<i128> {
+        match self {
+            CastResult::Exact(value)
+            | CastResult::Converted(value)
+            | CastResult::ImplementationDefined(value)
+            | CastResult::Rounded(value) => Some(value),
+            CastResult::Undefined => None,
+        }
+    }
+}
+
+/// conversion_across classifies converting values of type `from` to `to`
+/// on each of `models`, with `None` where a model lacks one of the types.
+/// It answers whether an assignment safe on one target is lossy on
+/// another.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::arith::{conversion_across, ConversionKind};
+/// let kinds = conversion_across(CType::Long, CType::Int, &[DataModel::LLP64, DataModel::LP64]);
+/// assert_eq!(
+///     kinds,
+///     vec![
+///         (DataModel::LLP64, Some(ConversionKind::Lossless)),
+///         (DataModel::LP64, Some(ConversionKind::MayTruncate)),
+///     ]
+/// );
+/// ```
+pub fn conversion_across(
+    from: CType,
+    to: CType,
+    models: &[DataModel],
+) -> Vec<(DataModel, Option<ConversionKind>)> {
+    models
+        .iter()
+        .map(|&model| (model, model.conversion(from, to)))
+        .collect()
+}
+
+impl SignedRepresentation {
+    /// min returns the least value of a signed integer of `bits` bits.
+    pub fn min(self, bits: usize) -> i128 {
+        match self {
+            SignedRepresentation::TwosComplement => -(1 << (bits - 1)),
+            _ => -self.max(bits),
+        }
+    }
+
+    /// max returns the greatest value of a signed integer of `bits` bits,
+    /// which is the same in every representation.
+    pub fn max(self, bits: usize) -> i128 {
+        (1 << (bits - 1)) - 1
+    }
+
+    /// wrap reduces `value` to a signed integer of `bits` bits the way the
+    /// hardware does on overflow: modulo `2^bits` in two's complement,
+    /// modulo `2^bits - 1` with the end-around carry of ones' complement,
+    /// and by dropping the high bits of the magnitude in sign-magnitude.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use SignedRepresentation::*;
+    /// assert_eq!(TwosComplement.wrap(8, 128), -128);
+    /// assert_eq!(OnesComplement.wrap(8, 128), -127);
+    /// assert_eq!(SignMagnitude.wrap(8, 128), 0);
+    /// assert_eq!(SignMagnitude.wrap(8, -129), -1);
+    /// ```
+    pub fn wrap(self, bits: usize, value: i128) -> i128 {
+        if bits >= 128 {
+            return value;
+        }
+        match self {
+            SignedRepresentation::TwosComplement => value << (128 - bits) >> (128 - bits),
+            SignedRepresentation::OnesComplement => {
+                let modulus = (1 << bits) - 1;
+                match value.rem_euclid(modulus) {
+                    rem if rem > self.max(bits) => rem - modulus,
+                    rem => rem,
+                }
+            }
+            SignedRepresentation::SignMagnitude => {
+                let magnitude = value.unsigned_abs() as i128 & self.max(bits);
+                magnitude * value.signum()
+            }
+        }
+    }
+
+    /// encode returns the `bits` bits representing `value`, wrapped to
+    /// their range first.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(SignedRepresentation::TwosComplement.encode(8, -1), 0xff);
+    /// assert_eq!(SignedRepresentation::OnesComplement.encode(8, -1), 0xfe);
+    /// assert_eq!(SignedRepresentation::SignMagnitude.encode(8, -1), 0x81);
+    /// ```
+    pub fn encode(self, bits: usize, value: i128) -> u128 {
+        let mask = u128::MAX >> (128 - bits.min(128));
+        let value = self.wrap(bits, value);
+        let raw = match self {
+            _ if value >= 0 => value as u128,
+            SignedRepresentation::TwosComplement => value as u128,
+            SignedRepresentation::OnesComplement => !(-value as u128),
+            SignedRepresentation::SignMagnitude => 1 << (bits - 1) | -value as u128,
+        };
+        raw & mask
+    }
+
+    /// decode returns the value of the low `bits` bits of `raw` read as a
+    /// signed integer. Negative zeros read as 0.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(SignedRepresentation::TwosComplement.decode(8, 0xfe), -2);
+    /// assert_eq!(SignedRepresentation::OnesComplement.decode(8, 0xfe), -1);
+    /// assert_eq!(SignedRepresentation::SignMagnitude.decode(8, 0x80), 0);
+    /// ```
+    pub fn decode(self, bits: usize, raw: u128) -> i128 {
+        if bits == 0 || bits >= 128 {
+            return raw as i128;
+        }
+        let mask = (1 << bits) - 1;
+        let raw = raw & mask;
+        if raw >> (bits - 1) == 0 {
+            return raw as i128;
+        }
+        match self {
+            SignedRepresentation::TwosComplement => raw as i128 - (1 << bits),
+            SignedRepresentation::OnesComplement => -((!raw & mask) as i128),
+            SignedRepresentation::SignMagnitude => -((raw & mask >> 1) as i128),
+        }
+    }
+}
+
+impl CType {
+    /// is_signed reports whether the type is a signed integer type. Plain
+    /// `char` counts as signed, as on most targets.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert!(CType::Long.is_signed());
+    /// assert!(!CType::UShort.is_signed());
+    /// assert!(!CType::Bool.is_signed());
+    /// ```
+    pub const fn is_signed(self) -> bool {
+        use CType::*;
+        matches!(self, Char | SChar | Short | Int | Long | LongLong)
+    }
+
+    /// rank is the integer conversion rank of an integer type, ordering
+    /// `_Bool` below the character types, `short`, `int`, `long` and
+    /// `long long`. Signed and unsigned types share a rank.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert!(CType::UShort.rank() < CType::Int.rank());
+    /// assert_eq!(CType::Long.rank(), CType::ULong.rank());
+    /// ```
+    pub const fn rank(self) -> usize {
+        use CType::*;
+        match self {
+            Bool => 0,
+            Char | SChar | UChar => 1,
+            Short | UShort => 2,
+            Int | UInt => 3,
+            Long | ULong => 4,
+            LongLong | ULongLong => 5,
+            Pointer | Float | Double => 0,
+        }
+    }
+
+    /// to_unsigned returns the unsigned type of the same rank as an integer
+    /// type, which is the type itself if it is unsigned.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(CType::Long.to_unsigned(), CType::ULong);
+    /// assert_eq!(CType::Char.to_unsigned(), CType::UChar);
+    /// ```
+    pub fn to_unsigned(self) -> CType {
+        use CType::*;
+        match self {
+            Char | SChar => UChar,
+            Short => UShort,
+            Int => UInt,
+            Long => ULong,
+            LongLong => ULongLong,
+            ty => ty,
+        }
+    }
+}
+
+impl DataModel {
+    /// signed_representation returns how the model encodes negative
+    /// integers, which is two's complement for every model here. The
+    /// ones' complement and sign-magnitude machines are described by a
+    /// [`TargetModel`](crate::TargetModel) with another representation.
+    pub fn signed_representation(self) -> SignedRepresentation {
+        SignedRepresentation::TwosComplement
+    }
+
+    /// rank returns the integer conversion rank of `ty` in this model, see
+    /// [`CType::rank`], or `None` if `ty` is not an integer type or the
+    /// model lacks it. Of two operands, the one of higher rank decides the
+    /// type of the result of the usual arithmetic conversions.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let model = DataModel::ILP32;
+    /// assert!(model.rank(CType::Long) > model.rank(CType::UInt));
+    /// assert_eq!(model.rank(CType::Long), model.rank(CType::ULong));
+    /// assert_eq!(model.rank(CType::Double), None);
+    /// assert_eq!(DataModel::IP16.rank(CType::Long), None);
+    /// ```
+    pub fn rank(self, ty: CType) -> Option<usize> {
+        match ty.is_integer() && self.has(ty) {
+            true => Some(ty.rank()),
+            false => None,
+        }
+    }
+
+    /// promote applies the integer promotions to `ty`: an integer type
+    /// ranking below `int` becomes `int` if `int` can represent all of its
+    /// values and `unsigned int` otherwise. Other types are unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LP64.promote(CType::UShort), CType::Int);
+    /// assert_eq!(DataModel::IP16L32.promote(CType::UShort), CType::UInt);
+    /// assert_eq!(DataModel::LP64.promote(CType::ULong), CType::ULong);
+    /// ```
+    pub fn promote(self, ty: CType) -> CType {
+        match ty.is_integer() && ty.rank() < CType::Int.rank() {
+            true if self.represents(CType::Int, ty) => CType::Int,
+            true => CType::UInt,
+            false => ty,
+        }
+    }
+
+    /// arithmetic_result applies the usual arithmetic conversions to the
+    /// operands of a binary operator and returns their common type, the
+    /// type of the result of `lhs + rhs`.
+    ///
+    /// A floating operand makes the result the wider floating type. Integer
+    /// operands are promoted, then the one of lower rank is converted to
+    /// the other's type, except that a signed type of higher rank that
+    /// cannot represent all values of the unsigned type is replaced by its
+    /// unsigned counterpart. A pointer operand makes the result a pointer,
+    /// as in pointer arithmetic.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use CType::*;
+    /// assert_eq!(DataModel::LP64.arithmetic_result(Long, UInt), Long);
+    /// assert_eq!(DataModel::ILP32.arithmetic_result(Long, UInt), ULong);
+    /// assert_eq!(DataModel::LP64.arithmetic_result(Int, Double), Double);
+    /// ```
+    pub fn arithmetic_result(self, lhs: CType, rhs: CType) -> CType {
+        use CType::*;
+        match (lhs, rhs) {
+            (Pointer, _) | (_, Pointer) => return Pointer,
+            (Double, _) | (_, Double) => return Double,
+            (Float, _) | (_, Float) => return Float,
+            _ => {}
+        }
+        let (lhs, rhs) = (self.promote(lhs), self.promote(rhs));
+        if lhs == rhs {
+            return lhs;
+        }
+        if lhs.is_signed() == rhs.is_signed() {
+            return if lhs.rank() >= rhs.rank() { lhs } else { rhs };
+        }
+        let (signed, unsigned) = if lhs.is_signed() {
+            (lhs, rhs)
+        } else {
+            (rhs, lhs)
+        };
+        if unsigned.rank() >= signed.rank() {
+            unsigned
+        } else if self.represents(signed, unsigned) {
+            signed
+        } else {
+            signed.to_unsigned()
+        }
+    }
+
+    /// wrap_as converts `value` to the integer or pointer type `ty` the way
+    /// the target does, keeping the low bits that fit the width of the type
+    /// and reading them back as signed or unsigned. Conversion to `_Bool`
+    /// gives 0 or 1. It returns `None` if the model lacks the type or it is
+    /// a floating type.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LP64.wrap_as(CType::UInt, -1), Some(0xffff_ffff));
+    /// assert_eq!(DataModel::LP32.wrap_as(CType::Int, 40000), Some(-25536));
+    /// assert_eq!(DataModel::IP16.wrap_as(CType::Long, 1), None);
+    /// ```
+    pub fn wrap_as(self, ty: CType, value: i128) -> Option<i128> {
+        let bits = 8 * self.size(ty);
+        match ty {
+            CType::Float | CType::Double => None,
+            _ if bits == 0 => None,
+            CType::Bool => Some((value != 0) as i128),
+            _ if bits >= 128 => Some(value),
+            _ if ty.is_signed() => Some(value << (128 - bits) >> (128 - bits)),
+            _ => Some(value & ((1 << bits) - 1)),
+        }
+    }
+
+    /// cast converts `value` of type `from` to type `to` as C does, see
+    /// [`TargetModel::cast`](crate::TargetModel::cast), on a target of the
+    /// model with two's complement integers and a signed plain `char`. It
+    /// returns `None` if the model lacks one of the types.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::arith::CastResult;
+    /// use CType::*;
+    /// assert_eq!(DataModel::LP64.cast(70000, Long, Int), Some(CastResult::Exact(70000)));
+    /// assert_eq!(DataModel::LP32.cast(70000, Long, Int), Some(CastResult::ImplementationDefined(4464)));
+    /// assert_eq!(DataModel::LP64.cast(1 << 40, Double, Int), Some(CastResult::Undefined));
+    /// ```
+    pub fn cast(self, value: i128, from: CType, to: CType) -> Option<CastResult> {
+        TargetModel::new(self).cast(value, from, to)
+    }
+
+    /// smallest_type_holding returns the integer type of lowest rank that
+    /// is at least `bits` wide, signed or unsigned, or `None` if the model
+    /// has no type that wide. Where types share a width, as `int` and
+    /// `long` do in ILP32, the lower rank wins.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LP64.smallest_type_holding(32, true), Some(CType::Int));
+    /// assert_eq!(DataModel::LP32.smallest_type_holding(32, false), Some(CType::ULong));
+    /// assert_eq!(DataModel::IP16L32.smallest_type_holding(64, true), None);
+    /// ```
+    pub fn smallest_type_holding(self, bits: usize, signed: bool) -> Option<CType> {
+        use CType::*;
+        let types = match signed {
+            true => [SChar, Short, Int, Long, LongLong],
+            false => [UChar, UShort, UInt, ULong, ULongLong],
+        };
+        types.iter().copied().find(|&ty| {
+            let size = self.size(ty);
+            size != 0 && 8 * size >= bits
+        })
+    }
+
+    /// wrap converts `value` to one of the marker types, such as [`UInt`].
+    /// See [`DataModel::wrap_as`].
+    ///
+    /// [`UInt`]: crate::UInt
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::ILP32.wrap::<UInt>(1 << 32), Some(0));
+    /// assert_eq!(DataModel::LP64.wrap::<UInt>(1 << 32), Some(0));
+    /// assert_eq!(DataModel::LP64.wrap::<ULong>(1 << 32), Some(1 << 32));
+    /// ```
+    pub fn wrap<T>(self, value: i128) -> Option<i128> {
+        self.wrap_as(CType::of::<T>()?, value)
+    }
+
+    /// add_wrapping adds two values of a marker type with the wrapping of
+    /// the target: unsigned arithmetic is modulo the width of the type and
+    /// signed overflow, undefined in C, wraps as two's complement hardware
+    /// does.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LLP64.add_wrapping::<ULong>(0xffff_ffff, 1), Some(0));
+    /// assert_eq!(DataModel::LP64.add_wrapping::<ULong>(0xffff_ffff, 1), Some(1 << 32));
+    /// assert_eq!(DataModel::LP32.add_wrapping::<Int>(0x7fff, 1), Some(-0x8000));
+    /// ```
+    pub fn add_wrapping<T>(self, a: i128, b: i128) -> Option<i128> {
+        self.wrap::<T>(a.wrapping_add(b))
+    }
+
+    /// sub_wrapping subtracts `b` from `a` as values of a marker type. See
+    /// [`DataModel::add_wrapping`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::ILP32.sub_wrapping::<UInt>(0, 1), Some(0xffff_ffff));
+    /// ```
+    pub fn sub_wrapping<T>(self, a: i128, b: i128) -> Option<i128> {
+        self.wrap::<T>(a.wrapping_sub(b))
+    }
+
+    /// mul_wrapping multiplies two values of a marker type. See
+    /// [`DataModel::add_wrapping`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LP64.mul_wrapping::<Int>(0x10000, 0x10000), Some(0));
+    /// assert_eq!(DataModel::ILP64.mul_wrapping::<Int>(0x10000, 0x10000), Some(1 << 32));
+    /// ```
+    pub fn mul_wrapping<T>(self, a: i128, b: i128) -> Option<i128> {
+        self.wrap::<T>(a.wrapping_mul(b))
+    }
+
+    /// conversion classifies converting, as by assignment, a value of type
+    /// `from` to type `to`. Pointers count as unsigned integers of their
+    /// width. It returns `None` if the model lacks one of the types or C
+    /// has no such conversion, between pointers and floating types.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::arith::ConversionKind;
+    /// use CType::*;
+    /// assert_eq!(DataModel::LLP64.conversion(Long, Int), Some(ConversionKind::Lossless));
+    /// assert_eq!(DataModel::LP64.conversion(Pointer, Long), Some(ConversionKind::SignChange));
+    /// assert_eq!(DataModel::LP64.conversion(Int, Float), Some(ConversionKind::MayTruncate));
+    /// assert_eq!(DataModel::LP64.conversion(Int, Double), Some(ConversionKind::Lossless));
+    /// ```
+    pub fn conversion(self, from: CType, to: CType) -> Option<ConversionKind> {
+        use CType::*;
+        if !self.has(from) || !self.has(to) {
+            return None;
+        }
+        let floating = |ty| matches!(ty, Float | Double);
+        Some(match (from, to) {
+            _ if from == to => ConversionKind::Lossless,
+            (Pointer, Float | Double) | (Float | Double, Pointer) => return None,
+            (Float, Double) => ConversionKind::Lossless,
+            (_, Bool) => ConversionKind::MayTruncate,
+            _ if floating(from) => ConversionKind::MayTruncate,
+            _ if floating(to) => {
+                let digits = if to == Float { 24 } else { 53 };
+                let bits = 8 * self.size(from) - from.is_signed() as usize;
+                match from == Bool || bits <= digits {
+                    true => ConversionKind::Lossless,
+                    false => ConversionKind::MayTruncate,
+                }
+            }
+            _ if self.represents(to, from) => ConversionKind::Lossless,
+            _ if self.size(to) >= self.size(from) => ConversionKind::SignChange,
+            _ => ConversionKind::MayTruncate,
+        })
+    }
+
+    /// max_shift returns the largest amount a value of the integer type
+    /// `ty` can be shifted by, which is one less than the width of the type
+    /// it promotes to, or `None` if the model lacks it.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::ILP32.max_shift(CType::Int), Some(31));
+    /// assert_eq!(DataModel::ILP64.max_shift(CType::Int), Some(63));
+    /// assert_eq!(DataModel::LP64.max_shift(CType::Char), Some(31));
+    /// ```
+    pub fn max_shift(self, ty: CType) -> Option<usize> {
+        if !ty.is_integer() || !self.has(ty) {
+            return None;
+        }
+        Some(8 * self.size(self.promote(ty)) - 1)
+    }
+
+    /// shift_is_defined reports whether shifting a value of the integer
+    /// type `ty` by `amount` is defined: the amount must not be negative
+    /// nor reach the width of the promoted type. A left shift of a signed
+    /// value can still overflow, which depends on the value.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert!(!DataModel::ILP32.shift_is_defined(CType::Int, 32));
+    /// assert!(DataModel::ILP64.shift_is_defined(CType::Int, 32));
+    /// assert!(DataModel::LP64.shift_is_defined(CType::UShort, 16));
+    /// assert!(!DataModel::LP64.shift_is_defined(CType::Long, -1));
+    /// ```
+    pub fn shift_is_defined(self, ty: CType, amount: i128) -> bool {
+        match self.max_shift(ty) {
+            Some(max) => (0..=max as i128).contains(&amount),
+            None => false,
+        }
+    }
+
+    /// int_can_hold_pointer reports whether a pointer converted to the
+    /// integer type `ty` and back compares equal to the original, which
+    /// takes an integer at least as wide as a pointer. Signed types do, as
+    /// `intptr_t` is signed.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert!(DataModel::LP64.int_can_hold_pointer(CType::ULong));
+    /// assert!(!DataModel::LLP64.int_can_hold_pointer(CType::ULong));
+    /// assert!(DataModel::LLP64.int_can_hold_pointer(CType::LongLong));
+    /// assert!(!DataModel::LP64.int_can_hold_pointer(CType::Double));
+    /// ```
+    pub fn int_can_hold_pointer(self, ty: CType) -> bool {
+        let pointer = self.size(CType::Pointer);
+        ty.is_integer() && ty != CType::Bool && pointer != 0 && self.size(ty) >= pointer
+    }
+
+    /// pointer_fits_in_long reports whether a `long` can hold a pointer,
+    /// which Unix code often assumes and Win64 breaks.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert!(DataModel::ILP32.pointer_fits_in_long());
+    /// assert!(DataModel::LP64.pointer_fits_in_long());
+    /// assert!(!DataModel::LLP64.pointer_fits_in_long());
+    /// ```
+    pub fn pointer_fits_in_long(self) -> bool {
+        self.int_can_hold_pointer(CType::Long)
+    }
+
+    /// represents reports whether `to` can represent every value of the
+    /// integer type `from`.
+    fn represents(self, to: CType, from: CType) -> bool {
+        let (to_size, from_size) = (self.size(to), self.size(from));
+        match (to.is_signed(), from.is_signed()) {
+            _ if from == CType::Bool => true,
+            (true, true) | (false, false) => from_size <= to_size,
+            (true, false) => from_size < to_size,
+            (false, true) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CType, DataModel};
+
+    #[test]
+    fn test_promote() {
+        use CType::*;
+        for model in [DataModel::ILP32, DataModel::LP64, DataModel::ILP64] {
+            for ty in [Bool, Char, SChar, UChar, Short, UShort] {
+                assert_eq!(model.promote(ty), Int, "{:?} {:?}", model, ty);
+            }
+            for ty in [
+                Int, UInt, Long, ULong, LongLong, ULongLong, Pointer, Float, Double,
+            ] {
+                assert_eq!(model.promote(ty), ty);
+            }
+        }
+        // short is as wide as int.
+        assert_eq!(DataModel::IP16L32.promote(UShort), UInt);
+        assert_eq!(DataModel::LP32.promote(UShort), UInt);
+        assert_eq!(DataModel::LP32.promote(UChar), Int);
+        assert_eq!(DataModel::SILP64.promote(UShort), UInt);
+        assert_eq!(DataModel::SILP64.promote(Short), Int);
+        assert_eq!(DataModel::SILP64.promote(UChar), Int);
+    }
+
+    #[test]
+    fn test_rank() {
+        use CType::*;
+        let model = DataModel::LP64;
+        let ranks: Vec<_> = [Bool, UChar, Short, Int, ULong, LongLong]
+            .iter()
+            .map(|&ty| model.rank(ty).unwrap())
+            .collect();
+        assert!(ranks.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(model.rank(Char), model.rank(SChar));
+        assert_eq!(model.rank(Pointer), None);
+        assert_eq!(DataModel::IP16.rank(Short), None);
+        assert_eq!(DataModel::IP16L32.rank(LongLong), None);
+    }
+
+    #[test]
+    fn test_arithmetic_result() {
+        use CType::*;
+        let cases = [
+            (Int, UInt, [UInt, UInt, UInt]),
+            (Long, UInt, [ULong, ULong, Long]),
+            (Long, ULong, [ULong, ULong, ULong]),
+            (LongLong, ULong, [LongLong, LongLong, ULongLong]),
+            (LongLong, UInt, [LongLong, LongLong, LongLong]),
+            (Short, UShort, [Int, Int, Int]),
+            (Char, Bool, [Int, Int, Int]),
+            (UChar, Long, [Long, Long, Long]),
+            (ULongLong, Float, [Float, Float, Float]),
+            (Float, Double, [Double, Double, Double]),
+            (Pointer, Long, [Pointer, Pointer, Pointer]),
+        ];
+        let models = [DataModel::ILP32, DataModel::LLP64, DataModel::LP64];
+        for (lhs, rhs, expected) in cases {
+            for (model, &expected) in models.iter().zip(expected.iter()) {
+                assert_eq!(model.arithmetic_result(lhs, rhs), expected);
+                assert_eq!(model.arithmetic_result(rhs, lhs), expected);
+            }
+        }
+        assert_eq!(DataModel::IP16L32.arithmetic_result(Short, UShort), UInt);
+        assert_eq!(DataModel::IP16L32.arithmetic_result(Long, UShort), Long);
+    }
+
+    #[test]
+    fn test_smallest_type_holding() {
+        use CType::*;
+        let model = DataModel::ILP32;
+        assert_eq!(model.smallest_type_holding(0, true), Some(SChar));
+        assert_eq!(model.smallest_type_holding(9, false), Some(UShort));
+        assert_eq!(model.smallest_type_holding(17, true), Some(Int));
+        assert_eq!(model.smallest_type_holding(33, false), Some(ULongLong));
+        assert_eq!(model.smallest_type_holding(65, true), None);
+        // IP16 has no short, and SILP64's short is 64 bits.
+        assert_eq!(DataModel::IP16.smallest_type_holding(16, true), Some(Int));
+        assert_eq!(
+            DataModel::SILP64.smallest_type_holding(9, false),
+            Some(UShort)
+        );
+        assert_eq!(
+            DataModel::SILP64.smallest_type_holding(40, false),
+            Some(UShort)
+        );
+        assert_eq!(DataModel::Unknown.smallest_type_holding(8, true), None);
+    }
+
+    #[test]
+    fn test_wrap() {
+        use crate::{LongLong, Pointer, UChar, ULongLong};
+        let model = DataModel::LP64;
+        assert_eq!(model.wrap_as(CType::Char, 200), Some(-56));
+        assert_eq!(model.wrap_as(CType::UChar, -1), Some(255));
+        assert_eq!(model.wrap_as(CType::Bool, 256), Some(1));
+        assert_eq!(model.wrap_as(CType::Double, 1), None);
+        assert_eq!(model.wrap::<UChar>(0x1ff), Some(0xff));
+        assert_eq!(model.wrap::<DataModel>(1), None);
+        assert_eq!(
+            model.add_wrapping::<LongLong>(i64::MAX as i128, 1),
+            Some(i64::MIN as i128)
+        );
+        assert_eq!(
+            model.mul_wrapping::<ULongLong>(u64::MAX as i128, u64::MAX as i128),
+            Some(1)
+        );
+        assert_eq!(DataModel::IP16.sub_wrapping::<Pointer>(0, 2), Some(0xfffe));
+        assert_eq!(DataModel::IP16.add_wrapping::<LongLong>(1, 1), None);
+    }
+
+    #[test]
+    fn test_conversion() {
+        use super::ConversionKind::*;
+        use CType::*;
+        let model = DataModel::LP64;
+        assert_eq!(model.conversion(Short, Long), Some(Lossless));
+        assert_eq!(model.conversion(UInt, Long), Some(Lossless));
+        assert_eq!(model.conversion(UInt, Int), Some(SignChange));
+        assert_eq!(model.conversion(Int, ULong), Some(SignChange));
+        assert_eq!(model.conversion(ULong, Int), Some(MayTruncate));
+        assert_eq!(model.conversion(Int, Bool), Some(MayTruncate));
+        assert_eq!(model.conversion(Bool, UChar), Some(Lossless));
+        assert_eq!(model.conversion(Bool, Float), Some(Lossless));
+        assert_eq!(model.conversion(Double, Float), Some(MayTruncate));
+        assert_eq!(model.conversion(Float, Double), Some(Lossless));
+        assert_eq!(model.conversion(Double, LongLong), Some(MayTruncate));
+        assert_eq!(model.conversion(Short, Float), Some(Lossless));
+        assert_eq!(model.conversion(Pointer, Double), None);
+        assert_eq!(model.conversion(Pointer, Int), Some(MayTruncate));
+        assert_eq!(DataModel::ILP32.conversion(Pointer, ULong), Some(Lossless));
+        assert_eq!(DataModel::IP16.conversion(Long, Int), None);
+
+        let kinds = super::conversion_across(
+            Pointer,
+            ULong,
+            &[DataModel::ILP32, DataModel::LLP64, DataModel::LP64],
+        );
+        let kinds: Vec<_> = kinds.into_iter().map(|(_, kind)| kind).collect();
+        assert_eq!(
+            kinds,
+            vec![Some(Lossless), Some(MayTruncate), Some(Lossless)]
+        );
+    }
+
+    #[test]
+    fn test_cast() {
+        use super::CastResult::*;
+        use CType::*;
+        let model = DataModel::LP64;
+        assert_eq!(model.cast(-1, Int, Long), Some(Exact(-1)));
+        assert_eq!(
+            model.cast(-1, Long, ULong),
+            Some(Converted(u64::MAX as i128))
+        );
+        assert_eq!(model.cast(300, Int, UChar), Some(Converted(44)));
+        assert_eq!(model.cast(200, Int, Char), Some(ImplementationDefined(-56)));
+        assert_eq!(
+            model.cast(0x1_0000_0001, Pointer, Int),
+            Some(ImplementationDefined(1))
+        );
+        assert_eq!(model.cast(7, Int, Bool), Some(Converted(1)));
+        assert_eq!(model.cast(-1, Double, UInt), Some(Undefined));
+        assert_eq!(model.cast(3, Double, UInt), Some(Exact(3)));
+        assert_eq!(
+            model.cast(i64::MAX as i128, Long, Double),
+            Some(Rounded(1 << 63))
+        );
+        assert_eq!(
+            model
+                .cast(70000, UInt, UShort)
+                .and_then(super::CastResult::value),
+            Some(4464)
+        );
+        assert_eq!(DataModel::IP16.cast(1, Long, Int), None);
+        assert_eq!(Undefined.value(), None);
+    }
+
+    #[test]
+    fn test_pointer_round_trip() {
+        use CType::*;
+        let holders = |model: DataModel| {
+            [Bool, Char, Short, Int, Long, LongLong, Pointer, Double]
+                .iter()
+                .copied()
+                .filter(|&ty| model.int_can_hold_pointer(ty))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(holders(DataModel::IP16), vec![Int]);
+        assert_eq!(holders(DataModel::LP32), vec![Long, LongLong]);
+        assert_eq!(holders(DataModel::LLP64), vec![LongLong]);
+        assert_eq!(holders(DataModel::ILP64), vec![Int, Long, LongLong]);
+        assert!(holders(DataModel::Unknown).is_empty());
+        assert!(!DataModel::IP16.pointer_fits_in_long());
+        assert!(DataModel::SILP64.pointer_fits_in_long());
+    }
+
+    #[test]
+    fn test_signed_representation() {
+        use crate::SignedRepresentation::*;
+        for repr in [TwosComplement, OnesComplement, SignMagnitude] {
+            for value in -127..=127 {
+                assert_eq!(repr.decode(8, repr.encode(8, value)), value, "{:?}", repr);
+                assert_eq!(repr.wrap(8, value), value);
+            }
+            assert_eq!(repr.max(16), i16::MAX as i128);
+        }
+        assert_eq!(OnesComplement.min(36), -(1 << 35) + 1);
+        assert_eq!(SignMagnitude.min(16), -(i16::MAX as i128));
+        assert_eq!(TwosComplement.decode(8, 0x80), -128);
+        assert_eq!(OnesComplement.decode(8, 0xff), 0);
+        assert_eq!(OnesComplement.decode(8, 0x80), -127);
+        assert_eq!(SignMagnitude.decode(8, 0xff), -127);
+        assert_eq!(OnesComplement.wrap(8, 254), -1);
+        assert_eq!(OnesComplement.wrap(8, -128), 127);
+        assert_eq!(SignMagnitude.wrap(8, 300), 44);
+        assert_eq!(TwosComplement.encode(16, 0x12345), 0x2345);
+        assert_eq!(DataModel::LP64.signed_representation(), TwosComplement);
+    }
+
+    #[test]
+    fn test_shift() {
+        use CType::*;
+        assert_eq!(DataModel::IP16.max_shift(Int), Some(15));
+        assert_eq!(DataModel::IP16.max_shift(Long), None);
+        assert_eq!(DataModel::LP32.max_shift(UShort), Some(15));
+        assert_eq!(DataModel::LLP64.max_shift(Long), Some(31));
+        assert_eq!(DataModel::LP64.max_shift(ULongLong), Some(63));
+        assert_eq!(DataModel::LP64.max_shift(Bool), Some(31));
+        assert_eq!(DataModel::LP64.max_shift(Double), None);
+        assert!(DataModel::LP64.shift_is_defined(Long, 32));
+        assert!(!DataModel::LLP64.shift_is_defined(Long, 32));
+        assert!(!DataModel::SILP64.shift_is_defined(Short, 64));
+        assert!(DataModel::SILP64.shift_is_defined(Short, 63));
+        assert!(!DataModel::LP64.shift_is_defined(Pointer, 1));
+    }
+}
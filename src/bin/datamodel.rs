@@ -0,0 +1,398 @@
+//! datamodel answers questions about C data models from the shell.
+//!
+//! Targets are named by data model (`lp64`), platform (`LinuxX86_64`) or
+//! LLVM target triple (`x86_64-unknown-linux-gnu`).
+
+use data_models::layout::{FieldType, Layout, Record, RecordKind};
+use data_models::matrix::{render_table, TableStyle};
+use data_models::registry::Registry;
+use data_models::{CType, DataModel, Platform, TargetModel};
+use std::{env, fs, process};
+
+const USAGE: &str = "usage:
+    datamodel size TARGET TYPE...      print the size of a C type, e.g. `size lp64 unsigned long`
+    datamodel compare [--markdown] MODEL...
+                                       print a table of type sizes for the models
+    datamodel triple TRIPLE            print the platform and data model of a target triple
+    datamodel layout TARGET FILE       lay out the structs and unions declared in FILE
+    datamodel json TARGET              print the target as JSON
+
+TARGET is a data model (lp64), a platform (LinuxX86_64) or a target triple.
+
+FILE declares records in a subset of C, one field per declaration:
+
+    struct node {
+        unsigned int flags : 3;
+        long values[4];
+        struct node *next;
+    };
+";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(out) => print!("{}", out),
+        Err(err) => {
+            eprintln!("datamodel: {}", err);
+            process::exit(2);
+        }
+    }
+}
+
+/// run executes the command in `args` and returns its output.
+fn run(args: &[String]) -> Result<String, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["size", target, ty @ ..] if !ty.is_empty() => {
+            let target = target_model(target)?;
+            let ty = c_type(ty).ok_or_else(|| format!("unknown type {:?}", ty.join(" ")))?;
+            match target.abi.size(ty) {
+                0 => Err(format!("{:?} has no {}", target.data_model(), ty.c_name())),
+                size => Ok(format!("{}\n", size)),
+            }
+        }
+        ["compare", models @ ..] => {
+            let (style, models) = match models {
+                ["--markdown", models @ ..] => (TableStyle::Markdown, models),
+                _ => (TableStyle::Ascii, models),
+            };
+            let models = models
+                .iter()
+                .map(|name| {
+                    data_model(name).ok_or_else(|| format!("unknown data model {:?}", name))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            match models.is_empty() {
+                true => Ok(render_table(DataModel::ALL, style)),
+                false => Ok(render_table(&models, style)),
+            }
+        }
+        ["triple", triple] => {
+            let platform =
+                platform(triple).ok_or_else(|| format!("unknown triple {:?}", triple))?;
+            let target = platform.target();
+            Ok(format!(
+                "platform: {:?}\ndata model: {:?}\nendianness: {:?}\nchar signed: {}\n",
+                platform,
+                target.data_model(),
+                target.endianness,
+                target.char_signed
+            ))
+        }
+        ["layout", target, file] => {
+            let target = target_model(target)?;
+            let src = fs::read_to_string(file).map_err(|err| format!("{}: {}", file, err))?;
+            let mut out = String::new();
+            for record in records(&src)? {
+                let layout = record
+                    .layout(target.abi)
+                    .map_err(|err| format!("{} {}: {}", keyword(record.kind), record.name, err))?;
+                out += &describe(&record, &layout);
+            }
+            Ok(out)
+        }
+        ["json", target] => Ok(target_model(target)?.to_json() + "\n"),
+        _ => Err(format!("bad arguments\n{}", USAGE)),
+    }
+}
+
+/// data_model finds a data model by name, ignoring case.
+fn data_model(name: &str) -> Option<DataModel> {
+    DataModel::ALL
+        .iter()
+        .copied()
+        .find(|model| format!("{:?}", model).eq_ignore_ascii_case(name))
+}
+
+/// platform finds the platform of a target triple.
+fn platform(triple: &str) -> Option<Platform> {
+    Platform::ALL
+        .iter()
+        .copied()
+        .find(|platform| platform.triple() == triple)
+}
+
+/// target_model finds a target by data model, platform or triple.
+fn target_model(name: &str) -> Result<TargetModel, String> {
+    data_model(name)
+        .map(TargetModel::new)
+        .or_else(|| Registry::new().get(name))
+        .or_else(|| platform(name).map(Platform::target))
+        .ok_or_else(|| format!("unknown target {:?}", name))
+}
+
+/// c_type reads the C spelling of an integer, floating or pointer type,
+/// such as `unsigned long int` or `void *`.
+fn c_type(words: &[&str]) -> Option<CType> {
+    use CType::*;
+    let count = |word| words.iter().filter(|&&w| w == word).count();
+    let known = [
+        "_Bool", "char", "short", "int", "long", "signed", "unsigned", "float", "double", "void",
+        "*",
+    ];
+    if words.is_empty() || words.iter().any(|word| !known.contains(word)) {
+        return None;
+    }
+    let unsigned = count("unsigned") == 1;
+    let ty = match (count("long"), words) {
+        _ if count("signed") + count("unsigned") > 1 => return None,
+        (0, ["void", "*"]) => Pointer,
+        (0, ["_Bool"]) => Bool,
+        (0, ["float"]) => Float,
+        (0, ["double"]) => Double,
+        _ if count("void") + count("_Bool") + count("float") + count("double") > 0 => return None,
+        _ if count("*") > 0 => return None,
+        (0, _) if count("char") == 1 => match (count("signed"), unsigned) {
+            (_, true) => UChar,
+            (1, _) => SChar,
+            _ => Char,
+        },
+        _ if count("char") > 0 => return None,
+        (0, _) if count("short") == 1 => [Short, UShort][unsigned as usize],
+        _ if count("short") > 0 || count("int") > 1 => return None,
+        (0, _) => [Int, UInt][unsigned as usize],
+        (1, _) => [Long, ULong][unsigned as usize],
+        (2, _) => [LongLong, ULongLong][unsigned as usize],
+        _ => return None,
+    };
+    Some(ty)
+}
+
+/// records parses the struct and union declarations of `src`.
+fn records(src: &str) -> Result<Vec<Record>, String> {
+    let tokens = tokens(src);
+    let mut tokens = tokens.iter().map(String::as_str).peekable();
+    let mut records: Vec<Record> = Vec::new();
+    while let Some(kind) = tokens.next() {
+        let name = tokens.next().ok_or("expected a record name")?;
+        let mut record = match kind {
+            "struct" => Record::new(name),
+            "union" => Record::union(name),
+            _ => return Err(format!("expected struct or union, found {:?}", kind)),
+        };
+        if tokens.next() != Some("{") {
+            return Err(format!("expected {{ after {} {}", kind, name));
+        }
+        loop {
+            let mut words = Vec::new();
+            while let Some(&token) = tokens.peek() {
+                if matches!(token, ";" | ":" | "[" | "}") {
+                    break;
+                }
+                words.push(token);
+                tokens.next();
+            }
+            if words.is_empty() && tokens.peek() == Some(&"}") {
+                tokens.next();
+                break;
+            }
+            let field = words.pop().ok_or("expected a field name")?;
+            let ty = field_type(&words, &records, &record)?;
+            record = match tokens.next() {
+                Some(";") => record.field(field, ty),
+                Some(":") => {
+                    let bits = tokens.next().and_then(|bits| bits.parse().ok());
+                    match (ty, bits, tokens.next()) {
+                        (FieldType::Scalar(ty), Some(bits), Some(";")) => {
+                            record.bitfield(field, ty, bits)
+                        }
+                        _ => return Err(format!("bad bit-field {}", field)),
+                    }
+                }
+                Some("[") => {
+                    let ty = match tokens.next() {
+                        Some("]") => FieldType::flexible(ty),
+                        Some(n) => match (n.parse(), tokens.next()) {
+                            (Ok(n), Some("]")) => FieldType::array(ty, n),
+                            _ => return Err(format!("bad array length of {}", field)),
+                        },
+                        None => return Err(format!("bad array length of {}", field)),
+                    };
+                    match tokens.next() {
+                        Some(";") => record.field(field, ty),
+                        _ => return Err(format!("expected ; after {}", field)),
+                    }
+                }
+                _ => return Err(format!("expected ; after {}", field)),
+            };
+        }
+        if tokens.peek() == Some(&";") {
+            tokens.next();
+        }
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// field_type reads the type of a field, which may name an earlier record
+/// or, through a pointer, the record being declared.
+fn field_type(words: &[&str], records: &[Record], current: &Record) -> Result<FieldType, String> {
+    let (base, pointers) = match words.iter().position(|&word| word == "*") {
+        Some(i) if words[i..].iter().all(|&word| word == "*") => (&words[..i], words.len() - i),
+        Some(_) => return Err(format!("bad type {:?}", words.join(" "))),
+        None => (words, 0),
+    };
+    let mut ty = match base {
+        ["struct" | "union", name] => {
+            let record = records
+                .iter()
+                .chain((pointers > 0).then_some(current))
+                .find(|record| record.name == *name)
+                .ok_or_else(|| format!("unknown record {}", name))?;
+            FieldType::from(record.clone())
+        }
+        ["void"] if pointers > 0 => {
+            return Ok(
+                (1..pointers).fold(FieldType::Scalar(CType::Pointer), |ty, _| {
+                    FieldType::pointer(ty)
+                }),
+            )
+        }
+        _ => FieldType::Scalar(
+            c_type(base).ok_or_else(|| format!("unknown type {:?}", base.join(" ")))?,
+        ),
+    };
+    for _ in 0..pointers {
+        ty = FieldType::pointer(ty);
+    }
+    Ok(ty)
+}
+
+/// tokens splits `src` into words and punctuation, dropping comments.
+fn tokens(src: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for line in src.lines() {
+        let line = line.split("//").next().unwrap_or("");
+        let mut word = String::new();
+        for c in line.chars() {
+            if c.is_alphanumeric() || c == '_' {
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        }
+        if !word.is_empty() {
+            tokens.push(word);
+        }
+    }
+    tokens
+}
+
+/// describe prints the offset and size of each field of a layout.
+fn describe(record: &Record, layout: &Layout) -> String {
+    let mut out = format!(
+        "{} {}: size {}, align {}\n",
+        keyword(record.kind),
+        layout.name,
+        layout.size,
+        layout.align
+    );
+    for field in &layout.fields {
+        let suffix = match (field.bits, &field.ty) {
+            (Some(bits), _) => format!(" : {} at bit {}", bits, field.bit_offset),
+            (None, FieldType::FlexibleArray(_)) => "[]".to_string(),
+            (None, _) => String::new(),
+        };
+        out += &format!(
+            "  {:>6} {:>6}  {}{}\n",
+            field.offset, field.size, field.name, suffix
+        );
+    }
+    out
+}
+
+/// keyword spells the kind of a record.
+fn keyword(kind: RecordKind) -> &'static str {
+    match kind {
+        RecordKind::Struct => "struct",
+        RecordKind::Union => "union",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &str) -> Result<String, String> {
+        let args: Vec<String> = args.split_whitespace().map(String::from).collect();
+        super::run(&args)
+    }
+
+    #[test]
+    fn test_size() {
+        assert_eq!(run("size lp64 long"), Ok("8\n".to_string()));
+        assert_eq!(run("size LLP64 unsigned long int"), Ok("4\n".to_string()));
+        assert_eq!(run("size LinuxArmhf void *"), Ok("4\n".to_string()));
+        assert_eq!(
+            run("size x86_64-pc-windows-msvc long"),
+            Ok("4\n".to_string())
+        );
+        assert!(run("size ip16 long").is_err());
+        assert!(run("size lp64 long float").is_err());
+        assert!(run("size vax long").is_err());
+    }
+
+    #[test]
+    fn test_c_type() {
+        use CType::*;
+        let c_type = |s: &str| c_type(&s.split_whitespace().collect::<Vec<_>>());
+        for &ty in CType::ALL {
+            assert_eq!(c_type(ty.c_name()), Some(ty));
+        }
+        assert_eq!(c_type("unsigned"), Some(UInt));
+        assert_eq!(c_type("long long int"), Some(LongLong));
+        assert_eq!(c_type("signed char"), Some(SChar));
+        assert_eq!(c_type("unsigned short int"), Some(UShort));
+        assert_eq!(c_type("long double"), None);
+        assert_eq!(c_type("unsigned signed"), None);
+        assert_eq!(c_type("char *"), None);
+    }
+
+    #[test]
+    fn test_commands() {
+        let table = run("compare --markdown ilp32 lp64").unwrap();
+        assert!(table.starts_with("| type"));
+        assert!(table.contains("| long "));
+        let triple = run("triple aarch64-unknown-linux-gnu").unwrap();
+        assert!(triple.contains("data model: LP64\n"));
+        assert!(triple.contains("char signed: false\n"));
+        let json = run("json ilp32").unwrap();
+        assert!(json.contains("\"data_model\": \"ILP32\""));
+        assert!(run("frobnicate").unwrap_err().contains("usage:"));
+    }
+
+    #[test]
+    fn test_records() {
+        let src = "
+            // A list node.
+            struct node {
+                unsigned int flags : 3;
+                long values[2];
+                struct node *next;
+                char name[];
+            };
+            union value { double d; struct node n; }
+        ";
+        let parsed = records(src).unwrap();
+        assert_eq!(parsed.len(), 2);
+        let layout = parsed[0].layout(DataModel::LP64).unwrap();
+        let out = describe(&parsed[0], &layout);
+        assert_eq!(
+            out,
+            "struct node: size 32, align 8\n\
+             \x20      0      4  flags : 3 at bit 0\n\
+             \x20      8     16  values\n\
+             \x20     24      8  next\n\
+             \x20     32      0  name[]\n"
+        );
+        let layout = parsed[1].layout(DataModel::ILP32).unwrap();
+        assert_eq!(layout.size, 16);
+        assert!(records("struct a { struct b x; };").is_err());
+        assert!(records("struct a { int x }").is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! Generate bindgen bindings for a foreign target.
+//!
+//! bindgen asks the host's libclang about the C types, so bindings made on
+//! x86-64 Linux for a Windows or 32-bit ARM target get the host's `long`,
+//! `wchar_t` and `long double`, and layout tests that fail on the target.
+//! [`Bindgen`] holds what bindgen needs to see the target instead: the
+//! clang target and flags, a module of C types for `ctypes_prefix`,
+//! definitions of `wchar_t` and `long double`, and the type of each
+//! integer macro for the `int_macro` callback.
+//!
+//! [`Bindgen`] implements bindgen's `ParseCallbacks`, and
+//! [`Bindgen::configure`] applies all of it to a `bindgen::Builder`:
+//!
+//! ```no_run
+//! use data_models::*;
+//! let bindings = Platform::Windows64
+//!     .bindgen()
+//!     .configure(::bindgen::Builder::default().header("wrapper.h"))
+//!     .generate()
+//!     .unwrap();
+//! bindings.write_to_file("bindings.rs").unwrap();
+//! ```
+
+use crate::rust::RustType;
+use crate::{CStandard, LongDouble, Platform};
+use ::bindgen::callbacks::{IntKind, ParseCallbacks};
+use ::bindgen::Builder;
+
+/// Bindgen adapts bindgen to a [`Platform`].
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let target = Platform::Windows64.bindgen();
+/// assert_eq!(target.clang_args(), ["--target=x86_64-pc-windows-msvc"]);
+/// assert!(target.ctypes_module("ctypes").contains("    pub type c_long = i32;\n"));
+/// assert!(target.raw_lines().contains(&"pub type wchar_t = u16;".to_string()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bindgen {
+    /// The target of the bindings.
+    pub platform: Platform,
+}
+
+impl Platform {
+    /// bindgen returns the adapter generating bindings for the platform.
+    pub fn bindgen(self) -> Bindgen {
+        Bindgen { platform: self }
+    }
+}
+
+impl Bindgen {
+    /// clang_args returns the arguments making libclang parse headers for
+    /// the target, for `Builder::clang_args`.
+    pub fn clang_args(&self) -> Vec<String> {
+        vec![format!("--target={}", self.platform.triple())]
+    }
+
+    /// ctypes_module emits Rust source for a module named `module` of the
+    /// C types of the target, to point `Builder::ctypes_prefix` at in place
+    /// of `std::os::raw`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let src = Platform::LinuxAarch64.bindgen().ctypes_module("ctypes");
+    /// assert!(src.contains("    pub type c_char = u8;\n"));
+    /// assert!(src.contains("    pub use core::ffi::c_void;\n"));
+    /// ```
+    pub fn ctypes_module(&self, module: &str) -> String {
+        let src = self.platform.target().rust_type_aliases(module);
+        let end = src.len() - "}\n".len();
+        format!("{}    pub use core::ffi::c_void;\n}}\n", &src[..end])
+    }
+
+    /// blocklist_types returns the types whose bindgen definitions follow
+    /// the host, for `Builder::blocklist_type`; [`Bindgen::raw_lines`]
+    /// defines them for the target instead.
+    pub fn blocklist_types(&self) -> &'static [&'static str] {
+        &["wchar_t"]
+    }
+
+    /// raw_lines returns the definitions of `wchar_t` and of `c_longdouble`,
+    /// a type with the size and alignment of `long double`, for
+    /// `Builder::raw_line`. Only a `long double` that is a `double` has a
+    /// Rust float; the others are opaque.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let lines = Platform::LinuxI686.bindgen().raw_lines();
+    /// assert_eq!(lines, ["pub type wchar_t = i32;", "pub type c_longdouble = [u32; 3];"]);
+    /// let lines = Platform::MacAarch64.bindgen().raw_lines();
+    /// assert_eq!(lines[1], "pub type c_longdouble = f64;");
+    /// ```
+    pub fn raw_lines(&self) -> Vec<String> {
+        let platform = self.platform;
+        // wchar_t is an unsigned UTF-16 unit where it is 2 bytes, and the
+        // ARM ABIs make the 4-byte one unsigned too.
+        let wchar_signed = platform.wchar_size() == 4
+            && !matches!(platform, Platform::LinuxAarch64 | Platform::LinuxArmhf);
+        let wchar = RustType::integer(platform.wchar_size(), wchar_signed).unwrap();
+        let long_double = match (platform.long_double(), platform.long_double_size()) {
+            (LongDouble::Binary64, _) => "f64",
+            (_, 12) => "[u32; 3]",
+            _ => "u128",
+        };
+        vec![
+            format!("pub type wchar_t = {};", wchar),
+            format!("pub type c_longdouble = {};", long_double),
+        ]
+    }
+
+    /// int_macro returns the Rust type of an integer macro expanding to
+    /// `value`, the type C gives the constant on the target, for
+    /// `ParseCallbacks::int_macro`. bindgen otherwise picks a type from the
+    /// value alone.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::rust::RustType;
+    /// assert_eq!(Platform::Windows64.bindgen().int_macro(1 << 40), Some(RustType::I64));
+    /// assert_eq!(Platform::Windows64.bindgen().int_macro(-1), Some(RustType::I32));
+    /// assert_eq!(Platform::LinuxI686.bindgen().int_macro(1 << 31), Some(RustType::I64));
+    /// ```
+    pub fn int_macro(&self, value: i64) -> Option<RustType> {
+        let target = self.platform.target();
+        // A negative macro is a negated constant, which keeps its type.
+        let ty = target
+            .data_model()
+            .literal_type(&value.unsigned_abs().to_string(), CStandard::C11)
+            .ok()?;
+        target.rust_type_of(ty)
+    }
+
+    /// configure makes `builder` generate bindings for the target: it
+    /// passes the clang target, defines the C types in a `ctypes` module at
+    /// the top of the bindings, replaces `wchar_t` and adds
+    /// `c_longdouble`, and types integer macros with [`Bindgen::int_macro`].
+    pub fn configure(self, builder: Builder) -> Builder {
+        let mut builder = builder
+            .clang_args(self.clang_args())
+            .raw_line(self.ctypes_module("ctypes"))
+            .ctypes_prefix("ctypes")
+            .parse_callbacks(Box::new(self));
+        for ty in self.blocklist_types() {
+            builder = builder.blocklist_type(ty);
+        }
+        for line in self.raw_lines() {
+            builder = builder.raw_line(line);
+        }
+        builder
+    }
+}
+
+impl ParseCallbacks for Bindgen {
+    fn int_macro(&self, _name: &str, value: i64) -> Option<IntKind> {
+        match Bindgen::int_macro(self, value)? {
+            RustType::I8 => Some(IntKind::I8),
+            RustType::U8 => Some(IntKind::U8),
+            RustType::I16 => Some(IntKind::I16),
+            RustType::U16 => Some(IntKind::U16),
+            RustType::I32 => Some(IntKind::I32),
+            RustType::U32 => Some(IntKind::U32),
+            RustType::I64 => Some(IntKind::I64),
+            RustType::U64 => Some(IntKind::U64),
+            RustType::Bool | RustType::F32 | RustType::F64 => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindgen() {
+        for &platform in Platform::ALL {
+            let target = platform.bindgen();
+            let src = target.ctypes_module("ctypes");
+            assert!(src.starts_with("/// C types"));
+            assert!(src.ends_with("    pub use core::ffi::c_void;\n}\n"));
+            assert_eq!(target.raw_lines().len(), 2);
+            assert_eq!(target.int_macro(0), Some(RustType::I32));
+        }
+        let arm = Platform::LinuxArmhf.bindgen();
+        assert_eq!(arm.raw_lines()[0], "pub type wchar_t = u32;");
+        let aix = Platform::Aix32.bindgen();
+        assert_eq!(aix.raw_lines()[0], "pub type wchar_t = u16;");
+        assert_eq!(
+            Platform::LinuxS390x.bindgen().raw_lines()[1],
+            "pub type c_longdouble = u128;"
+        );
+        assert_eq!(
+            Platform::LinuxX86_64.bindgen().int_macro(1 << 40),
+            Some(RustType::I64)
+        );
+        assert_eq!(Platform::LinuxX86_64.bindgen().int_macro(i64::MIN), None);
+
+        let windows = Platform::Windows64.bindgen();
+        let callbacks: &dyn ParseCallbacks = &windows;
+        assert_eq!(callbacks.int_macro("BIG", 1 << 40), Some(IntKind::I64));
+        assert_eq!(callbacks.int_macro("MINUS_ONE", -1), Some(IntKind::I32));
+        let flags = windows.configure(Builder::default()).command_line_flags();
+        assert!(flags.contains(&"--target=x86_64-pc-windows-msvc".to_string()));
+        assert!(flags.contains(&"--ctypes-prefix".to_string()));
+        assert!(flags.contains(&"pub type wchar_t = u16;".to_string()));
+    }
+}
@@ -0,0 +1,328 @@
+//! C source generation.
+//!
+//! The predictions of this crate are only as good as their agreement with
+//! the real compiler. The generators here write them out as C11
+//! `_Static_assert`s, so a C build can check them: drop the header into the
+//! project, include it from any translation unit and the compiler refuses
+//! to build if one of the sizes or offsets is wrong.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::layout::Record;
+//! let header = DataModel::LP64.c_static_asserts();
+//! assert!(header.contains("_Static_assert(sizeof(long) == 8, \"sizeof(long)\");\n"));
+//!
+//! let layout = Record::new("pair").field("a", CType::Char).field("b", CType::Int);
+//! let header = layout.layout(DataModel::LP64).unwrap().c_static_asserts();
+//! assert!(header.contains("_Static_assert(offsetof(struct pair, b) == 4, \"offsetof(struct pair, b)\");\n"));
+//! ```
+
+use crate::layout::{Abi, FieldType, Layout, RecordKind};
+use crate::stdint::StdType;
+use crate::{CType, DataModel, Endianness, TargetModel};
+use std::fmt;
+
+/// static_assert formats one assertion, using the checked expression as the
+/// message so a failing build names it.
+fn static_assert(expr: &str, value: impl fmt::Display) -> String {
+    format!("_Static_assert({} == {}, \"{}\");\n", expr, value, expr)
+}
+
+impl RecordKind {
+    /// c_keyword is the C keyword introducing the record, `"struct"` or
+    /// `"union"`.
+    pub fn c_keyword(self) -> &'static str {
+        match self {
+            RecordKind::Struct => "struct",
+            RecordKind::Union => "union",
+        }
+    }
+}
+
+impl DataModel {
+    /// c_static_asserts emits a C header asserting the size of every C type
+    /// the model has.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let header = DataModel::LLP64.c_static_asserts();
+    /// assert!(header.contains("_Static_assert(sizeof(long) == 4, \"sizeof(long)\");\n"));
+    /// assert!(header.contains("_Static_assert(sizeof(void *) == 8, \"sizeof(void *)\");\n"));
+    /// ```
+    pub fn c_static_asserts(self) -> String {
+        use CType::*;
+        let mut src = format!("/* Type sizes of the {:?} data model. */\n", self);
+        src += "#include <limits.h>\n\n";
+        src += &static_assert("CHAR_BIT", 8);
+        let types = [
+            Char, SChar, UChar, Short, UShort, Int, UInt, Long, ULong, LongLong, ULongLong, Pointer,
+        ];
+        for &ty in types.iter() {
+            let size = self.size(ty);
+            if size != 0 {
+                src += &static_assert(&format!("sizeof({})", ty.c_name()), size);
+            }
+        }
+        src
+    }
+}
+
+/// STD_TYPES are the typedefs the verification unit checks.
+const STD_TYPES: [StdType; 7] = [
+    StdType::Size,
+    StdType::PtrDiff,
+    StdType::IntPtr,
+    StdType::UIntPtr,
+    StdType::IntMax,
+    StdType::UIntMax,
+    StdType::Int64,
+];
+
+/// verification_unit emits the assertions of a verification unit for the
+/// types of `abi`. Plain `char` and the byte order are only checked for a
+/// `target`.
+fn verification_unit(abi: &Abi, target: Option<&TargetModel>) -> String {
+    use CType::*;
+    let model = abi.model();
+    let mut src = format!("/* Verification of the {:?} data model. */\n", model);
+    src += "#include <limits.h>\n#include <stddef.h>\n#include <stdint.h>\n\n";
+    src += &static_assert("CHAR_BIT", 8);
+    let types = [
+        Bool, Char, SChar, UChar, Short, UShort, Int, UInt, Long, ULong, LongLong, ULongLong,
+        Pointer, Float, Double,
+    ];
+    for &ty in types.iter() {
+        let size = model.size(ty);
+        if size == 0 {
+            continue;
+        }
+        let name = ty.c_name();
+        src += &static_assert(&format!("sizeof({})", name), size);
+        src += &static_assert(&format!("_Alignof({})", name), abi.align(ty));
+        let signed = match (ty, target) {
+            (Pointer | Float | Double, _) | (Char, None) => continue,
+            (Char, Some(target)) => target.char_signed,
+            _ => ty.is_signed(),
+        };
+        src += &static_assert(&format!("({})-1 < 0", name), signed as u8);
+    }
+    for &ty in STD_TYPES.iter() {
+        if let Some(base) = model.resolve(ty) {
+            src += &static_assert(&format!("sizeof({})", ty.c_name()), model.size(base));
+            let expr = format!("({})-1 < 0", ty.c_name());
+            src += &static_assert(&expr, ty.is_signed() as u8);
+        }
+    }
+    let limits = match target {
+        Some(target) => target.limits(),
+        None => model.limits(),
+    };
+    for limit in limits.iter() {
+        if limit.name == "CHAR_BIT" || limit.name.starts_with("CHAR_") && target.is_none() {
+            continue;
+        }
+        src += &static_assert(limit.name, limit.c_literal());
+    }
+    if let Some(target) = target {
+        let order = match target.endianness {
+            Endianness::Little => "__ORDER_LITTLE_ENDIAN__",
+            Endianness::Big => "__ORDER_BIG_ENDIAN__",
+            Endianness::PdpMiddle => "__ORDER_PDP_ENDIAN__",
+        };
+        src += "#ifdef __BYTE_ORDER__\n";
+        src += &static_assert("__BYTE_ORDER__", order);
+        src += "#endif\n";
+    }
+    src
+}
+
+impl DataModel {
+    /// c_verification_unit emits a self-contained C11 source file asserting
+    /// the size, alignment and signedness of every C type the model has,
+    /// the sizes of the standard typedefs and the values of the limits of
+    /// `limits.h` and `stdint.h`. Compiling it with the real toolchain of a
+    /// target checks every prediction of this crate for the model at once.
+    ///
+    /// Plain `char` and the byte order depend on more than the model;
+    /// [`TargetModel::c_verification_unit`] checks those too.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let src = DataModel::ILP32.c_verification_unit();
+    /// assert!(src.contains("_Static_assert(_Alignof(long long) == 8, \"_Alignof(long long)\");\n"));
+    /// assert!(src.contains("_Static_assert((unsigned int)-1 < 0 == 0, \"(unsigned int)-1 < 0\");\n"));
+    /// assert!(src.contains("_Static_assert(sizeof(size_t) == 4, \"sizeof(size_t)\");\n"));
+    /// assert!(src.contains("_Static_assert(LONG_MAX == 2147483647L, \"LONG_MAX\");\n"));
+    /// ```
+    pub fn c_verification_unit(self) -> String {
+        verification_unit(&Abi::new(self), None)
+    }
+}
+
+impl TargetModel {
+    /// c_verification_unit emits a self-contained C11 source file checking
+    /// the predictions of this crate for the target, including the
+    /// alignments of its ABI, the signedness of plain `char` and, with
+    /// compilers that define `__BYTE_ORDER__`, the byte order. See
+    /// [`DataModel::c_verification_unit`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let src = Platform::LinuxI686.target().c_verification_unit();
+    /// assert!(src.contains("_Static_assert(_Alignof(long long) == 4, \"_Alignof(long long)\");\n"));
+    /// assert!(src.contains("_Static_assert((char)-1 < 0 == 1, \"(char)-1 < 0\");\n"));
+    /// assert!(src.contains("_Static_assert(__BYTE_ORDER__ == __ORDER_LITTLE_ENDIAN__, \"__BYTE_ORDER__\");\n"));
+    /// ```
+    pub fn c_verification_unit(&self) -> String {
+        verification_unit(&self.abi, Some(self))
+    }
+}
+
+impl Layout {
+    /// c_static_asserts emits a C header asserting the size and alignment
+    /// of the record and the offset and size of each of its fields,
+    /// including the fields of nested records.
+    ///
+    /// The record is named by its tag (`struct name`), which must be in
+    /// scope where the header is included. Bit-fields cannot be named by
+    /// `offsetof` and are left out.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::Record;
+    /// let record = Record::union("value").field("i", CType::Int).field("d", CType::Double);
+    /// let header = record.layout(DataModel::ILP32).unwrap().c_static_asserts();
+    /// assert!(header.contains("_Static_assert(sizeof(union value) == 8, \"sizeof(union value)\");\n"));
+    /// ```
+    pub fn c_static_asserts(&self) -> String {
+        let tag = format!("{} {}", self.kind.c_keyword(), self.name);
+        let mut src = format!("/* Layout of {}. */\n", tag);
+        src += "#include <stddef.h>\n\n";
+        src += &static_assert(&format!("sizeof({})", tag), self.size);
+        src += &static_assert(&format!("_Alignof({})", tag), self.align);
+        self.field_asserts(&tag, "", 0, &mut src);
+        src
+    }
+
+    /// field_asserts appends the assertions for the fields of a record
+    /// nested `base` bytes into `tag`, reached through the member `prefix`.
+    fn field_asserts(&self, tag: &str, prefix: &str, base: usize, src: &mut String) {
+        for field in self.fields.iter().filter(|field| field.bits.is_none()) {
+            let offset = base + field.offset;
+            if field.name.is_empty() {
+                // Anonymous members are reached through their own fields.
+                if let Some(nested) = &field.nested {
+                    nested.field_asserts(tag, prefix, offset, src);
+                }
+                continue;
+            }
+            let path = format!("{}{}", prefix, field.name);
+            src.push_str(&static_assert(
+                &format!("offsetof({}, {})", tag, path),
+                offset,
+            ));
+            if let FieldType::FlexibleArray(_) = field.ty {
+                continue;
+            }
+            src.push_str(&static_assert(
+                &format!("sizeof((({} *)0)->{})", tag, path),
+                field.size,
+            ));
+            match (&field.ty, &field.nested) {
+                (FieldType::Record(_), Some(nested)) | (FieldType::Layout(_), Some(nested)) => {
+                    nested.field_asserts(tag, &format!("{}.", path), offset, src)
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{Field, Record};
+
+    #[test]
+    fn test_model_asserts() {
+        let src = DataModel::LP64.c_static_asserts();
+        assert!(src.starts_with("/* Type sizes of the LP64 data model. */\n"));
+        assert!(src.contains("_Static_assert(CHAR_BIT == 8, \"CHAR_BIT\");\n"));
+        assert!(src.contains(
+            "_Static_assert(sizeof(unsigned long long) == 8, \"sizeof(unsigned long long)\");\n"
+        ));
+
+        let src = DataModel::IP16.c_static_asserts();
+        assert!(!src.contains("sizeof(long)"));
+        assert!(src.contains("_Static_assert(sizeof(int) == 2, \"sizeof(int)\");\n"));
+    }
+
+    #[test]
+    fn test_layout_asserts() {
+        let inner = Record::new("inner")
+            .field("c", CType::Char)
+            .field("l", CType::Long);
+        let record = Record::new("outer")
+            .field("tag", CType::Char)
+            .field("inner", inner)
+            .bitfield("flag", CType::UInt, 1)
+            .with_field(Field::new(
+                "",
+                Record::union("")
+                    .field("i", CType::Int)
+                    .field("p", CType::Pointer),
+            ))
+            .field("data", FieldType::flexible(CType::Char));
+        let src = record.layout(DataModel::LP64).unwrap().c_static_asserts();
+        let expected = "\
+/* Layout of struct outer. */
+#include <stddef.h>
+
+_Static_assert(sizeof(struct outer) == 40, \"sizeof(struct outer)\");
+_Static_assert(_Alignof(struct outer) == 8, \"_Alignof(struct outer)\");
+_Static_assert(offsetof(struct outer, tag) == 0, \"offsetof(struct outer, tag)\");
+_Static_assert(sizeof(((struct outer *)0)->tag) == 1, \"sizeof(((struct outer *)0)->tag)\");
+_Static_assert(offsetof(struct outer, inner) == 8, \"offsetof(struct outer, inner)\");
+_Static_assert(sizeof(((struct outer *)0)->inner) == 16, \"sizeof(((struct outer *)0)->inner)\");
+_Static_assert(offsetof(struct outer, inner.c) == 8, \"offsetof(struct outer, inner.c)\");
+_Static_assert(sizeof(((struct outer *)0)->inner.c) == 1, \"sizeof(((struct outer *)0)->inner.c)\");
+_Static_assert(offsetof(struct outer, inner.l) == 16, \"offsetof(struct outer, inner.l)\");
+_Static_assert(sizeof(((struct outer *)0)->inner.l) == 8, \"sizeof(((struct outer *)0)->inner.l)\");
+_Static_assert(offsetof(struct outer, i) == 32, \"offsetof(struct outer, i)\");
+_Static_assert(sizeof(((struct outer *)0)->i) == 4, \"sizeof(((struct outer *)0)->i)\");
+_Static_assert(offsetof(struct outer, p) == 32, \"offsetof(struct outer, p)\");
+_Static_assert(sizeof(((struct outer *)0)->p) == 8, \"sizeof(((struct outer *)0)->p)\");
+_Static_assert(offsetof(struct outer, data) == 40, \"offsetof(struct outer, data)\");
+";
+        assert_eq!(src, expected);
+    }
+
+    #[test]
+    fn test_verification_unit() {
+        let src = DataModel::LP64.c_verification_unit();
+        assert!(src.starts_with("/* Verification of the LP64 data model. */\n"));
+        assert!(src.contains("_Static_assert(sizeof(_Bool) == 1, \"sizeof(_Bool)\");\n"));
+        assert!(src.contains("_Static_assert(_Alignof(double) == 8, \"_Alignof(double)\");\n"));
+        assert!(src.contains("_Static_assert((long)-1 < 0 == 1, \"(long)-1 < 0\");\n"));
+        assert!(src.contains("_Static_assert((uintptr_t)-1 < 0 == 0, \"(uintptr_t)-1 < 0\");\n"));
+        assert!(src.contains("_Static_assert(INT_MIN == (-2147483647 - 1), \"INT_MIN\");\n"));
+        assert!(src.contains("_Static_assert(SIZE_MAX == 18446744073709551615UL, \"SIZE_MAX\");\n"));
+        assert!(!src.contains("(char)-1"));
+        assert!(!src.contains("(CHAR_MIN =="));
+        assert!(!src.contains("__BYTE_ORDER__"));
+
+        let src = crate::Platform::LinuxS390x.target().c_verification_unit();
+        assert!(src.contains("_Static_assert((char)-1 < 0 == 0, \"(char)-1 < 0\");\n"));
+        assert!(src.contains("_Static_assert(CHAR_MIN == 0, \"CHAR_MIN\");\n"));
+        assert!(src.contains("_Static_assert(__BYTE_ORDER__ == __ORDER_BIG_ENDIAN__"));
+
+        let src = DataModel::IP16.c_verification_unit();
+        assert!(!src.contains("long"));
+        assert!(src.contains("_Static_assert(sizeof(ptrdiff_t) == 2, \"sizeof(ptrdiff_t)\");\n"));
+    }
+}
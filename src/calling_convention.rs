@@ -0,0 +1,496 @@
+//! The calling conventions of C functions.
+//!
+//! Code that calls into a target, like the stubs a debugger injects, needs
+//! more than the sizes of the types: the width of the registers arguments
+//! travel in, how far the stack has to be aligned at the call, who pops
+//! the arguments and how variadic arguments are promoted. The
+//! [`CallingConvention`]s here carry them for the C convention of each
+//! [`Profile`] in the database, and
+//! [`classify_argument`](CallingConvention::classify_argument) tells
+//! whether a record passed by value travels in registers.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::calling_convention::{self, Cleanup};
+//! let cc = data_models::database::find("i686-pc-windows-msvc").unwrap().calling_convention().unwrap();
+//! assert_eq!(cc, calling_convention::CDECL);
+//! assert_eq!(cc.stack_align, 4);
+//! assert_eq!(calling_convention::STDCALL.cleanup, Cleanup::Callee);
+//! assert_eq!(cc.variadic_slot_size(DataModel::ILP32, CType::Double), 8);
+//! ```
+
+use crate::database::{self, Profile};
+use crate::layout::{FieldType, Layout};
+use crate::{CType, DataModel, Platform};
+
+/// Cleanup is who removes the stack arguments after a call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Cleanup {
+    /// The caller, which lets it pass any number of arguments, as variadic
+    /// functions need.
+    Caller,
+    /// The called function, with `ret n`.
+    Callee,
+}
+
+/// CallingConvention describes how C functions are called on a target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CallingConvention {
+    /// The name of the convention, or of the ABI document defining it.
+    pub name: &'static str,
+    /// The width of the general purpose registers in bytes, which is also
+    /// the size of a stack slot for an argument.
+    pub register_width: usize,
+    /// The alignment of the stack pointer at a call instruction.
+    pub stack_align: usize,
+    /// Who removes the stack arguments.
+    pub cleanup: Cleanup,
+}
+
+/// convention returns a calling convention.
+const fn convention(
+    name: &'static str,
+    register_width: usize,
+    stack_align: usize,
+    cleanup: Cleanup,
+) -> CallingConvention {
+    CallingConvention {
+        name,
+        register_width,
+        stack_align,
+        cleanup,
+    }
+}
+
+/// The default convention of 32-bit x86 on Windows and UEFI.
+pub const CDECL: CallingConvention = convention("cdecl", 4, 4, Cleanup::Caller);
+/// The convention of the Win32 API, `WINAPI`.
+pub const STDCALL: CallingConvention = convention("stdcall", 4, 4, Cleanup::Callee);
+/// The i386 System V ABI, as GCC and Clang align the stack on Unix.
+pub const I386_SYSV: CallingConvention = convention("i386 System V", 4, 16, Cleanup::Caller);
+/// The System V AMD64 ABI.
+pub const X86_64_SYSV: CallingConvention = convention("System V AMD64", 8, 16, Cleanup::Caller);
+/// The x32 ABI: System V AMD64 with 32-bit pointers.
+pub const X32: CallingConvention = convention("System V x32", 8, 16, Cleanup::Caller);
+/// The Microsoft x64 convention.
+pub const WIN64: CallingConvention = convention("Microsoft x64", 8, 16, Cleanup::Caller);
+/// The Arm Procedure Call Standard of 32-bit Arm.
+pub const AAPCS: CallingConvention = convention("AAPCS", 4, 8, Cleanup::Caller);
+/// The Arm Procedure Call Standard of AArch64.
+pub const AAPCS64: CallingConvention = convention("AAPCS64", 8, 16, Cleanup::Caller);
+/// The 32-bit PowerPC System V ABI.
+pub const PPC_SYSV: CallingConvention = convention("PowerPC System V", 4, 16, Cleanup::Caller);
+/// The 64-bit PowerPC ELF ABIs, v1 and v2.
+pub const PPC64_ELF: CallingConvention = convention("PowerPC64 ELF", 8, 16, Cleanup::Caller);
+/// The 32-bit AIX ABI.
+pub const AIX32: CallingConvention = convention("AIX 32-bit", 4, 16, Cleanup::Caller);
+/// The 64-bit AIX ABI.
+pub const AIX64: CallingConvention = convention("AIX 64-bit", 8, 16, Cleanup::Caller);
+/// The s390x ELF ABI.
+pub const S390X: CallingConvention = convention("s390x ELF", 8, 8, Cleanup::Caller);
+/// The MIPS o32 ABI.
+pub const MIPS_O32: CallingConvention = convention("MIPS o32", 4, 8, Cleanup::Caller);
+/// The MIPS n64 ABI.
+pub const MIPS_N64: CallingConvention = convention("MIPS n64", 8, 16, Cleanup::Caller);
+/// The SPARC V8 ABI.
+pub const SPARC_V8: CallingConvention = convention("SPARC V8", 4, 8, Cleanup::Caller);
+/// The SPARC V9 ABI.
+pub const SPARC_V9: CallingConvention = convention("SPARC V9", 8, 16, Cleanup::Caller);
+/// The RISC-V ILP32 psABI.
+pub const RISCV32: CallingConvention = convention("RISC-V ILP32", 4, 16, Cleanup::Caller);
+/// The RISC-V LP64 psABI.
+pub const RISCV64: CallingConvention = convention("RISC-V LP64", 8, 16, Cleanup::Caller);
+/// The LoongArch LP64 ABI.
+pub const LOONGARCH64: CallingConvention = convention("LoongArch LP64", 8, 16, Cleanup::Caller);
+/// The Hexagon ABI.
+pub const HEXAGON: CallingConvention = convention("Hexagon", 4, 8, Cleanup::Caller);
+/// The 32-bit PA-RISC runtime architecture, with frames in 64-byte
+/// multiples on a stack growing up.
+pub const HPPA: CallingConvention = convention("PA-RISC 32-bit", 4, 64, Cleanup::Caller);
+/// The C ABI of wasm32, for the stack Clang keeps in linear memory.
+pub const WASM32: CallingConvention = convention("WebAssembly C", 4, 16, Cleanup::Caller);
+/// The C ABI of wasm64.
+pub const WASM64: CallingConvention = convention("WebAssembly C", 8, 16, Cleanup::Caller);
+/// The MSP430 EABI.
+pub const MSP430: CallingConvention = convention("MSP430 EABI", 2, 2, Cleanup::Caller);
+
+impl CallingConvention {
+    /// variadic_slot_size returns the bytes a variadic argument of type
+    /// `ty` takes on the stack: its size after the default argument
+    /// promotions, rounded up to whole registers.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::calling_convention::X86_64_SYSV;
+    /// assert_eq!(X86_64_SYSV.variadic_slot_size(DataModel::LP64, CType::Char), 8);
+    /// assert_eq!(X86_64_SYSV.variadic_slot_size(DataModel::LP64, CType::Float), 8);
+    /// ```
+    pub fn variadic_slot_size(&self, model: DataModel, ty: CType) -> usize {
+        let size = model.size(model.promote_argument(ty));
+        size.div_ceil(self.register_width) * self.register_width
+    }
+}
+
+/// RegisterClass is the kind of register part of a record travels in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RegisterClass {
+    /// A general purpose register.
+    Integer,
+    /// A floating point or vector register: SSE on x86-64, a V register on
+    /// AArch64.
+    Float,
+}
+
+/// Passing is how a record is passed to or returned from a function.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Passing {
+    /// In registers, one per eightbyte or per member, in order.
+    Registers(Vec<RegisterClass>),
+    /// Copied onto the stack by value.
+    Stack,
+    /// Through a pointer to a copy made by the caller, which for a result
+    /// is the buffer the callee writes to.
+    Reference,
+}
+
+impl Passing {
+    /// in_registers reports whether the record travels in registers.
+    pub fn in_registers(&self) -> bool {
+        matches!(self, Passing::Registers(_))
+    }
+}
+
+/// Scalar is a scalar member of a record flattened to its bytes.
+#[derive(Debug, Clone, Copy)]
+struct Scalar {
+    offset: usize,
+    size: usize,
+    float: bool,
+}
+
+/// scalars appends the scalars of a record laid out `base` bytes in,
+/// counting the storage unit of each bit-field as an integer.
+fn scalars(layout: &Layout, base: usize, out: &mut Vec<Scalar>) {
+    for field in layout.bases.iter().chain(&layout.fields) {
+        let offset = base + field.offset;
+        match field.bits {
+            Some(0) => {}
+            Some(_) => out.push(Scalar {
+                offset,
+                size: field.size,
+                float: false,
+            }),
+            None => element(&field.ty, field.nested.as_deref(), offset, field.size, out),
+        }
+    }
+}
+
+/// element appends the scalars of a value of type `ty`, `size` bytes at
+/// `offset`, whose record type, if any, is laid out as `nested`.
+fn element(
+    ty: &FieldType,
+    nested: Option<&Layout>,
+    offset: usize,
+    size: usize,
+    out: &mut Vec<Scalar>,
+) {
+    match ty {
+        FieldType::Array(element_ty, n) => {
+            let each = size.checked_div(*n).unwrap_or(0);
+            for i in 0..*n {
+                element(element_ty, nested, offset + i * each, each, out);
+            }
+        }
+        FieldType::FlexibleArray(_) => {}
+        FieldType::Record(_) | FieldType::Layout(_) => {
+            if let Some(nested) = nested {
+                scalars(nested, offset, out);
+            }
+        }
+        FieldType::Scalar(ty) => out.push(Scalar {
+            offset,
+            size,
+            float: matches!(ty, CType::Float | CType::Double),
+        }),
+        FieldType::Enum(_) | FieldType::Pointer(_) => out.push(Scalar {
+            offset,
+            size,
+            float: false,
+        }),
+    }
+}
+
+impl CallingConvention {
+    /// classify_argument returns how a record is passed by value, for the
+    /// conventions whose rules this module knows: System V AMD64 and x32,
+    /// Microsoft x64 and AAPCS64. The record is classified on its own, as
+    /// if registers were left for it.
+    ///
+    /// System V splits a record of up to 16 bytes into eightbytes, each in
+    /// an SSE register if it holds only `float`s and `double`s and in a
+    /// general purpose one otherwise; larger or misaligned records go on
+    /// the stack. Microsoft x64 passes records of 1, 2, 4 or 8 bytes in a
+    /// general purpose register and others by reference. AAPCS64 passes up
+    /// to four `float`s or four `double`s in V registers, other records of
+    /// up to 16 bytes in general purpose registers, and larger ones by
+    /// reference.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::Record;
+    /// use data_models::calling_convention::{Passing, RegisterClass::*, AAPCS64, WIN64, X86_64_SYSV};
+    /// let point = Record::new("point").field("x", CType::Double).field("y", CType::Double);
+    /// let layout = point.layout(DataModel::LP64).unwrap();
+    /// assert_eq!(X86_64_SYSV.classify_argument(&layout), Some(Passing::Registers(vec![Float, Float])));
+    /// assert_eq!(AAPCS64.classify_argument(&layout), Some(Passing::Registers(vec![Float, Float])));
+    /// assert_eq!(WIN64.classify_argument(&layout), Some(Passing::Reference));
+    /// ```
+    pub fn classify_argument(&self, layout: &Layout) -> Option<Passing> {
+        self.classify(layout, Passing::Stack)
+    }
+
+    /// classify_result returns how a record is returned by value: like an
+    /// argument, except that where an argument would go on the stack the
+    /// caller passes a pointer to the buffer for the result.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::Record;
+    /// use data_models::calling_convention::{Passing, X86_64_SYSV};
+    /// let triple = Record::new("triple")
+    ///     .field("a", CType::Long)
+    ///     .field("b", CType::Long)
+    ///     .field("c", CType::Long);
+    /// let layout = triple.layout(DataModel::LP64).unwrap();
+    /// assert_eq!(X86_64_SYSV.classify_argument(&layout), Some(Passing::Stack));
+    /// assert_eq!(X86_64_SYSV.classify_result(&layout), Some(Passing::Reference));
+    /// ```
+    pub fn classify_result(&self, layout: &Layout) -> Option<Passing> {
+        self.classify(layout, Passing::Reference)
+    }
+
+    /// classify classifies a record, which goes to `memory` if it does not
+    /// fit in registers.
+    fn classify(&self, layout: &Layout, memory: Passing) -> Option<Passing> {
+        let mut members = Vec::new();
+        scalars(layout, 0, &mut members);
+        if *self == X86_64_SYSV || *self == X32 {
+            let misaligned = members.iter().any(|s| s.size > 0 && s.offset % s.size != 0);
+            if layout.size > 16 || layout.dynamic || misaligned {
+                return Some(memory);
+            }
+            let classes = (0..layout.size.div_ceil(8))
+                .map(|i| {
+                    let overlaps = |s: &&Scalar| s.offset < 8 * i + 8 && s.offset + s.size > 8 * i;
+                    match members.iter().filter(overlaps).all(|s| s.float) {
+                        true => RegisterClass::Float,
+                        false => RegisterClass::Integer,
+                    }
+                })
+                .collect();
+            Some(Passing::Registers(classes))
+        } else if *self == WIN64 {
+            match layout.size {
+                1 | 2 | 4 | 8 => Some(Passing::Registers(vec![RegisterClass::Integer])),
+                _ => Some(Passing::Reference),
+            }
+        } else if *self == AAPCS64 {
+            let hfa = match members.first() {
+                Some(first) => {
+                    members.len() <= 4
+                        && members.iter().all(|s| s.float && s.size == first.size)
+                        && members.len() * first.size == layout.size
+                }
+                None => false,
+            };
+            if hfa {
+                return Some(Passing::Registers(vec![
+                    RegisterClass::Float;
+                    members.len()
+                ]));
+            }
+            match layout.size {
+                0..=16 => Some(Passing::Registers(vec![
+                    RegisterClass::Integer;
+                    layout.size.div_ceil(8)
+                ])),
+                _ => Some(Passing::Reference),
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl DataModel {
+    /// promote_argument applies the default argument promotions, which C
+    /// applies to the variadic arguments of a call and to the arguments of
+    /// a function without a prototype: the integer promotions, and `float`
+    /// to `double`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LP64.promote_argument(CType::Float), CType::Double);
+    /// assert_eq!(DataModel::LP64.promote_argument(CType::Short), CType::Int);
+    /// assert_eq!(DataModel::IP16L32.promote_argument(CType::UShort), CType::UInt);
+    /// ```
+    pub fn promote_argument(self, ty: CType) -> CType {
+        match ty {
+            CType::Float => CType::Double,
+            ty => self.promote(ty),
+        }
+    }
+}
+
+impl Profile {
+    /// calling_convention returns the convention of C functions on the
+    /// target, or `None` for an architecture this module does not know.
+    pub fn calling_convention(&self) -> Option<CallingConvention> {
+        let arch = self.triple.split('-').next().unwrap_or("");
+        let windows = self.triple.contains("-windows") || self.triple.contains("-uefi");
+        let aix = self.triple.contains("-aix");
+        Some(match arch {
+            "x86_64" if windows => WIN64,
+            "x86_64" if self.triple.ends_with("x32") => X32,
+            "x86_64" => X86_64_SYSV,
+            "i586" | "i686" if windows => CDECL,
+            "i586" | "i686" => I386_SYSV,
+            "aarch64" | "aarch64_be" | "arm64" | "arm64_32" => AAPCS64,
+            "powerpc" if aix => AIX32,
+            "powerpc" => PPC_SYSV,
+            "powerpc64" | "powerpc64le" if aix => AIX64,
+            "powerpc64" | "powerpc64le" => PPC64_ELF,
+            "s390x" => S390X,
+            "mips" | "mipsel" => MIPS_O32,
+            "mips64" | "mips64el" => MIPS_N64,
+            "sparc" => SPARC_V8,
+            "sparc64" | "sparcv9" => SPARC_V9,
+            "loongarch64" => LOONGARCH64,
+            "hexagon" => HEXAGON,
+            "hppa" => HPPA,
+            "wasm32" => WASM32,
+            "wasm64" => WASM64,
+            "msp430" => MSP430,
+            arch if arch.starts_with("riscv32") => RISCV32,
+            arch if arch.starts_with("riscv64") => RISCV64,
+            arch if arch.starts_with("arm") || arch.starts_with("thumb") => AAPCS,
+            _ => return None,
+        })
+    }
+}
+
+impl Platform {
+    /// calling_convention returns the convention of C functions on the
+    /// platform.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::calling_convention::{AAPCS64, WIN64};
+    /// assert_eq!(Platform::Windows64.calling_convention(), WIN64);
+    /// assert_eq!(Platform::MacAarch64.calling_convention(), AAPCS64);
+    /// ```
+    pub fn calling_convention(self) -> CallingConvention {
+        match database::find(self.triple()).and_then(Profile::calling_convention) {
+            Some(cc) => cc,
+            None => unreachable!("{} is in the database", self.triple()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::PROFILES;
+
+    #[test]
+    fn test_profiles() {
+        for profile in PROFILES {
+            let cc = match profile.calling_convention() {
+                Some(cc) => cc,
+                None => {
+                    let gpu = ["nvptx", "amdgcn", "spir"]
+                        .iter()
+                        .any(|arch| profile.triple.starts_with(arch));
+                    assert!(profile.os == "none" || gpu, "{}", profile.triple);
+                    continue;
+                }
+            };
+            let arm = profile.triple.starts_with("arm") || profile.triple.starts_with("thumb");
+            assert_eq!(
+                cc == AAPCS,
+                arm && cc.register_width == 4,
+                "{}",
+                profile.triple
+            );
+            assert!(cc.stack_align.is_power_of_two());
+            assert!(cc.register_width >= profile.data_model.size(CType::Int));
+        }
+        assert_eq!(Platform::LinuxI686.calling_convention(), I386_SYSV);
+        assert_eq!(Platform::LinuxX32.calling_convention(), X32);
+        assert_eq!(Platform::Aix32.calling_convention(), AIX32);
+        assert_eq!(Platform::LinuxMips.calling_convention().stack_align, 8);
+        let msp430 = database::find("msp430-none-elf")
+            .unwrap()
+            .calling_convention()
+            .unwrap();
+        assert_eq!(
+            msp430.variadic_slot_size(DataModel::IP16L32, CType::Char),
+            2
+        );
+        assert_eq!(
+            msp430.variadic_slot_size(DataModel::IP16L32, CType::Long),
+            4
+        );
+        assert_eq!(AAPCS.variadic_slot_size(DataModel::ILP32, CType::UChar), 4);
+    }
+
+    #[test]
+    fn test_classify() {
+        use crate::layout::Record;
+        use RegisterClass::*;
+        let mixed = Record::new("mixed")
+            .field("n", CType::Int)
+            .field("f", CType::Float)
+            .field("d", CType::Double);
+        let layout = mixed.layout(DataModel::LP64).unwrap();
+        let sysv = X86_64_SYSV.classify_argument(&layout);
+        assert_eq!(sysv, Some(Passing::Registers(vec![Integer, Float])));
+        let aapcs64 = AAPCS64.classify_argument(&layout);
+        assert_eq!(aapcs64, Some(Passing::Registers(vec![Integer, Integer])));
+        assert_eq!(WIN64.classify_result(&layout), Some(Passing::Reference));
+        assert_eq!(AAPCS.classify_argument(&layout), None);
+
+        let floats = Record::new("v").field("xyz", FieldType::array(CType::Float, 3));
+        let layout = floats.layout(DataModel::LP64).unwrap();
+        let sysv = X86_64_SYSV.classify_argument(&layout);
+        assert_eq!(sysv, Some(Passing::Registers(vec![Float, Float])));
+        let aapcs64 = AAPCS64.classify_result(&layout);
+        assert_eq!(aapcs64, Some(Passing::Registers(vec![Float; 3])));
+
+        let packed = Record::new("p")
+            .field("c", CType::Char)
+            .field("n", CType::Int)
+            .packed();
+        let layout = packed.layout(DataModel::LP64).unwrap();
+        assert_eq!(X86_64_SYSV.classify_argument(&layout), Some(Passing::Stack));
+        let win64 = WIN64.classify_argument(&layout);
+        assert_eq!(win64, Some(Passing::Reference));
+        let pair = Record::new("pair")
+            .field("a", CType::Int)
+            .field("b", CType::Float);
+        let layout = pair.layout(DataModel::LLP64).unwrap();
+        assert!(WIN64.classify_argument(&layout).unwrap().in_registers());
+        let big = Record::new("big").field("a", FieldType::array(CType::Double, 5));
+        let layout = big.layout(DataModel::LP64).unwrap();
+        assert_eq!(AAPCS64.classify_argument(&layout), Some(Passing::Reference));
+    }
+}
@@ -0,0 +1,327 @@
+//! A C API over the tables, for C and C++ build tooling.
+//!
+//! Build the crate as a shared library with the `capi` feature and write
+//! out the header for it:
+//!
+//! ```text
+//! cargo rustc --release --features capi --crate-type cdylib
+//! ```
+//!
+//! ```ignore
+//! std::fs::write("data_models.h", data_models::capi::header())?;
+//! ```
+//!
+//! Data models and C types are passed as the `dm_model` and `dm_ctype`
+//! constants of the header, in the order of [`DataModel::ALL`] and
+//! [`CType::ALL`]. Records are built field by field and laid out for a
+//! model:
+//!
+//! ```c
+//! dm_record *point = dm_record_new(0);
+//! dm_record_add(point, DM_INT, 1);
+//! dm_record_add(point, DM_LONG, 1);
+//! size_t size, align, offsets[2];
+//! dm_record_layout(point, DM_LLP64, &size, &align, offsets); /* 8, 4, {0, 4} */
+//! dm_record_free(point);
+//! ```
+
+use crate::layout::{Field, FieldType, Record};
+use crate::{CType, DataModel, Platform};
+use std::convert::TryFrom;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+/// dm_record is a record being built through the C API.
+#[allow(non_camel_case_types)]
+pub struct dm_record {
+    record: Record,
+}
+
+/// model returns the data model of a `dm_model` constant.
+fn model(model: c_int) -> Option<DataModel> {
+    usize::try_from(model)
+        .ok()
+        .and_then(|i| DataModel::ALL.get(i))
+        .copied()
+}
+
+/// c_type returns the C type of a `dm_ctype` constant.
+fn c_type(ty: c_int) -> Option<CType> {
+    usize::try_from(ty)
+        .ok()
+        .and_then(|i| CType::ALL.get(i))
+        .copied()
+}
+
+/// dm_size_of returns the size of a C type in a data model, or 0 if the
+/// model lacks it or either constant is unknown.
+#[no_mangle]
+pub extern "C" fn dm_size_of(model: c_int, ty: c_int) -> usize {
+    match (self::model(model), c_type(ty)) {
+        (Some(model), Some(ty)) => model.size(ty),
+        _ => 0,
+    }
+}
+
+/// dm_align_of returns the alignment of a C type in a data model, or 0 if
+/// the model lacks it or either constant is unknown.
+#[no_mangle]
+pub extern "C" fn dm_align_of(model: c_int, ty: c_int) -> usize {
+    match (self::model(model), c_type(ty)) {
+        (Some(model), Some(ty)) => model.align(ty),
+        _ => 0,
+    }
+}
+
+/// dm_model_name returns the name of a data model, e.g. `"LP64"`, or NULL
+/// if the constant is unknown.
+#[no_mangle]
+pub extern "C" fn dm_model_name(model: c_int) -> *const c_char {
+    const NAMES: [&[u8]; 8] = [
+        b"IP16\0",
+        b"IP16L32\0",
+        b"LP32\0",
+        b"ILP32\0",
+        b"LLP64\0",
+        b"LP64\0",
+        b"ILP64\0",
+        b"SILP64\0",
+    ];
+    match self::model(model) {
+        Some(model) => NAMES[DataModel::ALL.iter().position(|&m| m == model).unwrap()]
+            .as_ptr()
+            .cast(),
+        None => ptr::null(),
+    }
+}
+
+/// dm_from_triple returns the data model of a known target triple, or -1.
+///
+/// # Safety
+///
+/// `triple` must be NULL or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn dm_from_triple(triple: *const c_char) -> c_int {
+    if triple.is_null() {
+        return -1;
+    }
+    let triple = CStr::from_ptr(triple).to_bytes();
+    let model = Platform::ALL
+        .iter()
+        .find(|platform| platform.triple().as_bytes() == triple)
+        .map(|platform| platform.data_model());
+    match model.and_then(|model| DataModel::ALL.iter().position(|&m| m == model)) {
+        Some(i) => i as c_int,
+        None => -1,
+    }
+}
+
+/// dm_record_new returns a new empty struct, or union if `is_union` is
+/// not 0, to be freed with [`dm_record_free`].
+#[no_mangle]
+pub extern "C" fn dm_record_new(is_union: c_int) -> *mut dm_record {
+    let record = match is_union {
+        0 => Record::new("record"),
+        _ => Record::union("record"),
+    };
+    Box::into_raw(Box::new(dm_record { record }))
+}
+
+/// dm_record_add appends a field of `count` elements of a C type, a
+/// scalar if `count` is 1, and returns its index, or -1 if the type is
+/// unknown.
+///
+/// # Safety
+///
+/// `record` must come from [`dm_record_new`] and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn dm_record_add(record: *mut dm_record, ty: c_int, count: usize) -> c_int {
+    let (record, ty) = match (record.as_mut(), c_type(ty)) {
+        (Some(record), Some(ty)) => (&mut record.record, ty),
+        _ => return -1,
+    };
+    let i = record.fields.len();
+    let name = format!("f{}", i);
+    let ty = match count {
+        1 => FieldType::Scalar(ty),
+        n => FieldType::array(ty, n),
+    };
+    record.fields.push(Field::new(&name, ty));
+    i as c_int
+}
+
+/// dm_record_layout lays out a record for a data model, storing its size,
+/// alignment and the offset of each field. It returns 0, or -1 if the
+/// model is unknown or lacks the type of a field, or if the record is too
+/// large for `size_t`.
+///
+/// # Safety
+///
+/// `record` must come from [`dm_record_new`] and not have been freed.
+/// `size` and `align` must be NULL or valid for writes, and `offsets` NULL
+/// or valid for writes of one `size_t` per field.
+#[no_mangle]
+pub unsafe extern "C" fn dm_record_layout(
+    record: *const dm_record,
+    model: c_int,
+    size: *mut usize,
+    align: *mut usize,
+    offsets: *mut usize,
+) -> c_int {
+    let layout = match (record.as_ref(), self::model(model)) {
+        (Some(record), Some(model)) => record.record.layout(model),
+        _ => return -1,
+    };
+    let layout = match layout {
+        Ok(layout) => layout,
+        Err(_) => return -1,
+    };
+    if !size.is_null() {
+        *size = layout.size;
+    }
+    if !align.is_null() {
+        *align = layout.align;
+    }
+    if !offsets.is_null() {
+        for (i, field) in layout.fields.iter().enumerate() {
+            *offsets.add(i) = field.offset;
+        }
+    }
+    0
+}
+
+/// dm_record_free frees a record from [`dm_record_new`].
+///
+/// # Safety
+///
+/// `record` must be NULL or come from [`dm_record_new`] and not have been
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn dm_record_free(record: *mut dm_record) {
+    if !record.is_null() {
+        drop(Box::from_raw(record));
+    }
+}
+
+/// header returns the C header declaring the API, with the `dm_model` and
+/// `dm_ctype` constants generated from [`DataModel::ALL`] and
+/// [`CType::ALL`].
+///
+/// # Example
+/// ```
+/// let header = data_models::capi::header();
+/// assert!(header.contains("    DM_LP64 = 5,\n"));
+/// assert!(header.contains("    DM_ULONGLONG = 11,\n"));
+/// assert!(header.contains("size_t dm_size_of(int model, int ctype);\n"));
+/// ```
+pub fn header() -> String {
+    let mut src = String::from(
+        "/* The C API of data_models, generated by data_models::capi::header(). */\n\
+         #ifndef DATA_MODELS_H\n#define DATA_MODELS_H\n\n#include <stddef.h>\n\n\
+         #ifdef __cplusplus\nextern \"C\" {\n#endif\n\nenum dm_model {\n",
+    );
+    for (i, model) in DataModel::ALL.iter().enumerate() {
+        src += &format!("    DM_{:?} = {},\n", model, i);
+    }
+    src += "};\n\nenum dm_ctype {\n";
+    for (i, ty) in CType::ALL.iter().enumerate() {
+        src += &format!("    DM_{} = {},\n", format!("{:?}", ty).to_uppercase(), i);
+    }
+    src + "};\n\n\
+           typedef struct dm_record dm_record;\n\n\
+           size_t dm_size_of(int model, int ctype);\n\
+           size_t dm_align_of(int model, int ctype);\n\
+           const char *dm_model_name(int model);\n\
+           int dm_from_triple(const char *triple);\n\
+           dm_record *dm_record_new(int is_union);\n\
+           int dm_record_add(dm_record *record, int ctype, size_t count);\n\
+           int dm_record_layout(const dm_record *record, int model, size_t *size, size_t *align,\n\
+           \x20                    size_t *offsets);\n\
+           void dm_record_free(dm_record *record);\n\n\
+           #ifdef __cplusplus\n}\n#endif\n\n#endif\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// id returns the `dm_model` or `dm_ctype` constant of a value.
+    fn id<T: PartialEq>(all: &[T], value: T) -> c_int {
+        all.iter().position(|v| *v == value).unwrap() as c_int
+    }
+
+    #[test]
+    fn test_size_of() {
+        let lp64 = id(DataModel::ALL, DataModel::LP64);
+        let long = id(CType::ALL, CType::Long);
+        assert_eq!(dm_size_of(lp64, long), 8);
+        assert_eq!(dm_align_of(id(DataModel::ALL, DataModel::LLP64), long), 4);
+        assert_eq!(dm_size_of(id(DataModel::ALL, DataModel::IP16), long), 0);
+        assert_eq!(dm_size_of(-1, long), 0);
+        assert_eq!(dm_size_of(lp64, 99), 0);
+        let name = unsafe { CStr::from_ptr(dm_model_name(lp64)) };
+        assert_eq!(name.to_str(), Ok("LP64"));
+        assert!(dm_model_name(8).is_null());
+        let triple = b"i686-unknown-linux-gnu\0".as_ptr().cast();
+        assert_eq!(
+            unsafe { dm_from_triple(triple) },
+            id(DataModel::ALL, DataModel::ILP32)
+        );
+        assert_eq!(unsafe { dm_from_triple(b"vax\0".as_ptr().cast()) }, -1);
+        assert_eq!(unsafe { dm_from_triple(ptr::null()) }, -1);
+    }
+
+    #[test]
+    fn test_record() {
+        let llp64 = id(DataModel::ALL, DataModel::LLP64);
+        let (mut size, mut align, mut offsets) = (0, 0, [0; 3]);
+        unsafe {
+            let record = dm_record_new(0);
+            assert_eq!(dm_record_add(record, id(CType::ALL, CType::Char), 1), 0);
+            assert_eq!(dm_record_add(record, id(CType::ALL, CType::Pointer), 2), 1);
+            assert_eq!(dm_record_add(record, id(CType::ALL, CType::Long), 1), 2);
+            assert_eq!(dm_record_add(record, 99, 1), -1);
+            let status =
+                dm_record_layout(record, llp64, &mut size, &mut align, offsets.as_mut_ptr());
+            assert_eq!(status, 0);
+            let ip16 = id(DataModel::ALL, DataModel::IP16);
+            assert_eq!(
+                dm_record_layout(record, ip16, &mut size, ptr::null_mut(), ptr::null_mut()),
+                -1
+            );
+            dm_record_free(record);
+            dm_record_free(ptr::null_mut());
+        }
+        assert_eq!((size, align, offsets), (32, 8, [0, 8, 24]));
+
+        unsafe {
+            let record = dm_record_new(0);
+            dm_record_add(record, id(CType::ALL, CType::Int), usize::MAX / 2);
+            let lp64 = id(DataModel::ALL, DataModel::LP64);
+            let status = dm_record_layout(record, lp64, &mut size, &mut align, ptr::null_mut());
+            assert_eq!(status, -1);
+            dm_record_free(record);
+        }
+    }
+
+    #[test]
+    fn test_header() {
+        let header = header();
+        for name in [
+            "dm_size_of(",
+            "dm_align_of(",
+            "dm_model_name(",
+            "dm_from_triple(",
+            "dm_record_new(",
+            "dm_record_add(",
+            "dm_record_layout(",
+            "dm_record_free(",
+        ] {
+            assert!(header.contains(name), "{}", name);
+        }
+        assert!(header.contains("    DM_IP16 = 0,\n"));
+        assert!(header.contains("    DM_DOUBLE = 14,\n"));
+        assert!(header.ends_with("#endif\n"));
+    }
+}
@@ -0,0 +1,251 @@
+//! Layouts computed at compile time.
+//!
+//! [`Record::layout`](crate::layout::Record::layout) runs when the program
+//! does. An FFI struct checked against a foreign target is better checked
+//! when it is compiled, which takes `const` layouts: [`ConstLayout`] places
+//! fields with `const fn`s, and with the `derive` feature
+//! `#[derive(CLayout)]` builds one for a Rust struct, mapping its
+//! `c_int`, `c_long` and pointer fields through the data model named by a
+//! `#[c_layout(...)]` attribute, or the native one.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::clayout::{ConstField, ConstLayout};
+//! const FIELDS: [ConstField; 2] = ConstField::place([
+//!     ConstField::of("tag", CType::Char, DataModel::LP64),
+//!     ConstField::of("len", CType::Long, DataModel::LP64),
+//! ]);
+//! const LAYOUT: ConstLayout = ConstLayout::new("msg", &FIELDS);
+//! const _: () = assert!(LAYOUT.size == 16);
+//! const _: () = assert!(matches!(LAYOUT.offset_of("len"), Some(8)));
+//! ```
+
+use crate::{CType, DataModel};
+
+/// CLayout is implemented by types with a layout known at compile time,
+/// usually with `#[derive(CLayout)]`.
+pub trait CLayout {
+    /// The layout of the type on its data model.
+    const LAYOUT: ConstLayout;
+}
+
+/// ConstField is the placement of one field of a [`ConstLayout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstField {
+    /// The field name.
+    pub name: &'static str,
+    /// `offsetof` the field, once placed.
+    pub offset: usize,
+    /// `sizeof` the field.
+    pub size: usize,
+    /// The alignment of the field.
+    pub align: usize,
+}
+
+impl ConstField {
+    /// new returns an unplaced field of `size` bytes aligned to `align`.
+    pub const fn new(name: &'static str, size: usize, align: usize) -> ConstField {
+        ConstField {
+            name,
+            offset: 0,
+            size,
+            align,
+        }
+    }
+
+    /// of returns an unplaced field of the scalar type `ty` on `model`,
+    /// naturally aligned.
+    pub const fn of(name: &'static str, ty: CType, model: DataModel) -> ConstField {
+        ConstField::new(name, model.size(ty), model.align(ty))
+    }
+
+    /// array returns an unplaced field of `n` elements with the size and
+    /// alignment of `element`.
+    pub const fn array(name: &'static str, element: ConstField, n: usize) -> ConstField {
+        ConstField::new(name, element.size * n, element.align)
+    }
+
+    /// place sets the offsets of the fields of a struct in declaration
+    /// order, each at the next multiple of its alignment.
+    pub const fn place<const N: usize>(mut fields: [ConstField; N]) -> [ConstField; N] {
+        let mut end: usize = 0;
+        let mut i = 0;
+        while i < N {
+            let align = if fields[i].align == 0 {
+                1
+            } else {
+                fields[i].align
+            };
+            fields[i].offset = end.div_ceil(align) * align;
+            end = fields[i].offset + fields[i].size;
+            i += 1;
+        }
+        fields
+    }
+}
+
+/// ConstLayout is the layout of a struct computed at compile time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstLayout {
+    /// The struct name.
+    pub name: &'static str,
+    /// `sizeof` the struct, including tail padding.
+    pub size: usize,
+    /// `_Alignof` the struct.
+    pub align: usize,
+    /// The fields, placed by [`ConstField::place`].
+    pub fields: &'static [ConstField],
+}
+
+impl ConstLayout {
+    /// new returns the layout of a struct with the placed `fields`.
+    pub const fn new(name: &'static str, fields: &'static [ConstField]) -> ConstLayout {
+        let (mut end, mut align): (usize, usize) = (0, 1);
+        let mut i = 0;
+        while i < fields.len() {
+            if fields[i].offset + fields[i].size > end {
+                end = fields[i].offset + fields[i].size;
+            }
+            if fields[i].align > align {
+                align = fields[i].align;
+            }
+            i += 1;
+        }
+        ConstLayout {
+            name,
+            size: end.div_ceil(align) * align,
+            align,
+            fields,
+        }
+    }
+
+    /// as_field returns an unplaced field called `name` holding a struct of
+    /// this layout, to nest it in another layout.
+    pub const fn as_field(&self, name: &'static str) -> ConstField {
+        ConstField::new(name, self.size, self.align)
+    }
+
+    /// offset_of returns the offset of the field called `name`.
+    pub const fn offset_of(&self, name: &str) -> Option<usize> {
+        let mut i = 0;
+        while i < self.fields.len() {
+            if str_eq(self.fields[i].name, name) {
+                return Some(self.fields[i].offset);
+            }
+            i += 1;
+        }
+        None
+    }
+}
+
+/// str_eq compares two strings in a `const fn`.
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{FieldType, Record};
+
+    const POINT: [ConstField; 2] = ConstField::place([
+        ConstField::of("x", CType::Short, DataModel::LLP64),
+        ConstField::of("y", CType::Long, DataModel::LLP64),
+    ]);
+
+    #[test]
+    fn test_const_layout() {
+        const LAYOUT: ConstLayout = ConstLayout::new("point", &POINT);
+        assert_eq!((LAYOUT.size, LAYOUT.align), (8, 4));
+        assert_eq!(LAYOUT.offset_of("y"), Some(4));
+        assert_eq!(LAYOUT.offset_of("z"), None);
+
+        const PATH: [ConstField; 3] = ConstField::place([
+            ConstField::of("len", CType::Char, DataModel::LLP64),
+            ConstField::array("points", LAYOUT.as_field("points"), 3),
+            ConstField::of("next", CType::Pointer, DataModel::LLP64),
+        ]);
+        const OUTER: ConstLayout = ConstLayout::new("path", &PATH);
+        let point = Record::new("point")
+            .field("x", CType::Short)
+            .field("y", CType::Long);
+        let path = Record::new("path")
+            .field("len", CType::Char)
+            .field("points", FieldType::array(point, 3))
+            .field("next", CType::Pointer)
+            .layout(DataModel::LLP64)
+            .unwrap();
+        assert_eq!((OUTER.size, OUTER.align), (path.size, path.align));
+        for (field, expected) in OUTER.fields.iter().zip(path.fields.iter()) {
+            assert_eq!(field.offset, expected.offset);
+        }
+        assert_eq!(ConstLayout::new("empty", &[]).size, 0);
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn test_derive() {
+        use crate::layout::FieldType;
+        use std::os::raw::{c_char, c_long, c_short};
+
+        #[derive(crate::CLayout)]
+        #[c_layout(LLP64)]
+        #[allow(dead_code)]
+        struct Point {
+            x: c_short,
+            pub y: c_long,
+        }
+
+        #[derive(crate::CLayout)]
+        #[c_layout(LLP64)]
+        #[allow(dead_code)]
+        struct Path {
+            len: c_char,
+            points: [Point; 3],
+            next: *const Path,
+            visit: Option<extern "C" fn(*mut Point) -> i32>,
+            id: u64,
+        }
+
+        #[derive(crate::CLayout)]
+        #[allow(dead_code)]
+        struct Native {
+            tag: u8,
+            size: usize,
+        }
+
+        let point = Record::new("point")
+            .field("x", CType::Short)
+            .field("y", CType::Long);
+        let path = Record::new("path")
+            .field("len", CType::Char)
+            .field("points", FieldType::array(point, 3))
+            .field("next", CType::Pointer)
+            .field("visit", CType::Pointer)
+            .field("id", CType::LongLong)
+            .layout(DataModel::LLP64)
+            .unwrap();
+        let layout = <Path as CLayout>::LAYOUT;
+        assert_eq!(layout.name, "Path");
+        assert_eq!((layout.size, layout.align), (path.size, path.align));
+        for (field, expected) in layout.fields.iter().zip(path.fields.iter()) {
+            assert_eq!(
+                (field.name, field.offset),
+                (expected.name.as_str(), expected.offset)
+            );
+        }
+        assert_eq!(Native::LAYOUT.size, std::mem::size_of::<Native>());
+    }
+}
@@ -0,0 +1,167 @@
+//! The compilers whose conventions shape record layouts.
+//!
+//! The data model fixes the sizes of the types, but the compiler decides
+//! the rest: how bit-fields share storage, what `long double` is, how
+//! large an enum is and how tightly records are packed by default. Two
+//! compilers on the same operating system can disagree, like MinGW GCC
+//! and Open Watcom on 32-bit Windows. [`Compiler::abi`] and
+//! [`Compiler::target`] combine a compiler with a data model.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::layout::{Enum, FieldType, Record};
+//! let flags = Enum::new("flags").value("A", 1).value("B", 2);
+//! let record = Record::new("s").field("c", CType::Char).field("f", FieldType::Enum(flags));
+//! let gcc = record.layout(Compiler::Gcc.abi(DataModel::ILP32)).unwrap();
+//! let watcom = record.layout(Compiler::Watcom.abi(DataModel::ILP32)).unwrap();
+//! assert_eq!((gcc.size, watcom.size), (8, 2));
+//! ```
+
+use crate::layout::{Abi, BitFieldRules};
+use crate::{DataModel, LongDouble, TargetModel};
+
+/// Compiler is a C compiler family with its own layout conventions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Compiler {
+    /// GCC.
+    Gcc,
+    /// Clang, which follows GCC except where it imitates MSVC.
+    Clang,
+    /// Microsoft Visual C++.
+    Msvc,
+    /// Borland C++ and Turbo C.
+    Borland,
+    /// Watcom and Open Watcom C/C++.
+    Watcom,
+    /// IBM XL C on AIX.
+    XlC,
+}
+
+impl Compiler {
+    /// Every compiler known to this crate.
+    pub const ALL: &'static [Compiler] = &[
+        Compiler::Gcc,
+        Compiler::Clang,
+        Compiler::Msvc,
+        Compiler::Borland,
+        Compiler::Watcom,
+        Compiler::XlC,
+    ];
+
+    /// name returns the usual name of the compiler.
+    pub fn name(self) -> &'static str {
+        match self {
+            Compiler::Gcc => "GCC",
+            Compiler::Clang => "Clang",
+            Compiler::Msvc => "MSVC",
+            Compiler::Borland => "Borland C++",
+            Compiler::Watcom => "Open Watcom",
+            Compiler::XlC => "IBM XL C",
+        }
+    }
+
+    /// bitfields returns how the compiler allocates bit-fields. The
+    /// Windows compilers start a new storage unit when the declared type
+    /// changes size, as MSVC does.
+    pub fn bitfields(self) -> BitFieldRules {
+        match self {
+            Compiler::Msvc | Compiler::Borland | Compiler::Watcom => BitFieldRules::Msvc,
+            Compiler::Gcc | Compiler::Clang | Compiler::XlC => BitFieldRules::Itanium,
+        }
+    }
+
+    /// long_double returns the format of `long double`, or `None` where it
+    /// depends on the target, as for GCC and Clang. MSVC, Watcom and XL C
+    /// (without `-qlongdouble`) make it a `double`; Borland uses the x87
+    /// format.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Compiler::Msvc.long_double(), Some(LongDouble::Binary64));
+    /// assert_eq!(Compiler::Borland.long_double(), Some(LongDouble::X87Extended));
+    /// assert_eq!(Compiler::Gcc.long_double(), None);
+    /// ```
+    pub fn long_double(self) -> Option<LongDouble> {
+        match self {
+            Compiler::Msvc | Compiler::Watcom | Compiler::XlC => Some(LongDouble::Binary64),
+            Compiler::Borland => Some(LongDouble::X87Extended),
+            Compiler::Gcc | Compiler::Clang => None,
+        }
+    }
+
+    /// short_enums reports whether enums default to the smallest integer
+    /// type holding their values, as in Watcom.
+    pub fn short_enums(self) -> bool {
+        self == Compiler::Watcom
+    }
+
+    /// pack returns the default packing of records: 8 for MSVC's `/Zp8`,
+    /// and 1 for classic Borland, which aligns nothing unless given `-a`.
+    pub fn pack(self) -> Option<usize> {
+        match self {
+            Compiler::Msvc => Some(8),
+            Compiler::Borland => Some(1),
+            Compiler::Gcc | Compiler::Clang | Compiler::Watcom | Compiler::XlC => None,
+        }
+    }
+
+    /// abi returns the layout rules of the compiler for a data model.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::{BitFieldRules, Record};
+    /// let abi = Compiler::Msvc.abi(DataModel::LLP64);
+    /// assert_eq!(abi.bitfields(), BitFieldRules::Msvc);
+    /// let record = Record::new("s").field("c", CType::Char).field("n", CType::Int);
+    /// assert_eq!(record.layout(Compiler::Borland.abi(DataModel::IP16)).unwrap().size, 3);
+    /// ```
+    pub fn abi(self, model: DataModel) -> Abi {
+        let mut abi = Abi::new(model).with_bitfields(self.bitfields());
+        if let Some(n) = self.pack() {
+            abi = abi.with_pack(n);
+        }
+        match self.short_enums() {
+            true => abi.with_short_enums(),
+            false => abi,
+        }
+    }
+
+    /// target returns a target of a data model laid out by the compiler,
+    /// with the compiler's `long double` where it has one of its own.
+    pub fn target(self, model: DataModel) -> TargetModel {
+        let target = TargetModel::new(model).with_abi(self.abi(model));
+        match self.long_double() {
+            Some(long_double) => target.with_long_double(long_double),
+            None => target,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Record;
+    use crate::CType;
+
+    #[test]
+    fn test_compilers() {
+        let record = Record::new("s")
+            .bitfield("a", CType::UChar, 4)
+            .bitfield("b", CType::UInt, 4)
+            .field("d", CType::Double);
+        let size = |compiler: Compiler| record.layout(compiler.abi(DataModel::ILP32)).unwrap().size;
+        assert_eq!(size(Compiler::Gcc), 16);
+        assert_eq!(size(Compiler::Msvc), 16);
+        assert_eq!(size(Compiler::Borland), 13);
+        let names: Vec<_> = Compiler::ALL.iter().map(|c| c.name()).collect();
+        assert_eq!(names.len(), 6);
+        let target = Compiler::XlC.target(DataModel::LP64);
+        assert_eq!(target.long_double, LongDouble::Binary64);
+        assert_eq!(target.abi, Compiler::XlC.abi(DataModel::LP64));
+        assert!(Compiler::Watcom.target(DataModel::ILP32).abi.short_enums());
+    }
+}
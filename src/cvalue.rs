@@ -0,0 +1,311 @@
+//! Integers that behave like the C types of a data model.
+//!
+//! [`CValue<M, T>`] holds a value of the C type `T`, like [`Long`], in the
+//! data model `M`, like [`Lp64`](crate::Lp64), so emulators and sandboxes
+//! can compute what the target computes without masking by hand. The
+//! aliases [`CInt`], [`CLong`] and so on name the common ones. Using a type
+//! the model lacks, like `CLong<Ip16>`, fails to compile.
+//!
+//! An operator on two values of the same type computes as C does after the
+//! integer promotions and converts the result back to the type, as in `a =
+//! a + b`. Overflow wraps the way the hardware does, including for signed
+//! types where C leaves it undefined, and right shifts of negative values
+//! are arithmetic. Division by zero and shifts by at least the width of
+//! the promoted type panic.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::cvalue::{CInt, CLong, CUShort};
+//! let big = CLong::<Llp64>::new(i32::MAX as i128) + CLong::new(1);
+//! assert_eq!(big.get(), i32::MIN as i128);
+//! let big = CLong::<Lp64>::new(i32::MAX as i128) + CLong::new(1);
+//! assert_eq!(big.get(), 1 << 31);
+//! assert_eq!((CUShort::<Lp64>::new(0) - CUShort::new(1)).get(), 0xffff);
+//! assert_eq!(big.try_convert::<Int>(), None::<CInt<Lp64>>);
+//! ```
+//!
+//! ```compile_fail
+//! use data_models::*;
+//! use data_models::cvalue::CLong;
+//! let long = CLong::<Ip16>::new(1);
+//! ```
+
+use crate::models::StaticModel;
+use crate::{
+    CTypeInfo, Char, Int, Long, LongLong, Pointer, SChar, Short, UChar, UInt, ULong, ULongLong,
+    UShort,
+};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Not, Rem, Shl, Shr, Sub};
+
+/// CValue is a value of the C type `T` in the data model `M`.
+pub struct CValue<M, T> {
+    value: i128,
+    marker: PhantomData<fn() -> (M, T)>,
+}
+
+/// `char`, which is signed here whatever [`TargetModel::char_signed`] of
+/// the target, since a [`StaticModel`] only fixes the data model.
+///
+/// [`TargetModel::char_signed`]: crate::TargetModel::char_signed
+pub type CChar<M> = CValue<M, Char>;
+/// `signed char`.
+pub type CSChar<M> = CValue<M, SChar>;
+/// `unsigned char`.
+pub type CUChar<M> = CValue<M, UChar>;
+/// `short`.
+pub type CShort<M> = CValue<M, Short>;
+/// `unsigned short`.
+pub type CUShort<M> = CValue<M, UShort>;
+/// `int`.
+pub type CInt<M> = CValue<M, Int>;
+/// `unsigned int`.
+pub type CUInt<M> = CValue<M, UInt>;
+/// `long`.
+pub type CLong<M> = CValue<M, Long>;
+/// `unsigned long`.
+pub type CULong<M> = CValue<M, ULong>;
+/// `long long`.
+pub type CLongLong<M> = CValue<M, LongLong>;
+/// `unsigned long long`.
+pub type CULongLong<M> = CValue<M, ULongLong>;
+/// A pointer as an unsigned address.
+pub type CPointer<M> = CValue<M, Pointer>;
+
+impl<M: StaticModel, T: CTypeInfo> CValue<M, T> {
+    /// The width of the type in bits, which stops the build if the model
+    /// lacks the type.
+    const BITS: usize = {
+        assert!(M::MODEL.has(T::C_TYPE), "the data model lacks the type");
+        8 * M::MODEL.size(T::C_TYPE)
+    };
+
+    /// new converts `value` to the type as a C cast does, keeping the low
+    /// bits that fit its width.
+    pub fn new(value: i128) -> Self {
+        CValue {
+            value: wrap(value, Self::BITS, T::SIGNED),
+            marker: PhantomData,
+        }
+    }
+
+    /// try_new returns the value if the type represents it, and `None`
+    /// otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::cvalue::CInt;
+    /// assert!(CInt::<Lp32>::try_new(40000).is_none());
+    /// assert_eq!(CInt::<Ilp32>::try_new(40000).unwrap().get(), 40000);
+    /// ```
+    pub fn try_new(value: i128) -> Option<Self> {
+        let result = Self::new(value);
+        (result.value == value).then_some(result)
+    }
+
+    /// get returns the value.
+    pub fn get(self) -> i128 {
+        self.value
+    }
+
+    /// min returns the least value of the type.
+    pub fn min() -> Self {
+        match T::SIGNED {
+            true => Self::new(-(1 << (Self::BITS - 1))),
+            false => Self::new(0),
+        }
+    }
+
+    /// max returns the greatest value of the type.
+    pub fn max() -> Self {
+        Self::new(-1 ^ (T::SIGNED as i128) << (Self::BITS - 1))
+    }
+
+    /// cast converts the value to the type `U` as a C cast does.
+    pub fn cast<U: CTypeInfo>(self) -> CValue<M, U> {
+        CValue::new(self.value)
+    }
+
+    /// try_convert converts the value to the type `U` if it represents
+    /// the value, and returns `None` otherwise.
+    pub fn try_convert<U: CTypeInfo>(self) -> Option<CValue<M, U>> {
+        CValue::try_new(self.value)
+    }
+
+    /// promoted_bits returns the width of the type the value promotes to,
+    /// which limits shifts.
+    fn promoted_bits() -> usize {
+        8 * M::MODEL.size(M::MODEL.promote(T::C_TYPE))
+    }
+}
+
+/// wrap keeps the low `bits` bits of `value` and reads them back as signed
+/// or unsigned.
+fn wrap(value: i128, bits: usize, signed: bool) -> i128 {
+    match signed {
+        _ if bits >= 128 => value,
+        true => value << (128 - bits) >> (128 - bits),
+        false => value & ((1 << bits) - 1),
+    }
+}
+
+/// binary implements an operator by computing on the exact values, which
+/// wrapping back to the type makes the same as computing at the promoted
+/// width.
+macro_rules! binary {
+    ($($trait:ident $method:ident $op:expr;)*) => {
+        $(
+            impl<M: StaticModel, T: CTypeInfo> $trait for CValue<M, T> {
+                type Output = Self;
+
+                fn $method(self, rhs: Self) -> Self {
+                    let op: fn(i128, i128) -> i128 = $op;
+                    Self::new(op(self.value, rhs.value))
+                }
+            }
+        )*
+    };
+}
+
+binary! {
+    Add add i128::wrapping_add;
+    Sub sub i128::wrapping_sub;
+    Mul mul i128::wrapping_mul;
+    Div div i128::wrapping_div;
+    Rem rem i128::wrapping_rem;
+    BitAnd bitand |a, b| a & b;
+    BitOr bitor |a, b| a | b;
+    BitXor bitxor |a, b| a ^ b;
+}
+
+impl<M: StaticModel, T: CTypeInfo> Neg for CValue<M, T> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(self.value.wrapping_neg())
+    }
+}
+
+impl<M: StaticModel, T: CTypeInfo> Not for CValue<M, T> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::new(!self.value)
+    }
+}
+
+impl<M: StaticModel, T: CTypeInfo> Shl<u32> for CValue<M, T> {
+    type Output = Self;
+
+    fn shl(self, rhs: u32) -> Self {
+        assert!((rhs as usize) < Self::promoted_bits(), "shift out of range");
+        Self::new(self.value << rhs)
+    }
+}
+
+impl<M: StaticModel, T: CTypeInfo> Shr<u32> for CValue<M, T> {
+    type Output = Self;
+
+    fn shr(self, rhs: u32) -> Self {
+        assert!((rhs as usize) < Self::promoted_bits(), "shift out of range");
+        Self::new(self.value >> rhs)
+    }
+}
+
+impl<M, T> Clone for CValue<M, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M, T> Copy for CValue<M, T> {}
+
+impl<M, T> PartialEq for CValue<M, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<M, T> Eq for CValue<M, T> {}
+
+impl<M, T> PartialOrd for CValue<M, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<M, T> Ord for CValue<M, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<M, T> Hash for CValue<M, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+impl<M: StaticModel, T: CTypeInfo> Default for CValue<M, T> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<M: StaticModel, T: CTypeInfo> fmt::Debug for CValue<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}) {}", T::C_NAME, self.value)
+    }
+}
+
+impl<M, T> fmt::Display for CValue<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Ilp32, Ip16L32, Lp64, Silp64};
+
+    #[test]
+    fn test_values() {
+        assert_eq!(CInt::<Ilp32>::max().get(), i32::MAX as i128);
+        assert_eq!(CInt::<Ilp32>::min().get(), i32::MIN as i128);
+        assert_eq!(CULong::<Lp64>::max().get(), u64::MAX as i128);
+        assert_eq!(CUShort::<Silp64>::max().get(), u64::MAX as i128);
+        assert_eq!(CUChar::<Lp64>::min().get(), 0);
+
+        let a = CUShort::<Ip16L32>::new(0xffff);
+        assert_eq!((a * a).get(), 1);
+        assert_eq!((a + CUShort::new(1)).get(), 0);
+        assert_eq!((-CUInt::<Lp64>::new(1)).get(), 0xffff_ffff);
+        assert_eq!((CChar::<Lp64>::new(-128) / CChar::new(-1)).get(), -128);
+        assert_eq!((CInt::<Lp64>::new(-7) % CInt::new(2)).get(), -1);
+        assert_eq!((CInt::<Lp64>::new(-8) >> 1).get(), -4);
+        assert_eq!((CUChar::<Lp64>::new(1) << 31).get(), 0);
+        assert_eq!((!CULongLong::<Lp64>::new(0)).get(), u64::MAX as i128);
+        let product = CULongLong::<Lp64>::max() * CULongLong::max();
+        assert_eq!(product.get(), 1);
+
+        let long = CLong::<Lp64>::new(-1);
+        assert_eq!(long.cast::<UInt>().get(), 0xffff_ffff);
+        assert_eq!(long.try_convert::<UInt>(), None);
+        assert_eq!(long.try_convert::<Short>().map(CValue::get), Some(-1));
+        assert!(CInt::<Lp64>::new(1) < CInt::new(2));
+        assert_eq!(format!("{:?}", long), "(long) -1");
+        assert_eq!(CPointer::<Ilp32>::new(-4).to_string(), "4294967292");
+    }
+
+    #[test]
+    #[should_panic(expected = "shift out of range")]
+    fn test_shift_out_of_range() {
+        let _ = CUChar::<Lp64>::new(1) << 32;
+    }
+}
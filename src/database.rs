@@ -0,0 +1,620 @@
+//! A compiled-in database of concrete targets.
+//!
+//! [`Platform`](crate::Platform) covers the platforms most code meets. The
+//! [`PROFILES`] here go further, to BSDs, mobile and embedded targets, GPUs,
+//! WebAssembly and UEFI, each keyed by its LLVM target triple and telling
+//! the operating system, the compiler whose ABI it follows and everything
+//! that shapes C types on it.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::database;
+//! let profile = database::find("aarch64-linux-android").unwrap();
+//! assert_eq!(profile.data_model, DataModel::LP64);
+//! assert!(!profile.char_signed);
+//! assert_eq!(profile.long_double, LongDouble::Binary128);
+//!
+//! let keil = database::search("keil c51")[0];
+//! assert_eq!(keil.data_model, DataModel::IP16L32);
+//! assert_eq!(keil.pointers[0], ("", 3));
+//!
+//! let amdgpu = database::find("amdgcn-amd-amdhsa").unwrap();
+//! assert_eq!(amdgpu.pointer_size(database::AddressSpace::Shared), 4);
+//!
+//! let bsds = database::search("bsd aarch64");
+//! assert!(bsds.iter().all(|profile| profile.triple.contains("bsd")));
+//! ```
+
+use crate::layout::{Abi, BitFieldRules};
+use crate::{
+    CType, DataModel, Endianness, FltEvalMethod, LongDouble, SignedRepresentation, TargetModel,
+};
+use DataModel::{ILP32, IP16L32, LLP64, LP64};
+use Endianness::{Big as B, Little as L};
+use LongDouble::{Binary128 as F128, Binary64 as F64, DoubleDouble as DD, X87Extended as X87};
+
+/// StackGrowth is the direction the call stack grows in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StackGrowth {
+    /// Toward lower addresses, as on nearly every architecture.
+    Down,
+    /// Toward higher addresses, as on PA-RISC.
+    Up,
+}
+
+/// AddressSpace is a memory of a GPU or other accelerator that pointers
+/// can be qualified with, under the names CUDA and OpenCL give it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AddressSpace {
+    /// Unqualified pointers, which may point into any of the others.
+    Generic,
+    /// Device memory visible to every thread, CUDA `__device__` and OpenCL
+    /// `__global`.
+    Global,
+    /// Memory shared by a block or work-group, CUDA `__shared__` and
+    /// OpenCL `__local`.
+    Shared,
+    /// Read-only memory, CUDA `__constant__` and OpenCL `__constant`.
+    Constant,
+    /// Memory private to a thread, CUDA local memory and OpenCL
+    /// `__private`.
+    Private,
+}
+
+/// Profile describes the C types of one target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Profile {
+    /// The LLVM target triple, as passed to `clang --target`.
+    pub triple: &'static str,
+    /// The operating system, with the oldest version the ABI holds for
+    /// where it matters.
+    pub os: &'static str,
+    /// The compiler whose ABI the target follows.
+    pub compiler: &'static str,
+    /// The data model.
+    pub data_model: DataModel,
+    /// The byte order.
+    pub endianness: Endianness,
+    /// Whether plain `char` is signed.
+    pub char_signed: bool,
+    /// The size of `wchar_t` in bytes, or 0 if the compiler lacks it.
+    pub wchar_size: usize,
+    /// The format of `long double`.
+    pub long_double: LongDouble,
+    /// The cap on the alignment of fields in records, as the 4 bytes of
+    /// `double` and `long long` on i386 System V.
+    pub max_align: Option<usize>,
+    /// How bit-fields are allocated.
+    pub bitfields: BitFieldRules,
+    /// The precision floating point expressions are evaluated in.
+    pub flt_eval_method: FltEvalMethod,
+    /// Whether enums are stored in the smallest integer type holding
+    /// their values.
+    pub short_enums: bool,
+    /// Whether records follow the power alignment rule of AIX, see
+    /// [`Abi::with_power_align`].
+    pub power_align: bool,
+    /// The size of `double` in bytes, 4 on 8-bit and 16-bit toolchains
+    /// that make it a `float`. The data model, and so [`Profile::target`],
+    /// still has an 8-byte `double`.
+    pub double_size: usize,
+    /// The pointer qualifiers of a segmented or Harvard architecture with
+    /// the size of each kind of pointer, like the 3-byte generic and 1-byte
+    /// `__data` pointers of the 8051. The empty qualifier is an unqualified
+    /// pointer where it is wider than the pointers of the data model, and
+    /// [`Profile::target`] keeps the size of the data model.
+    pub pointers: &'static [(&'static str, usize)],
+    /// The size of pointers into each address space of a GPU that differs
+    /// from the size of an unqualified pointer, like the 32-bit pointers
+    /// to shared and private memory of AMD GPUs. Clang's
+    /// `-fcuda-short-ptr` does the same for NVIDIA GPUs.
+    pub address_spaces: &'static [(AddressSpace, usize)],
+}
+
+impl Profile {
+    /// abi returns the record layout rules of the target.
+    pub fn abi(&self) -> Abi {
+        let mut abi = Abi::new(self.data_model).with_bitfields(self.bitfields);
+        if let Some(align) = self.max_align {
+            abi = abi.with_max_align(align);
+        }
+        if self.power_align {
+            abi = abi.with_power_align();
+        }
+        match self.short_enums {
+            true => abi.with_short_enums(),
+            false => abi,
+        }
+    }
+
+    /// target returns the full description of the target.
+    ///
+    /// Records are laid out with the sizes of the data model, so the 4-byte
+    /// `double` of [`double_size`](Profile::double_size) and the pointer
+    /// sizes of [`pointers`](Profile::pointers) are not applied. A record
+    /// with such fields on those targets has to be laid out with them by
+    /// hand.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let target = data_models::database::find("i686-pc-windows-gnu").unwrap().target();
+    /// assert_eq!(target.data_model(), DataModel::ILP32);
+    /// assert_eq!(target.long_double, LongDouble::X87Extended);
+    /// ```
+    pub fn target(&self) -> TargetModel {
+        TargetModel::new(self.data_model)
+            .with_abi(self.abi())
+            .with_endianness(self.endianness)
+            .with_char_signed(self.char_signed)
+            .with_signed_representation(SignedRepresentation::TwosComplement)
+            .with_long_double(self.long_double)
+    }
+
+    /// long_double_size returns the size of `long double` in bytes: the
+    /// x87 format takes 12 bytes on 32-bit x86 except on Apple targets.
+    ///
+    /// # Example
+    /// ```
+    /// let find = |triple| data_models::database::find(triple).unwrap();
+    /// assert_eq!(find("i686-unknown-linux-gnu").long_double_size(), 12);
+    /// assert_eq!(find("i686-apple-darwin").long_double_size(), 16);
+    /// assert_eq!(find("x86_64-pc-windows-msvc").long_double_size(), 8);
+    /// ```
+    pub fn long_double_size(&self) -> usize {
+        let x86 = self.triple.starts_with('i') && !self.triple.contains("-apple-");
+        match self.long_double {
+            LongDouble::Binary64 => self.double_size,
+            LongDouble::X87Extended if x86 => 12,
+            _ => 16,
+        }
+    }
+
+    /// float_t_size returns the size of `float_t`, which is `long double`
+    /// where the x87 unit evaluates expressions.
+    ///
+    /// # Example
+    /// ```
+    /// let find = |triple| data_models::database::find(triple).unwrap();
+    /// assert_eq!(find("i686-unknown-linux-musl").float_t_size(), 12);
+    /// assert_eq!(find("i686-pc-windows-msvc").float_t_size(), 4);
+    /// ```
+    pub fn float_t_size(&self) -> usize {
+        self.flt_eval_method.float_t_size(self.long_double_size())
+    }
+
+    /// double_t_size returns the size of `double_t`.
+    pub fn double_t_size(&self) -> usize {
+        self.flt_eval_method.double_t_size(self.long_double_size())
+    }
+
+    /// pointer_size returns the size in bytes of a pointer into an address
+    /// space. Only GPUs have address spaces with pointers of their own
+    /// size; elsewhere every pointer is as wide as the data model says.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::database::{self, AddressSpace};
+    /// let amdgpu = database::find("amdgcn-amd-amdhsa").unwrap();
+    /// assert_eq!(amdgpu.pointer_size(AddressSpace::Global), 8);
+    /// assert_eq!(amdgpu.pointer_size(AddressSpace::Shared), 4);
+    /// let linux = database::find("x86_64-unknown-linux-gnu").unwrap();
+    /// assert_eq!(linux.pointer_size(AddressSpace::Shared), 8);
+    /// ```
+    pub fn pointer_size(&self, space: AddressSpace) -> usize {
+        match self.address_spaces.iter().find(|&&(s, _)| s == space) {
+            Some(&(_, size)) => size,
+            None => self.data_model.size(CType::Pointer),
+        }
+    }
+
+    /// arch returns the architecture part of the triple.
+    fn arch(&self) -> &'static str {
+        self.triple.split('-').next().unwrap_or("")
+    }
+
+    /// page_size returns the usual size of a virtual memory page in bytes,
+    /// or `None` on bare metal. Apple Silicon and LoongArch use 16 KiB
+    /// pages, 64-bit PowerPC Linux 64 KiB and WebAssembly grows memory in
+    /// 64 KiB pages.
+    ///
+    /// # Example
+    /// ```
+    /// let find = |triple| data_models::database::find(triple).unwrap();
+    /// assert_eq!(find("x86_64-unknown-linux-gnu").page_size(), Some(4096));
+    /// assert_eq!(find("arm64-apple-darwin").page_size(), Some(16384));
+    /// assert_eq!(find("thumbv7m-none-eabi").page_size(), None);
+    /// ```
+    pub fn page_size(&self) -> Option<usize> {
+        let apple = self.triple.contains("-apple-");
+        match self.arch() {
+            _ if self.os == "none" && !self.arch().starts_with("wasm") => None,
+            "nvptx" | "nvptx64" | "amdgcn" | "spir" | "spir64" | "spirv64" => None,
+            "arm64" | "aarch64" | "arm64_32" if apple => Some(16384),
+            "powerpc64" | "powerpc64le" if self.os.starts_with("Linux") => Some(65536),
+            "loongarch64" => Some(16384),
+            "sparc64" | "sparcv9" => Some(8192),
+            arch if arch.starts_with("wasm") => Some(65536),
+            _ => Some(4096),
+        }
+    }
+
+    /// cache_line_size returns the usual size of a data cache line in
+    /// bytes, or `None` for microcontrollers that usually lack a cache.
+    ///
+    /// # Example
+    /// ```
+    /// let find = |triple| data_models::database::find(triple).unwrap();
+    /// assert_eq!(find("x86_64-pc-windows-msvc").cache_line_size(), Some(64));
+    /// assert_eq!(find("powerpc64le-unknown-linux-gnu").cache_line_size(), Some(128));
+    /// assert_eq!(find("s390x-unknown-linux-gnu").cache_line_size(), Some(256));
+    /// ```
+    pub fn cache_line_size(&self) -> Option<usize> {
+        let apple = self.triple.contains("-apple-");
+        match self.arch() {
+            "thumbv6m" | "thumbv7m" | "msp430" | "avr" | "mcs51" | "z80" | "stm8" => None,
+            "arm64" | "aarch64" | "arm64_32" if apple => Some(128),
+            "powerpc" | "powerpc64" | "powerpc64le" | "nvptx" | "nvptx64" => Some(128),
+            "spir" | "spir64" | "spirv64" => None,
+            "s390x" => Some(256),
+            "mips" | "mipsel" | "hexagon" => Some(32),
+            arch if arch.starts_with("arm") || arch.starts_with("thumb") => Some(32),
+            _ => Some(64),
+        }
+    }
+
+    /// stack_growth returns the direction the call stack grows in.
+    pub fn stack_growth(&self) -> StackGrowth {
+        match self.arch() {
+            "hppa" | "hppa64" => StackGrowth::Up,
+            _ => StackGrowth::Down,
+        }
+    }
+
+    /// max_align caps the alignment of fields in records.
+    const fn max_align(self, align: usize) -> Profile {
+        Profile {
+            max_align: Some(align),
+            ..self
+        }
+    }
+
+    /// x87 evaluates floating point expressions in the x87 registers, as
+    /// `long double`.
+    const fn x87(self) -> Profile {
+        Profile {
+            flt_eval_method: FltEvalMethod::LongDouble,
+            ..self
+        }
+    }
+
+    /// short_enums stores enums in the smallest integer type holding their
+    /// values.
+    const fn short_enums(self) -> Profile {
+        Profile {
+            short_enums: true,
+            ..self
+        }
+    }
+
+    /// power_align follows the power alignment rule of AIX.
+    const fn power_align(self) -> Profile {
+        Profile {
+            power_align: true,
+            ..self
+        }
+    }
+
+    /// float_double makes `double` and `long double` 4-byte floats.
+    const fn float_double(self) -> Profile {
+        Profile {
+            double_size: 4,
+            ..self
+        }
+    }
+
+    /// pointers lists the qualified pointers of the target.
+    const fn pointers(self, pointers: &'static [(&'static str, usize)]) -> Profile {
+        Profile { pointers, ..self }
+    }
+
+    /// address_spaces lists the GPU address spaces whose pointers differ
+    /// from unqualified ones.
+    const fn address_spaces(self, address_spaces: &'static [(AddressSpace, usize)]) -> Profile {
+        Profile {
+            address_spaces,
+            ..self
+        }
+    }
+
+    /// msvc switches to the bit-field rules of MSVC.
+    const fn msvc(self) -> Profile {
+        Profile {
+            bitfields: BitFieldRules::Msvc,
+            ..self
+        }
+    }
+}
+
+/// profile returns a profile with natural alignment, Itanium bit-fields,
+/// `int`-sized enums, an 8-byte `double`, one size of pointer and floating
+/// point evaluated in the precision of each type.
+#[allow(clippy::too_many_arguments)]
+const fn profile(
+    triple: &'static str,
+    os: &'static str,
+    compiler: &'static str,
+    data_model: DataModel,
+    endianness: Endianness,
+    char_signed: bool,
+    wchar_size: usize,
+    long_double: LongDouble,
+) -> Profile {
+    Profile {
+        triple,
+        os,
+        compiler,
+        data_model,
+        endianness,
+        char_signed,
+        wchar_size,
+        long_double,
+        max_align: None,
+        bitfields: BitFieldRules::Itanium,
+        flt_eval_method: FltEvalMethod::Native,
+        short_enums: false,
+        power_align: false,
+        double_size: 8,
+        pointers: &[],
+        address_spaces: &[],
+    }
+}
+
+const SIGNED: bool = true;
+const UNSIGNED: bool = false;
+
+/// PROFILES holds every target of the database, grouped by operating
+/// system.
+#[rustfmt::skip]
+pub const PROFILES: &[Profile] = &[
+    // Linux
+    profile("x86_64-unknown-linux-gnu", "Linux 3.2+, glibc 2.17+", "GCC", LP64, L, SIGNED, 4, X87),
+    profile("x86_64-unknown-linux-musl", "Linux 3.2+, musl", "GCC", LP64, L, SIGNED, 4, X87),
+    profile("x86_64-unknown-linux-gnux32", "Linux 3.4+, glibc", "GCC", ILP32, L, SIGNED, 4, X87),
+    profile("i686-unknown-linux-gnu", "Linux 3.2+, glibc 2.17+", "GCC", ILP32, L, SIGNED, 4, X87).max_align(4).x87(),
+    profile("i586-unknown-linux-gnu", "Linux 3.2+, glibc 2.17+", "GCC", ILP32, L, SIGNED, 4, X87).max_align(4).x87(),
+    profile("i686-unknown-linux-musl", "Linux 3.2+, musl", "GCC", ILP32, L, SIGNED, 4, X87).max_align(4).x87(),
+    profile("aarch64-unknown-linux-gnu", "Linux 4.1+, glibc 2.17+", "GCC", LP64, L, UNSIGNED, 4, F128),
+    profile("aarch64-unknown-linux-musl", "Linux 4.1+, musl", "GCC", LP64, L, UNSIGNED, 4, F128),
+    profile("aarch64_be-unknown-linux-gnu", "Linux, glibc", "GCC", LP64, B, UNSIGNED, 4, F128),
+    profile("armv7-unknown-linux-gnueabihf", "Linux 3.2+, glibc 2.17+", "GCC", ILP32, L, UNSIGNED, 4, F64),
+    profile("armv7-unknown-linux-musleabihf", "Linux 3.2+, musl", "GCC", ILP32, L, UNSIGNED, 4, F64),
+    profile("arm-unknown-linux-gnueabi", "Linux 3.2+, glibc 2.17+", "GCC", ILP32, L, UNSIGNED, 4, F64),
+    profile("arm-unknown-linux-gnueabihf", "Linux 3.2+, glibc 2.17+", "GCC", ILP32, L, UNSIGNED, 4, F64),
+    profile("armeb-unknown-linux-gnueabi", "Linux, glibc", "GCC", ILP32, B, UNSIGNED, 4, F64),
+    profile("thumbv7neon-unknown-linux-gnueabihf", "Linux 3.2+, glibc 2.17+", "GCC", ILP32, L, UNSIGNED, 4, F64),
+    profile("powerpc64le-unknown-linux-gnu", "Linux 3.10+, glibc 2.17+", "GCC", LP64, L, UNSIGNED, 4, DD),
+    profile("powerpc64-unknown-linux-gnu", "Linux 3.2+, glibc 2.17+", "GCC", LP64, B, UNSIGNED, 4, DD),
+    profile("powerpc-unknown-linux-gnu", "Linux 3.2+, glibc 2.17+", "GCC", ILP32, B, UNSIGNED, 4, DD),
+    profile("s390x-unknown-linux-gnu", "Linux 3.2+, glibc 2.17+", "GCC", LP64, B, UNSIGNED, 4, F128),
+    profile("riscv64-unknown-linux-gnu", "Linux 4.20+, glibc 2.29+", "GCC", LP64, L, UNSIGNED, 4, F128),
+    profile("riscv64-unknown-linux-musl", "Linux 4.20+, musl", "GCC", LP64, L, UNSIGNED, 4, F128),
+    profile("riscv32-unknown-linux-gnu", "Linux 5.4+, glibc 2.33+", "GCC", ILP32, L, UNSIGNED, 4, F128),
+    profile("mips-unknown-linux-gnu", "Linux 4.4+, glibc 2.23+", "GCC", ILP32, B, SIGNED, 4, F64),
+    profile("mipsel-unknown-linux-gnu", "Linux 4.4+, glibc 2.23+", "GCC", ILP32, L, SIGNED, 4, F64),
+    profile("mips64-unknown-linux-gnuabi64", "Linux 4.4+, glibc 2.23+", "GCC", LP64, B, SIGNED, 4, F128),
+    profile("mips64el-unknown-linux-gnuabi64", "Linux 4.4+, glibc 2.23+", "GCC", LP64, L, SIGNED, 4, F128),
+    profile("sparc64-unknown-linux-gnu", "Linux 4.4+, glibc 2.23+", "GCC", LP64, B, SIGNED, 4, F128),
+    profile("sparc-unknown-linux-gnu", "Linux, glibc", "GCC", ILP32, B, SIGNED, 4, F128),
+    profile("loongarch64-unknown-linux-gnu", "Linux 5.19+, glibc 2.36+", "GCC", LP64, L, SIGNED, 4, F128),
+    profile("hexagon-unknown-linux-musl", "Linux, musl", "Clang", ILP32, L, SIGNED, 4, F64),
+    profile("hppa-unknown-linux-gnu", "Linux, glibc", "GCC", ILP32, B, SIGNED, 4, F64),
+    // Android
+    profile("aarch64-linux-android", "Android 5+", "Clang", LP64, L, UNSIGNED, 4, F128),
+    profile("armv7-linux-androideabi", "Android 5+", "Clang", ILP32, L, UNSIGNED, 4, F64),
+    profile("x86_64-linux-android", "Android 5+", "Clang", LP64, L, SIGNED, 4, F128),
+    profile("i686-linux-android", "Android 5+", "Clang", ILP32, L, SIGNED, 4, F64).max_align(4),
+    // Windows
+    profile("x86_64-pc-windows-msvc", "Windows 10+", "MSVC", LLP64, L, SIGNED, 2, F64).msvc(),
+    profile("i686-pc-windows-msvc", "Windows 10+", "MSVC", ILP32, L, SIGNED, 2, F64).msvc(),
+    profile("aarch64-pc-windows-msvc", "Windows 10+", "MSVC", LLP64, L, SIGNED, 2, F64).msvc(),
+    profile("thumbv7a-pc-windows-msvc", "Windows 10+", "MSVC", ILP32, L, SIGNED, 2, F64).msvc(),
+    profile("x86_64-pc-windows-gnu", "Windows 10+", "MinGW-w64 GCC", LLP64, L, SIGNED, 2, X87).msvc(),
+    profile("i686-pc-windows-gnu", "Windows 10+", "MinGW-w64 GCC", ILP32, L, SIGNED, 2, X87).msvc().x87(),
+    // Apple
+    profile("x86_64-apple-darwin", "macOS 10.12+", "Clang", LP64, L, SIGNED, 4, X87),
+    profile("arm64-apple-darwin", "macOS 11+", "Clang", LP64, L, SIGNED, 4, F64),
+    profile("aarch64-apple-darwin", "macOS 11+", "Clang", LP64, L, SIGNED, 4, F64),
+    profile("i686-apple-darwin", "macOS 10.12 to 10.14", "Clang", ILP32, L, SIGNED, 4, X87).max_align(4),
+    profile("aarch64-apple-ios", "iOS 10+", "Clang", LP64, L, SIGNED, 4, F64),
+    profile("aarch64-apple-ios-simulator", "iOS 14+ simulator", "Clang", LP64, L, SIGNED, 4, F64),
+    profile("x86_64-apple-ios-simulator", "iOS 10+ simulator", "Clang", LP64, L, SIGNED, 4, X87),
+    profile("armv7-apple-ios", "iOS 10", "Clang", ILP32, L, SIGNED, 4, F64),
+    profile("aarch64-apple-tvos", "tvOS 10+", "Clang", LP64, L, SIGNED, 4, F64),
+    profile("arm64_32-apple-watchos", "watchOS 5+", "Clang", ILP32, L, SIGNED, 4, F64),
+    // BSD
+    profile("x86_64-unknown-freebsd", "FreeBSD 12+", "Clang", LP64, L, SIGNED, 4, X87),
+    profile("i686-unknown-freebsd", "FreeBSD 12+", "Clang", ILP32, L, SIGNED, 4, X87).max_align(4).x87(),
+    profile("aarch64-unknown-freebsd", "FreeBSD 12+", "Clang", LP64, L, UNSIGNED, 4, F128),
+    profile("armv7-unknown-freebsd", "FreeBSD 12+", "Clang", ILP32, L, UNSIGNED, 4, F64),
+    profile("powerpc64-unknown-freebsd", "FreeBSD 13+", "Clang", LP64, B, UNSIGNED, 4, F64),
+    profile("powerpc64le-unknown-freebsd", "FreeBSD 13+", "Clang", LP64, L, UNSIGNED, 4, F64),
+    profile("riscv64-unknown-freebsd", "FreeBSD 13+", "Clang", LP64, L, UNSIGNED, 4, F128),
+    profile("x86_64-unknown-netbsd", "NetBSD 9+", "GCC", LP64, L, SIGNED, 4, X87),
+    profile("aarch64-unknown-netbsd", "NetBSD 9+", "GCC", LP64, L, UNSIGNED, 4, F128),
+    profile("sparc64-unknown-netbsd", "NetBSD 9+", "GCC", LP64, B, SIGNED, 4, F128),
+    profile("x86_64-unknown-openbsd", "OpenBSD 7+", "Clang", LP64, L, SIGNED, 4, X87),
+    profile("i686-unknown-openbsd", "OpenBSD 7+", "Clang", ILP32, L, SIGNED, 4, X87).max_align(4).x87(),
+    profile("aarch64-unknown-openbsd", "OpenBSD 7+", "Clang", LP64, L, UNSIGNED, 4, F128),
+    profile("x86_64-unknown-dragonfly", "DragonFly BSD 6+", "GCC", LP64, L, SIGNED, 4, X87),
+    // Unix
+    profile("x86_64-pc-solaris", "Solaris 11", "GCC", LP64, L, SIGNED, 4, X87),
+    profile("x86_64-unknown-illumos", "illumos", "GCC", LP64, L, SIGNED, 4, X87),
+    profile("sparcv9-sun-solaris", "Solaris 11", "Oracle Developer Studio", LP64, B, SIGNED, 4, F128),
+    profile("powerpc-ibm-aix", "AIX 7.2+", "IBM XL C", ILP32, B, UNSIGNED, 2, F64).power_align(),
+    profile("powerpc64-ibm-aix", "AIX 7.2+", "IBM XL C", LP64, B, UNSIGNED, 4, F64).power_align(),
+    profile("x86_64-unknown-fuchsia", "Fuchsia", "Clang", LP64, L, SIGNED, 4, X87),
+    profile("aarch64-unknown-fuchsia", "Fuchsia", "Clang", LP64, L, UNSIGNED, 4, F128),
+    // WebAssembly
+    profile("wasm32-unknown-unknown", "none", "Clang", ILP32, L, SIGNED, 4, F128),
+    profile("wasm32-wasip1", "WASI preview 1", "Clang", ILP32, L, SIGNED, 4, F128),
+    profile("wasm32-unknown-emscripten", "Emscripten", "Clang", ILP32, L, SIGNED, 4, F128),
+    profile("wasm64-unknown-unknown", "none", "Clang", LP64, L, SIGNED, 4, F128),
+    // UEFI
+    profile("x86_64-unknown-uefi", "UEFI", "Clang", LLP64, L, SIGNED, 2, F64).msvc(),
+    profile("i686-unknown-uefi", "UEFI", "Clang", ILP32, L, SIGNED, 2, F64).msvc(),
+    profile("aarch64-unknown-uefi", "UEFI", "Clang", LLP64, L, SIGNED, 2, F64).msvc(),
+    // Bare metal
+    profile("thumbv6m-none-eabi", "none", "GCC", ILP32, L, UNSIGNED, 4, F64),
+    profile("thumbv7m-none-eabi", "none", "GCC", ILP32, L, UNSIGNED, 4, F64),
+    profile("thumbv7em-none-eabihf", "none", "GCC", ILP32, L, UNSIGNED, 4, F64),
+    profile("thumbv8m.main-none-eabihf", "none", "GCC", ILP32, L, UNSIGNED, 4, F64),
+    profile("aarch64-unknown-none", "none", "GCC", LP64, L, UNSIGNED, 4, F128),
+    profile("riscv32imac-unknown-none-elf", "none", "GCC", ILP32, L, UNSIGNED, 4, F128),
+    profile("riscv64gc-unknown-none-elf", "none", "GCC", LP64, L, UNSIGNED, 4, F128),
+    profile("msp430-none-elf", "none", "GCC", IP16L32, L, SIGNED, 2, F64).max_align(2),
+    // Embedded toolchains
+    profile("thumbv7em-keil-none-eabi", "none", "Keil MDK, Arm Compiler 6", ILP32, L, UNSIGNED, 2, F64).short_enums(),
+    profile("thumbv7em-iar-none-eabi", "none", "IAR Embedded Workbench for Arm", ILP32, L, UNSIGNED, 4, F64).short_enums(),
+    profile("msp430-iar-none-elf", "none", "IAR Embedded Workbench for MSP430", IP16L32, L, UNSIGNED, 2, F64)
+        .max_align(2).short_enums().float_double(),
+    profile("avr-iar-none", "none", "IAR Embedded Workbench for AVR", IP16L32, L, UNSIGNED, 2, F64)
+        .max_align(1).short_enums().float_double()
+        .pointers(&[("__tiny", 1), ("__near", 2), ("__far", 3), ("__huge", 3)]),
+    profile("mcs51-keil-none", "none", "Keil C51", IP16L32, B, SIGNED, 0, F64)
+        .max_align(1).short_enums().float_double()
+        .pointers(&[("", 3), ("data", 1), ("idata", 1), ("pdata", 1), ("xdata", 2), ("code", 2)]),
+    profile("mcs51-sdcc-none", "none", "SDCC", IP16L32, L, UNSIGNED, 4, F64)
+        .max_align(1).float_double()
+        .pointers(&[("", 3), ("__data", 1), ("__idata", 1), ("__pdata", 1), ("__xdata", 2), ("__code", 2)]),
+    profile("z80-sdcc-none", "none", "SDCC", IP16L32, L, UNSIGNED, 4, F64).max_align(1).float_double(),
+    profile("stm8-sdcc-none", "none", "SDCC", IP16L32, B, UNSIGNED, 4, F64).max_align(1).float_double(),
+    // GPUs, for device code matching the types of a 64-bit Linux host
+    profile("nvptx64-nvidia-cuda", "CUDA", "NVCC, Clang", LP64, L, SIGNED, 4, F64),
+    profile("nvptx-nvidia-cuda", "CUDA", "NVCC, Clang", ILP32, L, SIGNED, 4, F64),
+    profile("amdgcn-amd-amdhsa", "ROCm, HIP", "Clang", LP64, L, SIGNED, 4, F64)
+        .address_spaces(&[(AddressSpace::Shared, 4), (AddressSpace::Private, 4)]),
+    profile("spir64-unknown-unknown", "OpenCL", "Clang", LP64, L, SIGNED, 4, F64),
+    profile("spirv64-unknown-unknown", "OpenCL, Vulkan", "Clang", LP64, L, SIGNED, 4, F64),
+];
+
+/// find returns the profile of a target triple.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let profile = data_models::database::find("x86_64-pc-windows-msvc").unwrap();
+/// assert_eq!(profile.compiler, "MSVC");
+/// assert_eq!(profile.wchar_size, 2);
+/// assert!(data_models::database::find("vax-dec-ultrix").is_none());
+/// ```
+pub fn find(triple: &str) -> Option<&'static Profile> {
+    PROFILES.iter().find(|profile| profile.triple == triple)
+}
+
+/// search returns the profiles matching every word of `query`, ignoring
+/// case, in the triple, the operating system or the compiler.
+///
+/// # Example
+/// ```
+/// let profiles = data_models::database::search("windows gnu");
+/// let triples: Vec<_> = profiles.iter().map(|profile| profile.triple).collect();
+/// assert_eq!(triples, ["x86_64-pc-windows-gnu", "i686-pc-windows-gnu"]);
+/// ```
+pub fn search(query: &str) -> Vec<&'static Profile> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    PROFILES
+        .iter()
+        .filter(|profile| {
+            let text = format!("{} {} {}", profile.triple, profile.os, profile.compiler);
+            let text = text.to_lowercase();
+            words.iter().all(|word| text.contains(word.as_str()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Record;
+    use crate::Platform;
+
+    #[test]
+    fn test_platforms() {
+        for &platform in Platform::ALL {
+            let profile = find(platform.triple()).unwrap();
+            assert_eq!(profile.target(), platform.target(), "{:?}", platform);
+            assert_eq!(profile.wchar_size, platform.wchar_size(), "{:?}", platform);
+            let long_double = profile.long_double_size();
+            assert_eq!(long_double, platform.long_double_size(), "{:?}", platform);
+            let method = profile.flt_eval_method;
+            assert_eq!(method, platform.flt_eval_method(), "{:?}", platform);
+        }
+    }
+
+    #[test]
+    fn test_profiles() {
+        for (i, profile) in PROFILES.iter().enumerate() {
+            assert!(
+                PROFILES[..i].iter().all(|p| p.triple != profile.triple),
+                "{} is listed twice",
+                profile.triple
+            );
+            let windows = profile.os.starts_with("Windows") || profile.os == "UEFI";
+            assert_eq!(windows, profile.bitfields == BitFieldRules::Msvc);
+            assert!(!windows || profile.wchar_size == 2);
+            let x87 = profile.flt_eval_method == FltEvalMethod::LongDouble;
+            assert!(!x87 || profile.triple.starts_with('i') && profile.float_t_size() == 12);
+        }
+        let page_sizes: Vec<_> = PROFILES.iter().filter_map(Profile::page_size).collect();
+        assert!(page_sizes.iter().all(|size| size.is_power_of_two()));
+        assert_eq!(
+            find("wasm32-unknown-unknown").unwrap().page_size(),
+            Some(65536)
+        );
+        assert_eq!(find("x86_64-unknown-uefi").unwrap().page_size(), Some(4096));
+        assert_eq!(
+            find("thumbv7em-none-eabihf").unwrap().cache_line_size(),
+            Some(32)
+        );
+        let up: Vec<_> = PROFILES
+            .iter()
+            .filter(|p| p.stack_growth() == StackGrowth::Up)
+            .map(|p| p.triple)
+            .collect();
+        assert_eq!(up, vec!["hppa-unknown-linux-gnu"]);
+        let c51 = find("mcs51-keil-none").unwrap();
+        assert_eq!(c51.pointers[0], ("", 3));
+        assert_eq!(c51.long_double_size(), 4);
+        assert!(c51.abi().short_enums());
+        assert_eq!(c51.cache_line_size(), None);
+        let record = Record::new("s")
+            .field("d", CType::Double)
+            .field("p", CType::Pointer);
+        let layout = record.layout(c51.target().abi).unwrap();
+        assert_eq!((layout.fields[1].offset, layout.size), (8, 10));
+        assert_eq!(c51.double_size, 4);
+        assert_eq!(search("sdcc").len(), 3);
+        let amdgpu = find("amdgcn-amd-amdhsa").unwrap();
+        assert_eq!(amdgpu.pointer_size(AddressSpace::Generic), 8);
+        assert_eq!(amdgpu.pointer_size(AddressSpace::Constant), 8);
+        assert_eq!(amdgpu.pointer_size(AddressSpace::Private), 4);
+        assert_eq!(
+            find("nvptx-nvidia-cuda")
+                .unwrap()
+                .pointer_size(AddressSpace::Shared),
+            4
+        );
+        assert_eq!(amdgpu.page_size(), None);
+        let msp430 = find("msp430-none-elf").unwrap().abi();
+        assert_eq!(msp430.align(CType::Long), 2);
+        assert_eq!(search("").len(), PROFILES.len());
+        assert_eq!(
+            search("MUSL riscv64")[0].triple,
+            "riscv64-unknown-linux-musl"
+        );
+    }
+}
@@ -0,0 +1,598 @@
+//! C declarations parsed into type expressions.
+//!
+//! Building a [`FieldType`] by hand for `unsigned long long *names[4]` is
+//! tedious and easy to get backwards. [`parse_decl`] reads the declaration
+//! as C does, pointers binding looser than arrays unless parenthesized,
+//! into a [`TypeExpr`] whose size and alignment can be evaluated against
+//! any data model or ABI.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! let names = parse_decl("unsigned long long *names[4]").unwrap();
+//! assert_eq!(names.size(DataModel::ILP32), Some(16));
+//! assert_eq!(names.size(DataModel::LP64), Some(32));
+//! let rows = parse_decl("int (*rows)[4]").unwrap();
+//! assert_eq!(rows.size(DataModel::ILP32), Some(4));
+//! assert_eq!(rows.to_string(), "int (*)[4]");
+//! ```
+
+use crate::layout::{Abi, FieldType};
+use crate::CType;
+use std::error::Error;
+use std::fmt;
+
+/// Qualifiers are the type qualifiers of a base type or pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Qualifiers {
+    /// `const`
+    pub is_const: bool,
+    /// `volatile`
+    pub is_volatile: bool,
+    /// `restrict`, which only qualifies pointers.
+    pub is_restrict: bool,
+}
+
+/// TypeExpr is the type a C declaration gives its name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format), defmt(bound()))]
+pub enum TypeExpr {
+    /// `void`, only complete behind a pointer.
+    Void(Qualifiers),
+    /// One of the base types.
+    Base(CType, Qualifiers),
+    /// A pointer to the type.
+    Pointer(Box<TypeExpr>, Qualifiers),
+    /// An array of the type, with `None` elements for `[]`.
+    Array(Box<TypeExpr>, Option<usize>),
+}
+
+/// Declaration is a parsed C declaration: the declared name, if any, and
+/// its type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Declaration {
+    /// The declared identifier, or `None` for an abstract declarator such
+    /// as `char *[4]`.
+    pub name: Option<String>,
+    /// The type of the identifier.
+    pub ty: TypeExpr,
+}
+
+/// DeclError describes why a declaration was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DeclError {
+    /// A token is not allowed where it appears.
+    Unexpected(String),
+    /// The declaration ends early.
+    UnexpectedEnd,
+    /// The type specifiers do not name a base type, like `short char` or
+    /// `long double`, which has no [`CType`].
+    InvalidType(String),
+    /// The declarator has more than [`MAX_DEPTH`] pointers, arrays and
+    /// parentheses.
+    TooDeep,
+}
+
+impl fmt::Display for DeclError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeclError::Unexpected(token) => write!(f, "unexpected {:?} in declaration", token),
+            DeclError::UnexpectedEnd => write!(f, "unexpected end of declaration"),
+            DeclError::InvalidType(specifiers) => {
+                write!(f, "{:?} is not a supported base type", specifiers)
+            }
+            DeclError::TooDeep => {
+                write!(f, "declarator nests more than {} deep", MAX_DEPTH)
+            }
+        }
+    }
+}
+
+impl Error for DeclError {}
+
+/// MAX_DEPTH is how many pointers, arrays and parentheses a declarator can
+/// nest, well past the 12 the C standard asks compilers to support.
+pub const MAX_DEPTH: usize = 256;
+
+/// parse_decl returns the type of a C declaration such as `const char
+/// *argv[]`, or an abstract one such as `unsigned long *`. A trailing `;`
+/// is allowed.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::decl::TypeExpr;
+/// let ty = parse_decl("const char *argv[]").unwrap();
+/// assert!(matches!(ty, TypeExpr::Array(_, None)));
+/// assert_eq!(parse_decl("long").unwrap().size(DataModel::LLP64), Some(4));
+/// assert!(parse_decl("short char c").is_err());
+/// ```
+pub fn parse_decl(decl: &str) -> Result<TypeExpr, DeclError> {
+    parse_declaration(decl).map(|declaration| declaration.ty)
+}
+
+/// parse_declaration returns the name and type of a C declaration.
+///
+/// # Example
+/// ```
+/// use data_models::decl::parse_declaration;
+/// let decl = parse_declaration("volatile int *const flags[2];").unwrap();
+/// assert_eq!(decl.name.as_deref(), Some("flags"));
+/// assert_eq!(decl.ty.to_string(), "volatile int *const [2]");
+/// ```
+pub fn parse_declaration(decl: &str) -> Result<Declaration, DeclError> {
+    let tokens = tokenize(decl)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let base = parser.specifiers()?;
+    let (name, ty) = parser.declarator(base)?;
+    if parser.peek() == Some(";") {
+        parser.pos += 1;
+    }
+    match parser.peek() {
+        Some(token) => Err(DeclError::Unexpected(token.to_string())),
+        None => Ok(Declaration { name, ty }),
+    }
+}
+
+impl TypeExpr {
+    /// size returns `sizeof` the type under `abi`, or `None` for an
+    /// incomplete type (`void`, `T[]`), a base type the model lacks or a
+    /// size that overflows `usize`.
+    pub fn size(&self, abi: impl Into<Abi>) -> Option<usize> {
+        let abi = abi.into();
+        match self {
+            TypeExpr::Void(_) => None,
+            TypeExpr::Base(ty, _) => Some(abi.size(*ty)).filter(|&size| size > 0),
+            TypeExpr::Pointer(..) => Some(abi.size(CType::Pointer)).filter(|&size| size > 0),
+            TypeExpr::Array(element, n) => element.size(abi)?.checked_mul((*n)?),
+        }
+    }
+
+    /// align returns the alignment of the type inside a record under
+    /// `abi`, or `None` for `void` or a base type the model lacks.
+    pub fn align(&self, abi: impl Into<Abi>) -> Option<usize> {
+        let abi = abi.into();
+        match self {
+            TypeExpr::Void(_) => None,
+            TypeExpr::Base(ty, _) => (abi.size(*ty) > 0).then_some(abi.align(*ty)),
+            TypeExpr::Pointer(..) => Some(abi.align(CType::Pointer)),
+            TypeExpr::Array(element, _) => element.align(abi),
+        }
+    }
+
+    /// field_type returns the type as a record field, with `T[]` as a
+    /// flexible array member, or `None` for `void`. Qualifiers do not
+    /// change the layout and are dropped.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::Record;
+    /// let ty = parse_decl("void *slots[3]").unwrap().field_type().unwrap();
+    /// let layout = Record::new("s").field("slots", ty).layout(DataModel::LP64).unwrap();
+    /// assert_eq!(layout.size, 24);
+    /// ```
+    pub fn field_type(&self) -> Option<FieldType> {
+        match self {
+            TypeExpr::Void(_) => None,
+            TypeExpr::Base(ty, _) => Some(FieldType::Scalar(*ty)),
+            TypeExpr::Pointer(pointee, _) => match pointee.field_type() {
+                Some(pointee) => Some(FieldType::pointer(pointee)),
+                None => Some(FieldType::Scalar(CType::Pointer)),
+            },
+            TypeExpr::Array(element, Some(n)) => Some(FieldType::array(element.field_type()?, *n)),
+            TypeExpr::Array(element, None) => Some(FieldType::flexible(element.field_type()?)),
+        }
+    }
+
+    /// declare spells a declaration of `name` with the type, like
+    /// `char *names[4]`.
+    pub fn declare(&self, name: &str) -> String {
+        let spelled = self.spell(name.to_string());
+        spelled.trim_end().to_string()
+    }
+
+    /// spell wraps the declarator `inner` in the type.
+    fn spell(&self, inner: String) -> String {
+        let join = |base: String, inner: String| match inner.as_str() {
+            "" => base,
+            _ => format!("{} {}", base, inner),
+        };
+        match self {
+            TypeExpr::Void(q) => join(format!("{}void", q), inner),
+            TypeExpr::Base(ty, q) => join(format!("{}{}", q, ty.c_name()), inner),
+            TypeExpr::Pointer(pointee, q) => {
+                let qualifiers = q.to_string();
+                let pointer = match (qualifiers.is_empty(), inner.is_empty()) {
+                    (true, _) => format!("*{}", inner),
+                    (false, true) => format!("*{}", qualifiers.trim_end()),
+                    (false, false) => format!("*{}{}", qualifiers, inner),
+                };
+                match **pointee {
+                    TypeExpr::Array(..) => pointee.spell(format!("({})", pointer)),
+                    _ => pointee.spell(pointer),
+                }
+            }
+            TypeExpr::Array(element, n) => {
+                let n = n.map(|n| n.to_string()).unwrap_or_default();
+                let inner = match inner.is_empty() {
+                    true => format!("[{}]", n),
+                    false => format!("{}[{}]", inner, n),
+                };
+                element.spell(inner)
+            }
+        }
+    }
+}
+
+/// TypeExpr displays as the abstract declarator of the type, like
+/// `char *[4]`.
+impl fmt::Display for TypeExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.spell(String::new()))
+    }
+}
+
+/// Qualifiers display as the keywords, each followed by a space.
+impl fmt::Display for Qualifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_const {
+            f.write_str("const ")?;
+        }
+        if self.is_volatile {
+            f.write_str("volatile ")?;
+        }
+        if self.is_restrict {
+            f.write_str("restrict ")?;
+        }
+        Ok(())
+    }
+}
+
+/// tokenize splits a declaration into identifiers, numbers and the
+/// punctuation of declarators.
+fn tokenize(decl: &str) -> Result<Vec<String>, DeclError> {
+    let mut tokens = Vec::new();
+    let mut chars = decl.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(decl[start..end].to_string());
+        } else if "*[]();".contains(c) {
+            tokens.push(c.to_string());
+            chars.next();
+        } else {
+            return Err(DeclError::Unexpected(c.to_string()));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parser walks the tokens of a declaration.
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Result<String, DeclError> {
+        let token = self.tokens.get(self.pos).ok_or(DeclError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token.clone())
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), DeclError> {
+        match self.next()? {
+            token if token == expected => Ok(()),
+            token => Err(DeclError::Unexpected(token)),
+        }
+    }
+
+    /// qualifiers reads the qualifiers at the current token.
+    fn qualifiers(&mut self, qualifiers: &mut Qualifiers) -> bool {
+        let flag = match self.peek() {
+            Some("const") => &mut qualifiers.is_const,
+            Some("volatile") => &mut qualifiers.is_volatile,
+            Some("restrict") => &mut qualifiers.is_restrict,
+            _ => return false,
+        };
+        *flag = true;
+        self.pos += 1;
+        true
+    }
+
+    /// specifiers reads the type specifiers and qualifiers that start the
+    /// declaration as its base type.
+    fn specifiers(&mut self) -> Result<TypeExpr, DeclError> {
+        let mut qualifiers = Qualifiers::default();
+        let mut words = Vec::new();
+        loop {
+            if self.qualifiers(&mut qualifiers) {
+                continue;
+            }
+            match self.peek() {
+                Some(
+                    word @ ("void" | "_Bool" | "bool" | "char" | "short" | "int" | "long"
+                    | "signed" | "unsigned" | "float" | "double"),
+                ) => {
+                    words.push(word.to_string());
+                    self.pos += 1;
+                }
+                _ => break,
+            }
+        }
+        let invalid = || DeclError::InvalidType(words.join(" "));
+        if qualifiers.is_restrict {
+            return Err(invalid());
+        }
+        if words.is_empty() {
+            return match self.peek() {
+                Some(token) => Err(DeclError::Unexpected(token.to_string())),
+                None => Err(DeclError::UnexpectedEnd),
+            };
+        }
+        let count = |word: &str| words.iter().filter(|w| *w == word).count();
+        let (signed, unsigned) = (count("signed"), count("unsigned"));
+        if signed + unsigned > 1 {
+            return Err(invalid());
+        }
+        let rest: Vec<&str> = words
+            .iter()
+            .map(String::as_str)
+            .filter(|w| *w != "signed" && *w != "unsigned" && *w != "int")
+            .collect();
+        let int = count("int");
+        let sign = signed + unsigned == 1;
+        let ty = match (rest.as_slice(), int) {
+            (["void"], 0) if !sign => return Ok(TypeExpr::Void(qualifiers)),
+            (["_Bool"] | ["bool"], 0) if !sign => CType::Bool,
+            (["float"], 0) if !sign => CType::Float,
+            (["double"], 0) if !sign => CType::Double,
+            (["char"], 0) if signed == 1 => CType::SChar,
+            (["char"], 0) if unsigned == 1 => CType::UChar,
+            (["char"], 0) => CType::Char,
+            (["short"], 0 | 1) if unsigned == 1 => CType::UShort,
+            (["short"], 0 | 1) => CType::Short,
+            ([], 0 | 1) if unsigned == 1 => CType::UInt,
+            ([], 0 | 1) => CType::Int,
+            (["long"], 0 | 1) if unsigned == 1 => CType::ULong,
+            (["long"], 0 | 1) => CType::Long,
+            (["long", "long"], 0 | 1) if unsigned == 1 => CType::ULongLong,
+            (["long", "long"], 0 | 1) => CType::LongLong,
+            _ => return Err(invalid()),
+        };
+        Ok(TypeExpr::Base(ty, qualifiers))
+    }
+
+    /// declarator reads a declarator applied to `base`, returning the
+    /// declared name and its type.
+    fn declarator(&mut self, base: TypeExpr) -> Result<(Option<String>, TypeExpr), DeclError> {
+        let (name, derivations) = self.derivations(0)?;
+        let ty = derivations
+            .into_iter()
+            .fold(base, |ty, derivation| match derivation {
+                Derivation::Pointer(qualifiers) => TypeExpr::Pointer(Box::new(ty), qualifiers),
+                Derivation::Array(n) => TypeExpr::Array(Box::new(ty), n),
+            });
+        Ok((name, ty))
+    }
+
+    /// derivations reads a declarator inside `depth` parentheses,
+    /// returning the declared name and the steps from the base type to its
+    /// type, in the order they apply.
+    fn derivations(
+        &mut self,
+        depth: usize,
+    ) -> Result<(Option<String>, Vec<Derivation>), DeclError> {
+        let mut derivations = Vec::new();
+        while self.peek() == Some("*") {
+            self.pos += 1;
+            let mut qualifiers = Qualifiers::default();
+            while self.qualifiers(&mut qualifiers) {}
+            derivations.push(Derivation::Pointer(qualifiers));
+        }
+        // A parenthesized declarator applies to the type its suffixes
+        // make, so its steps come after theirs.
+        let mut name = None;
+        let mut nested = Vec::new();
+        match self.peek() {
+            Some("(") => {
+                // Only a declarator can follow, not `)` or a type as in a
+                // function's parameter list.
+                match self.tokens.get(self.pos + 1).map(String::as_str) {
+                    None | Some("*" | "(") => {}
+                    Some(token) if is_identifier(token) => {}
+                    _ => return Err(DeclError::Unexpected("(".to_string())),
+                }
+                if depth == MAX_DEPTH {
+                    return Err(DeclError::TooDeep);
+                }
+                self.pos += 1;
+                (name, nested) = self.derivations(depth + 1)?;
+                self.expect(")")?;
+            }
+            Some(token) if is_identifier(token) => name = Some(self.next()?),
+            _ => {}
+        }
+        let mut dimensions = Vec::new();
+        while self.peek() == Some("[") {
+            self.pos += 1;
+            match self.next()?.as_str() {
+                "]" => dimensions.push(None),
+                n => {
+                    dimensions.push(Some(parse_number(n)?));
+                    self.expect("]")?;
+                }
+            }
+        }
+        if self.peek() == Some("(") {
+            return Err(DeclError::Unexpected("(".to_string()));
+        }
+        derivations.extend(dimensions.into_iter().rev().map(Derivation::Array));
+        derivations.append(&mut nested);
+        if derivations.len() > MAX_DEPTH {
+            return Err(DeclError::TooDeep);
+        }
+        Ok((name, derivations))
+    }
+}
+
+/// Derivation is one step of a declarator from the base type toward the
+/// declared type.
+enum Derivation {
+    Pointer(Qualifiers),
+    Array(Option<usize>),
+}
+
+/// is_identifier reports whether `token` can name a declaration.
+fn is_identifier(token: &str) -> bool {
+    let keyword = matches!(
+        token,
+        "void"
+            | "_Bool"
+            | "bool"
+            | "char"
+            | "short"
+            | "int"
+            | "long"
+            | "signed"
+            | "unsigned"
+            | "float"
+            | "double"
+            | "const"
+            | "volatile"
+            | "restrict"
+    );
+    !keyword && token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+}
+
+/// parse_number reads an array length in decimal, octal or hexadecimal,
+/// with an optional integer suffix.
+fn parse_number(token: &str) -> Result<usize, DeclError> {
+    let digits = token.trim_end_matches(['u', 'U', 'l', 'L']);
+    let parsed = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        usize::from_str_radix(hex, 16)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        usize::from_str_radix(&digits[1..], 8)
+    } else {
+        digits.parse()
+    };
+    parsed.map_err(|_| DeclError::Unexpected(token.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataModel;
+
+    #[test]
+    fn test_parse_decl() {
+        let int = |q| Box::new(TypeExpr::Base(CType::Int, q));
+        let none = Qualifiers::default();
+        assert_eq!(
+            parse_decl("int *a[2][3]").unwrap(),
+            TypeExpr::Array(
+                Box::new(TypeExpr::Array(
+                    Box::new(TypeExpr::Pointer(int(none), none)),
+                    Some(3)
+                )),
+                Some(2)
+            )
+        );
+        assert_eq!(
+            parse_decl("int (*a)[0x10]").unwrap(),
+            TypeExpr::Pointer(Box::new(TypeExpr::Array(int(none), Some(16))), none)
+        );
+        let decl = parse_declaration("char *(*table[4])[8]").unwrap();
+        assert_eq!(decl.name.as_deref(), Some("table"));
+        assert_eq!(decl.ty.declare("table"), "char *(*table[4])[8]");
+        assert_eq!(decl.ty.size(DataModel::LP64), Some(32));
+
+        let cases = [
+            ("unsigned", CType::UInt),
+            ("signed char", CType::SChar),
+            ("long unsigned int", CType::ULong),
+            ("long long int", CType::LongLong),
+            ("short signed", CType::Short),
+            ("_Bool", CType::Bool),
+        ];
+        for (decl, ty) in cases {
+            assert_eq!(
+                parse_decl(decl).unwrap(),
+                TypeExpr::Base(ty, none),
+                "{}",
+                decl
+            );
+        }
+
+        let ty = parse_decl("const void *restrict p").unwrap();
+        assert_eq!(ty.size(DataModel::IP16), Some(2));
+        assert_eq!(ty.field_type(), Some(FieldType::Scalar(CType::Pointer)));
+        assert_eq!(parse_decl("long x").unwrap().size(DataModel::IP16), None);
+        assert_eq!(
+            parse_decl("char a[0x8000000000000000][2]")
+                .unwrap()
+                .size(DataModel::LP64),
+            None
+        );
+        assert_eq!(
+            parse_decl("double d").unwrap().align(DataModel::ILP32),
+            Some(8)
+        );
+
+        assert_eq!(
+            parse_decl("long double x"),
+            Err(DeclError::InvalidType("long double".into()))
+        );
+        assert_eq!(
+            parse_decl("int (*f)(void)"),
+            Err(DeclError::Unexpected("(".into()))
+        );
+        for decl in ["int ()", "int (int)", "int (const x)", "char (*)()"] {
+            assert_eq!(
+                parse_decl(decl),
+                Err(DeclError::Unexpected("(".into())),
+                "{}",
+                decl
+            );
+        }
+        assert_eq!(
+            parse_decl("int ((x))[2]").unwrap().size(DataModel::LP64),
+            Some(8)
+        );
+        assert_eq!(parse_decl("int a[4"), Err(DeclError::UnexpectedEnd));
+        let nested = |n| format!("int {}x{}", "(".repeat(n), ")".repeat(n));
+        assert_eq!(parse_decl(&nested(MAX_DEPTH)), Ok(*int(none)));
+        assert_eq!(parse_decl(&nested(100_000)), Err(DeclError::TooDeep));
+        let pointers = format!("char {}p", "*".repeat(MAX_DEPTH + 1));
+        assert_eq!(parse_decl(&pointers), Err(DeclError::TooDeep));
+        let arrays = format!("char (*a{})", "[1]".repeat(MAX_DEPTH));
+        assert_eq!(parse_decl(&arrays), Err(DeclError::TooDeep));
+        assert_eq!(
+            parse_decl("struct s x"),
+            Err(DeclError::Unexpected("struct".into()))
+        );
+        assert_eq!(
+            parse_decl("int a b"),
+            Err(DeclError::Unexpected("b".into()))
+        );
+    }
+}
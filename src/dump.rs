@@ -0,0 +1,274 @@
+//! Record layouts dumped by compilers, for checking the layout engine
+//! against the reference compiler of a target.
+//!
+//! A compiler asked to print its record layouts gives the ground truth for
+//! its target. [`clang::parse`] reads the output of
+//! `clang -Xclang -fdump-record-layouts` and [`msvc::parse`] that of
+//! `cl /d1reportSingleClassLayout` into [`DumpedRecord`]s, and
+//! [`DumpedRecord::compare`] reports where a computed [`Layout`] disagrees,
+//! so a CI job can compile a header per target and fail on any mismatch.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::dump::clang;
+//! use data_models::layout::Record;
+//! let output = "\
+//! *** Dumping AST Record Layout
+//!          0 | struct s
+//!          0 |   char c
+//!          8 |   long n
+//!            | [sizeof=16, align=8]
+//! ";
+//! let dumped = clang::parse(output).unwrap();
+//! let record = Record::new("s").field("c", CType::Char).field("n", CType::Long);
+//! assert!(dumped[0].compare(&record.layout(DataModel::LP64).unwrap()).is_empty());
+//! assert_eq!(dumped[0].compare(&record.layout(DataModel::LLP64).unwrap()).len(), 3);
+//! ```
+
+use crate::layout::{FieldLayout, FieldType, Layout, RecordKind};
+use std::error::Error;
+use std::fmt;
+
+pub mod clang;
+pub mod msvc;
+
+/// DumpedRecord is the layout of a record as a compiler printed it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpedRecord {
+    /// The struct, union or class tag.
+    pub name: String,
+    /// Whether the record is a struct or a union. Classes are structs.
+    pub kind: RecordKind,
+    /// `sizeof` the record.
+    pub size: usize,
+    /// The alignment of the record, or 0 where the dump does not give it,
+    /// as MSVC's does not.
+    pub align: usize,
+    /// Whether the record starts with a virtual table pointer.
+    pub dynamic: bool,
+    /// The base classes and fields in order.
+    pub fields: Vec<DumpedField>,
+}
+
+/// DumpedField is one base class or field of a [`DumpedRecord`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpedField {
+    /// The field name, or the tag of a base class.
+    pub name: String,
+    /// The type as the compiler spelled it, or empty where the dump does
+    /// not give it.
+    pub ty: String,
+    /// The offset in bytes from the start of the outermost record.
+    pub offset: usize,
+    /// For bit-fields, the first bit counted from the byte at `offset` and
+    /// the width in bits.
+    pub bits: Option<(usize, usize)>,
+    /// Whether the entry is a base class rather than a field.
+    pub base: bool,
+    /// The fields of a record-typed field, with offsets from the start of
+    /// the outermost record.
+    pub fields: Vec<DumpedField>,
+}
+
+/// Mismatch is a disagreement between a dumped and a computed layout.
+/// Fields are named by their path, like `outer.inner.x`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Mismatch {
+    /// The records differ in size.
+    Size {
+        /// The dumped size.
+        dumped: usize,
+        /// The computed size.
+        computed: usize,
+    },
+    /// The records differ in alignment.
+    Align {
+        /// The dumped alignment.
+        dumped: usize,
+        /// The computed alignment.
+        computed: usize,
+    },
+    /// A field or base is at a different offset.
+    Offset {
+        /// The field path.
+        field: String,
+        /// The dumped offset.
+        dumped: usize,
+        /// The computed offset.
+        computed: usize,
+    },
+    /// A bit-field starts at a different bit or has a different width.
+    Bits {
+        /// The field path.
+        field: String,
+        /// The dumped first bit, counted from the start of the outermost
+        /// record, and width.
+        dumped: (usize, usize),
+        /// The computed first bit and width.
+        computed: (usize, usize),
+    },
+    /// Only the computed layout has the field.
+    Missing {
+        /// The field path.
+        field: String,
+    },
+    /// Only the dump has the field.
+    Extra {
+        /// The field path.
+        field: String,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Size { dumped, computed } => {
+                write!(f, "size is {} in the dump, computed {}", dumped, computed)
+            }
+            Mismatch::Align { dumped, computed } => {
+                write!(f, "align is {} in the dump, computed {}", dumped, computed)
+            }
+            Mismatch::Offset {
+                field,
+                dumped,
+                computed,
+            } => write!(
+                f,
+                "{} is at offset {} in the dump, computed {}",
+                field, dumped, computed
+            ),
+            Mismatch::Bits {
+                field,
+                dumped,
+                computed,
+            } => write!(
+                f,
+                "{} is bits {}+{} in the dump, computed {}+{}",
+                field, dumped.0, dumped.1, computed.0, computed.1
+            ),
+            Mismatch::Missing { field } => write!(f, "{} is missing in the dump", field),
+            Mismatch::Extra { field } => write!(f, "{} is only in the dump", field),
+        }
+    }
+}
+
+/// DumpError describes why compiler output could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DumpError {
+    /// A line of a record layout is not in the expected form.
+    Malformed {
+        /// The line number, counted from 1.
+        line: usize,
+        /// The line as written.
+        text: String,
+    },
+    /// The output ends in the middle of a record layout.
+    Truncated {
+        /// The record tag.
+        record: String,
+    },
+}
+
+impl fmt::Display for DumpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DumpError::Malformed { line, text } => {
+                write!(f, "line {}: unexpected record layout line {:?}", line, text)
+            }
+            DumpError::Truncated { record } => {
+                write!(f, "the layout of {} is truncated", record)
+            }
+        }
+    }
+}
+
+impl Error for DumpError {}
+
+impl DumpedRecord {
+    /// compare returns every disagreement between the dump and the
+    /// computed `layout`, matching fields and bases by name. An empty
+    /// result means the layout engine agrees with the compiler. Unnamed
+    /// fields are skipped, since compilers leave them out or name them
+    /// after their position.
+    pub fn compare(&self, layout: &Layout) -> Vec<Mismatch> {
+        let mut mismatches = Vec::new();
+        if self.size != layout.size {
+            mismatches.push(Mismatch::Size {
+                dumped: self.size,
+                computed: layout.size,
+            });
+        }
+        if self.align != 0 && self.align != layout.align {
+            mismatches.push(Mismatch::Align {
+                dumped: self.align,
+                computed: layout.align,
+            });
+        }
+        compare_fields(&mut mismatches, "", &self.fields, layout, 0);
+        mismatches
+    }
+}
+
+/// compare_fields compares the dumped fields of a record at `base` bytes
+/// into the outermost record with its computed layout.
+fn compare_fields(
+    mismatches: &mut Vec<Mismatch>,
+    prefix: &str,
+    dumped: &[DumpedField],
+    layout: &Layout,
+    base: usize,
+) {
+    let path = |name: &str| format!("{}{}", prefix, name);
+    let computed: Vec<&FieldLayout> = layout
+        .bases
+        .iter()
+        .chain(&layout.fields)
+        .filter(|field| !field.name.is_empty())
+        .collect();
+    for field in &computed {
+        let entry = match dumped.iter().find(|entry| entry.name == field.name) {
+            Some(entry) => entry,
+            None => {
+                mismatches.push(Mismatch::Missing {
+                    field: path(&field.name),
+                });
+                continue;
+            }
+        };
+        match (entry.bits, field.bits) {
+            (Some((first, width)), Some(bits)) => {
+                let dumped = (entry.offset * 8 + first, width);
+                let computed = ((base + field.offset) * 8 + field.bit_offset, bits);
+                if dumped != computed {
+                    mismatches.push(Mismatch::Bits {
+                        field: path(&field.name),
+                        dumped,
+                        computed,
+                    });
+                }
+            }
+            _ if entry.offset != base + field.offset => mismatches.push(Mismatch::Offset {
+                field: path(&field.name),
+                dumped: entry.offset,
+                computed: base + field.offset,
+            }),
+            _ => {}
+        }
+        let record = matches!(field.ty, FieldType::Record(_) | FieldType::Layout(_));
+        if let (true, Some(nested)) = (record && !entry.fields.is_empty(), &field.nested) {
+            let prefix = format!("{}{}.", prefix, field.name);
+            let offset = base + field.offset;
+            compare_fields(mismatches, &prefix, &entry.fields, nested, offset);
+        }
+    }
+    for entry in dumped {
+        if !entry.name.is_empty() && !computed.iter().any(|f| f.name == entry.name) {
+            mismatches.push(Mismatch::Extra {
+                field: path(&entry.name),
+            });
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! The record layouts clang prints with `-Xclang -fdump-record-layouts`.
+//!
+//! Each record comes as a block like
+//!
+//! ```text
+//! *** Dumping AST Record Layout
+//!          0 | struct s
+//!          0 |   char c
+//!      1:0-2 |   unsigned int a
+//!          4 |   struct inner in
+//!          4 |     int x
+//!            | [sizeof=8, align=4]
+//! ```
+//!
+//! where a bit-field's offset reads as its byte, then the range of bits in
+//! that byte, and nesting shows as indentation. C++ classes add base
+//! classes, virtual table pointers and a second line of sizes. The other
+//! dumps clang interleaves, like `*** Dumping IRgen Record Layout`, are
+//! skipped.
+
+use super::{DumpError, DumpedField, DumpedRecord};
+use crate::layout::RecordKind;
+
+/// The line starting each record layout.
+const HEADER: &str = "*** Dumping AST Record Layout";
+
+/// Entry is one line of a record layout with its nesting depth.
+struct Entry {
+    depth: usize,
+    offset: usize,
+    bits: Option<(usize, usize)>,
+    text: String,
+}
+
+/// parse reads every record layout in the output of
+/// `clang -Xclang -fdump-record-layouts`, in order. Compiler diagnostics
+/// and other output between the layouts are ignored.
+///
+/// # Example
+/// ```
+/// use data_models::dump::clang;
+/// let output = "\
+/// *** Dumping AST Record Layout
+///          0 | struct s
+///          0 |   char c
+///      1:0-2 |   unsigned int a
+///            | [sizeof=4, align=4]
+/// ";
+/// let records = clang::parse(output).unwrap();
+/// assert_eq!((records[0].name.as_str(), records[0].size), ("s", 4));
+/// assert_eq!(records[0].fields[1].bits, Some((0, 3)));
+/// ```
+pub fn parse(output: &str) -> Result<Vec<DumpedRecord>, DumpError> {
+    let mut records = Vec::new();
+    let mut lines = output.lines().enumerate();
+    while let Some((_, line)) = lines.next() {
+        if line.trim() != HEADER {
+            continue;
+        }
+        let mut entries = Vec::new();
+        let mut sizes = String::new();
+        for (number, line) in &mut lines {
+            let malformed = || DumpError::Malformed {
+                line: number + 1,
+                text: line.to_string(),
+            };
+            let (offset, text) = line.split_once('|').ok_or_else(malformed)?;
+            let (offset, text) = (offset.trim(), text.trim_end());
+            if offset.is_empty() {
+                sizes.push_str(text.trim());
+                if sizes.ends_with(']') {
+                    break;
+                }
+                continue;
+            }
+            let spaces = text.len() - text.trim_start().len();
+            let (offset, bits) = parse_offset(offset).ok_or_else(malformed)?;
+            entries.push(Entry {
+                depth: spaces.saturating_sub(1) / 2,
+                offset,
+                bits,
+                text: text.trim().to_string(),
+            });
+        }
+        let mut entries = entries.into_iter();
+        let header = match entries.next() {
+            Some(header) => header,
+            None => continue,
+        };
+        let (keyword, name) = header.text.split_once(' ').unwrap_or(("struct", ""));
+        if !sizes.ends_with(']') {
+            return Err(DumpError::Truncated {
+                record: name.to_string(),
+            });
+        }
+        let mut rest: Vec<Entry> = entries.collect();
+        let dynamic = rest
+            .iter()
+            .any(|entry| entry.text.ends_with("vtable pointer)"));
+        rest.retain(|entry| !entry.text.ends_with("vtable pointer)"));
+        let mut index = 0;
+        records.push(DumpedRecord {
+            name: name.to_string(),
+            kind: match keyword {
+                "union" => RecordKind::Union,
+                _ => RecordKind::Struct,
+            },
+            size: size_value(&sizes, "sizeof").unwrap_or(0),
+            align: size_value(&sizes, "align").unwrap_or(0),
+            dynamic,
+            fields: build(&rest, &mut index, 1),
+        });
+    }
+    Ok(records)
+}
+
+/// parse_offset reads `8` or a bit-field's `1:0-2`, which is byte 1, bits
+/// 0 to 2. A zero-width bit-field reads `4:-`.
+fn parse_offset(offset: &str) -> Option<(usize, Option<(usize, usize)>)> {
+    match offset.split_once(':') {
+        None => Some((offset.parse().ok()?, None)),
+        Some((byte, "-")) => Some((byte.parse().ok()?, Some((0, 0)))),
+        Some((byte, range)) => {
+            let (first, last) = range.split_once('-')?;
+            let (first, last): (usize, usize) = (first.parse().ok()?, last.parse().ok()?);
+            Some((byte.parse().ok()?, Some((first, last + 1 - first))))
+        }
+    }
+}
+
+/// size_value finds `key=value` in the sizes line.
+fn size_value(sizes: &str, key: &str) -> Option<usize> {
+    sizes
+        .trim_matches(|c| c == '[' || c == ']')
+        .split(',')
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|&(k, _)| k == key)
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+/// build turns the entries at `depth` from `index` on into fields, with
+/// the deeper entries after each as its members.
+fn build(entries: &[Entry], index: &mut usize, depth: usize) -> Vec<DumpedField> {
+    let mut fields = Vec::new();
+    while let Some(entry) = entries.get(*index).filter(|entry| entry.depth >= depth) {
+        *index += 1;
+        let members = build(entries, index, depth + 1);
+        if entry.bits == Some((0, 0)) {
+            continue;
+        }
+        let (ty, name, base) = declaration(&entry.text);
+        fields.push(DumpedField {
+            name,
+            ty,
+            offset: entry.offset,
+            bits: entry.bits,
+            base,
+            fields: members,
+        });
+    }
+    fields
+}
+
+/// declaration splits `unsigned int a` into its type and name, and `class
+/// B (primary base)` into the base's type and tag.
+fn declaration(text: &str) -> (String, String, bool) {
+    let mut text = text;
+    let mut base = false;
+    for suffix in ["(empty)", "(base)", "(primary base)", "(virtual base)"] {
+        if let Some(rest) = text.strip_suffix(suffix) {
+            base |= suffix.contains("base");
+            text = rest.trim_end();
+        }
+    }
+    if base {
+        let tag = text.rsplit(' ').next().unwrap_or(text);
+        return (text.to_string(), tag.to_string(), true);
+    }
+    match text.rsplit_once(' ') {
+        Some((ty, name)) if !name.ends_with(')') => (ty.to_string(), name.to_string(), false),
+        _ => (text.to_string(), String::new(), false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dump::Mismatch;
+    use crate::layout::{Abi, Record};
+    use crate::{CType, DataModel};
+
+    #[test]
+    fn test_parse() {
+        let output = "\
+In file included from t.c:1:
+*** Dumping AST Record Layout
+         0 | struct inner
+         0 |   short x
+           | [sizeof=2, align=2]
+
+*** Dumping AST Record Layout
+         0 | struct s
+         0 |   char c
+     1:0-2 |   unsigned int a
+       4:- |   unsigned int
+     4:0-3 |   unsigned int b
+         6 |   struct inner in
+         6 |     short x
+         8 |   union (anonymous at t.c:9:3)
+         8 |     int u
+           | [sizeof=12, align=4]
+
+*** Dumping IRgen Record Layout
+Record: RecordDecl 0x0 <t.c:3:1> struct s definition
+";
+        let records = parse(output).unwrap();
+        assert_eq!(records.len(), 2);
+        let s = &records[1];
+        let names: Vec<&str> = s.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["c", "a", "b", "in", ""]);
+        assert_eq!(s.fields[2].bits, Some((0, 4)));
+        assert_eq!(s.fields[3].ty, "struct inner");
+        assert_eq!(s.fields[3].fields[0].offset, 6);
+
+        let inner = Record::new("inner").field("x", CType::Short);
+        let record = Record::new("s")
+            .field("c", CType::Char)
+            .bitfield("a", CType::UInt, 3)
+            .bitfield("", CType::UInt, 0)
+            .bitfield("b", CType::UInt, 4)
+            .field("in", inner)
+            .field("", Record::union("").field("u", CType::Int));
+        let layout = record.layout(DataModel::LP64).unwrap();
+        assert_eq!(s.compare(&layout), []);
+
+        let layout = record
+            .layout(Abi::new(DataModel::LP64).with_pack(1))
+            .unwrap();
+        let mismatches = s.compare(&layout);
+        assert!(mismatches.contains(&Mismatch::Align {
+            dumped: 4,
+            computed: 1
+        }));
+        assert!(mismatches.contains(&Mismatch::Offset {
+            field: "in.x".into(),
+            dumped: 6,
+            computed: 5
+        }));
+
+        let class = "\
+*** Dumping AST Record Layout
+         0 | class D
+         0 |   class B (primary base)
+         0 |     (B vtable pointer)
+         8 |     int x
+        12 |   int y
+           | [sizeof=16, dsize=16, align=8,
+           |  nvsize=16, nvalign=8]
+";
+        let records = parse(class).unwrap();
+        assert_eq!((records[0].size, records[0].align), (16, 8));
+        assert!(records[0].dynamic && records[0].fields[0].base);
+        assert_eq!(records[0].fields[0].name, "B");
+        assert_eq!(records[0].fields[0].fields[0].name, "x");
+
+        let truncated = "*** Dumping AST Record Layout\n         0 | struct s\n";
+        assert_eq!(
+            parse(truncated),
+            Err(DumpError::Truncated { record: "s".into() })
+        );
+    }
+}
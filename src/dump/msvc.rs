@@ -0,0 +1,236 @@
+//! The class layouts MSVC prints with `/d1reportSingleClassLayoutNAME` or
+//! `/d1reportAllClassLayout`.
+//!
+//! Each record comes as a block like
+//!
+//! ```text
+//! class D  size(24):
+//!     +---
+//!  0  | +--- (base class B)
+//!  0  | | {vfptr}
+//!  8  | | x
+//!     | | <alignment member> (size=4)
+//!     | +---
+//! 16  | a (bitstart=0,nbits=3)
+//! 16  | b (bitstart=3,nbits=4)
+//!     +---
+//! ```
+//!
+//! with a tab after each offset and the size, where base classes open
+//! nested blocks and bit-fields give their first bit within the storage
+//! unit at the offset. The report names neither field types nor the
+//! alignment of the record, and does not expand fields of record type.
+//! The virtual function tables that follow a class are skipped.
+
+use super::{DumpError, DumpedField, DumpedRecord};
+use crate::layout::RecordKind;
+
+/// parse reads every class layout in the output of `cl
+/// /d1reportSingleClassLayout`, in order. Compiler diagnostics and other
+/// output between the layouts are ignored.
+///
+/// # Example
+/// ```
+/// use data_models::dump::msvc;
+/// let output = "\
+/// class s\tsize(8):
+/// \t+---
+/// 0\t| c
+/// \t| <alignment member> (size=3)
+/// 4\t| a (bitstart=0,nbits=3)
+/// \t+---
+/// ";
+/// let records = msvc::parse(output).unwrap();
+/// assert_eq!((records[0].name.as_str(), records[0].size), ("s", 8));
+/// assert_eq!(records[0].fields[1].bits, Some((0, 3)));
+/// ```
+pub fn parse(output: &str) -> Result<Vec<DumpedRecord>, DumpError> {
+    let mut records = Vec::new();
+    let mut lines = output.lines().enumerate();
+    while let Some((_, line)) = lines.next() {
+        let (keyword, name, size) = match header(line) {
+            Some(header) => header,
+            None => continue,
+        };
+        let truncated = || DumpError::Truncated {
+            record: name.to_string(),
+        };
+        let (_, open) = lines.next().ok_or_else(truncated)?;
+        if open.trim() != "+---" {
+            return Err(truncated());
+        }
+        // The fields of the record and of each open base class.
+        let mut stack: Vec<Vec<DumpedField>> = vec![Vec::new()];
+        let mut dynamic = false;
+        let mut closed = false;
+        for (number, line) in &mut lines {
+            let malformed = || DumpError::Malformed {
+                line: number + 1,
+                text: line.to_string(),
+            };
+            if line.trim() == "+---" {
+                closed = true;
+                break;
+            }
+            let (offset, text) = line.split_once('|').ok_or_else(malformed)?;
+            let mut text = text.trim();
+            while let Some(rest) = text.strip_prefix('|') {
+                text = rest.trim_start();
+            }
+            let offset = offset.trim();
+            if text == "+---" {
+                if stack.len() < 2 {
+                    return Err(malformed());
+                }
+                let members = stack.pop().unwrap_or_default();
+                if let Some(base) = stack.last_mut().and_then(|fields| fields.last_mut()) {
+                    base.fields = members;
+                }
+                continue;
+            }
+            if text.starts_with("<alignment member>") {
+                continue;
+            }
+            if text == "{vfptr}" {
+                dynamic = true;
+                continue;
+            }
+            let offset: usize = offset.parse().map_err(|_| malformed())?;
+            let fields = stack.last_mut().ok_or_else(malformed)?;
+            if let Some(base) = text
+                .strip_prefix("+--- (base class ")
+                .or_else(|| text.strip_prefix("+--- (virtual base "))
+            {
+                let base = base.trim_end_matches(')');
+                fields.push(field(base, base, offset, None, true));
+                stack.push(Vec::new());
+                continue;
+            }
+            let (name, bits) = match text.split_once(" (bitstart=") {
+                Some((name, bits)) => (name, Some(bitfield(bits).ok_or_else(malformed)?)),
+                None => (text, None),
+            };
+            if bits.is_some_and(|(_, width)| width == 0) {
+                continue;
+            }
+            fields.push(field(name, "", offset, bits, false));
+        }
+        if !closed || stack.len() != 1 {
+            return Err(truncated());
+        }
+        records.push(DumpedRecord {
+            name: name.to_string(),
+            kind: match keyword {
+                "union" => RecordKind::Union,
+                _ => RecordKind::Struct,
+            },
+            size,
+            align: 0,
+            dynamic,
+            fields: stack.pop().unwrap_or_default(),
+        });
+    }
+    Ok(records)
+}
+
+/// header reads `class s\tsize(8):` as the keyword, tag and size.
+fn header(line: &str) -> Option<(&str, &str, usize)> {
+    let (declaration, size) = line.trim().strip_suffix("):")?.rsplit_once("size(")?;
+    let (keyword, name) = declaration.trim().split_once(' ')?;
+    if !["class", "struct", "union"].contains(&keyword) {
+        return None;
+    }
+    Some((keyword, name.trim(), size.parse().ok()?))
+}
+
+/// bitfield reads `0,nbits=3)`, the rest of a bit-field's annotation.
+fn bitfield(text: &str) -> Option<(usize, usize)> {
+    let (start, bits) = text.strip_suffix(')')?.split_once(",nbits=")?;
+    Some((start.parse().ok()?, bits.parse().ok()?))
+}
+
+/// field returns an entry without members.
+fn field(
+    name: &str,
+    ty: &str,
+    offset: usize,
+    bits: Option<(usize, usize)>,
+    base: bool,
+) -> DumpedField {
+    DumpedField {
+        name: name.to_string(),
+        ty: ty.to_string(),
+        offset,
+        bits,
+        base,
+        fields: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dump::Mismatch;
+    use crate::layout::{Abi, BitFieldRules, Record};
+    use crate::{CType, DataModel};
+
+    #[test]
+    fn test_parse() {
+        let output = "\
+t.c
+class s\tsize(12):
+\t+---
+ 0\t| c
+  \t| <alignment member> (size=3)
+ 4\t| a (bitstart=0,nbits=3)
+ 4\t| b (bitstart=3,nbits=4)
+ 8\t| n
+\t+---
+
+class D\tsize(24):
+\t+---
+ 0\t| +--- (base class B)
+ 0\t| | {vfptr}
+ 8\t| | x
+  \t| | <alignment member> (size=4)
+\t| +---
+16\t| y
+\t+---
+
+D::$vftable@:
+\t| &D_meta
+\t|  0
+ 0\t| &B::f
+";
+        let records = parse(output).unwrap();
+        assert_eq!(records.len(), 2);
+        let s = &records[0];
+        assert_eq!(s.fields[2].bits, Some((3, 4)));
+
+        let record = Record::new("s")
+            .field("c", CType::Char)
+            .bitfield("a", CType::UInt, 3)
+            .bitfield("b", CType::UInt, 4)
+            .field("n", CType::Short);
+        let msvc = Abi::new(DataModel::LLP64).with_bitfields(BitFieldRules::Msvc);
+        assert_eq!(s.compare(&record.layout(msvc).unwrap()), []);
+        let gcc = record.layout(DataModel::LLP64).unwrap();
+        assert!(s.compare(&gcc).contains(&Mismatch::Offset {
+            field: "n".into(),
+            dumped: 8,
+            computed: 2
+        }));
+
+        let d = &records[1];
+        assert!(d.dynamic);
+        assert_eq!((d.fields[0].name.as_str(), d.fields[0].base), ("B", true));
+        assert_eq!(d.fields[0].fields[0].name, "x");
+        assert_eq!(d.fields[1].offset, 16);
+
+        let truncated = "class s\tsize(4):\n\t+---\n 0\t| n\n";
+        assert_eq!(
+            parse(truncated),
+            Err(DumpError::Truncated { record: "s".into() })
+        );
+    }
+}
@@ -0,0 +1,891 @@
+//! Reconstruct the data model of a compiled object from its DWARF debug
+//! information.
+//!
+//! Every compilation unit records the C base types it uses as
+//! `DW_TAG_base_type` entries carrying a name, a byte size and an encoding.
+//! Together with the address size from the unit header that is enough to
+//! recover the data model the object was built for, along with the
+//! signedness of plain `char`.
+//!
+//! The same goes for `DW_TAG_structure_type` entries: as [`StructureType`]
+//! records, [`verify_layout`] checks a computed [`Layout`] against what the
+//! compiler actually did.
+//!
+//! [`DwarfObject`] reads both from an ELF, Mach-O, PE or other object file
+//! with `object` and `gimli`. Other DWARF readers can hand over the entries
+//! as [`BaseType`] and [`StructureType`] records themselves.
+//!
+//! # Example
+//! ```no_run
+//! use data_models::*;
+//! use data_models::dwarf::{verify_layout, DwarfObject};
+//! use data_models::layout::Record;
+//! let data = std::fs::read("header.o").unwrap();
+//! let object = DwarfObject::parse(&data).unwrap();
+//! let found = object.data_model().unwrap();
+//! let computed = Record::new("header")
+//!     .field("kind", CType::Char)
+//!     .field("length", CType::Long)
+//!     .layout(found.model)
+//!     .unwrap();
+//! let structure = object.structure("header").unwrap().unwrap();
+//! assert!(verify_layout(&structure, &computed).is_empty());
+//! ```
+
+use crate::layout::{FieldLayout, Layout};
+use crate::DataModel;
+use gimli::{AttributeValue, DebuggingInformationEntry, DwAt, RunTimeEndian, Unit, UnitOffset};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+/// `DW_ATE_boolean`
+pub const DW_ATE_BOOLEAN: u8 = 0x02;
+/// `DW_ATE_float`
+pub const DW_ATE_FLOAT: u8 = 0x04;
+/// `DW_ATE_signed`
+pub const DW_ATE_SIGNED: u8 = 0x05;
+/// `DW_ATE_signed_char`
+pub const DW_ATE_SIGNED_CHAR: u8 = 0x06;
+/// `DW_ATE_unsigned`
+pub const DW_ATE_UNSIGNED: u8 = 0x07;
+/// `DW_ATE_unsigned_char`
+pub const DW_ATE_UNSIGNED_CHAR: u8 = 0x08;
+
+/// BaseType holds the attributes of one `DW_TAG_base_type` entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaseType<'a> {
+    /// `DW_AT_name`, e.g. `"long unsigned int"`.
+    pub name: &'a str,
+    /// `DW_AT_byte_size`
+    pub byte_size: u64,
+    /// `DW_AT_encoding`, one of the `DW_ATE_*` constants.
+    pub encoding: u8,
+}
+
+/// DwarfModel is the data model recovered from debug information.
+#[derive(Debug, PartialEq)]
+pub struct DwarfModel {
+    /// The data model matching the sizes of `int`, `long` and pointers.
+    pub model: DataModel,
+    /// Whether plain `char` is signed, if the object describes `char` at all.
+    pub char_signed: Option<bool>,
+}
+
+/// The integer families a base type name can spell.
+#[derive(Debug, PartialEq)]
+enum Family {
+    PlainChar,
+    Char,
+    Short,
+    Int,
+    Long,
+    LongLong,
+}
+
+/// family normalizes the many spellings compilers use for the same type
+/// ("long int", "long unsigned int", "signed long", ...).
+fn family(name: &str) -> Option<Family> {
+    let mut longs = 0;
+    let (mut short, mut char, mut int, mut sign) = (false, false, false, false);
+    for word in name.split_whitespace() {
+        match word {
+            "long" => longs += 1,
+            "short" => short = true,
+            "char" => char = true,
+            "int" => int = true,
+            "signed" | "unsigned" => sign = true,
+            _ => return None,
+        }
+    }
+    match (char, short, longs) {
+        (true, false, 0) if !int && sign => Some(Family::Char),
+        (true, false, 0) if !int => Some(Family::PlainChar),
+        (false, true, 0) => Some(Family::Short),
+        (false, false, 0) if int || sign => Some(Family::Int),
+        (false, false, 1) => Some(Family::Long),
+        (false, false, 2) => Some(Family::LongLong),
+        _ => None,
+    }
+}
+
+/// from_base_types reconstructs the data model from the base types of a
+/// compilation unit and the unit's address size in bytes.
+///
+/// Types that are not integers (`float`, `_Bool`, `__int128`, ...) are
+/// ignored. A model without `long` (IP16) is recognized by its absence.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::dwarf::*;
+/// let types = vec![
+///     BaseType { name: "char", byte_size: 1, encoding: DW_ATE_SIGNED_CHAR },
+///     BaseType { name: "int", byte_size: 4, encoding: DW_ATE_SIGNED },
+///     BaseType { name: "long int", byte_size: 8, encoding: DW_ATE_SIGNED },
+/// ];
+/// let found = from_base_types(8, types);
+/// assert_eq!(found.model, DataModel::LP64);
+/// assert_eq!(found.char_signed, Some(true));
+/// ```
+pub fn from_base_types<'a, I>(address_size: u8, types: I) -> DwarfModel
+where
+    I: IntoIterator<Item = BaseType<'a>>,
+{
+    let (mut short, mut int, mut long) = (0, 0, 0);
+    let mut char_signed = None;
+    for ty in types {
+        let size = ty.byte_size as usize;
+        match family(ty.name) {
+            Some(Family::PlainChar) => match ty.encoding {
+                DW_ATE_SIGNED_CHAR | DW_ATE_SIGNED => char_signed = Some(true),
+                DW_ATE_UNSIGNED_CHAR | DW_ATE_UNSIGNED => char_signed = Some(false),
+                _ => {}
+            },
+            Some(Family::Short) => short = size,
+            Some(Family::Int) => int = size,
+            Some(Family::Long) => long = size,
+            Some(Family::Char) | Some(Family::LongLong) | None => {}
+        }
+    }
+    let model = match DataModel::new(int, long, address_size as usize) {
+        DataModel::ILP64 if short == 8 => DataModel::SILP64,
+        model => model,
+    };
+    DwarfModel { model, char_signed }
+}
+
+/// StructureType holds the attributes of one `DW_TAG_structure_type` or
+/// `DW_TAG_union_type` entry and of its `DW_TAG_member` children.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructureType<'a> {
+    /// `DW_AT_name`
+    pub name: &'a str,
+    /// `DW_AT_byte_size`
+    pub byte_size: u64,
+    /// The members in declaration order.
+    pub members: Vec<Member<'a>>,
+}
+
+/// Member holds the attributes of one `DW_TAG_member` entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member<'a> {
+    /// `DW_AT_name`
+    pub name: &'a str,
+    /// `DW_AT_data_member_location`, or the byte holding the first bit of
+    /// a bit-field described by `DW_AT_data_bit_offset`.
+    pub offset: u64,
+    /// `DW_AT_byte_size` of the member's type, found through `DW_AT_type`.
+    pub byte_size: u64,
+    /// `DW_AT_data_bit_offset` of a bit-field, counted from the start of
+    /// the record.
+    pub data_bit_offset: Option<u64>,
+    /// `DW_AT_bit_size` of a bit-field.
+    pub bit_size: Option<u64>,
+}
+
+/// Mismatch is a disagreement between the debug information and a
+/// computed layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Mismatch {
+    /// The records differ in size.
+    Size {
+        /// The size in the debug information.
+        dwarf: u64,
+        /// The computed size.
+        computed: usize,
+    },
+    /// A field is at a different offset.
+    Offset {
+        /// The field name.
+        field: String,
+        /// The offset in the debug information.
+        dwarf: u64,
+        /// The computed offset.
+        computed: usize,
+    },
+    /// A field has a different size.
+    FieldSize {
+        /// The field name.
+        field: String,
+        /// The size in the debug information.
+        dwarf: u64,
+        /// The computed size.
+        computed: usize,
+    },
+    /// A bit-field starts at a different bit of the record.
+    BitOffset {
+        /// The field name.
+        field: String,
+        /// `DW_AT_data_bit_offset`
+        dwarf: u64,
+        /// The computed first bit.
+        computed: usize,
+    },
+    /// Only the computed layout has the field.
+    Missing {
+        /// The field name.
+        field: String,
+    },
+    /// Only the debug information has the field.
+    Extra {
+        /// The field name.
+        field: String,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Size { dwarf, computed } => {
+                write!(f, "size is {} in DWARF, computed {}", dwarf, computed)
+            }
+            Mismatch::Offset {
+                field,
+                dwarf,
+                computed,
+            } => write!(
+                f,
+                "field {} is at offset {} in DWARF, computed {}",
+                field, dwarf, computed
+            ),
+            Mismatch::FieldSize {
+                field,
+                dwarf,
+                computed,
+            } => write!(
+                f,
+                "field {} has size {} in DWARF, computed {}",
+                field, dwarf, computed
+            ),
+            Mismatch::BitOffset {
+                field,
+                dwarf,
+                computed,
+            } => write!(
+                f,
+                "field {} starts at bit {} in DWARF, computed {}",
+                field, dwarf, computed
+            ),
+            Mismatch::Missing { field } => write!(f, "field {} is missing in DWARF", field),
+            Mismatch::Extra { field } => write!(f, "field {} is only in DWARF", field),
+        }
+    }
+}
+
+/// verify_layout compares a computed layout with the structure the
+/// compiler described, matching fields by name, and returns every
+/// disagreement. An empty result means the layout engine and the compiler
+/// agree. Unnamed bit-fields, which compilers leave out of the debug
+/// information, are skipped. The first bit of a bit-field is compared in
+/// memory order, which is how both `DW_AT_data_bit_offset` and
+/// [`FieldLayout::bit_offset`] count it.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::dwarf::*;
+/// use data_models::layout::*;
+/// let layout = Record::new("s")
+///     .field("c", CType::Char)
+///     .field("n", CType::Long)
+///     .layout(DataModel::LP64)
+///     .unwrap();
+/// let member = |name, offset, byte_size| Member {
+///     name,
+///     offset,
+///     byte_size,
+///     data_bit_offset: None,
+///     bit_size: None,
+/// };
+/// let structure = StructureType {
+///     name: "s",
+///     byte_size: 12,
+///     members: vec![member("c", 0, 1), member("n", 4, 8)],
+/// };
+/// let mismatches = verify_layout(&structure, &layout);
+/// assert_eq!(mismatches.len(), 2);
+/// assert_eq!(mismatches[1].to_string(), "field n is at offset 4 in DWARF, computed 8");
+/// ```
+pub fn verify_layout(structure: &StructureType, layout: &Layout) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+    if structure.byte_size != layout.size as u64 {
+        mismatches.push(Mismatch::Size {
+            dwarf: structure.byte_size,
+            computed: layout.size,
+        });
+    }
+    let fields: Vec<&FieldLayout> = layout
+        .fields
+        .iter()
+        .filter(|field| !field.name.is_empty())
+        .collect();
+    for field in &fields {
+        let member = match structure.members.iter().find(|m| m.name == field.name) {
+            Some(member) => member,
+            None => {
+                mismatches.push(Mismatch::Missing {
+                    field: field.name.clone(),
+                });
+                continue;
+            }
+        };
+        let name = || field.name.clone();
+        match (field.bits, member.data_bit_offset) {
+            (Some(_), Some(dwarf)) => {
+                let computed = field.offset * 8 + field.bit_offset;
+                if dwarf != computed as u64 {
+                    mismatches.push(Mismatch::BitOffset {
+                        field: name(),
+                        dwarf,
+                        computed,
+                    });
+                }
+            }
+            _ if member.offset != field.offset as u64 => mismatches.push(Mismatch::Offset {
+                field: name(),
+                dwarf: member.offset,
+                computed: field.offset,
+            }),
+            _ => {}
+        }
+        let size = match field.bits {
+            Some(bits) => member.bit_size.map(|dwarf| (dwarf, bits)),
+            None => Some((member.byte_size, field.size)),
+        };
+        if let Some((dwarf, computed)) = size.filter(|&(d, c)| d != c as u64) {
+            mismatches.push(Mismatch::FieldSize {
+                field: name(),
+                dwarf,
+                computed,
+            });
+        }
+    }
+    for member in &structure.members {
+        if !member.name.is_empty() && !fields.iter().any(|f| f.name == member.name) {
+            mismatches.push(Mismatch::Extra {
+                field: member.name.to_string(),
+            });
+        }
+    }
+    mismatches
+}
+
+/// DwarfError describes why the debug information of an object could not
+/// be read.
+#[derive(Debug)]
+pub enum DwarfError {
+    /// The file is not an object file `object` can read.
+    Object(object::Error),
+    /// The debug information is malformed.
+    Dwarf(gimli::Error),
+    /// A relocation of a debug section at this offset lies outside it.
+    Relocation(u64),
+    /// The object has no compilation units, as when it was built without
+    /// `-g`.
+    NoDebugInfo,
+}
+
+impl fmt::Display for DwarfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DwarfError::Object(err) => write!(f, "cannot read the object: {}", err),
+            DwarfError::Dwarf(err) => write!(f, "invalid DWARF: {}", err),
+            DwarfError::Relocation(offset) => {
+                write!(f, "relocation at offset {} is out of bounds", offset)
+            }
+            DwarfError::NoDebugInfo => write!(f, "the object has no debug information"),
+        }
+    }
+}
+
+impl Error for DwarfError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DwarfError::Object(err) => Some(err),
+            DwarfError::Dwarf(err) => Some(err),
+            DwarfError::Relocation(_) | DwarfError::NoDebugInfo => None,
+        }
+    }
+}
+
+/// DwarfError logs like its `Display`, without the text of the inner error.
+#[cfg(feature = "defmt")]
+impl defmt::Format for DwarfError {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            DwarfError::Object(_) => defmt::write!(f, "cannot read the object"),
+            DwarfError::Dwarf(_) => defmt::write!(f, "invalid DWARF"),
+            DwarfError::Relocation(offset) => {
+                defmt::write!(f, "relocation at offset {} is out of bounds", offset)
+            }
+            DwarfError::NoDebugInfo => defmt::write!(f, "the object has no debug information"),
+        }
+    }
+}
+
+impl From<object::Error> for DwarfError {
+    fn from(err: object::Error) -> DwarfError {
+        DwarfError::Object(err)
+    }
+}
+
+impl From<gimli::Error> for DwarfError {
+    fn from(err: gimli::Error) -> DwarfError {
+        DwarfError::Dwarf(err)
+    }
+}
+
+/// Reader reads the sections of a [`DwarfObject`].
+type Reader<'a> = gimli::EndianSlice<'a, RunTimeEndian>;
+
+/// DwarfObject is the debug information of an object file, executable or
+/// shared library.
+pub struct DwarfObject<'data> {
+    sections: gimli::DwarfSections<Cow<'data, [u8]>>,
+    endian: RunTimeEndian,
+}
+
+impl fmt::Debug for DwarfObject<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DwarfObject")
+            .field("endian", &self.endian)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'data> DwarfObject<'data> {
+    /// parse reads the DWARF sections of the object file in `data`,
+    /// decompressing them if needed and applying the relocations of a
+    /// relocatable object. Sections the object lacks are empty.
+    pub fn parse(data: &'data [u8]) -> Result<DwarfObject<'data>, DwarfError> {
+        use object::{Object, ObjectSection};
+        let file = object::File::parse(data)?;
+        let endian = match file.is_little_endian() {
+            true => RunTimeEndian::Little,
+            false => RunTimeEndian::Big,
+        };
+        let sections = gimli::DwarfSections::load(|id| match file.section_by_name(id.name()) {
+            Some(section) => relocate(&file, &section, section.uncompressed_data()?, endian),
+            None => Ok(Cow::Borrowed(&[][..])),
+        })?;
+        Ok(DwarfObject { sections, endian })
+    }
+
+    /// dwarf returns the sections for reading.
+    fn dwarf(&self) -> gimli::Dwarf<Reader<'_>> {
+        self.sections
+            .borrow(|section| gimli::EndianSlice::new(section, self.endian))
+    }
+
+    /// data_model reconstructs the data model, as [`from_base_types`]
+    /// does, from the base types of every compilation unit and the address
+    /// size of the first.
+    pub fn data_model(&self) -> Result<DwarfModel, DwarfError> {
+        let dwarf = self.dwarf();
+        let mut headers = dwarf.units();
+        let mut address_size = None;
+        let mut types = Vec::new();
+        while let Some(header) = headers.next()? {
+            address_size.get_or_insert(header.address_size());
+            let unit = dwarf.unit(header)?;
+            let mut entries = unit.entries();
+            while let Some((_, entry)) = entries.next_dfs()? {
+                if entry.tag() != gimli::DW_TAG_base_type {
+                    continue;
+                }
+                let name = match string(&dwarf, &unit, entry)? {
+                    Some(name) => name,
+                    None => continue,
+                };
+                let encoding = match entry.attr_value(gimli::DW_AT_encoding)? {
+                    Some(AttributeValue::Encoding(encoding)) => encoding.0,
+                    _ => 0,
+                };
+                types.push(BaseType {
+                    name,
+                    byte_size: udata(entry, gimli::DW_AT_byte_size)?.unwrap_or(0),
+                    encoding,
+                });
+            }
+        }
+        let address_size = address_size.ok_or(DwarfError::NoDebugInfo)?;
+        Ok(from_base_types(address_size, types))
+    }
+
+    /// structure returns the first complete `struct` or `union` called
+    /// `name`, or `None` if the object does not describe one.
+    ///
+    /// Bit-fields described in the DWARF 2 and 3 way, by
+    /// `DW_AT_bit_offset` from the most significant bit of their storage
+    /// unit, are converted to a `data_bit_offset`.
+    pub fn structure(&self, name: &str) -> Result<Option<StructureType<'_>>, DwarfError> {
+        let dwarf = self.dwarf();
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            let unit = dwarf.unit(header)?;
+            let mut entries = unit.entries();
+            while let Some((_, entry)) = entries.next_dfs()? {
+                let record = matches!(
+                    entry.tag(),
+                    gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type
+                );
+                if !record || entry.attr_value(gimli::DW_AT_declaration)?.is_some() {
+                    continue;
+                }
+                if let Some(found) = string(&dwarf, &unit, entry)?.filter(|&n| n == name) {
+                    let byte_size = udata(entry, gimli::DW_AT_byte_size)?.unwrap_or(0);
+                    let members = self.members(&dwarf, &unit, entry.offset())?;
+                    return Ok(Some(StructureType {
+                        name: found,
+                        byte_size,
+                        members,
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// members reads the `DW_TAG_member` children of the record at
+    /// `offset`.
+    fn members<'a>(
+        &self,
+        dwarf: &gimli::Dwarf<Reader<'a>>,
+        unit: &Unit<Reader<'a>>,
+        offset: UnitOffset,
+    ) -> Result<Vec<Member<'a>>, DwarfError> {
+        let mut tree = unit.entries_tree(Some(offset))?;
+        let mut children = tree.root()?.children();
+        let mut members = Vec::new();
+        while let Some(child) = children.next()? {
+            let entry = child.entry();
+            if entry.tag() != gimli::DW_TAG_member {
+                continue;
+            }
+            let type_size = match entry.attr_value(gimli::DW_AT_type)? {
+                Some(AttributeValue::UnitRef(offset)) => type_size(unit, offset, 0)?,
+                _ => 0,
+            };
+            // DWARF 2 and 3 give the size of a bit-field's storage unit on
+            // the member itself.
+            let byte_size = udata(entry, gimli::DW_AT_byte_size)?.unwrap_or(type_size);
+            let location = member_location(unit, entry)?;
+            let bit_size = udata(entry, gimli::DW_AT_bit_size)?;
+            let data_bit_offset = match udata(entry, gimli::DW_AT_data_bit_offset)? {
+                Some(bits) => Some(bits),
+                None => match (udata(entry, gimli::DW_AT_bit_offset)?, bit_size) {
+                    (Some(from_msb), Some(bits)) => {
+                        let start = location.unwrap_or(0) * 8;
+                        match self.endian {
+                            RunTimeEndian::Little => {
+                                (start + byte_size * 8).checked_sub(from_msb + bits)
+                            }
+                            RunTimeEndian::Big => Some(start + from_msb),
+                        }
+                    }
+                    _ => None,
+                },
+            };
+            let offset = match (location, data_bit_offset) {
+                (Some(location), _) => location,
+                (None, Some(bits)) => bits / 8,
+                (None, None) => 0,
+            };
+            members.push(Member {
+                name: string(dwarf, unit, entry)?.unwrap_or(""),
+                offset,
+                byte_size,
+                data_bit_offset,
+                bit_size,
+            });
+        }
+        Ok(members)
+    }
+}
+
+/// relocate applies the absolute relocations of `section` to its `data`.
+/// In a relocatable object they hold the offsets of, for one, the names in
+/// `.debug_str`, which read as 0 until applied.
+fn relocate<'data>(
+    file: &object::File<'data>,
+    section: &object::Section<'data, '_>,
+    mut data: Cow<'data, [u8]>,
+    endian: RunTimeEndian,
+) -> Result<Cow<'data, [u8]>, DwarfError> {
+    use gimli::Endianity;
+    use object::{Object, ObjectSection, ObjectSymbol, RelocationKind, RelocationTarget};
+    for (offset, relocation) in section.relocations() {
+        let symbol = match (relocation.kind(), relocation.target()) {
+            (RelocationKind::Absolute, RelocationTarget::Symbol(index)) => {
+                file.symbol_by_index(index)?.address()
+            }
+            (RelocationKind::Absolute, RelocationTarget::Absolute) => 0,
+            _ => continue,
+        };
+        let width = usize::from(relocation.size() / 8);
+        if width != 4 && width != 8 {
+            continue;
+        }
+        let bytes = usize::try_from(offset)
+            .ok()
+            .and_then(|start| data.to_mut().get_mut(start..start.checked_add(width)?))
+            .ok_or(DwarfError::Relocation(offset))?;
+        let implicit = match (relocation.has_implicit_addend(), width) {
+            (false, _) => 0,
+            (true, 4) => u64::from(endian.read_u32(bytes)),
+            _ => endian.read_u64(bytes),
+        };
+        let value = symbol
+            .wrapping_add(implicit)
+            .wrapping_add_signed(relocation.addend());
+        match width {
+            4 => endian.write_u32(bytes, value as u32),
+            _ => endian.write_u64(bytes, value),
+        }
+    }
+    Ok(data)
+}
+
+/// string returns the `DW_AT_name` of an entry.
+fn string<'a>(
+    dwarf: &gimli::Dwarf<Reader<'a>>,
+    unit: &Unit<Reader<'a>>,
+    entry: &DebuggingInformationEntry<'_, '_, Reader<'a>>,
+) -> Result<Option<&'a str>, DwarfError> {
+    match entry.attr_value(gimli::DW_AT_name)? {
+        Some(value) => Ok(Some(dwarf.attr_string(unit, value)?.to_string()?)),
+        None => Ok(None),
+    }
+}
+
+/// udata returns an attribute of an entry as an unsigned constant.
+fn udata(
+    entry: &DebuggingInformationEntry<'_, '_, Reader<'_>>,
+    name: DwAt,
+) -> Result<Option<u64>, DwarfError> {
+    Ok(entry
+        .attr_value(name)?
+        .and_then(|value| value.udata_value()))
+}
+
+/// member_location returns the `DW_AT_data_member_location` of a member,
+/// a constant or, before DWARF 3, a `DW_OP_plus_uconst` expression.
+fn member_location(
+    unit: &Unit<Reader<'_>>,
+    entry: &DebuggingInformationEntry<'_, '_, Reader<'_>>,
+) -> Result<Option<u64>, DwarfError> {
+    match entry.attr_value(gimli::DW_AT_data_member_location)? {
+        Some(AttributeValue::Exprloc(expression)) => {
+            let mut operations = expression.operations(unit.encoding());
+            match operations.next()? {
+                Some(gimli::Operation::PlusConstant { value }) => Ok(Some(value)),
+                _ => Ok(None),
+            }
+        }
+        Some(value) => Ok(value.udata_value()),
+        None => Ok(None),
+    }
+}
+
+/// type_size returns the size of the type at `offset`, looking through
+/// typedefs and qualifiers and multiplying out array bounds. It gives up
+/// with 0 `depth` types deep.
+fn type_size(unit: &Unit<Reader<'_>>, offset: UnitOffset, depth: usize) -> Result<u64, DwarfError> {
+    let entry = unit.entry(offset)?;
+    if let Some(size) = udata(&entry, gimli::DW_AT_byte_size)? {
+        return Ok(size);
+    }
+    match entry.tag() {
+        gimli::DW_TAG_pointer_type | gimli::DW_TAG_reference_type => {
+            return Ok(unit.encoding().address_size as u64)
+        }
+        _ if depth == 64 => return Ok(0),
+        _ => {}
+    }
+    let element = match entry.attr_value(gimli::DW_AT_type)? {
+        Some(AttributeValue::UnitRef(offset)) => type_size(unit, offset, depth + 1)?,
+        _ => return Ok(0),
+    };
+    if entry.tag() != gimli::DW_TAG_array_type {
+        return Ok(element);
+    }
+    let mut tree = unit.entries_tree(Some(offset))?;
+    let mut children = tree.root()?.children();
+    let mut size = element;
+    while let Some(child) = children.next()? {
+        let subrange = child.entry();
+        if subrange.tag() != gimli::DW_TAG_subrange_type {
+            continue;
+        }
+        let count = match udata(subrange, gimli::DW_AT_count)? {
+            Some(count) => count,
+            None => udata(subrange, gimli::DW_AT_upper_bound)?.map_or(0, |upper| upper + 1),
+        };
+        size = size.saturating_mul(count);
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base(name: &str, byte_size: u64, encoding: u8) -> BaseType<'_> {
+        BaseType {
+            name,
+            byte_size,
+            encoding,
+        }
+    }
+
+    #[test]
+    fn test_family() {
+        assert_eq!(family("char"), Some(Family::PlainChar));
+        assert_eq!(family("signed char"), Some(Family::Char));
+        assert_eq!(family("short unsigned int"), Some(Family::Short));
+        assert_eq!(family("unsigned"), Some(Family::Int));
+        assert_eq!(family("long unsigned int"), Some(Family::Long));
+        assert_eq!(family("long long int"), Some(Family::LongLong));
+        assert_eq!(family("long double"), None);
+        assert_eq!(family("_Bool"), None);
+    }
+
+    #[test]
+    fn test_llp64() {
+        let found = from_base_types(
+            8,
+            vec![
+                base("char", 1, DW_ATE_UNSIGNED_CHAR),
+                base("int", 4, DW_ATE_SIGNED),
+                base("long", 4, DW_ATE_SIGNED),
+                base("long long", 8, DW_ATE_SIGNED),
+                base("double", 8, DW_ATE_FLOAT),
+            ],
+        );
+        assert_eq!(found.model, DataModel::LLP64);
+        assert_eq!(found.char_signed, Some(false));
+    }
+
+    #[test]
+    fn test_ip16_and_silp64() {
+        let found = from_base_types(2, vec![base("int", 2, DW_ATE_SIGNED)]);
+        assert_eq!(found.model, DataModel::IP16);
+        assert_eq!(found.char_signed, None);
+
+        let found = from_base_types(
+            8,
+            vec![
+                base("short", 8, DW_ATE_SIGNED),
+                base("int", 8, DW_ATE_SIGNED),
+                base("long", 8, DW_ATE_SIGNED),
+            ],
+        );
+        assert_eq!(found.model, DataModel::SILP64);
+    }
+
+    #[test]
+    fn test_verify_layout() {
+        use crate::layout::Record;
+        use crate::CType;
+
+        // struct s { char c; unsigned a : 3, : 0, b : 4; short s; } from
+        // gcc -g on x86_64 Linux.
+        let layout = Record::new("s")
+            .field("c", CType::Char)
+            .bitfield("a", CType::UInt, 3)
+            .bitfield("", CType::UInt, 0)
+            .bitfield("b", CType::UInt, 4)
+            .field("s", CType::Short)
+            .layout(DataModel::LP64)
+            .unwrap();
+        let member = |name, offset, byte_size, bits: Option<(u64, u64)>| Member {
+            name,
+            offset,
+            byte_size,
+            data_bit_offset: bits.map(|(offset, _)| offset),
+            bit_size: bits.map(|(_, size)| size),
+        };
+        let mut structure = StructureType {
+            name: "s",
+            byte_size: 8,
+            members: vec![
+                member("c", 0, 1, None),
+                member("a", 1, 4, Some((8, 3))),
+                member("b", 4, 4, Some((32, 4))),
+                member("s", 6, 2, None),
+            ],
+        };
+        assert_eq!(verify_layout(&structure, &layout), []);
+
+        structure.members[2].data_bit_offset = Some(11);
+        structure.members[3].name = "t";
+        let mismatches = verify_layout(&structure, &layout);
+        assert_eq!(mismatches.len(), 3);
+        assert_eq!(
+            mismatches[0].to_string(),
+            "field b starts at bit 11 in DWARF, computed 32"
+        );
+        assert_eq!(mismatches[1], Mismatch::Missing { field: "s".into() });
+        assert_eq!(mismatches[2], Mismatch::Extra { field: "t".into() });
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = DwarfObject::parse(b"not an object").unwrap_err();
+        assert!(matches!(err, DwarfError::Object(_)));
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    #[test]
+    fn test_object() {
+        use crate::layout::{FieldType, Record};
+        use crate::CType;
+        use std::process::Command;
+
+        if Command::new("cc").arg("--version").output().is_err() {
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("data_models-dwarf-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("s.c");
+        std::fs::write(
+            &source,
+            "struct s { char c; long n; unsigned a : 3, b : 7; short t[3]; } s;\n",
+        )
+        .unwrap();
+        let layout = Record::new("s")
+            .field("c", CType::Char)
+            .field("n", CType::Long)
+            .bitfield("a", CType::UInt, 3)
+            .bitfield("b", CType::UInt, 7)
+            .field("t", FieldType::array(CType::Short, 3))
+            .layout(DataModel::LP64)
+            .unwrap();
+        // DWARF 5 gives bit-fields DW_AT_data_bit_offset, strict DWARF 2
+        // DW_AT_bit_offset and a DW_OP_plus_uconst member location.
+        for flags in [&["-gdwarf-5"][..], &["-gdwarf-2", "-gstrict-dwarf"]] {
+            let output = dir.join("s.o");
+            let status = Command::new("cc")
+                .args(flags)
+                .args(["-g", "-c", "-o"])
+                .arg(&output)
+                .arg(&source)
+                .status()
+                .unwrap();
+            assert!(status.success());
+            let data = std::fs::read(&output).unwrap();
+            let object = DwarfObject::parse(&data).unwrap();
+            let found = object.data_model().unwrap();
+            assert_eq!(found.model, DataModel::LP64);
+            assert_eq!(found.char_signed, Some(true));
+            let structure = object.structure("s").unwrap().unwrap();
+            assert_eq!(structure.byte_size, 24);
+            assert_eq!(verify_layout(&structure, &layout), [], "{:?}", flags);
+            assert!(object.structure("t").unwrap().is_none());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+//! Stable fingerprints of models, targets and layouts.
+//!
+//! Build caches and ABI compatibility databases need a short key for "this
+//! exact ABI". [`Fingerprint::canonical`] writes a description in a fixed
+//! text form, and [`Fingerprint::fingerprint`] hashes it with 64-bit
+//! FNV-1a, so the key is the same across runs, platforms and Rust versions,
+//! unlike [`std::hash::Hash`]. Only what decides the placement of bytes
+//! goes in: a profile's triple and operating system do not, so targets
+//! sharing an ABI share a fingerprint.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::fingerprint::Fingerprint;
+//! let linux = Platform::LinuxX86_64.target();
+//! assert_eq!(linux.fingerprint(), Platform::FreeBsdX86_64.target().fingerprint());
+//! assert_ne!(linux.fingerprint(), Platform::Windows64.target().fingerprint());
+//! assert_eq!(DataModel::LP64.fingerprint_hex().len(), 16);
+//! ```
+
+use crate::database::Profile;
+use crate::layout::{FieldLayout, FieldType, Layout, RecordKind};
+use crate::{CType, DataModel, TargetModel};
+use std::fmt::Write;
+
+/// Fingerprint is a description with a stable text form and hash.
+pub trait Fingerprint {
+    /// canonical writes the description in its canonical text form. The
+    /// form of a given description never changes between releases.
+    fn canonical(&self) -> String;
+
+    /// fingerprint returns the 64-bit FNV-1a hash of the canonical form.
+    fn fingerprint(&self) -> u64 {
+        fnv1a(self.canonical().as_bytes())
+    }
+
+    /// fingerprint_hex returns the fingerprint as 16 hexadecimal digits.
+    fn fingerprint_hex(&self) -> String {
+        format!("{:016x}", self.fingerprint())
+    }
+}
+
+/// fnv1a returns the 64-bit FNV-1a hash of `bytes`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The data model is its name followed by the size of each type of
+/// [`CType::ALL`].
+impl Fingerprint for DataModel {
+    fn canonical(&self) -> String {
+        let sizes: Vec<String> = CType::ALL
+            .iter()
+            .map(|&ty| self.size(ty).to_string())
+            .collect();
+        format!("model {:?} {}\n", self, sizes.join(" "))
+    }
+}
+
+/// The target is its [JSON description](crate::json).
+impl Fingerprint for TargetModel {
+    fn canonical(&self) -> String {
+        self.to_json()
+    }
+}
+
+/// The profile is its target followed by the sizes of `wchar_t`, `double`,
+/// `long double` and `float_t` and its qualified pointers.
+impl Fingerprint for Profile {
+    fn canonical(&self) -> String {
+        let mut out = self.target().canonical();
+        let _ = writeln!(
+            out,
+            "wchar_t {}\ndouble {}\nlong double {}\nfloat_t {}",
+            self.wchar_size,
+            self.double_size,
+            self.long_double_size(),
+            self.float_t_size()
+        );
+        for (qualifier, size) in self.pointers {
+            let _ = writeln!(out, "pointer {:?} {}", qualifier, size);
+        }
+        for (space, size) in self.address_spaces {
+            let _ = writeln!(out, "address space {:?} {}", space, size);
+        }
+        out
+    }
+}
+
+/// The layout is a line for the record and one for each base and field,
+/// with nested records indented under their field.
+impl Fingerprint for Layout {
+    fn canonical(&self) -> String {
+        let mut out = String::new();
+        write_layout(&mut out, self, 0);
+        out
+    }
+}
+
+fn write_layout(out: &mut String, layout: &Layout, indent: usize) {
+    let kind = match layout.kind {
+        RecordKind::Struct => "struct",
+        RecordKind::Union => "union",
+    };
+    let _ = write!(
+        out,
+        "{:indent$}{} {:?} size {} align {}",
+        "",
+        kind,
+        layout.name,
+        layout.size,
+        layout.align,
+        indent = indent
+    );
+    if layout.dynamic {
+        out.push_str(" dynamic");
+    }
+    if let Some(flexible) = layout.flexible {
+        let _ = write!(out, " flexible {} {}", flexible.offset, flexible.stride);
+    }
+    out.push('\n');
+    for base in &layout.bases {
+        write_field(out, "base", base, indent + 2);
+    }
+    for field in &layout.fields {
+        write_field(out, "field", field, indent + 2);
+    }
+}
+
+fn write_field(out: &mut String, what: &str, field: &FieldLayout, indent: usize) {
+    let _ = write!(
+        out,
+        "{:indent$}{} {:?} {} offset {} size {} align {}",
+        "",
+        what,
+        field.name,
+        type_name(&field.ty),
+        field.offset,
+        field.size,
+        field.align,
+        indent = indent
+    );
+    if let Some(bits) = field.bits {
+        let _ = write!(out, " bits {} {}", field.bit_offset, bits);
+    }
+    out.push('\n');
+    if let Some(nested) = &field.nested {
+        write_layout(out, nested, indent + 2);
+    }
+}
+
+/// type_name spells the shape of a field type, leaving nested records to
+/// their own lines.
+fn type_name(ty: &FieldType) -> String {
+    match ty {
+        FieldType::Scalar(ty) => format!("{:?}", ty.c_name()),
+        FieldType::Array(element, n) => format!("{}[{}]", type_name(element), n),
+        FieldType::FlexibleArray(element) => format!("{}[]", type_name(element)),
+        FieldType::Pointer(_) => "pointer".to_string(),
+        FieldType::Enum(_) => "enum".to_string(),
+        FieldType::Record(_) | FieldType::Layout(_) => "record".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+    use crate::layout::Record;
+    use crate::Platform;
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        let fingerprints: Vec<u64> = DataModel::ALL.iter().map(|m| m.fingerprint()).collect();
+        for (i, a) in fingerprints.iter().enumerate() {
+            assert!(!fingerprints[..i].contains(a));
+        }
+        assert_eq!(
+            DataModel::ILP32.canonical(),
+            "model ILP32 1 1 1 1 2 2 4 4 4 4 8 8 4 4 8\n"
+        );
+
+        let find = |triple| database::find(triple).unwrap();
+        let gnu = find("x86_64-unknown-linux-gnu");
+        assert_eq!(
+            gnu.fingerprint(),
+            find("x86_64-unknown-freebsd").fingerprint()
+        );
+        assert_ne!(
+            gnu.fingerprint(),
+            find("x86_64-pc-windows-gnu").fingerprint()
+        );
+
+        let record = Record::new("s")
+            .field("c", CType::Char)
+            .bitfield("b", CType::UInt, 3)
+            .field("n", CType::Long);
+        let lp64 = record.layout(Platform::LinuxX86_64.target().abi).unwrap();
+        let llp64 = record.layout(Platform::Windows64.target().abi).unwrap();
+        assert_ne!(lp64.fingerprint(), llp64.fingerprint());
+        assert_eq!(lp64.fingerprint(), lp64.clone().fingerprint());
+        assert!(lp64
+            .canonical()
+            .contains("field \"b\" \"unsigned int\" offset 0 size 4 align 4 bits 8 3\n"));
+    }
+}
@@ -0,0 +1,186 @@
+//! Data models from compiler command lines.
+//!
+//! Build system integrations often see only the command line a compiler is
+//! run with. The target architecture of the compiler gives a default data
+//! model, and flags like `-m32`, `-mx32` or `-mabi=ilp32` switch it, as
+//! does a `--target` triple. [`DataModel::from_compiler_flags`] follows
+//! them in order, as GCC and Clang do, so the last one wins.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! assert_eq!(DataModel::from_compiler_flags("x86_64", &["-O2", "-m32"]), Some(DataModel::ILP32));
+//! assert_eq!(DataModel::from_compiler_flags("mips64", &["-mabi=n32"]), Some(DataModel::ILP32));
+//! assert_eq!(
+//!     DataModel::from_compiler_flags("x86_64", &["--target=x86_64-pc-windows-msvc"]),
+//!     Some(DataModel::LLP64)
+//! );
+//! ```
+
+use crate::{database, DataModel};
+
+/// Family is a group of architectures sharing the flags that select the
+/// data model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Family {
+    X86,
+    Arm64,
+    Arm,
+    Mips,
+    PowerPc,
+    Sparc,
+    S390,
+    RiscV,
+    LoongArch,
+    Wasm,
+    Msp430,
+    Avr,
+}
+
+/// family returns the family of an architecture and its default model.
+fn family(arch: &str) -> Option<(Family, DataModel)> {
+    use DataModel::*;
+    Some(match arch {
+        "x86_64" | "amd64" | "x86_64h" => (Family::X86, LP64),
+        "i386" | "i486" | "i586" | "i686" | "x86" => (Family::X86, ILP32),
+        "aarch64" | "aarch64_be" | "arm64" => (Family::Arm64, LP64),
+        "arm64_32" => (Family::Arm64, ILP32),
+        "mips" | "mipsel" => (Family::Mips, ILP32),
+        "mips64" | "mips64el" => (Family::Mips, LP64),
+        "powerpc" | "ppc" | "powerpcle" => (Family::PowerPc, ILP32),
+        "powerpc64" | "powerpc64le" | "ppc64" | "ppc64le" => (Family::PowerPc, LP64),
+        "sparc" | "sparcel" => (Family::Sparc, ILP32),
+        "sparc64" | "sparcv9" => (Family::Sparc, LP64),
+        "s390" => (Family::S390, ILP32),
+        "s390x" => (Family::S390, LP64),
+        "loongarch64" => (Family::LoongArch, LP64),
+        "wasm32" => (Family::Wasm, ILP32),
+        "wasm64" => (Family::Wasm, LP64),
+        "msp430" => (Family::Msp430, IP16L32),
+        "avr" => (Family::Avr, IP16L32),
+        arch if arch.starts_with("riscv32") => (Family::RiscV, ILP32),
+        arch if arch.starts_with("riscv64") => (Family::RiscV, LP64),
+        arch if arch.starts_with("arm") || arch.starts_with("thumb") => (Family::Arm, ILP32),
+        _ => return None,
+    })
+}
+
+/// mabi returns the model selected by `-mabi=value` on a family, `Some`
+/// of the current model for values that leave it alone, or `None` for a
+/// value the family rejects.
+fn mabi(family: Family, value: &str, model: DataModel) -> Option<DataModel> {
+    use DataModel::*;
+    match (family, value) {
+        (Family::Arm64, "ilp32") => Some(ILP32),
+        (Family::Arm64, "lp64") => Some(LP64),
+        (Family::Arm64, _) => None,
+        (Family::RiscV, abi) if abi.starts_with("ilp32") => Some(ILP32),
+        (Family::RiscV, abi) if abi.starts_with("lp64") => Some(LP64),
+        (Family::RiscV, _) => None,
+        (Family::Mips, "32" | "o32" | "n32" | "eabi") => Some(ILP32),
+        (Family::Mips, "64" | "n64" | "o64") => Some(LP64),
+        (Family::Mips, _) => None,
+        (Family::LoongArch, abi) if abi.starts_with("lp64") => Some(LP64),
+        (Family::LoongArch, abi) if abi.starts_with("ilp32") => Some(ILP32),
+        // -mabi=ms on x86-64, -mabi=aapcs on Arm and -mabi=elfv2 on
+        // PowerPC change the calling convention, not the types.
+        _ => Some(model),
+    }
+}
+
+impl DataModel {
+    /// from_compiler_flags returns the data model a GCC or Clang compiler
+    /// for the architecture `arch`, as in the first part of a target
+    /// triple, uses with the given command line arguments, or `None` if
+    /// the architecture is unknown or a flag does not apply to it.
+    ///
+    /// It understands `-m16`, `-m31`, `-m32`, `-mx32` and `-m64`, AIX's
+    /// `-maix32`/`-maix64` and `-q32`/`-q64`, `-mabi=` on AArch64, RISC-V,
+    /// MIPS and LoongArch, and `--target=`, `--target` and `-target`,
+    /// which look the triple up in the [database](crate::database) and
+    /// otherwise switch the architecture. Other arguments are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::from_compiler_flags("x86_64", &["-mx32"]), Some(DataModel::ILP32));
+    /// assert_eq!(DataModel::from_compiler_flags("aarch64", &["-mabi=ilp32"]), Some(DataModel::ILP32));
+    /// assert_eq!(DataModel::from_compiler_flags("s390x", &["-m31"]), Some(DataModel::ILP32));
+    /// assert_eq!(DataModel::from_compiler_flags("aarch64", &["-m32"]), None);
+    /// ```
+    pub fn from_compiler_flags(arch: &str, flags: &[&str]) -> Option<DataModel> {
+        use DataModel::*;
+        let (mut family, mut model) = family(arch)?;
+        let mut args = flags.iter();
+        while let Some(&flag) = args.next() {
+            let triple = match flag {
+                "-target" | "--target" => Some(*args.next()?),
+                flag => flag.strip_prefix("--target="),
+            };
+            if let Some(triple) = triple {
+                let arch = triple.split('-').next().unwrap_or("");
+                let (new_family, default) = self::family(arch)?;
+                family = new_family;
+                model = match database::find(triple) {
+                    Some(profile) => profile.data_model,
+                    None => default,
+                };
+                continue;
+            }
+            model = match (family, flag) {
+                (Family::X86, "-m16" | "-m32" | "-mx32") => ILP32,
+                (Family::X86 | Family::PowerPc | Family::Sparc | Family::S390, "-m64") => LP64,
+                (Family::PowerPc | Family::Sparc, "-m32") => ILP32,
+                (Family::S390, "-m31") => ILP32,
+                (Family::PowerPc, "-maix32" | "-q32") => ILP32,
+                (Family::PowerPc, "-maix64" | "-q64") => LP64,
+                (_, "-m16" | "-m31" | "-m32" | "-mx32" | "-m64") => return None,
+                (family, flag) => match flag.strip_prefix("-mabi=") {
+                    Some(value) => mabi(family, value, model)?,
+                    None => model,
+                },
+            };
+        }
+        Some(model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_compiler_flags() {
+        use DataModel::*;
+        let model = DataModel::from_compiler_flags;
+        assert_eq!(model("i686", &[]), Some(ILP32));
+        assert_eq!(model("i686", &["-m64"]), Some(LP64));
+        assert_eq!(model("x86_64", &["-m32", "-m64"]), Some(LP64));
+        assert_eq!(model("x86_64", &["-mabi=ms"]), Some(LP64));
+        assert_eq!(model("powerpc64", &["-maix32"]), Some(ILP32));
+        assert_eq!(model("sparc", &["-m64"]), Some(LP64));
+        assert_eq!(model("riscv64gc", &["-mabi=ilp32d"]), Some(ILP32));
+        assert_eq!(model("riscv32imac", &["-mabi=lp64"]), Some(LP64));
+        assert_eq!(model("mips", &["-mabi=64"]), Some(LP64));
+        assert_eq!(model("mips", &["-mabi=n99"]), None);
+        assert_eq!(
+            model("armv7", &["-mabi=aapcs-linux", "-mthumb"]),
+            Some(ILP32)
+        );
+        assert_eq!(model("msp430", &["-mmcu=msp430f5529"]), Some(IP16L32));
+        assert_eq!(model("vax", &[]), None);
+        assert_eq!(
+            model("x86_64", &["-target", "aarch64-apple-darwin"]),
+            Some(LP64)
+        );
+        assert_eq!(
+            model("x86_64", &["--target", "i686-pc-windows-msvc", "-m64"]),
+            Some(LP64)
+        );
+        assert_eq!(
+            model("x86_64", &["--target=aarch64-linux-gnu", "-m32"]),
+            None
+        );
+        assert_eq!(model("x86_64", &["-target"]), None);
+    }
+}
@@ -0,0 +1,649 @@
+//! printf and scanf conversion specifiers.
+//!
+//! A format string names the types of its arguments, and a mismatch that
+//! works on one model reads garbage on another: `%ld` prints a `size_t`
+//! fine where `long` is as wide as a pointer, but not on LLP64.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::stdint::StdType;
+//! let args = [StdType::Size.into()];
+//! assert!(DataModel::LP64.check_printf("%ld\n", &args).is_empty());
+//! assert_eq!(DataModel::LLP64.check_printf("%ld\n", &args).len(), 1);
+//! ```
+
+use crate::stdint::{StdType, TypeName};
+use crate::{CType, DataModel, Platform};
+use std::error::Error;
+use std::fmt;
+
+/// FormatIssue is a problem found checking a format string against its
+/// arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FormatIssue {
+    /// The conversion reads an argument of another size or kind than the
+    /// one passed.
+    Mismatch {
+        /// The index of the argument.
+        index: usize,
+        /// The conversion specification, e.g. `"%ld"`.
+        spec: String,
+        /// The type the conversion reads.
+        expected: CType,
+        /// The type of the argument on the model.
+        found: CType,
+    },
+    /// There is no argument left for the conversion.
+    MissingArgument {
+        /// The index the argument would have.
+        index: usize,
+        /// The conversion specification.
+        spec: String,
+    },
+    /// More arguments are passed than the format converts.
+    ExtraArguments {
+        /// The index of the first unused argument.
+        index: usize,
+    },
+    /// The conversion specification is malformed or not supported, like
+    /// `%Lf` for `long double`.
+    InvalidSpec {
+        /// The conversion specification.
+        spec: String,
+    },
+    /// The model lacks the type of an argument or of a conversion.
+    MissingType {
+        /// The index of the argument.
+        index: usize,
+    },
+}
+
+impl fmt::Display for FormatIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatIssue::Mismatch {
+                index,
+                spec,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} expects {} but argument {} is {}",
+                spec,
+                expected.c_name(),
+                index,
+                found.c_name()
+            ),
+            FormatIssue::MissingArgument { index, spec } => {
+                write!(f, "{} has no argument {}", spec, index)
+            }
+            FormatIssue::ExtraArguments { index } => {
+                write!(f, "arguments from {} are not converted", index)
+            }
+            FormatIssue::InvalidSpec { spec } => write!(f, "invalid conversion {}", spec),
+            FormatIssue::MissingType { index } => {
+                write!(f, "the data model lacks the type of argument {}", index)
+            }
+        }
+    }
+}
+
+impl Error for FormatIssue {}
+
+/// Flavor tells the printf family from the scanf family.
+#[derive(Clone, Copy, PartialEq)]
+enum Flavor {
+    Printf,
+    Scanf,
+}
+
+/// Spec is a parsed conversion specification.
+struct Spec<'a> {
+    /// The whole specification as written.
+    text: &'a str,
+    /// The number of `*` widths and precisions taking an `int` argument.
+    stars: usize,
+    /// Whether the conversion assigns no argument, as `%*d` in scanf.
+    suppressed: bool,
+    length: &'a str,
+    conversion: char,
+}
+
+/// parse splits a format string into its conversion specifications,
+/// skipping `%%`. An unterminated specification ends with `'\0'`.
+fn parse(format: &str, flavor: Flavor) -> Vec<Spec<'_>> {
+    let mut specs = Vec::new();
+    let bytes = format.as_bytes();
+    let mut i = 0;
+    while let Some(start) = format[i..].find('%').map(|at| i + at) {
+        let mut j = start + 1;
+        let take = |j: &mut usize, set: &str| {
+            while *j < bytes.len() && set.contains(bytes[*j] as char) {
+                *j += 1;
+            }
+        };
+        let mut stars = 0;
+        let mut suppressed = false;
+        match flavor {
+            Flavor::Printf => {
+                take(&mut j, "-+ #0'");
+                if bytes.get(j) == Some(&b'*') {
+                    stars += 1;
+                    j += 1;
+                }
+                take(&mut j, "0123456789");
+                if bytes.get(j) == Some(&b'.') {
+                    j += 1;
+                    if bytes.get(j) == Some(&b'*') {
+                        stars += 1;
+                        j += 1;
+                    }
+                    take(&mut j, "0123456789");
+                }
+            }
+            Flavor::Scanf => {
+                if bytes.get(j) == Some(&b'*') {
+                    suppressed = true;
+                    j += 1;
+                }
+                take(&mut j, "0123456789");
+            }
+        }
+        let length_start = j;
+        take(&mut j, "hljztL");
+        let length = &format[length_start..j];
+        let conversion = format[j..].chars().next().unwrap_or('\0');
+        let mut end = j + conversion.len_utf8().min(format.len() - j);
+        if conversion == '[' {
+            let close = format[end..]
+                .char_indices()
+                .skip(usize::from(format[end..].starts_with(']')))
+                .find(|&(_, c)| c == ']');
+            end = close.map_or(format.len(), |(at, _)| end + at + 1);
+        }
+        if conversion != '%' || length_start != start + 1 {
+            specs.push(Spec {
+                text: &format[start..end],
+                stars,
+                suppressed,
+                length,
+                conversion,
+            });
+        }
+        i = end;
+    }
+    specs
+}
+
+impl Spec<'_> {
+    /// expected returns the type the conversion reads, or `None` if it is
+    /// invalid. For scanf it is the type the argument points to.
+    fn expected(&self, flavor: Flavor) -> Option<TypeName> {
+        use CType::*;
+        let signed = |length: &str| -> Option<TypeName> {
+            Some(match length {
+                "hh" => SChar.into(),
+                "h" => Short.into(),
+                "" => Int.into(),
+                "l" => Long.into(),
+                "ll" => LongLong.into(),
+                "j" => StdType::IntMax.into(),
+                "z" => StdType::SSize.into(),
+                "t" => StdType::PtrDiff.into(),
+                _ => return None,
+            })
+        };
+        Some(match (self.conversion, self.length) {
+            ('d' | 'i', length) => signed(length)?,
+            ('o' | 'u' | 'x' | 'X', "j") => StdType::UIntMax.into(),
+            ('o' | 'u' | 'x' | 'X', "z") => StdType::Size.into(),
+            ('o' | 'u' | 'x' | 'X', length) => match signed(length)? {
+                TypeName::C(ty) => ty.to_unsigned().into(),
+                ty => ty,
+            },
+            ('f' | 'F' | 'e' | 'E' | 'g' | 'G' | 'a' | 'A', "") => match flavor {
+                Flavor::Printf => Double.into(),
+                Flavor::Scanf => Float.into(),
+            },
+            ('f' | 'F' | 'e' | 'E' | 'g' | 'G' | 'a' | 'A', "l") => Double.into(),
+            ('c', "") => match flavor {
+                Flavor::Printf => Int.into(),
+                Flavor::Scanf => Char.into(),
+            },
+            ('s' | '[', "") => match flavor {
+                Flavor::Printf => Pointer.into(),
+                Flavor::Scanf => Char.into(),
+            },
+            ('p', "") => Pointer.into(),
+            ('n', _) if flavor == Flavor::Printf => Pointer.into(),
+            ('n', length) => signed(length)?,
+            _ => return None,
+        })
+    }
+}
+
+/// inttypes_macro returns what the `inttypes.h` macro combining
+/// `conversion` with an integer type expands to, given the base type the
+/// type resolves to. printf arguments are promoted, so `PRId8` is `"d"`
+/// while `SCNd8` is `"hhd"`.
+fn inttypes_macro(conversion: char, ty: CType, flavor: Flavor, model: DataModel) -> Option<String> {
+    if !"diouxX".contains(conversion) || !ty.is_integer() {
+        return None;
+    }
+    let ty = match flavor {
+        Flavor::Printf => model.promote(ty),
+        Flavor::Scanf => ty,
+    };
+    let length = match ty.rank() {
+        0 => return None,
+        1 => "hh",
+        2 => "h",
+        3 => "",
+        4 => "l",
+        _ => "ll",
+    };
+    Some(format!("{}{}", length, conversion))
+}
+
+/// parse_inttypes splits the name of an `inttypes.h` macro like `PRIu64`
+/// into its flavor, conversion and type.
+fn parse_inttypes(name: &str) -> Option<(Flavor, char, StdType)> {
+    let (flavor, rest) = match name.get(..3)? {
+        "PRI" => (Flavor::Printf, &name[3..]),
+        "SCN" => (Flavor::Scanf, &name[3..]),
+        _ => return None,
+    };
+    let conversion = rest.chars().next()?;
+    let signed = matches!(conversion, 'd' | 'i');
+    let (signed_ty, unsigned_ty) = match &rest[1..] {
+        "8" => (StdType::Int8, StdType::UInt8),
+        "16" => (StdType::Int16, StdType::UInt16),
+        "32" => (StdType::Int32, StdType::UInt32),
+        "64" => (StdType::Int64, StdType::UInt64),
+        "MAX" => (StdType::IntMax, StdType::UIntMax),
+        "PTR" => (StdType::IntPtr, StdType::UIntPtr),
+        _ => return None,
+    };
+    Some(match signed {
+        true => (flavor, conversion, signed_ty),
+        false => (flavor, conversion, unsigned_ty),
+    })
+}
+
+/// Class is the way an argument is passed and stored.
+#[derive(PartialEq)]
+enum Class {
+    Integer,
+    Floating,
+    Pointer,
+}
+
+fn class(ty: CType) -> Class {
+    match ty {
+        CType::Float | CType::Double => Class::Floating,
+        CType::Pointer => Class::Pointer,
+        _ => Class::Integer,
+    }
+}
+
+impl DataModel {
+    /// printf_specifier returns the printf conversion specification for a
+    /// value of type `ty`, e.g. `"%lu"` for `unsigned long` and `"%zu"` for
+    /// `size_t`, or `None` if the model lacks the type.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::stdint::StdType;
+    /// assert_eq!(DataModel::LP64.printf_specifier(CType::LongLong).as_deref(), Some("%lld"));
+    /// assert_eq!(DataModel::LP64.printf_specifier(StdType::Size).as_deref(), Some("%zu"));
+    /// assert_eq!(DataModel::IP16.printf_specifier(CType::Long), None);
+    /// ```
+    pub fn printf_specifier(self, ty: impl Into<TypeName>) -> Option<String> {
+        self.specifier(ty.into(), Flavor::Printf)
+    }
+
+    /// scanf_specifier returns the scanf conversion specification reading
+    /// a value of type `ty`, which differs from printf for floating types:
+    /// `"%lf"` reads a `double`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LP64.scanf_specifier(CType::Double).as_deref(), Some("%lf"));
+    /// assert_eq!(DataModel::LP64.scanf_specifier(CType::UShort).as_deref(), Some("%hu"));
+    /// ```
+    pub fn scanf_specifier(self, ty: impl Into<TypeName>) -> Option<String> {
+        self.specifier(ty.into(), Flavor::Scanf)
+    }
+
+    /// pri_macro returns what the `inttypes.h` printf macro for
+    /// `conversion` (one of `diouxX`) and an integer type expands to, e.g.
+    /// `"llu"` for `PRIu64` on ILP32 and `"lu"` on LP64.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::stdint::StdType;
+    /// assert_eq!(DataModel::ILP32.pri_macro('u', StdType::UInt64).as_deref(), Some("llu"));
+    /// assert_eq!(DataModel::LP64.pri_macro('u', StdType::UInt64).as_deref(), Some("lu"));
+    /// assert_eq!(DataModel::LLP64.pri_macro('d', StdType::IntPtr).as_deref(), Some("lld"));
+    /// ```
+    pub fn pri_macro(self, conversion: char, ty: impl Into<TypeName>) -> Option<String> {
+        inttypes_macro(conversion, self.resolve(ty)?, Flavor::Printf, self)
+    }
+
+    /// scn_macro returns what the `inttypes.h` scanf macro for
+    /// `conversion` and an integer type expands to. See
+    /// [`DataModel::pri_macro`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::stdint::StdType;
+    /// assert_eq!(DataModel::LP64.scn_macro('x', StdType::UInt8).as_deref(), Some("hhx"));
+    /// assert_eq!(DataModel::LP64.pri_macro('x', StdType::UInt8).as_deref(), Some("x"));
+    /// ```
+    pub fn scn_macro(self, conversion: char, ty: impl Into<TypeName>) -> Option<String> {
+        inttypes_macro(conversion, self.resolve(ty)?, Flavor::Scanf, self)
+    }
+
+    /// expand_inttypes returns what an `inttypes.h` macro named like
+    /// `PRIu64`, `SCNdPTR` or `PRIxMAX` expands to, or `None` if the name
+    /// is not one of them or the model lacks the type.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LP64.expand_inttypes("PRIdPTR").as_deref(), Some("ld"));
+    /// assert_eq!(DataModel::ILP32.expand_inttypes("PRIdPTR").as_deref(), Some("d"));
+    /// assert_eq!(DataModel::LP64.expand_inttypes("PRIq64"), None);
+    /// ```
+    pub fn expand_inttypes(self, name: &str) -> Option<String> {
+        let (flavor, conversion, ty) = parse_inttypes(name)?;
+        inttypes_macro(conversion, self.resolve(ty)?, flavor, self)
+    }
+
+    /// specifier returns the conversion specification for `ty`. Typedefs
+    /// with a length modifier of their own use it; the others, like
+    /// `int64_t`, get the one of their base type.
+    fn specifier(self, ty: TypeName, flavor: Flavor) -> Option<String> {
+        use CType::*;
+        let base = self.resolve(ty)?;
+        let spec = match ty {
+            TypeName::Std(StdType::Size) => "%zu",
+            TypeName::Std(StdType::SSize) => "%zd",
+            TypeName::Std(StdType::PtrDiff) => "%td",
+            TypeName::Std(StdType::IntMax) => "%jd",
+            TypeName::Std(StdType::UIntMax) => "%ju",
+            TypeName::C(_) | TypeName::Std(_) => match base {
+                Bool if flavor == Flavor::Scanf => return None,
+                Bool | Int => "%d",
+                Char | SChar => "%hhd",
+                UChar => "%hhu",
+                Short => "%hd",
+                UShort => "%hu",
+                UInt => "%u",
+                Long => "%ld",
+                ULong => "%lu",
+                LongLong => "%lld",
+                ULongLong => "%llu",
+                Pointer => "%p",
+                Float => "%f",
+                Double if flavor == Flavor::Scanf => "%lf",
+                Double => "%f",
+            },
+        };
+        Some(spec.to_string())
+    }
+
+    /// check_printf checks the conversions of a printf format string
+    /// against the types of its arguments on the model.
+    ///
+    /// An argument matches if, after the default argument promotions, it
+    /// has the size and kind (integer, floating or pointer) of the type the
+    /// conversion reads, so the callee reads what the caller passed. `%s`
+    /// and `%n` take any pointer.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::format::FormatIssue;
+    /// let args = [CType::Long.into(), CType::Pointer.into()];
+    /// assert!(DataModel::LP64.check_printf("%d: %p", &args).len() == 1);
+    /// assert!(DataModel::ILP32.check_printf("%d: %p", &args).is_empty());
+    /// assert_eq!(
+    ///     DataModel::LP64.check_printf("%d", &[]),
+    ///     vec![FormatIssue::MissingArgument { index: 0, spec: "%d".to_string() }]
+    /// );
+    /// ```
+    pub fn check_printf(self, format: &str, args: &[TypeName]) -> Vec<FormatIssue> {
+        self.check(format, args, Flavor::Printf)
+    }
+
+    /// check_scanf checks the conversions of a scanf format string against
+    /// the types its pointer arguments point to. See
+    /// [`DataModel::check_printf`]; no promotions apply.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let args = [CType::Double.into()];
+    /// assert_eq!(DataModel::LP64.check_scanf("%f", &args).len(), 1);
+    /// assert!(DataModel::LP64.check_scanf("%lf", &args).is_empty());
+    /// ```
+    pub fn check_scanf(self, format: &str, args: &[TypeName]) -> Vec<FormatIssue> {
+        self.check(format, args, Flavor::Scanf)
+    }
+
+    fn check(self, format: &str, args: &[TypeName], flavor: Flavor) -> Vec<FormatIssue> {
+        let mut issues = Vec::new();
+        let mut index = 0;
+        for spec in parse(format, flavor) {
+            let mut reads = vec![TypeName::C(CType::Int); spec.stars];
+            match spec.expected(flavor) {
+                Some(expected) => reads.extend((!spec.suppressed).then_some(expected)),
+                None => issues.push(FormatIssue::InvalidSpec {
+                    spec: spec.text.to_string(),
+                }),
+            }
+            for expected in reads {
+                let Some(&arg) = args.get(index) else {
+                    issues.push(FormatIssue::MissingArgument {
+                        index,
+                        spec: spec.text.to_string(),
+                    });
+                    break;
+                };
+                let (Some(expected), Some(found)) = (self.resolve(expected), self.resolve(arg))
+                else {
+                    issues.push(FormatIssue::MissingType { index });
+                    index += 1;
+                    continue;
+                };
+                let (expected, found) = match flavor {
+                    Flavor::Printf => (self.promote_arg(expected), self.promote_arg(found)),
+                    Flavor::Scanf => (expected, found),
+                };
+                if class(expected) != class(found) || self.size(expected) != self.size(found) {
+                    issues.push(FormatIssue::Mismatch {
+                        index,
+                        spec: spec.text.to_string(),
+                        expected,
+                        found,
+                    });
+                }
+                index += 1;
+            }
+        }
+        if index < args.len() {
+            issues.push(FormatIssue::ExtraArguments { index });
+        }
+        issues
+    }
+
+    /// promote_arg applies the default argument promotions of variadic
+    /// calls: the integer promotions and `float` to `double`.
+    fn promote_arg(self, ty: CType) -> CType {
+        match ty {
+            CType::Float => CType::Double,
+            _ => self.promote(ty),
+        }
+    }
+}
+
+impl Platform {
+    /// expand_inttypes returns what an `inttypes.h` macro expands to in the
+    /// C library of the platform, which can differ from the data model's
+    /// choice. See [`DataModel::expand_inttypes`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Platform::LinuxAarch64.expand_inttypes("PRId64").as_deref(), Some("ld"));
+    /// assert_eq!(Platform::MacAarch64.expand_inttypes("PRId64").as_deref(), Some("lld"));
+    /// ```
+    pub fn expand_inttypes(self, name: &str) -> Option<String> {
+        let (flavor, conversion, ty) = parse_inttypes(name)?;
+        inttypes_macro(conversion, self.resolve(ty)?, flavor, self.data_model())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(types: &[CType]) -> Vec<TypeName> {
+        types.iter().map(|&ty| ty.into()).collect()
+    }
+
+    #[test]
+    fn test_specifiers() {
+        use CType::*;
+        let model = DataModel::LP64;
+        for ty in [
+            Char, Short, UShort, Int, UInt, Long, ULong, LongLong, ULongLong,
+        ] {
+            let spec = model.printf_specifier(ty).unwrap();
+            assert!(
+                model.check_printf(&spec, &args(&[ty])).is_empty(),
+                "{}",
+                spec
+            );
+            let spec = model.scanf_specifier(ty).unwrap();
+            assert!(
+                model.check_scanf(&spec, &args(&[ty])).is_empty(),
+                "{}",
+                spec
+            );
+        }
+        assert_eq!(model.printf_specifier(Float).as_deref(), Some("%f"));
+        assert_eq!(model.scanf_specifier(Float).as_deref(), Some("%f"));
+        assert_eq!(model.scanf_specifier(Bool), None);
+        assert_eq!(
+            model.printf_specifier(StdType::PtrDiff).as_deref(),
+            Some("%td")
+        );
+    }
+
+    #[test]
+    fn test_inttypes() {
+        let expected = [
+            ("PRId8", ["d", "d", "d"]),
+            ("SCNd8", ["hhd", "hhd", "hhd"]),
+            ("SCNu16", ["hu", "hu", "hu"]),
+            ("PRIx32", ["x", "x", "x"]),
+            ("PRIu64", ["llu", "llu", "lu"]),
+            ("SCNi64", ["lli", "lli", "li"]),
+            ("PRIdMAX", ["lld", "lld", "ld"]),
+            ("PRIXPTR", ["X", "llX", "lX"]),
+            ("SCNoPTR", ["o", "llo", "lo"]),
+        ];
+        let models = [DataModel::ILP32, DataModel::LLP64, DataModel::LP64];
+        for &(name, expansions) in expected.iter() {
+            for (model, expansion) in models.iter().zip(expansions.iter()) {
+                assert_eq!(
+                    model.expand_inttypes(name).as_deref(),
+                    Some(*expansion),
+                    "{} {:?}",
+                    name,
+                    model
+                );
+            }
+        }
+        assert_eq!(
+            DataModel::LP32.expand_inttypes("PRId32").as_deref(),
+            Some("ld")
+        );
+        assert_eq!(DataModel::IP16.expand_inttypes("PRId64"), None);
+        assert_eq!(DataModel::LP64.expand_inttypes("PRIdFAST8"), None);
+        assert_eq!(DataModel::LP64.expand_inttypes("PRI"), None);
+        assert_eq!(DataModel::LP64.pri_macro('f', CType::Int), None);
+        assert_eq!(DataModel::LP64.pri_macro('d', CType::Double), None);
+    }
+
+    #[test]
+    fn test_check_printf() {
+        use CType::*;
+        let size = TypeName::from(StdType::Size);
+        for (model, issues) in [
+            (DataModel::ILP32, 0),
+            (DataModel::LLP64, 1),
+            (DataModel::LP64, 0),
+        ] {
+            assert_eq!(model.check_printf("%lu", &[size]).len(), issues);
+            assert!(model.check_printf("%zu", &[size]).is_empty());
+        }
+        let model = DataModel::LP64;
+        // Promotions and signedness do not matter.
+        assert!(model
+            .check_printf("%d %u %c %f", &args(&[UShort, Int, Char, Float]))
+            .is_empty());
+        assert!(model
+            .check_printf("%-*.*s|%5.2f%%", &args(&[Int, Int, Pointer, Double]))
+            .is_empty());
+        assert_eq!(
+            model.check_printf("%f %Lf %d", &args(&[Int, Double])),
+            vec![
+                FormatIssue::Mismatch {
+                    index: 0,
+                    spec: "%f".to_string(),
+                    expected: Double,
+                    found: Int
+                },
+                FormatIssue::InvalidSpec {
+                    spec: "%Lf".to_string()
+                },
+                FormatIssue::Mismatch {
+                    index: 1,
+                    spec: "%d".to_string(),
+                    expected: Int,
+                    found: Double
+                },
+            ]
+        );
+        assert_eq!(
+            model.check_printf("%d", &args(&[Int, Int])),
+            vec![FormatIssue::ExtraArguments { index: 1 }]
+        );
+        assert_eq!(
+            DataModel::IP16.check_printf("%ld", &args(&[Int])),
+            vec![FormatIssue::MissingType { index: 0 }]
+        );
+    }
+
+    #[test]
+    fn test_check_scanf() {
+        use CType::*;
+        let model = DataModel::LP64;
+        assert!(model
+            .check_scanf("%*d %5s %[^]x] %hhu %n", &args(&[Char, Char, UChar, Int]))
+            .is_empty());
+        assert_eq!(model.check_scanf("%d %ld", &args(&[Long, Int])).len(), 2);
+        assert!(DataModel::LLP64
+            .check_scanf("%d %ld", &args(&[Long, Int]))
+            .is_empty());
+    }
+}
@@ -0,0 +1,511 @@
+//! Hex editor templates of record layouts.
+//!
+//! Reverse engineers read structs in hex editors that overlay a template
+//! on the bytes. [`Layout::imhex_pattern`] writes the pattern language of
+//! [ImHex](https://imhex.werwolv.net) and [`Layout::binary_template`] a
+//! [010 Editor](https://www.sweetscape.com/010editor/) Binary Template for
+//! a record as a target lays it out. Every gap is spelled out as padding,
+//! so the template shows the fields at the offsets this crate computes
+//! rather than wherever the editor's own packing rules would put them.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::layout::Record;
+//! let record = Record::new("header").field("magic", CType::UShort).field("length", CType::Long);
+//! let layout = record.layout(DataModel::LP64).unwrap();
+//! let pattern = layout.imhex_pattern(DataModel::LP64);
+//! assert!(pattern.contains("struct header {\n    u16 magic;\n    padding[6];\n    s64 length;\n};\n"));
+//! let template = layout.binary_template(DataModel::LP64);
+//! assert!(template.contains("typedef struct {\n    uint16 magic;\n    uchar padding0[6];\n    int64 length;\n} header;\n"));
+//! ```
+
+use crate::layout::{FieldLayout, FieldType, Layout, RecordKind};
+use crate::{CType, Endianness, TargetModel};
+use std::fmt::Write;
+
+/// Dialect is the template language being written.
+#[derive(Clone, Copy, PartialEq)]
+enum Dialect {
+    ImHex,
+    Template010,
+}
+
+/// Templates collects the type definitions of a template in the order
+/// they have to be declared.
+struct Templates<'a> {
+    target: &'a TargetModel,
+    dialect: Dialect,
+    defs: Vec<(String, String)>,
+    bitfields: bool,
+    flexible: bool,
+}
+
+/// Run is a run of bit-fields sharing bytes: the bytes they span and each
+/// name with its first bit in the run and width.
+struct Run {
+    from: usize,
+    to: usize,
+    members: Vec<(String, usize, usize)>,
+}
+
+impl Layout {
+    /// imhex_pattern writes an ImHex pattern of the record as laid out
+    /// for `target`, placed at offset 0.
+    ///
+    /// Gaps become `padding` members and each run of bit-fields a
+    /// `bitfield` type whose bit order is that of the target. Enums,
+    /// unions and nested records become types of their own, named by
+    /// their tag or after the record and the field holding them. A
+    /// flexible array member reads to the end of the data.
+    pub fn imhex_pattern(&self, target: impl Into<TargetModel>) -> String {
+        let target = target.into();
+        let mut templates = Templates::new(&target, Dialect::ImHex);
+        let name = templates.record(self, &self.type_name());
+        let endian = match target.endianness {
+            Endianness::Big => "big",
+            Endianness::Little | Endianness::PdpMiddle => "little",
+        };
+        let mut out = format!("#pragma endian {}\n\n", endian);
+        if templates.bitfields {
+            out.push_str("import std.core;\n");
+        }
+        if templates.flexible {
+            out.push_str("import std.mem;\n");
+        }
+        if templates.bitfields || templates.flexible {
+            out.push('\n');
+        }
+        for (_, def) in &templates.defs {
+            out.push_str(def);
+            out.push('\n');
+        }
+        let _ = writeln!(out, "{} value @ 0x00;", name);
+        out
+    }
+
+    /// binary_template writes a 010 Editor Binary Template of the record
+    /// as laid out for `target`, read from the start of the file.
+    ///
+    /// Gaps become `paddingN` arrays. Bit-fields are read with padding
+    /// disabled in the bit order of the target, with their unused bits
+    /// spelled out, so each run covers exactly the bytes it does on the
+    /// target. Enums, unions and nested records become typedefs, and a
+    /// flexible array member reads to the end of the file.
+    pub fn binary_template(&self, target: impl Into<TargetModel>) -> String {
+        let target = target.into();
+        let mut templates = Templates::new(&target, Dialect::Template010);
+        let name = templates.record(self, &self.type_name());
+        let mut out = match target.endianness {
+            Endianness::Big => "BigEndian();\n".to_string(),
+            Endianness::Little | Endianness::PdpMiddle => "LittleEndian();\n".to_string(),
+        };
+        if templates.bitfields {
+            out.push_str("BitfieldDisablePadding();\n");
+            out.push_str(match target.endianness {
+                Endianness::Big => "BitfieldLeftToRight();\n",
+                _ => "BitfieldRightToLeft();\n",
+            });
+        }
+        out.push('\n');
+        for (_, def) in &templates.defs {
+            out.push_str(def);
+            out.push('\n');
+        }
+        let _ = writeln!(out, "{} value;", name);
+        out
+    }
+
+    /// type_name returns the tag of the record, or `anon` without one.
+    fn type_name(&self) -> String {
+        match self.name.as_str() {
+            "" => "anon".to_string(),
+            name => name.to_string(),
+        }
+    }
+}
+
+impl<'a> Templates<'a> {
+    fn new(target: &'a TargetModel, dialect: Dialect) -> Templates<'a> {
+        Templates {
+            target,
+            dialect,
+            defs: Vec::new(),
+            bitfields: false,
+            flexible: false,
+        }
+    }
+
+    /// record defines the record as the type `name`, after the types it
+    /// uses, and returns the name.
+    fn record(&mut self, layout: &Layout, name: &str) -> String {
+        if self.defs.iter().any(|(def, _)| def == name) {
+            return name.to_string();
+        }
+        // Reserve the name before recursing into a cycle.
+        self.defs.push((name.to_string(), String::new()));
+        let union = layout.kind == RecordKind::Union;
+        let mut body = String::new();
+        let (mut pos, mut paddings, mut runs, mut anons) = (0, 0, 0, 0);
+        let mut run: Option<Run> = None;
+        let members: Vec<&FieldLayout> = layout.bases.iter().chain(&layout.fields).collect();
+        for (i, field) in members.iter().enumerate() {
+            let flexible = matches!(field.ty, FieldType::FlexibleArray(_));
+            if field.bits == Some(0) || (field.size == 0 && !flexible) {
+                continue;
+            }
+            let member = match field.name.as_str() {
+                "" => {
+                    anons += 1;
+                    format!("_anon{}", anons - 1)
+                }
+                member => member.to_string(),
+            };
+            if let Some(width) = field.bits {
+                let start = field.offset * 8 + field.bit_offset;
+                let (from, to) = (start / 8, (start + width).div_ceil(8));
+                let current = run.get_or_insert(Run {
+                    from,
+                    to,
+                    members: Vec::new(),
+                });
+                current.to = current.to.max(to);
+                current
+                    .members
+                    .push((member, start - current.from * 8, width));
+                let end = current.to * 8;
+                let next = members[i + 1..].iter().find(|f| f.bits != Some(0));
+                let joins = next.is_some_and(|next| {
+                    !union && next.bits.is_some() && next.offset * 8 + next.bit_offset < end
+                });
+                if let Some(run) = run.take_if(|_| !joins) {
+                    if !union {
+                        body += &self.padding(&mut paddings, pos, run.from);
+                    }
+                    pos = pos.max(run.to);
+                    body += &self.run(name, runs, run);
+                    runs += 1;
+                }
+                continue;
+            }
+            if !union && field.offset < pos {
+                // Only an empty base or a reused tail padding gets here.
+                let _ = writeln!(body, "    // {} overlaps the fields before it", member);
+                continue;
+            }
+            if !union {
+                body += &self.padding(&mut paddings, pos, field.offset);
+            }
+            pos = pos.max(field.offset + field.size);
+            body += &self.field(field, &member, &format!("{}_{}", name, member));
+        }
+        match (layout.kind, layout.flexible) {
+            (RecordKind::Struct, None) => body += &self.padding(&mut paddings, pos, layout.size),
+            (RecordKind::Union, _) => body += &self.padding(&mut paddings, pos, layout.size),
+            (RecordKind::Struct, Some(_)) => {}
+        }
+
+        let keyword = if union { "union" } else { "struct" };
+        let def = match self.dialect {
+            Dialect::ImHex => format!("{} {} {{\n{}}};\n", keyword, name, body),
+            Dialect::Template010 => format!("typedef {} {{\n{}}} {};\n", keyword, body, name),
+        };
+        // Move the definition after the types it uses.
+        self.defs.retain(|(def, _)| def != name);
+        self.defs.push((name.to_string(), def));
+        name.to_string()
+    }
+
+    /// padding declares the bytes from `from` to `to` as padding.
+    fn padding(&self, paddings: &mut usize, from: usize, to: usize) -> String {
+        if to <= from {
+            return String::new();
+        }
+        *paddings += 1;
+        match self.dialect {
+            Dialect::ImHex => format!("    padding[{}];\n", to - from),
+            Dialect::Template010 => {
+                format!("    uchar padding{}[{}];\n", *paddings - 1, to - from)
+            }
+        }
+    }
+
+    /// run declares a run of bit-fields of the record `name`, with their
+    /// unused bits as padding.
+    fn run(&mut self, name: &str, index: usize, run: Run) -> String {
+        self.bitfields = true;
+        let bits = (run.to - run.from) * 8;
+        let mut members = String::new();
+        let (mut pos, mut gaps) = (0, 0);
+        let unit = match self.dialect {
+            Dialect::ImHex => "",
+            Dialect::Template010 => match run.to - run.from {
+                1 => "uchar ",
+                2 => "uint16 ",
+                3 | 4 => "uint32 ",
+                _ => "uint64 ",
+            },
+        };
+        let mut gap = |members: &mut String, width: usize| {
+            let _ = match self.dialect {
+                Dialect::ImHex => writeln!(members, "    padding : {};", width),
+                Dialect::Template010 => {
+                    gaps += 1;
+                    writeln!(members, "    {}padding_bits{} : {};", unit, gaps - 1, width)
+                }
+            };
+        };
+        for (member, start, width) in run.members {
+            if start > pos {
+                gap(&mut members, start - pos);
+            }
+            let _ = writeln!(members, "    {}{} : {};", unit, member, width);
+            pos = pos.max(start + width);
+        }
+        if bits > pos {
+            gap(&mut members, bits - pos);
+        }
+        match self.dialect {
+            Dialect::ImHex => {
+                let order = match self.target.endianness {
+                    Endianness::Big => "MostToLeastSignificant",
+                    _ => "LeastToMostSignificant",
+                };
+                let ty = format!("{}_bits{}", name, index);
+                let def = format!(
+                    "bitfield {} {{\n{}}} [[bitfield_order(std::core::BitfieldOrder::{}, {})]];\n",
+                    ty, members, order, bits
+                );
+                self.defs.push((ty.clone(), def));
+                format!("    {} bits{};\n", ty, index)
+            }
+            Dialect::Template010 => members,
+        }
+    }
+
+    /// field declares `field` as the member `member`. A nested record or
+    /// enum without a tag is defined as the type `name`.
+    fn field(&mut self, field: &FieldLayout, member: &str, name: &str) -> String {
+        match &field.ty {
+            FieldType::Array(..) => {
+                let (element, count) = field.ty.innermost();
+                let size = field.size.checked_div(count).unwrap_or(0);
+                match self.value(element, size, field, name) {
+                    (ty, None) => format!("    {} {}[{}];\n", ty, member, count),
+                    (ty, Some(n)) => format!("    {} {}[{}];\n", ty, member, n * count),
+                }
+            }
+            FieldType::FlexibleArray(element) => {
+                self.flexible = true;
+                let size = match &field.nested {
+                    Some(nested) => nested.size,
+                    None => self.target.data_model().size(element.scalar()),
+                };
+                let (ty, _) = self.value(element, size, field, name);
+                match self.dialect {
+                    Dialect::ImHex => {
+                        format!("    {} {}[while(!std::mem::eof())];\n", ty, member)
+                    }
+                    Dialect::Template010 => format!(
+                        "    {} {}[(FileSize() - FTell()) / {}];\n",
+                        ty,
+                        member,
+                        size.max(1)
+                    ),
+                }
+            }
+            ty => match self.value(ty, field.size, field, name) {
+                (ty, None) => format!("    {} {};\n", ty, member),
+                (ty, Some(n)) => format!("    {} {}[{}];\n", ty, member, n),
+            },
+        }
+    }
+
+    /// value returns the type of one value of `ty`, which takes `size`
+    /// bytes, and the length of the byte array standing in for a type the
+    /// language does not have.
+    fn value(
+        &mut self,
+        ty: &FieldType,
+        size: usize,
+        field: &FieldLayout,
+        name: &str,
+    ) -> (String, Option<usize>) {
+        let raw = || (self.integer(1, false).unwrap_or_default(), Some(size));
+        match ty {
+            FieldType::Scalar(CType::Float) | FieldType::Scalar(CType::Double) => match size {
+                4 => ("float".to_string(), None),
+                8 => ("double".to_string(), None),
+                _ => raw(),
+            },
+            FieldType::Scalar(CType::Char) if size == 1 => ("char".to_string(), None),
+            FieldType::Scalar(CType::Bool) if self.dialect == Dialect::ImHex && size == 1 => {
+                ("bool".to_string(), None)
+            }
+            FieldType::Scalar(ty) => match self.integer(size, self.target.is_signed(*ty)) {
+                Some(ty) => (ty, None),
+                None => raw(),
+            },
+            FieldType::Pointer(_) => match self.integer(size, false) {
+                Some(ty) => (ty, None),
+                None => raw(),
+            },
+            FieldType::Enum(e) => {
+                let signed = e.values.iter().any(|&(_, value)| value < 0);
+                let underlying = match self.integer(size, signed) {
+                    Some(ty) => ty,
+                    None => return raw(),
+                };
+                let ty = match e.name.as_str() {
+                    "" => name.to_string(),
+                    tag => tag.to_string(),
+                };
+                if !self.defs.iter().any(|(def, _)| *def == ty) {
+                    let values: Vec<String> = e
+                        .values
+                        .iter()
+                        .map(|(name, value)| format!("    {} = {}", name, value))
+                        .collect();
+                    let def = match self.dialect {
+                        Dialect::ImHex => {
+                            format!(
+                                "enum {} : {} {{\n{}\n}};\n",
+                                ty,
+                                underlying,
+                                values.join(",\n")
+                            )
+                        }
+                        Dialect::Template010 => format!(
+                            "typedef enum <{}> {{\n{}\n}} {};\n",
+                            underlying,
+                            values.join(",\n"),
+                            ty
+                        ),
+                    };
+                    self.defs.push((ty.clone(), def));
+                }
+                (ty, None)
+            }
+            FieldType::Record(_) | FieldType::Layout(_) => match &field.nested {
+                Some(nested) => {
+                    let ty = match nested.name.as_str() {
+                        "" => name.to_string(),
+                        tag => tag.to_string(),
+                    };
+                    (self.record(nested, &ty), None)
+                }
+                None => raw(),
+            },
+            FieldType::Array(..) | FieldType::FlexibleArray(_) => raw(),
+        }
+    }
+
+    /// integer returns the integer type of `size` bytes, if the language
+    /// has one in the byte order of the target.
+    fn integer(&self, size: usize, signed: bool) -> Option<String> {
+        let pdp = self.target.endianness == Endianness::PdpMiddle;
+        if !matches!(size, 1 | 2 | 4 | 8) || (pdp && size > 2) {
+            return None;
+        }
+        Some(match (self.dialect, signed) {
+            (Dialect::ImHex, true) => format!("s{}", size * 8),
+            (Dialect::ImHex, false) => format!("u{}", size * 8),
+            (Dialect::Template010, _) if size == 1 => {
+                (if signed { "char" } else { "uchar" }).to_string()
+            }
+            (Dialect::Template010, true) => format!("int{}", size * 8),
+            (Dialect::Template010, false) => format!("uint{}", size * 8),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layout::{Enum, FieldType, Record};
+    use crate::{CType, Platform};
+
+    #[test]
+    fn test_templates() {
+        let point = Record::new("point")
+            .field("x", CType::Short)
+            .field("y", CType::Short);
+        let record = Record::new("packet")
+            .field("kind", Enum::new("kind").value("PING", 1).value("PONG", 2))
+            .bitfield("urgent", CType::UInt, 1)
+            .bitfield("level", CType::UInt, 3)
+            .field("at", point)
+            .field("data", FieldType::flexible(CType::UChar));
+        let target = Platform::SolarisSparc64.target();
+        let layout = record.layout(target.abi).unwrap();
+        assert_eq!(
+            layout.imhex_pattern(target),
+            "#pragma endian big
+
+import std.core;
+import std.mem;
+
+enum kind : u32 {
+    PING = 1,
+    PONG = 2
+};
+
+bitfield packet_bits0 {
+    urgent : 1;
+    level : 3;
+    padding : 4;
+} [[bitfield_order(std::core::BitfieldOrder::MostToLeastSignificant, 8)]];
+
+struct point {
+    s16 x;
+    s16 y;
+};
+
+struct packet {
+    kind kind;
+    packet_bits0 bits0;
+    padding[1];
+    point at;
+    u8 data[while(!std::mem::eof())];
+};
+
+packet value @ 0x00;
+"
+        );
+        assert_eq!(
+            layout.binary_template(target),
+            "BigEndian();
+BitfieldDisablePadding();
+BitfieldLeftToRight();
+
+typedef enum <uint32> {
+    PING = 1,
+    PONG = 2
+} kind;
+
+typedef struct {
+    int16 x;
+    int16 y;
+} point;
+
+typedef struct {
+    kind kind;
+    uchar urgent : 1;
+    uchar level : 3;
+    uchar padding_bits0 : 4;
+    uchar padding0[1];
+    point at;
+    uchar data[(FileSize() - FTell()) / 1];
+} packet;
+
+packet value;
+"
+        );
+
+        let union = Record::union("u")
+            .field("c", CType::Char)
+            .field("d", FieldType::array(CType::Double, 2));
+        let target = Platform::LinuxX86_64.target();
+        let pattern = union.layout(target.abi).unwrap().imhex_pattern(target);
+        assert!(pattern.contains("union u {\n    char c;\n    double d[2];\n};\n"));
+    }
+}
@@ -0,0 +1,648 @@
+//! JSON descriptions of targets.
+//!
+//! Scripts and CI jobs that reason about a target can share the exact
+//! description this crate uses through [`TargetModel::to_json`] and
+//! [`TargetModel::from_json`]. The document is an object with the keys:
+//!
+//! | Key | Value |
+//! |-----|-------|
+//! | `data_model` | The name of the [`DataModel`], e.g. `"LP64"`. |
+//! | `sizes` | `sizeof` each C type the model has, keyed by its C name: `"_Bool"`, `"char"`, `"short"`, `"int"`, `"long"`, `"long long"`, `"void *"`, `"float"` and `"double"`. |
+//! | `alignments` | `_Alignof` the same types inside records. |
+//! | `endianness` | `"little"`, `"big"` or `"pdp"`. |
+//! | `char_signed` | Whether plain `char` is signed. |
+//! | `long_double` | `"binary64"`, `"x87_extended"`, `"binary128"` or `"double_double"`. |
+//! | `signed_representation` | `"twos_complement"`, `"ones_complement"` or `"sign_magnitude"`. |
+//! | `null_pointer` | The bits of a null pointer, as a number. |
+//! | `bitfields` | The bit-field rules, `"itanium"` or `"msvc"`. |
+//! | `pack` | The default packing of records, or `null`. |
+//! | `short_enums` | Whether enums take the smallest type holding their values. |
+//! | `power_align` | Whether records follow the power alignment rule of AIX. |
+//!
+//! When reading, `data_model` can be left out if `sizes` has at least
+//! `int`, and the model is then the one with the `int`, `long` and
+//! `void *` of `sizes`. The other keys default to [`TargetModel::new`], and
+//! keys this crate does not know are ignored.
+//! `sizes` and `alignments`, when present, must agree with the model and
+//! with an alignment cap, as written by `to_json`. `pack` and the
+//! alignments must be powers of two.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! let json = Platform::LinuxI686.target().to_json();
+//! assert!(json.contains("\"data_model\": \"ILP32\""));
+//! assert!(json.contains("\"long long\": 4"));
+//! assert_eq!(TargetModel::from_json(&json), Ok(Platform::LinuxI686.target()));
+//! ```
+
+use crate::layout::{Abi, BitFieldRules};
+use crate::{CType, DataModel, Endianness, LongDouble, SignedRepresentation, TargetModel};
+use std::error::Error;
+use std::fmt;
+
+/// JsonError describes why a JSON description was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum JsonError {
+    /// The text is not JSON; the error is at the byte offset.
+    Syntax(usize),
+    /// A required key is missing.
+    Missing(String),
+    /// The key holds a value the schema does not allow.
+    Invalid(String),
+    /// The sizes or alignments under the key do not match the data model.
+    Inconsistent(String),
+    /// Arrays and objects nest deeper than [`MAX_DEPTH`]; the error is at
+    /// the byte offset of the first value too deep.
+    Nesting(usize),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::Syntax(offset) => write!(f, "invalid JSON at byte {}", offset),
+            JsonError::Missing(key) => write!(f, "missing key {:?}", key),
+            JsonError::Invalid(key) => write!(f, "invalid value for {:?}", key),
+            JsonError::Inconsistent(key) => {
+                write!(f, "{:?} does not match the data model", key)
+            }
+            JsonError::Nesting(offset) => write!(f, "JSON nests too deeply at byte {}", offset),
+        }
+    }
+}
+
+impl Error for JsonError {}
+
+/// MAX_DEPTH is how deep values can nest in a JSON document.
+pub const MAX_DEPTH: usize = 128;
+
+/// MODELS are the data models by name.
+const MODELS: [DataModel; 9] = [
+    DataModel::IP16,
+    DataModel::IP16L32,
+    DataModel::LP32,
+    DataModel::ILP32,
+    DataModel::LLP64,
+    DataModel::LP64,
+    DataModel::ILP64,
+    DataModel::SILP64,
+    DataModel::Unknown,
+];
+
+/// TYPES are the C types described by `sizes` and `alignments`.
+const TYPES: [CType; 9] = [
+    CType::Bool,
+    CType::Char,
+    CType::Short,
+    CType::Int,
+    CType::Long,
+    CType::LongLong,
+    CType::Pointer,
+    CType::Float,
+    CType::Double,
+];
+
+const ENDIANNESS: [(Endianness, &str); 3] = [
+    (Endianness::Little, "little"),
+    (Endianness::Big, "big"),
+    (Endianness::PdpMiddle, "pdp"),
+];
+
+const LONG_DOUBLE: [(LongDouble, &str); 4] = [
+    (LongDouble::Binary64, "binary64"),
+    (LongDouble::X87Extended, "x87_extended"),
+    (LongDouble::Binary128, "binary128"),
+    (LongDouble::DoubleDouble, "double_double"),
+];
+
+const REPRESENTATIONS: [(SignedRepresentation, &str); 3] = [
+    (SignedRepresentation::TwosComplement, "twos_complement"),
+    (SignedRepresentation::OnesComplement, "ones_complement"),
+    (SignedRepresentation::SignMagnitude, "sign_magnitude"),
+];
+
+const BITFIELDS: [(BitFieldRules, &str); 2] = [
+    (BitFieldRules::Itanium, "itanium"),
+    (BitFieldRules::Msvc, "msvc"),
+];
+
+/// name returns the JSON name of `value` in `names`.
+fn name<T: PartialEq>(names: &[(T, &'static str)], value: T) -> &'static str {
+    names.iter().find(|(v, _)| *v == value).unwrap().1
+}
+
+impl TargetModel {
+    /// to_json writes the target as a JSON object in the schema of the
+    /// [module](crate::json).
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let json = Platform::Windows64.target().to_json();
+    /// assert!(json.contains("\"long\": 4"));
+    /// assert!(json.contains("\"bitfields\": \"msvc\""));
+    /// ```
+    pub fn to_json(&self) -> String {
+        let model = self.data_model();
+        let table = |value: &dyn Fn(CType) -> usize| {
+            let entries: Vec<String> = TYPES
+                .iter()
+                .filter(|&&ty| model.has(ty))
+                .map(|&ty| format!("    \"{}\": {}", ty.c_name(), value(ty)))
+                .collect();
+            format!("{{\n{}\n  }}", entries.join(",\n"))
+        };
+        let pack = self
+            .abi
+            .pack()
+            .map_or("null".to_string(), |pack| pack.to_string());
+        format!(
+            "{{\n  \"data_model\": \"{:?}\",\n  \"sizes\": {},\n  \"alignments\": {},\n  \
+             \"endianness\": \"{}\",\n  \"char_signed\": {},\n  \"long_double\": \"{}\",\n  \
+             \"signed_representation\": \"{}\",\n  \"null_pointer\": {},\n  \
+             \"bitfields\": \"{}\",\n  \"pack\": {},\n  \"short_enums\": {},\n  \
+             \"power_align\": {}\n}}\n",
+            model,
+            table(&|ty| model.size(ty)),
+            table(&|ty| self.abi.align(ty)),
+            name(&ENDIANNESS, self.endianness),
+            self.char_signed,
+            name(&LONG_DOUBLE, self.long_double),
+            name(&REPRESENTATIONS, self.signed_representation),
+            self.null_pointer,
+            name(&BITFIELDS, self.abi.bitfields()),
+            pack,
+            self.abi.short_enums(),
+            self.abi.power_align(),
+        )
+    }
+
+    /// from_json reads a target written in the schema of the
+    /// [module](crate::json).
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let target = TargetModel::from_json(r#"{"data_model": "LP64", "char_signed": false}"#).unwrap();
+    /// assert_eq!(target, TargetModel::new(DataModel::LP64).with_char_signed(false));
+    /// ```
+    pub fn from_json(json: &str) -> Result<TargetModel, JsonError> {
+        let mut parser = Parser {
+            src: json,
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.space();
+        if parser.pos != parser.src.len() {
+            return Err(JsonError::Syntax(parser.pos));
+        }
+        match &value {
+            Json::Object(object) => describe(object, None),
+            _ => Err(JsonError::Syntax(0)),
+        }
+    }
+}
+
+/// describe reads the keys of a target description from `object`, on top
+/// of `base` if there is one. Without a `data_model` the model is the one
+/// of `base`, or the one the `int`, `long` and `void *` sizes imply.
+pub(crate) fn describe(
+    object: &[(String, Json)],
+    base: Option<TargetModel>,
+) -> Result<TargetModel, JsonError> {
+    let get = |key: &str| object.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+    let invalid = |key: &str| JsonError::Invalid(key.to_string());
+    let string = |key: &str| match get(key) {
+        None => Ok(None),
+        Some(Json::String(s)) => Ok(Some(s.as_str())),
+        Some(_) => Err(invalid(key)),
+    };
+    let named = |key: &str, names: &[&str]| -> Result<Option<usize>, JsonError> {
+        match string(key)? {
+            None => Ok(None),
+            Some(s) => names
+                .iter()
+                .position(|name| *name == s)
+                .map(Some)
+                .ok_or_else(|| invalid(key)),
+        }
+    };
+    let boolean = |key: &str| match get(key) {
+        None => Ok(None),
+        Some(Json::Bool(b)) => Ok(Some(*b)),
+        Some(_) => Err(invalid(key)),
+    };
+
+    let table = |key: &str| -> Result<Option<Vec<(CType, usize)>>, JsonError> {
+        let entries = match get(key) {
+            None => return Ok(None),
+            Some(Json::Object(entries)) => entries,
+            Some(_) => return Err(invalid(key)),
+        };
+        let mut table = Vec::new();
+        for (name, value) in entries {
+            let ty = TYPES.iter().find(|ty| ty.c_name() == name);
+            match (ty, value) {
+                (Some(&ty), Json::Number(n)) => {
+                    table.push((ty, n.parse().map_err(|_| invalid(key))?));
+                }
+                (None, _) => {}
+                _ => return Err(invalid(key)),
+            }
+        }
+        Ok(Some(table))
+    };
+    let names: Vec<String> = MODELS.iter().map(|m| format!("{:?}", m)).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let sizes = table("sizes")?;
+    let size = |ty| {
+        let sizes = sizes.as_ref()?;
+        sizes.iter().find(|&&(t, _)| t == ty).map(|&(_, size)| size)
+    };
+    let model = match (named("data_model", &names)?, base, size(CType::Int)) {
+        (Some(i), _, _) => MODELS[i],
+        (None, Some(base), _) => base.data_model(),
+        (None, None, Some(int)) => {
+            let long = size(CType::Long).unwrap_or(0);
+            DataModel::new(int, long, size(CType::Pointer).unwrap_or(0))
+        }
+        (None, None, None) => return Err(JsonError::Missing("data_model".to_string())),
+    };
+    let mut target = base.unwrap_or_else(|| TargetModel::new(model));
+    let mut abi = match target.data_model() == model {
+        true => target.abi,
+        false => rebase(target.abi, model),
+    };
+    if let Some(i) = named("endianness", &ENDIANNESS.map(|(_, n)| n))? {
+        target.endianness = ENDIANNESS[i].0;
+    }
+    if let Some(b) = boolean("char_signed")? {
+        target.char_signed = b;
+    }
+    if let Some(i) = named("long_double", &LONG_DOUBLE.map(|(_, n)| n))? {
+        target.long_double = LONG_DOUBLE[i].0;
+    }
+    if let Some(i) = named("signed_representation", &REPRESENTATIONS.map(|(_, n)| n))? {
+        target.signed_representation = REPRESENTATIONS[i].0;
+    }
+    match get("null_pointer") {
+        None => {}
+        Some(Json::Number(n)) => {
+            target.null_pointer = n.parse().map_err(|_| invalid("null_pointer"))?;
+        }
+        Some(_) => return Err(invalid("null_pointer")),
+    }
+    if let Some(i) = named("bitfields", &BITFIELDS.map(|(_, n)| n))? {
+        abi = abi.with_bitfields(BITFIELDS[i].0);
+    }
+    match get("pack") {
+        None | Some(Json::Null) => {}
+        Some(Json::Number(n)) => match n.parse::<usize>() {
+            Ok(pack) if pack.is_power_of_two() => abi = abi.with_pack(pack),
+            _ => return Err(invalid("pack")),
+        },
+        Some(_) => return Err(invalid("pack")),
+    }
+    if boolean("short_enums")? == Some(true) {
+        abi = abi.with_short_enums();
+    }
+    if boolean("power_align")? == Some(true) {
+        abi = abi.with_power_align();
+    }
+
+    if let Some(sizes) = sizes {
+        if sizes.iter().any(|&(ty, size)| model.size(ty) != size) {
+            return Err(JsonError::Inconsistent("sizes".to_string()));
+        }
+    }
+    if let Some(aligns) = table("alignments")? {
+        if aligns.iter().any(|&(_, align)| !align.is_power_of_two()) {
+            return Err(invalid("alignments"));
+        }
+        let capped = aligns.iter().any(|&(ty, align)| align != abi.align(ty));
+        if capped {
+            let max = aligns.iter().map(|&(_, align)| align).max().unwrap_or(1);
+            abi = abi.with_max_align(max);
+        }
+        if aligns.iter().any(|&(ty, align)| align != abi.align(ty)) {
+            return Err(JsonError::Inconsistent("alignments".to_string()));
+        }
+    }
+    Ok(target.with_abi(abi))
+}
+
+/// rebase returns `abi` with the sizes of `model`, keeping its rules.
+fn rebase(abi: Abi, model: DataModel) -> Abi {
+    let mut rebased = Abi::new(model).with_bitfields(abi.bitfields());
+    if let Some(pack) = abi.pack() {
+        rebased = rebased.with_pack(pack);
+    }
+    if let Some(align) = abi.max_align() {
+        rebased = rebased.with_max_align(align);
+    }
+    if abi.short_enums() {
+        rebased = rebased.with_short_enums();
+    }
+    if abi.power_align() {
+        rebased = rebased.with_power_align();
+    }
+    rebased
+}
+
+/// Json is a parsed JSON value. Numbers keep their text, to be parsed as
+/// the type the schema wants.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// Parser reads JSON from text.
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    /// How many values the next one is nested in.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    /// space skips whitespace.
+    fn space(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.src.as_bytes().get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    /// eat consumes `token` if it comes next.
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.src[self.pos..].starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    /// value parses the next value, which can nest [`MAX_DEPTH`] deep.
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.space();
+        if self.depth == MAX_DEPTH {
+            return Err(JsonError::Nesting(self.pos));
+        }
+        self.depth += 1;
+        let value = self.item();
+        self.depth -= 1;
+        value
+    }
+
+    /// item parses the value at the current position.
+    fn item(&mut self) -> Result<Json, JsonError> {
+        let syntax = JsonError::Syntax(self.pos);
+        match self.src.as_bytes().get(self.pos) {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut object = Vec::new();
+                self.space();
+                if self.eat("}") {
+                    return Ok(Json::Object(object));
+                }
+                loop {
+                    self.space();
+                    let key = self.string()?;
+                    self.space();
+                    if !self.eat(":") {
+                        return Err(JsonError::Syntax(self.pos));
+                    }
+                    object.push((key, self.value()?));
+                    self.space();
+                    if self.eat("}") {
+                        return Ok(Json::Object(object));
+                    }
+                    if !self.eat(",") {
+                        return Err(JsonError::Syntax(self.pos));
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut array = Vec::new();
+                self.space();
+                if self.eat("]") {
+                    return Ok(Json::Array(array));
+                }
+                loop {
+                    array.push(self.value()?);
+                    self.space();
+                    if self.eat("]") {
+                        return Ok(Json::Array(array));
+                    }
+                    if !self.eat(",") {
+                        return Err(JsonError::Syntax(self.pos));
+                    }
+                }
+            }
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'-' | b'0'..=b'9') => {
+                let start = self.pos;
+                while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') =
+                    self.src.as_bytes().get(self.pos)
+                {
+                    self.pos += 1;
+                }
+                let text = &self.src[start..self.pos];
+                match text.parse::<f64>() {
+                    Ok(_) => Ok(Json::Number(text.to_string())),
+                    Err(_) => Err(syntax),
+                }
+            }
+            _ if self.eat("true") => Ok(Json::Bool(true)),
+            _ if self.eat("false") => Ok(Json::Bool(false)),
+            _ if self.eat("null") => Ok(Json::Null),
+            _ => Err(syntax),
+        }
+    }
+
+    /// string parses a string.
+    fn string(&mut self) -> Result<String, JsonError> {
+        if !self.eat("\"") {
+            return Err(JsonError::Syntax(self.pos));
+        }
+        let mut s = String::new();
+        loop {
+            let start = self.pos;
+            let c = self.src[start..]
+                .chars()
+                .next()
+                .ok_or(JsonError::Syntax(start))?;
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(s),
+                '\\' => {
+                    let escape = self.src.as_bytes().get(self.pos).copied();
+                    self.pos += 1;
+                    s.push(match escape {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => self.unicode()?,
+                        _ => return Err(JsonError::Syntax(start)),
+                    });
+                }
+                c if (c as u32) < 0x20 => return Err(JsonError::Syntax(start)),
+                c => s.push(c),
+            }
+        }
+    }
+
+    /// unicode parses the digits of a `\u` escape, and the low half of a
+    /// surrogate pair.
+    fn unicode(&mut self) -> Result<char, JsonError> {
+        let start = self.pos;
+        let mut hex = || {
+            let digits = self.src.get(self.pos..self.pos + 4);
+            let code = digits.and_then(|d| u32::from_str_radix(d, 16).ok());
+            self.pos += 4;
+            code.ok_or(JsonError::Syntax(start))
+        };
+        let high = hex()?;
+        let code = match high {
+            0xd800..=0xdbff => {
+                if !self.eat("\\u") {
+                    return Err(JsonError::Syntax(start));
+                }
+                let low = self.unicode()? as u32;
+                0x10000 + ((high - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff)
+            }
+            _ => high,
+        };
+        char::from_u32(code).ok_or(JsonError::Syntax(start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Platform;
+
+    #[test]
+    fn test_round_trip() {
+        for &platform in Platform::ALL {
+            let target = platform.target();
+            assert_eq!(TargetModel::from_json(&target.to_json()), Ok(target));
+        }
+        let target = TargetModel::new(DataModel::IP16)
+            .with_endianness(Endianness::PdpMiddle)
+            .with_signed_representation(SignedRepresentation::OnesComplement)
+            .with_null_pointer(0xffff)
+            .with_abi(Abi::new(DataModel::IP16).with_pack(1).with_short_enums());
+        let json = target.to_json();
+        assert!(!json.contains("\"long\""));
+        assert!(json.contains("\"pack\": 1,"));
+        assert_eq!(TargetModel::from_json(&json), Ok(target));
+    }
+
+    #[test]
+    fn test_parser() {
+        let json = r#" {"data_model" : "ILP32", "comment": ["é😀", 1.5e3, null, {}],
+            "sizes": {"int": 4, "wchar_t": 4}} "#;
+        let target = TargetModel::from_json(json).unwrap();
+        assert_eq!(target, TargetModel::new(DataModel::ILP32));
+        let mut parser = Parser {
+            src: r#""é😀\n""#,
+            pos: 0,
+            depth: 0,
+        };
+        assert_eq!(parser.value(), Ok(Json::String("é😀\n".to_string())));
+
+        let long = format!("\"{}\"", "é".repeat(1 << 20));
+        let mut parser = Parser {
+            src: &long,
+            pos: 0,
+            depth: 0,
+        };
+        assert_eq!(parser.value(), Ok(Json::String("é".repeat(1 << 20))));
+        for (depth, expected) in [
+            (MAX_DEPTH, Ok(())),
+            (10000, Err(JsonError::Nesting(MAX_DEPTH))),
+        ] {
+            let nested = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+            let mut parser = Parser {
+                src: &nested,
+                pos: 0,
+                depth: 0,
+            };
+            assert_eq!(parser.value().map(|_| ()), expected);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let cases = [
+            ("", JsonError::Syntax(0)),
+            ("{\"data_model\": \"LP64\",}", JsonError::Syntax(22)),
+            ("{\"data_model\": \"LP64\"} x", JsonError::Syntax(23)),
+            ("[]", JsonError::Syntax(0)),
+            ("{}", JsonError::Missing("data_model".to_string())),
+            (
+                "{\"data_model\": \"LP128\"}",
+                JsonError::Invalid("data_model".to_string()),
+            ),
+            (
+                "{\"data_model\": \"LP64\", \"endianness\": 1}",
+                JsonError::Invalid("endianness".to_string()),
+            ),
+            (
+                "{\"data_model\": \"LP64\", \"null_pointer\": -1}",
+                JsonError::Invalid("null_pointer".to_string()),
+            ),
+            (
+                "{\"data_model\": \"LP64\", \"pack\": 0}",
+                JsonError::Invalid("pack".to_string()),
+            ),
+            (
+                "{\"data_model\": \"LP64\", \"pack\": 6}",
+                JsonError::Invalid("pack".to_string()),
+            ),
+            (
+                "{\"data_model\": \"LP64\", \"alignments\": {\"int\": 0}}",
+                JsonError::Invalid("alignments".to_string()),
+            ),
+            (
+                "{\"data_model\": \"LP64\", \"alignments\": {\"double\": 3}}",
+                JsonError::Invalid("alignments".to_string()),
+            ),
+            (
+                "{\"data_model\": \"LP64\", \"sizes\": {\"long\": 4}}",
+                JsonError::Inconsistent("sizes".to_string()),
+            ),
+            (
+                "{\"data_model\": \"LP64\", \"alignments\": {\"int\": 2, \"long\": 4}}",
+                JsonError::Inconsistent("alignments".to_string()),
+            ),
+        ];
+        for (json, expected) in cases.iter() {
+            assert_eq!(
+                TargetModel::from_json(json).as_ref(),
+                Err(expected),
+                "{}",
+                json
+            );
+        }
+        let json = "{\"data_model\": \"LP64\", \"alignments\": {\"long\": 4, \"double\": 4}}";
+        let target = TargetModel::from_json(json).unwrap();
+        assert_eq!(target.abi.max_align(), Some(4));
+        let json = "{\"sizes\": {\"int\": 2, \"long\": 4, \"void *\": 4}}";
+        let target = TargetModel::from_json(json).unwrap();
+        assert_eq!(target.data_model(), DataModel::LP32);
+    }
+}
@@ -0,0 +1,404 @@
+//! Kaitai Struct definitions of record layouts.
+//!
+//! [Kaitai Struct](https://kaitai.io) compiles a `.ksy` description of a
+//! binary format into parsers for C++, C#, Go, Java, JavaScript, Python,
+//! Rust and more. [`Layout::kaitai_struct`] writes that description for a
+//! record as a target lays it out, so a format defined by C structs gets
+//! parsers for every one of those languages, with the padding, byte order
+//! and bit-field placement of the target built in.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::layout::Record;
+//! let record = Record::new("header").field("magic", CType::UShort).field("length", CType::Long);
+//! let ksy = record
+//!     .layout(Platform::LinuxX86_64.target().abi)
+//!     .unwrap()
+//!     .kaitai_struct(Platform::LinuxX86_64.target());
+//! assert!(ksy.starts_with("meta:\n  id: header\n  endian: le\n"));
+//! assert!(ksy.contains("  - id: padding0\n    size: 6\n  - id: length\n    type: s8\n"));
+//! ```
+
+use crate::layout::{FieldLayout, FieldType, Layout, RecordKind};
+use crate::{CType, Endianness, TargetModel};
+use std::fmt::Write;
+
+/// Attrs are the keys and values describing one attribute of a type.
+type Attrs = Vec<(&'static str, String)>;
+
+/// Ksy collects the user types and enums of a definition.
+struct Ksy<'a> {
+    target: &'a TargetModel,
+    types: Vec<(String, String)>,
+    enums: Vec<(String, String)>,
+}
+
+impl Layout {
+    /// kaitai_struct writes a Kaitai Struct definition of the record as
+    /// laid out for `target`, whose byte order becomes the `endian` of the
+    /// definition and the `bit-endian` of its bit-fields.
+    ///
+    /// Every gap becomes an explicit `paddingN` attribute, so offsets do
+    /// not depend on the parser. Bit-fields become `bN` attributes, arrays
+    /// repeat their element, flexible array members repeat to the end of
+    /// the stream, enums get an `enums` entry, and nested records become
+    /// user types named by their tag. Union members, which Kaitai cannot
+    /// read in sequence, become instances at position 0 of a type that
+    /// consumes the size of the union. Scalars Kaitai has no type for,
+    /// like `long double` or the 32-bit integers of the PDP-11, are read
+    /// as raw bytes.
+    pub fn kaitai_struct(&self, target: impl Into<TargetModel>) -> String {
+        let target = target.into();
+        let mut ksy = Ksy {
+            target: &target,
+            types: Vec::new(),
+            enums: Vec::new(),
+        };
+        let id = match self.name.as_str() {
+            "" => "anon".to_string(),
+            name => kaitai_id(name),
+        };
+        let body = ksy.record(self, &id);
+        let endian = match target.endianness {
+            Endianness::Big => "be",
+            Endianness::Little | Endianness::PdpMiddle => "le",
+        };
+        let mut out = format!("meta:\n  id: {}\n  endian: {}\n", id, endian);
+        if has_bitfields(self) {
+            let _ = writeln!(out, "  bit-endian: {}", endian);
+        }
+        out.push_str(&body);
+        if !ksy.types.is_empty() {
+            out.push_str("types:\n");
+            for (name, body) in &ksy.types {
+                let _ = writeln!(out, "  {}:", name);
+                out.push_str(&indent(body, 4));
+            }
+        }
+        if !ksy.enums.is_empty() {
+            out.push_str("enums:\n");
+            for (name, body) in &ksy.enums {
+                let _ = writeln!(out, "  {}:", name);
+                out.push_str(body);
+            }
+        }
+        out
+    }
+}
+
+impl Ksy<'_> {
+    /// record returns the `seq` and `instances` of the record, defining
+    /// the types nested in it along the way.
+    fn record(&mut self, layout: &Layout, id: &str) -> String {
+        let mut seq: Vec<(String, Attrs)> = Vec::new();
+        let mut instances: Vec<(String, Attrs)> = Vec::new();
+        let mut pos = 0;
+        let (mut paddings, mut anons) = (0, 0);
+        let mut pad = |seq: &mut Vec<(String, Attrs)>, pos: &mut usize, to: usize| {
+            for attrs in padding(*pos, to) {
+                seq.push((format!("padding{}", paddings), attrs));
+                paddings += 1;
+            }
+            *pos = (*pos).max(to);
+        };
+        for field in layout.bases.iter().chain(&layout.fields) {
+            let flexible = matches!(field.ty, FieldType::FlexibleArray(_));
+            if field.bits == Some(0) || (field.size == 0 && !flexible) {
+                continue;
+            }
+            let name = match field.name.as_str() {
+                "" => {
+                    anons += 1;
+                    format!("anon{}", anons - 1)
+                }
+                name => kaitai_id(name),
+            };
+            let attrs = self.field(field, &format!("{}_{}", id, name));
+            let start = field.offset * 8 + field.bit_offset;
+            if layout.kind == RecordKind::Union || start < pos {
+                let mut attrs = attrs;
+                attrs.insert(0, ("pos", field.offset.to_string()));
+                instances.push((name, attrs));
+                continue;
+            }
+            pad(&mut seq, &mut pos, start);
+            seq.push((name, attrs));
+            pos = start + field.bits.unwrap_or(field.size * 8);
+        }
+        match (layout.kind, layout.flexible) {
+            (RecordKind::Union, _) => {
+                let size = layout.size.to_string();
+                seq.push(("value".to_string(), vec![("size", size)]));
+            }
+            (RecordKind::Struct, None) => pad(&mut seq, &mut pos, layout.size * 8),
+            (RecordKind::Struct, Some(_)) => {}
+        }
+
+        let mut out = String::new();
+        for (section, attributes) in [("seq", &seq), ("instances", &instances)] {
+            if attributes.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "{}:", section);
+            for (name, attrs) in attributes {
+                let _ = match section {
+                    "seq" => writeln!(out, "  - id: {}", name),
+                    _ => writeln!(out, "  {}:", name),
+                };
+                for (key, value) in attrs {
+                    let _ = writeln!(out, "    {}: {}", key, value);
+                }
+            }
+        }
+        out
+    }
+
+    /// field returns the attributes reading `field`. A nested record
+    /// without a tag is defined as the type `name`.
+    fn field(&mut self, field: &FieldLayout, name: &str) -> Attrs {
+        if let Some(bits) = field.bits {
+            return vec![("type", format!("b{}", bits))];
+        }
+        match &field.ty {
+            FieldType::Array(..) => {
+                let (element, count) = field.ty.innermost();
+                let size = field.size.checked_div(count).unwrap_or(0);
+                if let FieldType::Scalar(CType::Char | CType::SChar | CType::UChar) = element {
+                    return vec![("size", field.size.to_string())];
+                }
+                let mut attrs = self.value(element, size, field, name);
+                attrs.push(("repeat", "expr".to_string()));
+                attrs.push(("repeat-expr", count.to_string()));
+                attrs
+            }
+            FieldType::FlexibleArray(element) => {
+                let size = match &field.nested {
+                    Some(nested) => nested.size,
+                    None => self.target.data_model().size(element.scalar()),
+                };
+                let mut attrs = self.value(element, size, field, name);
+                attrs.push(("repeat", "eos".to_string()));
+                attrs
+            }
+            ty => self.value(ty, field.size, field, name),
+        }
+    }
+
+    /// value returns the attributes reading one value of `ty`, which
+    /// takes `size` bytes.
+    fn value(&mut self, ty: &FieldType, size: usize, field: &FieldLayout, name: &str) -> Attrs {
+        let raw = || vec![("size", size.to_string())];
+        match ty {
+            FieldType::Scalar(CType::Float) | FieldType::Scalar(CType::Double) => match size {
+                4 | 8 => vec![("type", format!("f{}", size))],
+                _ => raw(),
+            },
+            FieldType::Scalar(ty) => match self.integer(size, self.target.is_signed(*ty)) {
+                Some(ty) => vec![("type", ty)],
+                None => raw(),
+            },
+            FieldType::Pointer(_) => match self.integer(size, false) {
+                Some(ty) => vec![("type", ty)],
+                None => raw(),
+            },
+            FieldType::Enum(e) => {
+                let signed = e.values.iter().any(|&(_, value)| value < 0);
+                let ty = match self.integer(size, signed) {
+                    Some(ty) => ty,
+                    None => return raw(),
+                };
+                let id = kaitai_id(&e.name);
+                if !self.enums.iter().any(|(name, _)| *name == id) {
+                    let mut body = String::new();
+                    for (name, value) in &e.values {
+                        let _ = writeln!(body, "    {}: {}", value, kaitai_id(name));
+                    }
+                    self.enums.push((id.clone(), body));
+                }
+                vec![("type", ty), ("enum", id)]
+            }
+            FieldType::Record(_) | FieldType::Layout(_) => {
+                let nested = match &field.nested {
+                    Some(nested) => nested,
+                    None => return raw(),
+                };
+                let id = match nested.name.as_str() {
+                    "" => name.to_string(),
+                    tag => kaitai_id(tag),
+                };
+                if !self.types.iter().any(|(name, _)| *name == id) {
+                    // Reserve the name before recursing into a cycle.
+                    self.types.push((id.clone(), String::new()));
+                    let body = self.record(nested, &id);
+                    if let Some(ty) = self.types.iter_mut().find(|(name, _)| *name == id) {
+                        ty.1 = body;
+                    }
+                }
+                match nested.kind {
+                    RecordKind::Struct => vec![("type", id)],
+                    RecordKind::Union => vec![("type", id), ("size", size.to_string())],
+                }
+            }
+            FieldType::Array(..) | FieldType::FlexibleArray(_) => raw(),
+        }
+    }
+
+    /// integer returns the Kaitai integer type of `size` bytes, if it has
+    /// one in the byte order of the target.
+    fn integer(&self, size: usize, signed: bool) -> Option<String> {
+        let sign = if signed { 's' } else { 'u' };
+        match size {
+            1 | 2 => Some(format!("{}{}", sign, size)),
+            4 | 8 if self.target.endianness != Endianness::PdpMiddle => {
+                Some(format!("{}{}", sign, size))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// padding returns the attributes skipping from bit `from` to bit `to`:
+/// bits up to the next byte, whole bytes, then the bits into the last
+/// byte.
+fn padding(mut from: usize, to: usize) -> Vec<Attrs> {
+    let mut attrs = Vec::new();
+    if from >= to {
+        return attrs;
+    }
+    if !from.is_multiple_of(8) {
+        let stop = to.min(from.next_multiple_of(8));
+        attrs.push(vec![("type", format!("b{}", stop - from))]);
+        from = stop;
+    }
+    if to / 8 * 8 > from {
+        attrs.push(vec![("size", ((to / 8 * 8 - from) / 8).to_string())]);
+        from = to / 8 * 8;
+    }
+    if to > from {
+        attrs.push(vec![("type", format!("b{}", to - from))]);
+    }
+    attrs
+}
+
+/// has_bitfields reports whether the record or one nested in it has
+/// bit-fields.
+fn has_bitfields(layout: &Layout) -> bool {
+    layout.fields.iter().chain(&layout.bases).any(|field| {
+        field.bits.is_some_and(|bits| bits > 0)
+            || field.nested.as_deref().is_some_and(has_bitfields)
+    })
+}
+
+/// kaitai_id spells a C identifier as a Kaitai one, which is lower case
+/// and starts with a letter.
+fn kaitai_id(name: &str) -> String {
+    let mut id = String::new();
+    let mut lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && lower {
+            id.push('_');
+        }
+        lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        match c {
+            'a'..='z' | '0'..='9' | '_' => id.push(c),
+            'A'..='Z' => id.push(c.to_ascii_lowercase()),
+            _ => id.push('_'),
+        }
+    }
+    match id.chars().next() {
+        Some('a'..='z') => id,
+        _ => format!("f{}", id),
+    }
+}
+
+/// indent indents every line of `text` by `n` spaces.
+fn indent(text: &str, n: usize) -> String {
+    text.lines()
+        .map(|line| format!("{:n$}{}\n", "", line, n = n))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{Enum, Record};
+    use crate::Platform;
+
+    #[test]
+    fn test_kaitai_struct() {
+        assert_eq!(kaitai_id("fooBar"), "foo_bar");
+        assert_eq!(kaitai_id("_x"), "f_x");
+        assert_eq!(
+            padding(3, 40),
+            [
+                vec![("type", "b5".to_string())],
+                vec![("size", "4".to_string())]
+            ]
+        );
+
+        let point = Record::new("point")
+            .field("x", CType::Short)
+            .field("y", CType::Short);
+        let record = Record::new("packet")
+            .field("kind", Enum::new("Kind").value("PING", 1).value("PONG", 2))
+            .bitfield("urgent", CType::UInt, 1)
+            .bitfield("level", CType::UInt, 3)
+            .field("at", point)
+            .field("tags", FieldType::array(CType::Int, 2))
+            .field("data", FieldType::flexible(CType::UChar));
+        let target = Platform::SolarisSparc64.target();
+        let ksy = record.layout(target.abi).unwrap().kaitai_struct(target);
+        assert_eq!(
+            ksy,
+            "meta:
+  id: packet
+  endian: be
+  bit-endian: be
+seq:
+  - id: kind
+    type: u4
+    enum: kind
+  - id: urgent
+    type: b1
+  - id: level
+    type: b3
+  - id: padding0
+    type: b4
+  - id: padding1
+    size: 1
+  - id: at
+    type: point
+  - id: padding2
+    size: 2
+  - id: tags
+    type: s4
+    repeat: expr
+    repeat-expr: 2
+  - id: data
+    type: u1
+    repeat: eos
+types:
+  point:
+    seq:
+      - id: x
+        type: s2
+      - id: y
+        type: s2
+enums:
+  kind:
+    1: ping
+    2: pong
+"
+        );
+
+        let union = Record::union("u")
+            .field("i", CType::Int)
+            .field("d", CType::Double);
+        let target = Platform::LinuxX86_64.target();
+        let ksy = union.layout(target.abi).unwrap().kaitai_struct(target);
+        assert!(ksy.contains(
+            "seq:\n  - id: value\n    size: 8\ninstances:\n  i:\n    pos: 0\n    type: s4\n"
+        ));
+    }
+}
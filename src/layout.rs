@@ -0,0 +1,1736 @@
+//! C record layout.
+//!
+//! Sizes are only half of what FFI code needs; the other half is where the
+//! compiler puts each field of a struct. A [`Record`] describes the fields
+//! of a struct or union, and [`Record::layout`] computes their offsets, the
+//! padding between them and the size and alignment of the whole under an
+//! [`Abi`].
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::layout::*;
+//! let record = Record::new("entry")
+//!     .field("tag", CType::Char)
+//!     .field("value", CType::Long);
+//!
+//! let layout = record.layout(DataModel::LP64).unwrap();
+//! assert_eq!((layout.size, layout.align), (16, 8));
+//! assert_eq!(layout.fields[1].offset, 8);
+//!
+//! let layout = record.layout(DataModel::ILP32).unwrap();
+//! assert_eq!((layout.size, layout.align), (8, 4));
+//! assert_eq!(layout.fields[1].offset, 4);
+//! ```
+
+use crate::{CType, DataModel, Platform};
+use std::error::Error;
+use std::fmt;
+
+mod cxx;
+mod decode;
+mod diff;
+mod encode;
+mod enums;
+mod hexdump;
+mod holes;
+mod query;
+mod reorder;
+mod sharing;
+
+pub use decode::Value;
+pub use diff::{FieldDiff, LayoutDiff};
+pub use enums::Enum;
+pub use holes::Hole;
+pub use reorder::Reordering;
+pub use sharing::{CacheLine, FalseSharing, Writers};
+
+/// Abi holds the rules a compiler follows when laying out records for a
+/// data model.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::layout::*;
+/// // i386 System V aligns `double` and `long long` to 4 bytes in structs.
+/// let abi = Abi::new(DataModel::ILP32).with_max_align(4);
+/// assert_eq!(abi.align(CType::Double), 4);
+/// assert_eq!(Abi::new(DataModel::ILP32).align(CType::Double), 8);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Abi {
+    model: DataModel,
+    max_align: Option<usize>,
+    bitfields: BitFieldRules,
+    pack: Option<usize>,
+    short_enums: bool,
+    power_align: bool,
+}
+
+impl Abi {
+    /// new returns the ABI of a data model with naturally aligned scalars
+    /// and Itanium bit-field rules.
+    pub fn new(model: DataModel) -> Abi {
+        Abi {
+            model,
+            max_align: None,
+            bitfields: BitFieldRules::Itanium,
+            pack: None,
+            short_enums: false,
+            power_align: false,
+        }
+    }
+
+    /// with_pack sets the packing applied to records without a
+    /// `#pragma pack` of their own, like MSVC's `/Zp` or GCC's
+    /// `-fpack-struct=n`. [`Record::layout`] fails with
+    /// [`LayoutError::Alignment`] unless `n` is a power of two.
+    pub fn with_pack(self, n: usize) -> Abi {
+        Abi {
+            pack: Some(n),
+            ..self
+        }
+    }
+
+    /// pack returns the default packing, if any.
+    pub fn pack(&self) -> Option<usize> {
+        self.pack
+    }
+
+    /// with_bitfields selects the bit-field allocation rules.
+    pub fn with_bitfields(self, bitfields: BitFieldRules) -> Abi {
+        Abi { bitfields, ..self }
+    }
+
+    /// bitfields returns the bit-field allocation rules.
+    pub fn bitfields(&self) -> BitFieldRules {
+        self.bitfields
+    }
+
+    /// with_short_enums stores enums in the smallest integer type holding
+    /// their values, like GCC's `-fshort-enums`.
+    pub fn with_short_enums(self) -> Abi {
+        Abi {
+            short_enums: true,
+            ..self
+        }
+    }
+
+    /// short_enums reports whether enums are stored in the smallest
+    /// integer type holding their values.
+    pub fn short_enums(&self) -> bool {
+        self.short_enums
+    }
+
+    /// with_power_align follows the power alignment rule of AIX: `double`
+    /// aligns to 4 bytes inside records, and so does a member starting
+    /// with one, but a record whose first member starts with a `double`
+    /// aligns to 8.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let abi = Abi::new(DataModel::ILP32).with_power_align();
+    /// let tail = Record::new("t").field("c", CType::Char).field("d", CType::Double);
+    /// let layout = tail.layout(abi).unwrap();
+    /// assert_eq!((layout.fields[1].offset, layout.size, layout.align), (4, 12, 4));
+    /// let head = Record::new("h").field("d", CType::Double).field("c", CType::Char);
+    /// let layout = head.layout(abi).unwrap();
+    /// assert_eq!((layout.size, layout.align), (16, 8));
+    /// ```
+    pub fn with_power_align(self) -> Abi {
+        Abi {
+            power_align: true,
+            ..self
+        }
+    }
+
+    /// power_align reports whether the power alignment rule of AIX applies.
+    pub fn power_align(&self) -> bool {
+        self.power_align
+    }
+
+    /// with_max_align caps the alignment of scalar types, as i386 System V
+    /// (4) and m68k (2) do. [`Record::layout`] fails with
+    /// [`LayoutError::Alignment`] unless `align` is a power of two.
+    pub fn with_max_align(self, align: usize) -> Abi {
+        Abi {
+            max_align: Some(align),
+            ..self
+        }
+    }
+
+    /// max_align returns the cap on the alignment of scalar types, if any.
+    pub fn max_align(&self) -> Option<usize> {
+        self.max_align
+    }
+
+    /// model returns the data model the sizes come from.
+    pub fn model(&self) -> DataModel {
+        self.model
+    }
+
+    /// size returns the size in bytes of a scalar type.
+    pub fn size(&self, ty: CType) -> usize {
+        self.model.size(ty)
+    }
+
+    /// align returns the alignment in bytes of a scalar type inside a record.
+    pub fn align(&self, ty: CType) -> usize {
+        let natural = match (self.power_align, ty) {
+            (true, CType::Double) => self.model.align(ty).min(4),
+            _ => self.model.align(ty),
+        };
+        let natural = natural.max(1);
+        self.max_align
+            .map_or(natural, |max| natural.min(max).max(1))
+    }
+}
+
+impl From<DataModel> for Abi {
+    fn from(model: DataModel) -> Abi {
+        Abi::new(model)
+    }
+}
+
+impl From<Platform> for Abi {
+    fn from(platform: Platform) -> Abi {
+        platform.abi()
+    }
+}
+
+impl Platform {
+    /// abi returns the record layout rules of the platform's C compiler.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Platform::LinuxI686.abi().align(CType::LongLong), 4);
+    /// assert_eq!(Platform::Windows32.abi().align(CType::LongLong), 8);
+    /// ```
+    pub fn abi(self) -> Abi {
+        let abi = Abi::new(self.data_model());
+        match self {
+            Platform::LinuxI686 => abi.with_max_align(4),
+            Platform::Aix32 | Platform::Aix64 => abi.with_power_align(),
+            Platform::Windows32 | Platform::Windows64 | Platform::WindowsAarch64 => {
+                abi.with_bitfields(BitFieldRules::Msvc)
+            }
+            _ => abi,
+        }
+    }
+}
+
+/// FieldType is the type of a record field.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format), defmt(bound()))]
+pub enum FieldType {
+    /// A scalar C type.
+    Scalar(CType),
+    /// A fixed-size array of `n` elements.
+    Array(Box<FieldType>, usize),
+    /// A nested record held by value.
+    Record(Record),
+    /// An enum, stored as its underlying integer type.
+    Enum(Enum),
+    /// A nested record held by value whose layout is already computed. It
+    /// is reused as is, so a header hierarchy is laid out once.
+    Layout(Box<Layout>),
+    /// A pointer to a value of the type. The pointee is not laid out, so it
+    /// can be an incomplete record, like the record holding the pointer.
+    Pointer(Box<FieldType>),
+    /// A flexible array member, `T arr[]`, which must be the last field of
+    /// a struct.
+    FlexibleArray(Box<FieldType>),
+}
+
+impl FieldType {
+    /// array returns the type of an array of `n` elements of `ty`. An array
+    /// of 0 elements is the GNU zero-length array.
+    pub fn array(ty: impl Into<FieldType>, n: usize) -> FieldType {
+        FieldType::Array(Box::new(ty.into()), n)
+    }
+
+    /// flexible returns the type of a flexible array member of `ty`
+    /// elements.
+    pub fn flexible(ty: impl Into<FieldType>) -> FieldType {
+        FieldType::FlexibleArray(Box::new(ty.into()))
+    }
+
+    /// pointer returns the type of a pointer to `ty`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let node = Record::new("node")
+    ///     .field("value", CType::Int)
+    ///     .field("next", FieldType::pointer(Record::new("node")));
+    /// let layout = node.layout(DataModel::LP64).unwrap();
+    /// assert_eq!(layout.offset_of("next"), Some(8));
+    /// assert_eq!(layout.size, 16);
+    /// ```
+    pub fn pointer(ty: impl Into<FieldType>) -> FieldType {
+        FieldType::Pointer(Box::new(ty.into()))
+    }
+
+    /// innermost returns the element type of a possibly multidimensional
+    /// array and the number of elements in all.
+    pub(crate) fn innermost(&self) -> (&FieldType, usize) {
+        match self {
+            FieldType::Array(element, n) => {
+                let (element, count) = element.innermost();
+                (element, count * n)
+            }
+            ty => (ty, 1),
+        }
+    }
+
+    /// starts_with_double reports whether the first scalar of the type,
+    /// looking into arrays and unpacked records, is a `double`.
+    fn starts_with_double(&self) -> bool {
+        match self {
+            FieldType::Scalar(ty) => *ty == CType::Double,
+            FieldType::Array(element, _) => element.starts_with_double(),
+            FieldType::Record(record) => match record.fields.first() {
+                Some(field) => !record.packed && !field.packed && field.ty.starts_with_double(),
+                None => false,
+            },
+            FieldType::Layout(layout) => layout
+                .fields
+                .first()
+                .is_some_and(|field| field.ty.starts_with_double()),
+            _ => false,
+        }
+    }
+
+    /// scalar returns the C type of a scalar element, or `char` for others.
+    pub(crate) fn scalar(&self) -> CType {
+        match self {
+            FieldType::Scalar(ty) => *ty,
+            FieldType::Pointer(_) => CType::Pointer,
+            _ => CType::Char,
+        }
+    }
+}
+
+impl From<CType> for FieldType {
+    fn from(ty: CType) -> FieldType {
+        FieldType::Scalar(ty)
+    }
+}
+
+impl From<Record> for FieldType {
+    fn from(record: Record) -> FieldType {
+        FieldType::Record(record)
+    }
+}
+
+impl From<Enum> for FieldType {
+    fn from(e: Enum) -> FieldType {
+        FieldType::Enum(e)
+    }
+}
+
+impl From<Layout> for FieldType {
+    fn from(layout: Layout) -> FieldType {
+        FieldType::Layout(Box::new(layout))
+    }
+}
+
+/// Field is a named member of a record.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format), defmt(bound()))]
+pub struct Field {
+    /// The field name.
+    pub name: String,
+    /// The field type.
+    pub ty: FieldType,
+    /// The width of a bit-field.
+    pub bits: Option<usize>,
+    /// Whether the field has `__attribute__((packed))`, dropping its
+    /// alignment to 1.
+    pub packed: bool,
+    /// The alignment requested with `_Alignas` or `__attribute__((aligned))`,
+    /// which can only raise the alignment of the field.
+    pub aligned: Option<usize>,
+}
+
+impl Field {
+    /// new returns a field called `name` of type `ty`.
+    pub fn new(name: &str, ty: impl Into<FieldType>) -> Field {
+        Field {
+            name: name.to_string(),
+            ty: ty.into(),
+            bits: None,
+            packed: false,
+            aligned: None,
+        }
+    }
+
+    /// aligned raises the alignment of the field to at least `n` bytes, as
+    /// `_Alignas(n)` does.
+    ///
+    /// An explicit alignment survives `__attribute__((packed))` but is still
+    /// capped by `#pragma pack`. [`Record::layout`] fails with
+    /// [`LayoutError::Alignment`] unless `n` is a power of two.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("s")
+    ///     .field("c", CType::Char)
+    ///     .with_field(Field::new("i", CType::Int).aligned(16));
+    /// let layout = record.layout(DataModel::LP64).unwrap();
+    /// assert_eq!(layout.fields[1].offset, 16);
+    /// assert_eq!((layout.size, layout.align), (32, 16));
+    /// ```
+    pub fn aligned(self, n: usize) -> Field {
+        Field {
+            aligned: Some(n),
+            ..self
+        }
+    }
+
+    /// packed marks the field `__attribute__((packed))`.
+    pub fn packed(self) -> Field {
+        Field {
+            packed: true,
+            ..self
+        }
+    }
+
+    /// bitfield returns a bit-field called `name` of `bits` width declared
+    /// with type `ty`.
+    pub fn bitfield(name: &str, ty: CType, bits: usize) -> Field {
+        Field {
+            bits: Some(bits),
+            ..Field::new(name, ty)
+        }
+    }
+}
+
+/// RecordKind tells structs, whose fields follow each other, from unions,
+/// whose fields overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecordKind {
+    /// `struct`
+    Struct,
+    /// `union`
+    Union,
+}
+
+/// Record describes a C struct or union as its list of fields.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format), defmt(bound()))]
+pub struct Record {
+    /// The struct or union tag.
+    pub name: String,
+    /// Whether the record is a struct or a union.
+    pub kind: RecordKind,
+    /// The fields in declaration order.
+    pub fields: Vec<Field>,
+    /// Whether the record has `__attribute__((packed))`.
+    pub packed: bool,
+    /// The `#pragma pack` in effect for the record, capping the alignment
+    /// of every field.
+    pub pack: Option<usize>,
+    /// The alignment requested with `__attribute__((aligned))` on the
+    /// record, which can only raise its alignment.
+    pub aligned: Option<usize>,
+    /// Whether the record is a C++ class, laid out by the Itanium C++ ABI.
+    pub cxx: bool,
+    /// The non-virtual base classes in declaration order.
+    pub bases: Vec<Record>,
+    /// Whether the class has virtual functions.
+    pub polymorphic: bool,
+}
+
+impl Record {
+    /// new returns an empty struct called `name`.
+    pub fn new(name: &str) -> Record {
+        Record {
+            name: name.to_string(),
+            kind: RecordKind::Struct,
+            fields: Vec::new(),
+            packed: false,
+            pack: None,
+            aligned: None,
+            cxx: false,
+            bases: Vec::new(),
+            polymorphic: false,
+        }
+    }
+
+    /// aligned raises the alignment of the record to at least `n` bytes and
+    /// rounds its size up to match, as `__attribute__((aligned(n)))` on the
+    /// struct does. It is not capped by the record's own packing.
+    /// [`Record::layout`] fails with [`LayoutError::Alignment`] unless `n`
+    /// is a power of two.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let counter = Record::new("counter").field("hits", CType::Long).aligned(64);
+    /// let layout = counter.layout(DataModel::LP64).unwrap();
+    /// assert_eq!((layout.size, layout.align), (64, 64));
+    /// assert_eq!(layout.tail_padding, 56);
+    /// ```
+    pub fn aligned(self, n: usize) -> Record {
+        Record {
+            aligned: Some(n),
+            ..self
+        }
+    }
+
+    /// packed marks the record `__attribute__((packed))`: every field is
+    /// placed at the next free byte, or the next free bit for bit-fields.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let header = Record::new("header")
+    ///     .field("kind", CType::Char)
+    ///     .field("length", CType::UInt)
+    ///     .packed();
+    /// let layout = header.layout(DataModel::LP64).unwrap();
+    /// assert_eq!((layout.size, layout.align), (5, 1));
+    /// assert_eq!(layout.fields[1].offset, 1);
+    /// ```
+    pub fn packed(self) -> Record {
+        Record {
+            packed: true,
+            ..self
+        }
+    }
+
+    /// pack lays the record out under `#pragma pack(n)`, capping the
+    /// alignment of its fields, and with them its own, at `n` bytes.
+    /// [`Record::layout`] fails with [`LayoutError::Alignment`] unless `n`
+    /// is a power of two.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("s")
+    ///     .field("c", CType::Char)
+    ///     .field("d", CType::Double)
+    ///     .field("i", CType::Int)
+    ///     .pack(2);
+    /// let layout = record.layout(Platform::Windows64).unwrap();
+    /// assert_eq!((layout.size, layout.align), (14, 2));
+    /// assert_eq!(layout.fields[1].offset, 2);
+    /// ```
+    pub fn pack(self, n: usize) -> Record {
+        Record {
+            pack: Some(n),
+            ..self
+        }
+    }
+
+    /// with_field appends a field built with [`Field`], for fields carrying
+    /// attributes.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("s")
+    ///     .field("c", CType::Char)
+    ///     .with_field(Field::new("i", CType::Int).packed())
+    ///     .field("s", CType::Short);
+    /// let layout = record.layout(DataModel::LP64).unwrap();
+    /// assert_eq!(layout.fields[1].offset, 1);
+    /// assert_eq!((layout.size, layout.align), (8, 2));
+    /// ```
+    pub fn with_field(mut self, field: Field) -> Record {
+        self.fields.push(field);
+        self
+    }
+
+    /// union returns an empty union called `name`.
+    ///
+    /// Every member of a union starts at offset 0; the union is as large as
+    /// its largest member rounded up to its strictest alignment.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let value = Record::union("value")
+    ///     .field("i", CType::Int)
+    ///     .field("d", CType::Double)
+    ///     .field("s", FieldType::array(CType::Char, 9));
+    /// let layout = value.layout(DataModel::LP64).unwrap();
+    /// assert_eq!((layout.size, layout.align), (16, 8));
+    /// assert!(layout.fields.iter().all(|f| f.offset == 0));
+    /// ```
+    pub fn union(name: &str) -> Record {
+        Record {
+            kind: RecordKind::Union,
+            ..Record::new(name)
+        }
+    }
+
+    /// field appends a field called `name` of type `ty`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let point = Record::new("point").field("x", CType::Int).field("y", CType::Int);
+    /// let line = Record::new("line")
+    ///     .field("from", point.clone())
+    ///     .field("to", point)
+    ///     .field("name", FieldType::array(CType::Char, 6));
+    /// let layout = line.layout(DataModel::LP64).unwrap();
+    /// assert_eq!(layout.size, 24);
+    /// assert_eq!(layout.fields[2].offset, 16);
+    /// assert_eq!(layout.tail_padding, 2);
+    /// ```
+    pub fn field(mut self, name: &str, ty: impl Into<FieldType>) -> Record {
+        self.fields.push(Field::new(name, ty));
+        self
+    }
+
+    /// bitfield appends a bit-field called `name` of `bits` width declared
+    /// with the integer type `ty`. An unnamed bit-field has an empty name, and
+    /// a zero-width one ends the storage unit of the bit-fields before it.
+    ///
+    /// How bit-fields are packed into storage units depends on the
+    /// [`BitFieldRules`] of the ABI.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let flags = Record::new("flags")
+    ///     .bitfield("a", CType::UInt, 3)
+    ///     .bitfield("b", CType::UChar, 4);
+    /// let gcc = flags.layout(Platform::LinuxX86_64).unwrap();
+    /// assert_eq!((gcc.fields[1].offset, gcc.fields[1].bit_offset), (0, 3));
+    /// assert_eq!(gcc.size, 4);
+    /// let msvc = flags.layout(Platform::Windows64).unwrap();
+    /// assert_eq!((msvc.fields[1].offset, msvc.fields[1].bit_offset), (4, 0));
+    /// assert_eq!(msvc.size, 8);
+    /// ```
+    pub fn bitfield(mut self, name: &str, ty: CType, bits: usize) -> Record {
+        self.fields.push(Field::bitfield(name, ty, bits));
+        self
+    }
+
+    /// layout computes the layout of the record under an ABI.
+    pub fn layout(&self, abi: impl Into<Abi>) -> Result<Layout, LayoutError> {
+        let abi = abi.into();
+        check_align(&self.name, abi.pack)?;
+        check_align(&self.name, abi.max_align)?;
+        check_align(&self.name, self.pack)?;
+        check_align(&self.name, self.aligned)?;
+        let last = self.fields.len().saturating_sub(1);
+        for (i, field) in self.fields.iter().enumerate() {
+            check_align(&field.name, field.aligned)?;
+            if let FieldType::FlexibleArray(_) = field.ty {
+                if i != last || i == 0 || self.kind == RecordKind::Union {
+                    return Err(LayoutError::FlexibleArray {
+                        field: field.name.clone(),
+                    });
+                }
+            }
+        }
+        let mut placer = Placer {
+            kind: self.kind,
+            abi,
+            packed: self.packed,
+            pack: self.pack.or(abi.pack),
+            end: 0,
+            align: 1,
+            unit: None,
+            min_size: 0,
+            empty: Vec::new(),
+        };
+        let bases = self.place_bases(&mut placer)?;
+        let fields = self
+            .fields
+            .iter()
+            .map(|field| placer.place(field))
+            .collect::<Result<Vec<_>, _>>()?;
+        let end = placer.end.div_ceil(8);
+        let mut align = placer.align.max(self.aligned.unwrap_or(1));
+        if abi.power_align && !self.packed {
+            let first = self.fields.first().filter(|field| !field.packed);
+            if first.is_some_and(|field| field.ty.starts_with_double()) {
+                let double = abi.model.align(CType::Double);
+                align = align.max(placer.pack.map_or(double, |pack| double.min(pack)));
+            }
+        }
+        let size = round_up(&self.name, end.max(placer.min_size), align)?;
+        if size > MAX_SIZE {
+            return Err(LayoutError::TooLarge {
+                field: self.name.clone(),
+            });
+        }
+        let flexible = match self.fields.last().map(|field| (&field.ty, &field.name)) {
+            Some((FieldType::FlexibleArray(element), name))
+            | Some((FieldType::Array(element, 0), name)) => Some(FlexibleArray {
+                offset: fields[last].offset,
+                stride: type_layout(element, name, &abi)?.0,
+            }),
+            _ => None,
+        };
+        Ok(Layout {
+            name: self.name.clone(),
+            kind: self.kind,
+            size,
+            align,
+            tail_padding: size - end,
+            fields,
+            flexible,
+            bases,
+            dynamic: self.is_dynamic(),
+        })
+    }
+}
+
+/// BitFieldRules selects how a compiler packs bit-fields into storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BitFieldRules {
+    /// GCC and Clang (Itanium/System V): a bit-field starts at the next free
+    /// bit unless it would straddle an aligned unit of its declared type, so
+    /// bit-fields of different types share bytes.
+    Itanium,
+    /// MSVC: adjacent bit-fields share a storage unit only while their
+    /// declared types have the same size and the unit has room; otherwise a
+    /// new unit of the declared type starts.
+    Msvc,
+}
+
+/// Placer walks the fields of a record in order, tracking the end of the
+/// last field in bits.
+struct Placer {
+    kind: RecordKind,
+    abi: Abi,
+    packed: bool,
+    pack: Option<usize>,
+    /// End of the last field in bits.
+    end: usize,
+    align: usize,
+    /// The open MSVC storage unit as (offset, size, used bits).
+    unit: Option<(usize, usize, usize)>,
+    /// The least size of the record in bytes, for C++ classes.
+    min_size: usize,
+    /// The empty class subobjects placed so far, as (offset, class name).
+    empty: Vec<(usize, String)>,
+}
+
+impl Placer {
+    fn place(&mut self, field: &Field) -> Result<FieldLayout, LayoutError> {
+        let (size, align, nested) = type_layout(&field.ty, &field.name, &self.abi)?;
+        let align = match self.abi.power_align && field.ty.starts_with_double() {
+            true => align.min(4),
+            false => align,
+        };
+        let align = match (self.packed || field.packed, field.aligned) {
+            (_, Some(aligned)) => align.max(aligned),
+            (true, None) => 1,
+            (false, None) => align,
+        };
+        let align = self.pack.map_or(align, |pack| align.min(pack));
+        let start = self.end.div_ceil(8);
+        let (offset, bit_offset, end) = match (field.bits, self.kind) {
+            (Some(bits), _) => self.place_bits(field, size, align, bits)?,
+            (None, RecordKind::Struct) => {
+                let offset = round_up(&field.name, start, align)?;
+                let offset = match (&field.ty, &nested) {
+                    (FieldType::Record(_), Some(nested)) | (FieldType::Layout(_), Some(nested)) => {
+                        self.claim(nested, offset, align)
+                    }
+                    _ => offset,
+                };
+                (offset, 0, end_bits(&field.name, offset, size)?)
+            }
+            (None, RecordKind::Union) => (0, 0, end_bits(&field.name, 0, size)?),
+        };
+        if field.bits.is_none() {
+            self.unit = None;
+            self.align = self.align.max(align);
+        }
+        self.end = self.end.max(end);
+        Ok(FieldLayout {
+            name: field.name.clone(),
+            ty: field.ty.clone(),
+            offset,
+            size,
+            align,
+            padding: match self.kind {
+                RecordKind::Struct => offset.saturating_sub(start),
+                RecordKind::Union => 0,
+            },
+            bit_offset,
+            bits: field.bits,
+            nested,
+        })
+    }
+
+    /// place_bits places a bit-field, returning the offset of the storage
+    /// it is read from, its first bit within that storage and the end of
+    /// the field in bits.
+    fn place_bits(
+        &mut self,
+        field: &Field,
+        size: usize,
+        align: usize,
+        bits: usize,
+    ) -> Result<(usize, usize, usize), LayoutError> {
+        let ty = match field.ty {
+            FieldType::Scalar(ty) if ty.is_integer() => ty,
+            _ => {
+                return Err(LayoutError::BitFieldType {
+                    field: field.name.clone(),
+                })
+            }
+        };
+        if bits > size * 8 {
+            return Err(LayoutError::BitFieldWidth {
+                field: field.name.clone(),
+                bits,
+            });
+        }
+        let unit_bits = self.abi.align(ty) * 8;
+        // GCC only keeps bit-fields from straddling units of their type when
+        // neither packing nor #pragma pack is in effect.
+        let straddle = self.packed || field.packed || self.pack.is_some();
+        let from = match field.aligned {
+            Some(_) => {
+                let too_large = || LayoutError::TooLarge {
+                    field: field.name.clone(),
+                };
+                round_up(
+                    &field.name,
+                    self.end,
+                    align.checked_mul(8).ok_or_else(too_large)?,
+                )?
+            }
+            None => self.end,
+        };
+        match (self.abi.bitfields, self.kind) {
+            (BitFieldRules::Itanium, _) => {
+                // Unnamed bit-fields do not affect the record alignment.
+                if !field.name.is_empty() && bits > 0 {
+                    self.align = self.align.max(align);
+                }
+                let pos = match self.kind {
+                    RecordKind::Union => 0,
+                    _ if bits == 0 => return Ok((0, 0, round_up(&field.name, from, unit_bits)?)),
+                    _ if !straddle && from % unit_bits + bits > size * 8 => {
+                        round_up(&field.name, from, unit_bits)?
+                    }
+                    _ => from,
+                };
+                let offset = match straddle {
+                    true => pos / 8,
+                    false => (pos - pos % unit_bits) / 8,
+                };
+                Ok((offset, pos - offset * 8, pos + bits))
+            }
+            (BitFieldRules::Msvc, RecordKind::Union) => {
+                self.align = self.align.max(align);
+                Ok((0, 0, end_bits(&field.name, 0, size)?))
+            }
+            (BitFieldRules::Msvc, RecordKind::Struct) => {
+                if bits == 0 {
+                    self.unit = None;
+                    return Ok((self.end / 8, 0, self.end));
+                }
+                self.align = self.align.max(align);
+                match self.unit {
+                    Some((offset, unit, used)) if unit == size && used + bits <= size * 8 => {
+                        self.unit = Some((offset, unit, used + bits));
+                        Ok((offset, used, end_bits(&field.name, offset, size)?))
+                    }
+                    _ => {
+                        let offset = round_up(&field.name, self.end.div_ceil(8), align)?;
+                        self.unit = Some((offset, size, bits));
+                        Ok((offset, 0, end_bits(&field.name, offset, size)?))
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Layout is the computed layout of a record.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format), defmt(bound()))]
+pub struct Layout {
+    /// The struct or union tag.
+    pub name: String,
+    /// Whether the record is a struct or a union.
+    pub kind: RecordKind,
+    /// `sizeof` the record, including tail padding.
+    pub size: usize,
+    /// `_Alignof` the record.
+    pub align: usize,
+    /// Padding bytes after the last field, or after the largest member of a
+    /// union.
+    pub tail_padding: usize,
+    /// The fields in declaration order.
+    pub fields: Vec<FieldLayout>,
+    /// The trailing flexible or zero-length array, if the record ends in
+    /// one.
+    pub flexible: Option<FlexibleArray>,
+    /// The base class subobjects of a C++ class.
+    pub bases: Vec<FieldLayout>,
+    /// Whether the class has a virtual table pointer, at offset 0.
+    pub dynamic: bool,
+}
+
+/// FlexibleArray is the placement of a trailing `T arr[]` or `T arr[0]`.
+///
+/// The array adds its alignment to the record but nothing to its size, so
+/// `size` of the [`Layout`] is the header before any elements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FlexibleArray {
+    /// `offsetof` the array, which can be less than the size of the record.
+    pub offset: usize,
+    /// The size of one element.
+    pub stride: usize,
+}
+
+impl Layout {
+    /// allocation_size returns the bytes to allocate for the record with
+    /// `n` elements in its trailing array: the larger of the record size
+    /// and the end of the last element.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let msg = Record::new("msg")
+    ///     .field("len", CType::Long)
+    ///     .field("tag", CType::Char)
+    ///     .field("data", FieldType::flexible(CType::Char));
+    /// let layout = msg.layout(DataModel::LP64).unwrap();
+    /// assert_eq!(layout.size, 16);
+    /// assert_eq!(layout.flexible.unwrap().offset, 9);
+    /// assert_eq!(layout.allocation_size(3), 16);
+    /// assert_eq!(layout.allocation_size(10), 19);
+    /// ```
+    pub fn allocation_size(&self, n: usize) -> usize {
+        match self.flexible {
+            Some(array) => self.size.max(array.offset + n * array.stride),
+            None => self.size,
+        }
+    }
+}
+
+impl Layout {
+    /// stride returns the distance between consecutive elements of an
+    /// array of the record: its size rounded up to its alignment, so the
+    /// tail padding is never forgotten.
+    pub fn stride(&self) -> usize {
+        self.size.next_multiple_of(self.align.max(1))
+    }
+
+    /// array_layout returns the layout of an array of `n` records.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let pair = Record::new("pair").field("key", CType::Long).field("flag", CType::Char);
+    /// let layout = pair.layout(DataModel::LP64).unwrap();
+    /// let array = layout.array_layout(10);
+    /// assert_eq!(array.stride, 16);
+    /// assert_eq!(array.size, 160);
+    /// ```
+    pub fn array_layout(&self, n: usize) -> ArrayLayout {
+        ArrayLayout::new(self.stride(), self.align, n)
+    }
+}
+
+/// ArrayLayout is the layout of a C array `T arr[len]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArrayLayout {
+    /// The number of elements.
+    pub len: usize,
+    /// The distance in bytes between consecutive elements.
+    pub stride: usize,
+    /// `sizeof` the array.
+    pub size: usize,
+    /// `_Alignof` the array, which is that of its elements.
+    pub align: usize,
+}
+
+impl ArrayLayout {
+    fn new(stride: usize, align: usize, len: usize) -> ArrayLayout {
+        ArrayLayout {
+            len,
+            stride,
+            size: stride * len,
+            align,
+        }
+    }
+
+    /// offset_of returns the offset of element `i`.
+    pub fn offset_of(&self, i: usize) -> usize {
+        i * self.stride
+    }
+}
+
+impl DataModel {
+    /// array_layout returns the layout of an array of `n` elements of one
+    /// of the types defined in this crate, with natural alignment.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let array = DataModel::LLP64.array_layout::<Long>(16);
+    /// assert_eq!((array.stride, array.size, array.align), (4, 64, 4));
+    /// assert_eq!(array.offset_of(3), 12);
+    /// ```
+    pub fn array_layout<T>(self, n: usize) -> ArrayLayout {
+        let align = self.align_of::<T>();
+        ArrayLayout::new(self.size_of::<T>().next_multiple_of(align.max(1)), align, n)
+    }
+}
+
+impl Abi {
+    /// array_layout returns the layout of an array of `n` elements of a
+    /// scalar type under this ABI.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let array = Platform::LinuxI686.abi().array_layout(CType::Double, 4);
+    /// assert_eq!((array.stride, array.size, array.align), (8, 32, 4));
+    /// ```
+    pub fn array_layout(&self, ty: CType, n: usize) -> ArrayLayout {
+        let align = self.align(ty);
+        ArrayLayout::new(self.size(ty).next_multiple_of(align), align, n)
+    }
+}
+
+/// FieldLayout is the placement of one field within a [`Layout`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format), defmt(bound()))]
+pub struct FieldLayout {
+    /// The field name.
+    pub name: String,
+    /// The field type.
+    pub ty: FieldType,
+    /// `offsetof` the field. For bit-fields, the offset of the storage unit
+    /// holding it.
+    pub offset: usize,
+    /// `sizeof` the field. For bit-fields, the size of the declared type.
+    pub size: usize,
+    /// Alignment of the field.
+    pub align: usize,
+    /// Padding bytes inserted before the field.
+    pub padding: usize,
+    /// For bit-fields, the first bit of the field within the `size` bytes
+    /// at `offset`, counted from the least significant bit on
+    /// little-endian targets and from the most significant on big-endian
+    /// ones. Zero for other fields.
+    pub bit_offset: usize,
+    /// For bit-fields, the width in bits.
+    pub bits: Option<usize>,
+    /// Layout of the record type of the field, or of the array elements.
+    pub nested: Option<Box<Layout>>,
+}
+
+/// LayoutError describes why a record cannot be laid out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LayoutError {
+    /// The data model does not have the type of a field, like `long` on IP16.
+    MissingType {
+        /// The field name.
+        field: String,
+        /// The missing type.
+        ty: CType,
+    },
+    /// A bit-field is declared with a type other than an integer.
+    BitFieldType {
+        /// The field name.
+        field: String,
+    },
+    /// A flexible array member is not the last field of a struct with
+    /// other fields.
+    FlexibleArray {
+        /// The field name.
+        field: String,
+    },
+    /// A bit-field is wider than its declared type.
+    BitFieldWidth {
+        /// The field name.
+        field: String,
+        /// The declared width.
+        bits: usize,
+    },
+    /// The values of an enum do not fit its fixed underlying type, or any
+    /// integer type of the model.
+    EnumRange {
+        /// The enum tag.
+        name: String,
+    },
+    /// A field, or the record up to its end, is larger than [`MAX_SIZE`].
+    TooLarge {
+        /// The field name.
+        field: String,
+    },
+    /// A packing or alignment is not a power of two.
+    Alignment {
+        /// The field it is given to, or the record for its own and the
+        /// ABI's.
+        name: String,
+        /// The packing or alignment.
+        align: usize,
+    },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::MissingType { field, ty } => {
+                write!(f, "field {} has type {:?} which the model lacks", field, ty)
+            }
+            LayoutError::BitFieldType { field } => {
+                write!(f, "bit-field {} does not have an integer type", field)
+            }
+            LayoutError::FlexibleArray { field } => {
+                write!(
+                    f,
+                    "flexible array {} is not the last field of a struct",
+                    field
+                )
+            }
+            LayoutError::BitFieldWidth { field, bits } => {
+                write!(
+                    f,
+                    "bit-field {} is wider than its type ({} bits)",
+                    field, bits
+                )
+            }
+            LayoutError::EnumRange { name } => {
+                write!(f, "enum {} has values outside its underlying type", name)
+            }
+            LayoutError::TooLarge { field } => {
+                write!(f, "field {} makes the record too large", field)
+            }
+            LayoutError::Alignment { name, align } => {
+                write!(
+                    f,
+                    "{} has packing or alignment {} which is not a power of two",
+                    name, align
+                )
+            }
+        }
+    }
+}
+
+impl Error for LayoutError {}
+
+/// MAX_SIZE is the largest size in bytes of a field or record, which keeps
+/// offsets in bits, and rounding them up to a storage unit, within `usize`.
+pub const MAX_SIZE: usize = isize::MAX as usize / 8;
+
+/// end_bits returns the end in bits of `size` bytes at `offset`, or
+/// [`LayoutError::TooLarge`] if it is past [`MAX_SIZE`].
+fn end_bits(field: &str, offset: usize, size: usize) -> Result<usize, LayoutError> {
+    match offset.checked_add(size) {
+        Some(end) if end <= MAX_SIZE => Ok(end * 8),
+        _ => Err(LayoutError::TooLarge {
+            field: field.to_string(),
+        }),
+    }
+}
+
+/// check_align returns [`LayoutError::Alignment`] unless `align`, given to
+/// `name`, is a power of two.
+fn check_align(name: &str, align: Option<usize>) -> Result<(), LayoutError> {
+    match align {
+        Some(align) if !align.is_power_of_two() => Err(LayoutError::Alignment {
+            name: name.to_string(),
+            align,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// round_up rounds `offset` up to a multiple of `align`, or returns
+/// [`LayoutError::TooLarge`] for `name` if that overflows.
+fn round_up(name: &str, offset: usize, align: usize) -> Result<usize, LayoutError> {
+    offset
+        .checked_next_multiple_of(align)
+        .ok_or_else(|| LayoutError::TooLarge {
+            field: name.to_string(),
+        })
+}
+
+/// type_layout returns the size and alignment of a field type, plus the
+/// layout of the record it contains.
+fn type_layout(
+    ty: &FieldType,
+    field: &str,
+    abi: &Abi,
+) -> Result<(usize, usize, Option<Box<Layout>>), LayoutError> {
+    match ty {
+        FieldType::Scalar(ty) => match abi.size(*ty) {
+            0 => Err(LayoutError::MissingType {
+                field: field.to_string(),
+                ty: *ty,
+            }),
+            size => Ok((size, abi.align(*ty), None)),
+        },
+        FieldType::Array(element, n) => {
+            let (size, align, nested) = type_layout(element, field, abi)?;
+            match size.checked_mul(*n) {
+                Some(size) if size <= MAX_SIZE => Ok((size, align, nested)),
+                _ => Err(LayoutError::TooLarge {
+                    field: field.to_string(),
+                }),
+            }
+        }
+        FieldType::FlexibleArray(element) => {
+            let (_, align, nested) = type_layout(element, field, abi)?;
+            Ok((0, align, nested))
+        }
+        FieldType::Record(record) => {
+            let layout = record.layout(*abi)?;
+            Ok((layout.size, layout.align, Some(Box::new(layout))))
+        }
+        FieldType::Enum(e) => {
+            let ty = e.underlying_type(*abi)?;
+            type_layout(&FieldType::Scalar(ty), field, abi)
+        }
+        FieldType::Layout(layout) => Ok((layout.size, layout.align, Some(layout.clone()))),
+        FieldType::Pointer(_) => match abi.size(CType::Pointer) {
+            0 => Err(LayoutError::MissingType {
+                field: field.to_string(),
+                ty: CType::Pointer,
+            }),
+            size => Ok((size, abi.align(CType::Pointer), None)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Long, Pointer};
+
+    fn offsets(layout: &Layout) -> Vec<usize> {
+        layout.fields.iter().map(|f| f.offset).collect()
+    }
+
+    #[test]
+    fn test_padding() {
+        let record = Record::new("s")
+            .field("a", CType::Char)
+            .field("b", CType::Int)
+            .field("c", CType::Short)
+            .field("d", CType::Pointer)
+            .field("e", CType::Char);
+        let layout = record.layout(DataModel::LP64).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 4, 8, 16, 24]);
+        assert_eq!(layout.fields[1].padding, 3);
+        assert_eq!(layout.fields[3].padding, 6);
+        assert_eq!((layout.size, layout.align, layout.tail_padding), (32, 8, 7));
+
+        let layout = record.layout(DataModel::ILP32).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 4, 8, 12, 16]);
+        assert_eq!((layout.size, layout.align, layout.tail_padding), (20, 4, 3));
+    }
+
+    #[test]
+    fn test_i386_alignment() {
+        let record = Record::new("s")
+            .field("a", CType::Int)
+            .field("b", CType::LongLong)
+            .field("c", CType::Double);
+        let layout = record.layout(Platform::LinuxI686).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 4, 12]);
+        assert_eq!((layout.size, layout.align), (20, 4));
+
+        let layout = record.layout(Platform::Windows32).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 8, 16]);
+        assert_eq!((layout.size, layout.align), (24, 8));
+    }
+
+    #[test]
+    fn test_nested_and_arrays() {
+        let inner = Record::new("inner")
+            .field("a", CType::Double)
+            .field("b", CType::Char);
+        let outer = Record::new("outer")
+            .field("c", CType::Char)
+            .field("inner", FieldType::array(inner, 2))
+            .field("d", FieldType::array(CType::Short, 3));
+        let layout = outer.layout(DataModel::LP64).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 8, 40]);
+        assert_eq!(layout.fields[1].size, 32);
+        assert_eq!(layout.fields[1].nested.as_ref().unwrap().size, 16);
+        assert_eq!((layout.size, layout.align), (48, 8));
+    }
+
+    #[test]
+    fn test_reused_layouts() {
+        let header = Record::new("header")
+            .field("len", CType::Short)
+            .field("flags", CType::Int)
+            .packed()
+            .layout(DataModel::LP64)
+            .unwrap();
+        assert_eq!((header.size, header.align), (6, 1));
+        let packet = Record::new("packet")
+            .field("kind", CType::Char)
+            .field("hdr", header.clone())
+            .field("next", FieldType::pointer(Record::new("packet")))
+            .field("prev_hdr", FieldType::pointer(header.clone()));
+        let layout = packet.layout(DataModel::LP64).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 1, 8, 16]);
+        assert_eq!(layout.fields[1].nested.as_deref(), Some(&header));
+        assert_eq!(layout.fields[2].nested, None);
+        assert_eq!(layout.offset_of("hdr.flags"), Some(3));
+        assert_eq!(layout.offset_of("next.kind"), None);
+        assert_eq!((layout.size, layout.align), (24, 8));
+
+        let layout = packet.layout(DataModel::ILP32).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 1, 8, 12]);
+        assert_eq!((layout.size, layout.align), (16, 4));
+
+        let table = Record::new("table").field("rows", FieldType::array(layout, 3));
+        let table = table.layout(DataModel::ILP32).unwrap();
+        assert_eq!(table.size, 48);
+        assert_eq!(table.offset_of("rows[2].hdr.len"), Some(33));
+    }
+
+    #[test]
+    fn test_union() {
+        let value = Record::union("value")
+            .field("c", CType::Char)
+            .field("ll", CType::LongLong)
+            .field("s", FieldType::array(CType::Short, 5));
+        let layout = value.layout(Platform::LinuxI686).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 0, 0]);
+        assert_eq!((layout.size, layout.align, layout.tail_padding), (12, 4, 2));
+        let layout = value.layout(DataModel::LP64).unwrap();
+        assert_eq!((layout.size, layout.align, layout.tail_padding), (16, 8, 6));
+
+        // struct event { int type; union { long l; char c[12]; } u; short s; }
+        let event = Record::new("event")
+            .field("type", CType::Int)
+            .field(
+                "u",
+                Record::union("")
+                    .field("l", CType::Long)
+                    .field("c", FieldType::array(CType::Char, 12)),
+            )
+            .field("s", CType::Short);
+        let layout = event.layout(DataModel::LP64).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 8, 24]);
+        assert_eq!(
+            layout.fields[1].nested.as_ref().unwrap().kind,
+            RecordKind::Union
+        );
+        assert_eq!((layout.size, layout.align), (32, 8));
+        let layout = event.layout(DataModel::LLP64).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 4, 16]);
+        assert_eq!((layout.size, layout.align), (20, 4));
+    }
+
+    fn bit_positions(layout: &Layout) -> Vec<(usize, usize)> {
+        layout
+            .fields
+            .iter()
+            .map(|f| (f.offset, f.bit_offset))
+            .collect()
+    }
+
+    #[test]
+    fn test_itanium_bitfields() {
+        let abi = DataModel::LP64;
+        // Checked against GCC on x86-64.
+        let unnamed = Record::new("a")
+            .field("c", CType::Char)
+            .bitfield("", CType::Int, 4);
+        let layout = unnamed.layout(abi).unwrap();
+        assert_eq!((layout.size, layout.align), (2, 1));
+        let named = Record::new("b")
+            .field("c", CType::Char)
+            .bitfield("x", CType::Int, 4);
+        let layout = named.layout(abi).unwrap();
+        assert_eq!(bit_positions(&layout), vec![(0, 0), (0, 8)]);
+        assert_eq!((layout.size, layout.align), (4, 4));
+        let zero = Record::new("c")
+            .field("c", CType::Char)
+            .bitfield("", CType::Int, 0)
+            .field("d", CType::Char);
+        let layout = zero.layout(abi).unwrap();
+        assert_eq!(layout.fields[2].offset, 4);
+        assert_eq!((layout.size, layout.align), (5, 1));
+        let straddle = Record::new("d")
+            .bitfield("a", CType::Char, 7)
+            .bitfield("b", CType::Char, 2);
+        let layout = straddle.layout(abi).unwrap();
+        assert_eq!(bit_positions(&layout), vec![(0, 0), (1, 0)]);
+        assert_eq!(layout.size, 2);
+        let mixed = Record::new("e")
+            .bitfield("a", CType::Int, 3)
+            .bitfield("b", CType::Char, 4)
+            .bitfield("c", CType::Short, 9);
+        let layout = mixed.layout(abi).unwrap();
+        assert_eq!(bit_positions(&layout), vec![(0, 0), (0, 3), (0, 7)]);
+        assert_eq!((layout.size, layout.align), (4, 4));
+        let wide = Record::new("f")
+            .field("c", CType::Char)
+            .bitfield("x", CType::LongLong, 40);
+        let layout = wide.layout(abi).unwrap();
+        assert_eq!(bit_positions(&layout), vec![(0, 0), (0, 8)]);
+        assert_eq!(layout.size, 8);
+        let union = Record::union("g")
+            .bitfield("a", CType::Int, 3)
+            .field("b", CType::Char);
+        let layout = union.layout(abi).unwrap();
+        assert_eq!((layout.size, layout.align), (4, 4));
+    }
+
+    #[test]
+    fn test_msvc_bitfields() {
+        let abi = Abi::new(DataModel::LLP64).with_bitfields(BitFieldRules::Msvc);
+        let same_size = Record::new("a")
+            .bitfield("a", CType::Int, 3)
+            .bitfield("b", CType::UInt, 4)
+            .bitfield("c", CType::Short, 9);
+        let layout = same_size.layout(abi).unwrap();
+        assert_eq!(bit_positions(&layout), vec![(0, 0), (0, 3), (4, 0)]);
+        assert_eq!((layout.size, layout.align), (8, 4));
+        let full = Record::new("b")
+            .bitfield("a", CType::Char, 7)
+            .bitfield("b", CType::Char, 2)
+            .bitfield("", CType::Char, 0)
+            .bitfield("c", CType::Char, 1)
+            .field("d", CType::Char);
+        let layout = full.layout(abi).unwrap();
+        assert_eq!(
+            bit_positions(&layout),
+            vec![(0, 0), (1, 0), (2, 0), (2, 0), (3, 0)]
+        );
+        assert_eq!(layout.size, 4);
+        let unnamed = Record::new("c")
+            .field("c", CType::Char)
+            .bitfield("", CType::Int, 4);
+        let layout = unnamed.layout(abi).unwrap();
+        assert_eq!((layout.size, layout.align), (8, 4));
+    }
+
+    #[test]
+    fn test_packing() {
+        // Checked against GCC on x86-64.
+        let abi = DataModel::LP64;
+        let ints = |name| {
+            Record::new(name)
+                .field("c", CType::Char)
+                .bitfield("x", CType::Int, 31)
+                .bitfield("y", CType::Int, 3)
+        };
+        let layout = ints("b").pack(1).layout(abi).unwrap();
+        assert_eq!(bit_positions(&layout), vec![(0, 0), (1, 0), (4, 7)]);
+        assert_eq!((layout.size, layout.align), (6, 1));
+        let layout = ints("c").pack(2).layout(abi).unwrap();
+        assert_eq!((layout.size, layout.align), (6, 2));
+        let layout = ints("f").packed().layout(abi).unwrap();
+        assert_eq!((layout.size, layout.align), (6, 1));
+        let layout = ints("unpacked").layout(abi).unwrap();
+        assert_eq!(bit_positions(&layout), vec![(0, 0), (4, 0), (8, 0)]);
+        assert_eq!((layout.size, layout.align), (12, 4));
+
+        let inner = Record::new("")
+            .field("x", CType::Char)
+            .field("y", CType::Int)
+            .packed();
+        let outer = Record::new("i").field("c", CType::Char).field("in", inner);
+        let layout = outer.layout(abi).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 1]);
+        assert_eq!((layout.size, layout.align), (6, 1));
+    }
+
+    #[test]
+    fn test_power_align() {
+        let abi = Platform::Aix32.abi();
+        let head = || {
+            Record::new("head")
+                .field("d", CType::Double)
+                .field("i", CType::Int)
+        };
+        let layout = head().layout(abi).unwrap();
+        assert_eq!((layout.size, layout.align), (16, 8));
+        let outer = Record::new("o").field("i", CType::Int).field("h", head());
+        let layout = outer.layout(abi).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 4]);
+        assert_eq!((layout.size, layout.align), (20, 4));
+        let outer = Record::new("o").field("h", head()).field("c", CType::Char);
+        let layout = outer.layout(abi).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 16]);
+        assert_eq!((layout.size, layout.align), (24, 8));
+        let array = Record::new("a")
+            .field("d", FieldType::array(CType::Double, 2))
+            .field("c", CType::Char);
+        assert_eq!(array.layout(abi).unwrap().size, 24);
+        let long_long = Record::new("l")
+            .field("c", CType::Char)
+            .field("l", CType::LongLong);
+        assert_eq!(long_long.layout(abi).unwrap().size, 16);
+        let aligned = Record::new("a")
+            .field("c", CType::Char)
+            .with_field(Field::new("d", CType::Double).aligned(8));
+        assert_eq!(offsets(&aligned.layout(abi).unwrap()), vec![0, 8]);
+        let packed = head().packed();
+        assert_eq!(packed.layout(abi).unwrap().size, 12);
+        assert_eq!(head().pack(2).layout(abi).unwrap().align, 2);
+        assert_eq!(head().layout(Platform::LinuxPpc64le).unwrap().size, 16);
+    }
+
+    #[test]
+    fn test_default_pack() {
+        let record = Record::new("s")
+            .field("c", CType::Char)
+            .field("l", CType::LongLong);
+        let abi = Abi::new(DataModel::LLP64).with_pack(4);
+        assert_eq!(record.layout(abi).unwrap().size, 12);
+        assert_eq!(record.clone().pack(2).layout(abi).unwrap().size, 10);
+        let abi = abi.with_bitfields(BitFieldRules::Msvc).with_pack(1);
+        let bits = Record::new("b")
+            .field("c", CType::Char)
+            .bitfield("x", CType::Int, 4);
+        let layout = bits.layout(abi).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 1]);
+        assert_eq!(layout.size, 5);
+
+        for n in [0, 3, 12] {
+            let invalid = Err(LayoutError::Alignment {
+                name: "s".to_string(),
+                align: n,
+            });
+            assert_eq!(record.clone().pack(n).layout(DataModel::LP64), invalid);
+            let abi = Abi::new(DataModel::LP64).with_pack(n);
+            assert_eq!(record.layout(abi), invalid);
+        }
+    }
+
+    #[test]
+    fn test_aligned() {
+        // Checked against GCC on x86-64.
+        let abi = DataModel::LP64;
+        let field = |n| Field::new("i", CType::Int).aligned(n);
+        let packed = Record::new("b")
+            .field("c", CType::Char)
+            .with_field(field(8))
+            .packed();
+        let layout = packed.layout(abi).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 8]);
+        assert_eq!((layout.size, layout.align), (16, 8));
+        let pragma = Record::new("c")
+            .field("c", CType::Char)
+            .with_field(field(8))
+            .pack(2);
+        let layout = pragma.layout(abi).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 2]);
+        assert_eq!((layout.size, layout.align), (6, 2));
+
+        let e2 = Record::new("e2")
+            .field("c", CType::Char)
+            .field("i", CType::Int)
+            .aligned(16)
+            .pack(2);
+        let layout = e2.layout(abi).unwrap();
+        assert_eq!((layout.size, layout.align), (16, 16));
+        let e3 = Record::new("e3")
+            .field("c", CType::Char)
+            .field("x", e2)
+            .pack(2);
+        let layout = e3.layout(abi).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 2]);
+        assert_eq!((layout.size, layout.align), (18, 2));
+
+        let f = Record::new("f")
+            .field("c", CType::Char)
+            .field("i", CType::Int)
+            .packed()
+            .aligned(4);
+        let layout = f.layout(abi).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 1]);
+        assert_eq!((layout.size, layout.align), (8, 4));
+
+        let h = Record::new("h")
+            .field("c", CType::Char)
+            .with_field(Field::bitfield("x", CType::Int, 3).aligned(8));
+        let layout = h.layout(abi).unwrap();
+        assert_eq!(bit_positions(&layout), vec![(0, 0), (8, 0)]);
+        assert_eq!((layout.size, layout.align), (16, 8));
+
+        let invalid = |name: &str, n| {
+            Err(LayoutError::Alignment {
+                name: name.to_string(),
+                align: n,
+            })
+        };
+        for n in [0, 3, 24] {
+            let record = Record::new("r").with_field(field(n));
+            assert_eq!(record.layout(abi), invalid("i", n));
+            let record = Record::new("r").field("c", CType::Char).aligned(n);
+            assert_eq!(record.layout(abi), invalid("r", n));
+            let max_align = Abi::new(DataModel::ILP32).with_max_align(n);
+            let record = Record::new("r").field("d", CType::Double);
+            assert_eq!(record.layout(max_align), invalid("r", n));
+        }
+        let max_align = Abi::new(DataModel::ILP32).with_max_align(0);
+        assert_eq!(max_align.array_layout(CType::Double, 2).size, 16);
+    }
+
+    #[test]
+    fn test_flexible_array() {
+        let packet = Record::new("packet")
+            .field("len", CType::Short)
+            .field("data", FieldType::flexible(CType::Double));
+        let layout = packet.layout(DataModel::LP64).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 8]);
+        assert_eq!((layout.size, layout.align), (8, 8));
+        assert_eq!(
+            layout.flexible,
+            Some(FlexibleArray {
+                offset: 8,
+                stride: 8
+            })
+        );
+        assert_eq!(layout.allocation_size(0), 8);
+        assert_eq!(layout.allocation_size(2), 24);
+        let layout = packet.layout(Platform::LinuxI686).unwrap();
+        assert_eq!((layout.size, layout.align), (4, 4));
+
+        let zero = Record::new("zero")
+            .field("a", FieldType::array(CType::Int, 0))
+            .field("c", CType::Char)
+            .field("items", FieldType::array(CType::Short, 0));
+        let layout = zero.layout(DataModel::LP64).unwrap();
+        assert_eq!(offsets(&layout), vec![0, 0, 2]);
+        assert_eq!((layout.size, layout.align), (4, 4));
+        assert_eq!(layout.allocation_size(5), 12);
+
+        let plain = Record::new("plain").field("a", CType::Int);
+        assert_eq!(plain.layout(DataModel::LP64).unwrap().flexible, None);
+    }
+
+    #[test]
+    fn test_flexible_array_errors() {
+        let error = Err(LayoutError::FlexibleArray {
+            field: "data".to_string(),
+        });
+        let middle = Record::new("s")
+            .field("data", FieldType::flexible(CType::Char))
+            .field("len", CType::Int);
+        assert_eq!(middle.layout(DataModel::LP64), error);
+        let alone = Record::new("s").field("data", FieldType::flexible(CType::Char));
+        assert_eq!(alone.layout(DataModel::LP64), error);
+        let union = Record::union("u")
+            .field("len", CType::Int)
+            .field("data", FieldType::flexible(CType::Char));
+        assert_eq!(union.layout(DataModel::LP64), error);
+    }
+
+    #[test]
+    fn test_array_layout() {
+        let array = DataModel::LP64.array_layout::<Pointer>(3);
+        assert_eq!(
+            array,
+            ArrayLayout {
+                len: 3,
+                stride: 8,
+                size: 24,
+                align: 8
+            }
+        );
+        assert_eq!(DataModel::IP16.array_layout::<Long>(3).size, 0);
+
+        let record = Record::new("s")
+            .field("d", CType::Double)
+            .field("c", CType::Char)
+            .aligned(32);
+        let layout = record.layout(DataModel::LP64).unwrap();
+        assert_eq!(layout.stride(), 32);
+        assert_eq!(layout.array_layout(2).offset_of(1), 32);
+        let layout = record.packed().layout(DataModel::LP64).unwrap();
+        assert_eq!(layout.array_layout(4).size, 128);
+    }
+
+    #[test]
+    fn test_bitfield_errors() {
+        let record = Record::new("s").bitfield("f", CType::Double, 3);
+        assert_eq!(
+            record.layout(DataModel::LP64),
+            Err(LayoutError::BitFieldType {
+                field: "f".to_string()
+            })
+        );
+        let record = Record::new("s").bitfield("c", CType::Char, 9);
+        assert_eq!(
+            record.layout(DataModel::LP64),
+            Err(LayoutError::BitFieldWidth {
+                field: "c".to_string(),
+                bits: 9
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let layout = Record::new("empty").layout(DataModel::LP64).unwrap();
+        assert_eq!((layout.size, layout.align), (0, 1));
+    }
+
+    #[test]
+    fn test_missing_type() {
+        let record = Record::new("s").field("l", CType::Long);
+        assert_eq!(
+            record.layout(DataModel::IP16),
+            Err(LayoutError::MissingType {
+                field: "l".to_string(),
+                ty: CType::Long
+            })
+        );
+    }
+
+    #[test]
+    fn test_too_large() {
+        let too_large = Err(LayoutError::TooLarge {
+            field: "a".to_string(),
+        });
+        let huge = FieldType::array(CType::Int, usize::MAX / 2);
+        let record = Record::new("r").field("a", huge.clone());
+        assert_eq!(record.layout(DataModel::LP64), too_large);
+        assert_eq!(
+            Record::union("u").field("a", huge).layout(DataModel::LP64),
+            too_large
+        );
+        let half = FieldType::array(CType::Char, MAX_SIZE / 2 + 1);
+        let record = Record::new("r").field("b", half.clone()).field("a", half);
+        assert_eq!(record.layout(DataModel::LP64), too_large);
+        let record = Record::new("r").field("a", FieldType::array(CType::Char, MAX_SIZE));
+        assert_eq!(record.layout(DataModel::LP64).unwrap().size, MAX_SIZE);
+
+        let record = Record::new("r")
+            .field("c", CType::Char)
+            .with_field(Field::bitfield("a", CType::Int, 3).aligned(1 << 62));
+        assert_eq!(record.layout(DataModel::LP64), too_large);
+        let record = Record::new("a").field("c", CType::Char).aligned(1 << 63);
+        assert_eq!(record.layout(DataModel::LP64), too_large);
+    }
+}
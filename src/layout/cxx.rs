@@ -0,0 +1,250 @@
+//! C++ class layout following the Itanium C++ ABI, as used by GCC and Clang
+//! everywhere but Windows.
+//!
+//! On top of the C rules a class can have a virtual table pointer and base
+//! class subobjects. A polymorphic class without a polymorphic base starts
+//! with the vptr; otherwise its first polymorphic base (the primary base)
+//! goes first and shares it. The other bases follow in declaration order.
+//! Empty bases take no space unless another empty subobject of the same
+//! type is already at that offset, and fields may reuse the tail padding of
+//! a base that is not a POD. Virtual bases are not supported.
+
+use super::{
+    end_bits, round_up, Field, FieldLayout, FieldType, Layout, LayoutError, Placer, Record,
+};
+use crate::CType;
+
+impl Record {
+    /// class returns an empty C++ class called `name`. Unlike an empty C
+    /// struct it takes one byte.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let tag = Record::class("tag");
+    /// let widget = Record::class("widget")
+    ///     .polymorphic()
+    ///     .base(tag)
+    ///     .field("id", CType::Int);
+    /// let layout = widget.layout(DataModel::LP64).unwrap();
+    /// assert!(layout.dynamic);
+    /// assert_eq!(layout.bases[0].offset, 0);
+    /// assert_eq!(layout.offset_of("id"), Some(8));
+    /// assert_eq!(layout.size, 16);
+    /// assert_eq!(widget.layout(DataModel::ILP32).unwrap().size, 8);
+    /// ```
+    pub fn class(name: &str) -> Record {
+        Record {
+            cxx: true,
+            ..Record::new(name)
+        }
+    }
+
+    /// base appends a non-virtual base class, making the record a C++
+    /// class.
+    pub fn base(mut self, base: Record) -> Record {
+        self.cxx = true;
+        self.bases.push(Record { cxx: true, ..base });
+        self
+    }
+
+    /// polymorphic marks the record a C++ class with virtual functions.
+    pub fn polymorphic(self) -> Record {
+        Record {
+            cxx: true,
+            polymorphic: true,
+            ..self
+        }
+    }
+
+    /// is_dynamic reports whether the class has a vptr, of its own or
+    /// inherited.
+    pub(super) fn is_dynamic(&self) -> bool {
+        self.polymorphic || self.bases.iter().any(Record::is_dynamic)
+    }
+
+    /// place_bases places the vptr and the base class subobjects of a C++
+    /// class, returning the bases in declaration order.
+    pub(super) fn place_bases(&self, placer: &mut Placer) -> Result<Vec<FieldLayout>, LayoutError> {
+        if !self.cxx {
+            return Ok(Vec::new());
+        }
+        placer.min_size = 1;
+        let layouts = self
+            .bases
+            .iter()
+            .map(|base| base.layout(placer.abi))
+            .collect::<Result<Vec<_>, _>>()?;
+        let primary = self.bases.iter().position(Record::is_dynamic);
+        if self.is_dynamic() && primary.is_none() {
+            placer.place(&Field::new("", CType::Pointer))?;
+        }
+        let order = primary
+            .into_iter()
+            .chain((0..self.bases.len()).filter(|&i| Some(i) != primary));
+        let mut bases = vec![None; self.bases.len()];
+        for i in order {
+            let layout = &layouts[i];
+            let align = placer
+                .pack
+                .map_or(layout.align, |pack| layout.align.min(pack));
+            let start = placer.end.div_ceil(8);
+            let offset = if layout.is_empty_class() {
+                let offset = match placer.conflicts(layout, 0) {
+                    true => round_up(&self.bases[i].name, start, align)?,
+                    false => 0,
+                };
+                let offset = placer.claim(layout, offset, align);
+                placer.min_size = placer.min_size.max(offset + layout.size);
+                offset
+            } else {
+                let start = round_up(&self.bases[i].name, start, align)?;
+                let offset = placer.claim(layout, start, align);
+                placer.end = end_bits(&self.bases[i].name, offset, layout.data_size())?;
+                offset
+            };
+            placer.align = placer.align.max(align);
+            bases[i] = Some(FieldLayout {
+                name: self.bases[i].name.clone(),
+                ty: FieldType::Record(self.bases[i].clone()),
+                offset,
+                size: layout.size,
+                align,
+                padding: offset.saturating_sub(start),
+                bit_offset: 0,
+                bits: None,
+                nested: Some(Box::new(layout.clone())),
+            });
+        }
+        Ok(bases.into_iter().flatten().collect())
+    }
+}
+
+impl Placer {
+    /// conflicts reports whether placing `layout` at `offset` would put an
+    /// empty subobject where one of the same type already is.
+    fn conflicts(&self, layout: &Layout, offset: usize) -> bool {
+        let mut empty = Vec::new();
+        layout.empty_subobjects(offset, &mut empty);
+        empty.iter().any(|subobject| self.empty.contains(subobject))
+    }
+
+    /// claim places `layout` at the first offset from `offset`, in steps of
+    /// `align`, without an empty subobject conflict and records its empty
+    /// subobjects.
+    pub(super) fn claim(&mut self, layout: &Layout, offset: usize, align: usize) -> usize {
+        let mut offset = offset;
+        while self.conflicts(layout, offset) {
+            offset += align;
+        }
+        layout.empty_subobjects(offset, &mut self.empty);
+        offset
+    }
+}
+
+impl Layout {
+    /// is_empty_class reports whether the record is a class without data,
+    /// which the empty base optimization applies to.
+    fn is_empty_class(&self) -> bool {
+        self.fields.is_empty()
+            && !self.dynamic
+            && self.bases.iter().all(|base| {
+                base.nested
+                    .as_ref()
+                    .is_some_and(|nested| nested.is_empty_class())
+            })
+    }
+
+    /// data_size is the size of the record without tail padding a derived
+    /// class can reuse. The tail padding of a POD is never reused.
+    fn data_size(&self) -> usize {
+        match self.dynamic || !self.bases.is_empty() {
+            true => self.size - self.tail_padding,
+            false => self.size,
+        }
+    }
+
+    /// empty_subobjects appends the empty class subobjects of the record,
+    /// placed at `offset`, as (offset, class name).
+    fn empty_subobjects(&self, offset: usize, out: &mut Vec<(usize, String)>) {
+        if self.is_empty_class() {
+            out.push((offset, self.name.clone()));
+        }
+        let members = self
+            .fields
+            .iter()
+            .filter(|field| matches!(field.ty, FieldType::Record(_) | FieldType::Layout(_)));
+        for member in self.bases.iter().chain(members) {
+            if let Some(nested) = &member.nested {
+                nested.empty_subobjects(offset + member.offset, out);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layout::*;
+    use crate::{CType, DataModel};
+
+    fn base_offsets(layout: &Layout) -> Vec<usize> {
+        layout.bases.iter().map(|base| base.offset).collect()
+    }
+
+    #[test]
+    fn test_empty_classes() {
+        let empty = Record::class("empty").layout(DataModel::LP64).unwrap();
+        assert_eq!((empty.size, empty.align, empty.tail_padding), (1, 1, 1));
+
+        let a = Record::class("a");
+        let b = Record::class("b").base(a.clone()).field("a", a.clone());
+        let layout = b.layout(DataModel::LP64).unwrap();
+        assert_eq!(base_offsets(&layout), vec![0]);
+        assert_eq!(layout.offset_of("a"), Some(1));
+        assert_eq!(layout.size, 2);
+
+        let f = Record::class("f").base(a.clone()).field("x", CType::Int);
+        let g = Record::class("g").base(a).base(f);
+        let layout = g.layout(DataModel::LP64).unwrap();
+        assert_eq!(base_offsets(&layout), vec![0, 4]);
+        assert_eq!(layout.size, 8);
+    }
+
+    #[test]
+    fn test_vptr() {
+        let p = Record::class("p").polymorphic().field("x", CType::Int);
+        let layout = p.layout(DataModel::LP64).unwrap();
+        assert_eq!(layout.offset_of("x"), Some(8));
+        assert_eq!((layout.size, layout.align), (16, 8));
+        let layout = p.layout(DataModel::ILP32).unwrap();
+        assert_eq!(layout.offset_of("x"), Some(4));
+        assert_eq!((layout.size, layout.align), (8, 4));
+
+        // The tail padding of a dynamic base is reused.
+        let d = Record::class("d").base(p.clone()).field("c", CType::Char);
+        let layout = d.layout(DataModel::LP64).unwrap();
+        assert!(layout.dynamic);
+        assert_eq!(layout.offset_of("c"), Some(12));
+        assert_eq!(layout.size, 16);
+
+        // The primary base goes first whatever the declaration order.
+        let a = Record::new("a").field("a", CType::Int);
+        let c = Record::class("c").base(a).base(p).field("c", CType::Int);
+        let layout = c.layout(DataModel::LP64).unwrap();
+        assert_eq!(base_offsets(&layout), vec![12, 0]);
+        assert_eq!(layout.offset_of("c"), Some(16));
+        assert_eq!(layout.size, 24);
+    }
+
+    #[test]
+    fn test_pod_base() {
+        let a = Record::new("a")
+            .field("i", CType::Int)
+            .field("c", CType::Char);
+        let b = Record::class("b").base(a).field("d", CType::Char);
+        let layout = b.layout(DataModel::LP64).unwrap();
+        assert_eq!(layout.offset_of("d"), Some(8));
+        assert_eq!(layout.size, 12);
+    }
+}
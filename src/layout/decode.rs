@@ -0,0 +1,390 @@
+//! Decoding records from target memory.
+
+use super::{FieldLayout, FieldType, Layout};
+use crate::target::{AccessError, TargetModel};
+use crate::{CType, Endianness};
+use std::fmt;
+
+/// Value is a C value decoded from target memory.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format), defmt(bound()))]
+pub enum Value {
+    /// A signed integer, including plain `char` where it is signed.
+    Int(i128),
+    /// An unsigned integer or a pointer.
+    UInt(u128),
+    /// A `_Bool`.
+    Bool(bool),
+    /// A null pointer, whatever its bits on the target.
+    Null,
+    /// A `float` or `double`.
+    Float(f64),
+    /// The elements of an array.
+    Array(Vec<Value>),
+    /// The fields of a struct or union by name, in declaration order.
+    /// Members of anonymous structs and unions and of C++ base classes
+    /// appear as fields of the record holding them.
+    Record(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// field returns the value of the field called `name` of a record.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Record(fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Formats the value like a C initializer with designators.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{}", value),
+            Value::UInt(value) => write!(f, "{}", value),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Null => write!(f, "NULL"),
+            Value::Float(value) => write!(f, "{:?}", value),
+            Value::Array(elements) => {
+                write!(f, "{{")?;
+                for (i, element) in elements.iter().enumerate() {
+                    let sep = if i == 0 { " " } else { ", " };
+                    write!(f, "{}{}", sep, element)?;
+                }
+                write!(f, " }}")
+            }
+            Value::Record(fields) => {
+                write!(f, "{{")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    let sep = if i == 0 { " " } else { ", " };
+                    write!(f, "{}.{} = {}", sep, name, value)?;
+                }
+                write!(f, " }}")
+            }
+        }
+    }
+}
+
+impl Layout {
+    /// decode reads an instance of the record from `bytes`, which hold it
+    /// as the target stores it, and returns a [`Value::Record`].
+    ///
+    /// Unnamed bit-fields are skipped. A trailing flexible array gets the
+    /// elements that fit in `bytes`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("msg")
+    ///     .field("kind", CType::Char)
+    ///     .bitfield("urgent", CType::UInt, 1)
+    ///     .field("len", CType::UShort)
+    ///     .field("data", FieldType::array(CType::Char, 2));
+    /// let layout = record.layout(DataModel::ILP32).unwrap();
+    /// let bytes = [7, 1, 0x34, 0x12, b'h', b'i', 0, 0];
+    /// let value = layout.decode(Platform::LinuxI686, &bytes).unwrap();
+    /// assert_eq!(value.field("kind"), Some(&Value::Int(7)));
+    /// assert_eq!(value.field("urgent"), Some(&Value::UInt(1)));
+    /// assert_eq!(value.field("len"), Some(&Value::UInt(0x1234)));
+    /// assert_eq!(
+    ///     value.field("data"),
+    ///     Some(&Value::Array(vec![Value::Int(104), Value::Int(105)]))
+    /// );
+    /// ```
+    pub fn decode(
+        &self,
+        target: impl Into<TargetModel>,
+        bytes: &[u8],
+    ) -> Result<Value, AccessError> {
+        if bytes.len() < self.size {
+            return Err(AccessError::ShortBuffer {
+                needed: self.size,
+                len: bytes.len(),
+            });
+        }
+        let mut fields = Vec::new();
+        self.decode_fields(&target.into(), bytes, &mut fields)?;
+        Ok(Value::Record(fields))
+    }
+
+    /// decode_fields appends the fields of the record stored at the start
+    /// of `bytes` to `out`.
+    fn decode_fields(
+        &self,
+        target: &TargetModel,
+        bytes: &[u8],
+        out: &mut Vec<(String, Value)>,
+    ) -> Result<(), AccessError> {
+        for base in self.bases.iter() {
+            if let Some(nested) = &base.nested {
+                nested.decode_fields(target, &bytes[base.offset..], out)?;
+            }
+        }
+        for field in self.fields.iter() {
+            let at = &bytes[field.offset..];
+            let value = match (&field.ty, field.bits) {
+                (_, Some(_)) if field.name.is_empty() => continue,
+                (FieldType::Scalar(ty), Some(bits)) => decode_bits(target, field, *ty, bits, at),
+                (_, Some(_)) => continue,
+                (FieldType::Record(_), None) | (FieldType::Layout(_), None)
+                    if field.name.is_empty() =>
+                {
+                    if let Some(nested) = &field.nested {
+                        nested.decode_fields(target, at, out)?;
+                    }
+                    continue;
+                }
+                (FieldType::FlexibleArray(element), None) => {
+                    let stride = self.flexible.map_or(0, |flexible| flexible.stride);
+                    let n = at.len().checked_div(stride).unwrap_or(0);
+                    let elements = (0..n)
+                        .map(|i| decode_type(target, field, element, stride, &at[i * stride..]))
+                        .collect::<Result<_, _>>()?;
+                    Value::Array(elements)
+                }
+                (ty, None) => decode_type(target, field, ty, field.size, at)?,
+            };
+            out.push((field.name.clone(), value));
+        }
+        Ok(())
+    }
+}
+
+/// decode_type decodes a value of type `ty`, the type of `field` or of its
+/// elements, taking `size` bytes at the start of `bytes`.
+pub(super) fn decode_type(
+    target: &TargetModel,
+    field: &FieldLayout,
+    ty: &FieldType,
+    size: usize,
+    bytes: &[u8],
+) -> Result<Value, AccessError> {
+    let bytes = &bytes[..size];
+    Ok(match ty {
+        FieldType::Scalar(CType::Float) => {
+            Value::Float(f32::from_bits(target.load(bytes) as u32) as f64)
+        }
+        FieldType::Scalar(CType::Double) => Value::Float(f64::from_bits(target.load(bytes) as u64)),
+        FieldType::Scalar(CType::Bool) => Value::Bool(target.load(bytes) != 0),
+        FieldType::Scalar(CType::Pointer) | FieldType::Pointer(_) => pointer(target, bytes),
+        FieldType::Scalar(ty) => integer(target.read_as(*ty, bytes)?, target.is_signed(*ty)),
+        FieldType::Enum(e) => {
+            let signed = e.values.iter().any(|&(_, value)| value < 0);
+            integer(sign_extend(target.load(bytes), size * 8, signed), signed)
+        }
+        FieldType::Array(element, n) => {
+            let stride = size.checked_div(*n).unwrap_or(0);
+            let elements = (0..*n)
+                .map(|i| decode_type(target, field, element, stride, &bytes[i * stride..]))
+                .collect::<Result<_, _>>()?;
+            Value::Array(elements)
+        }
+        FieldType::FlexibleArray(_) => Value::Array(Vec::new()),
+        FieldType::Record(_) | FieldType::Layout(_) => {
+            let mut fields = Vec::new();
+            if let Some(nested) = &field.nested {
+                nested.decode_fields(target, bytes, &mut fields)?;
+            }
+            Value::Record(fields)
+        }
+    })
+}
+
+/// decode_bits extracts a bit-field from the bytes starting at its
+/// offset.
+pub(super) fn decode_bits(
+    target: &TargetModel,
+    field: &FieldLayout,
+    ty: CType,
+    bits: usize,
+    bytes: &[u8],
+) -> Value {
+    let (n, shift) = bit_span(target, field.bit_offset, bits);
+    let value = (load_span(target, &bytes[..n]) >> shift) & ((1 << bits) - 1);
+    match target.is_signed(ty) {
+        true => integer(target.signed_representation.decode(bits, value), true),
+        false => integer(value as i128, false),
+    }
+}
+
+/// bit_span returns the number of bytes a bit-field spans from its offset
+/// and the shift of its lowest bit in those bytes loaded with
+/// [`load_span`].
+pub(super) fn bit_span(target: &TargetModel, bit_offset: usize, bits: usize) -> (usize, usize) {
+    let n = (bit_offset + bits).div_ceil(8);
+    match target.endianness {
+        Endianness::Big => (n, n * 8 - bit_offset - bits),
+        _ => (n, bit_offset),
+    }
+}
+
+/// load_span loads the bytes holding a bit-field as one integer, taking
+/// the first byte as the most significant on big-endian targets and as the
+/// least significant elsewhere.
+pub(super) fn load_span(target: &TargetModel, bytes: &[u8]) -> u128 {
+    let fold = |raw: u128, &byte: &u8| raw << 8 | byte as u128;
+    match target.endianness {
+        Endianness::Big => bytes.iter().fold(0, fold),
+        _ => bytes.iter().rev().fold(0, fold),
+    }
+}
+
+/// sign_extend interprets the low `bits` bits of `value` as a signed
+/// integer when `signed` is set.
+pub(super) fn sign_extend(value: u128, bits: usize, signed: bool) -> i128 {
+    match (signed, bits) {
+        (true, 1..=127) => ((value << (128 - bits)) as i128) >> (128 - bits),
+        _ => value as i128,
+    }
+}
+
+/// pointer decodes a pointer as its address, or as [`Value::Null`] if it
+/// has the bits of a null pointer on the target.
+fn pointer(target: &TargetModel, bytes: &[u8]) -> Value {
+    match target.load(bytes) {
+        raw if target.is_null(raw) => Value::Null,
+        raw => Value::UInt(raw),
+    }
+}
+
+/// integer wraps a decoded integer in the value of its signedness.
+fn integer(value: i128, signed: bool) -> Value {
+    match signed {
+        true => Value::Int(value),
+        false => Value::UInt(value as u128),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{Enum, Field, Record};
+    use crate::{DataModel, Platform};
+
+    #[test]
+    fn test_decode_scalars() {
+        let record = Record::new("s")
+            .field("b", CType::Bool)
+            .field("c", CType::Char)
+            .field("s", CType::Short)
+            .field("f", CType::Float)
+            .field("d", CType::Double)
+            .field("p", CType::Pointer)
+            .field("e", Enum::new("e").value("neg", -1).packed());
+        let layout = record.layout(DataModel::ILP32).unwrap();
+        let mut bytes = vec![1, 0xff, 0xff, 0xfe];
+        bytes.extend_from_slice(&1.5f32.to_be_bytes());
+        bytes.extend_from_slice(&(-0.25f64).to_be_bytes());
+        bytes.extend_from_slice(&[0xc0, 0, 0, 0, 0xfe, 0, 0, 0]);
+        let target = Platform::LinuxMips.target();
+        let value = layout.decode(target, &bytes).unwrap();
+        let expected = Value::Record(vec![
+            ("b".to_string(), Value::Bool(true)),
+            ("c".to_string(), Value::Int(-1)),
+            ("s".to_string(), Value::Int(-2)),
+            ("f".to_string(), Value::Float(1.5)),
+            ("d".to_string(), Value::Float(-0.25)),
+            ("p".to_string(), Value::UInt(0xc000_0000)),
+            ("e".to_string(), Value::Int(-2)),
+        ]);
+        assert_eq!(value, expected);
+        let value = layout
+            .decode(target.with_char_signed(false), &bytes)
+            .unwrap();
+        assert_eq!(value.field("c"), Some(&Value::UInt(255)));
+        assert_eq!(
+            layout.decode(target, &bytes[..20]),
+            Err(AccessError::ShortBuffer {
+                needed: 24,
+                len: 20
+            })
+        );
+    }
+
+    #[test]
+    fn test_null_pointers() {
+        let record = Record::new("node")
+            .field("next", FieldType::pointer(Record::new("node")))
+            .field("data", CType::Pointer);
+        let layout = record.layout(DataModel::IP16).unwrap();
+        let target = TargetModel::new(DataModel::IP16);
+        let value = layout.decode(target, &[0, 0, 0xff, 0xff]).unwrap();
+        assert_eq!(value.to_string(), "{ .next = NULL, .data = 65535 }");
+
+        let target = target.with_null_pointer(0xffff);
+        let value = layout.decode(target, &[0, 0, 0xff, 0xff]).unwrap();
+        assert_eq!(value.field("next"), Some(&Value::UInt(0)));
+        assert_eq!(value.field("data"), Some(&Value::Null));
+        let bytes = layout.encode(target, &value).unwrap();
+        assert_eq!(bytes, [0, 0, 0xff, 0xff]);
+        let dump = layout.hexdump(target, &bytes).unwrap();
+        assert!(dump.contains("0002  ff ff                    data = NULL\n"));
+    }
+
+    #[test]
+    fn test_decode_nested() {
+        let point = Record::new("point")
+            .field("x", CType::Short)
+            .field("y", CType::Short);
+        let record = Record::new("shape")
+            .field("origin", point.clone())
+            .with_field(Field::new(
+                "",
+                Record::union("")
+                    .field("n", CType::Int)
+                    .field("u", CType::UInt),
+            ))
+            .field("pts", FieldType::array(point, 2))
+            .field("tail", FieldType::flexible(CType::UChar));
+        let layout = record.layout(DataModel::LP64).unwrap();
+        let bytes = [
+            1, 0, 2, 0, 0xff, 0xff, 0xff, 0xff, 3, 0, 4, 0, 5, 0, 6, 0, 9, 8,
+        ];
+        let value = layout.decode(DataModel::LP64, &bytes).unwrap();
+        let point = |x, y| {
+            Value::Record(vec![
+                ("x".to_string(), Value::Int(x)),
+                ("y".to_string(), Value::Int(y)),
+            ])
+        };
+        assert_eq!(value.field("origin"), Some(&point(1, 2)));
+        assert_eq!(value.field("n"), Some(&Value::Int(-1)));
+        assert_eq!(value.field("u"), Some(&Value::UInt(0xffff_ffff)));
+        assert_eq!(
+            value.field("pts"),
+            Some(&Value::Array(vec![point(3, 4), point(5, 6)]))
+        );
+        assert_eq!(
+            value.field("tail"),
+            Some(&Value::Array(vec![Value::UInt(9), Value::UInt(8)]))
+        );
+    }
+
+    #[test]
+    fn test_decode_bitfields() {
+        let record = Record::new("flags")
+            .bitfield("a", CType::Int, 3)
+            .bitfield("", CType::Int, 2)
+            .bitfield("b", CType::UInt, 7)
+            .bitfield("c", CType::Int, 4);
+        let layout = record.layout(DataModel::ILP32).unwrap();
+        // a = -3 (0b101), b = 0x55, c = 6
+        let raw: u32 = 0b101 | 0x55 << 5 | 6 << 12;
+        let little = layout.decode(DataModel::ILP32, &raw.to_le_bytes()).unwrap();
+        // Big-endian targets allocate from the most significant bit.
+        let raw: u32 = 0b101 << 29 | 0x55 << 20 | 6 << 16;
+        let target = TargetModel::new(DataModel::ILP32).with_endianness(Endianness::Big);
+        let big = layout.decode(target, &raw.to_be_bytes()).unwrap();
+        for value in [little, big] {
+            assert_eq!(value.field("a"), Some(&Value::Int(-3)));
+            assert_eq!(value.field("b"), Some(&Value::UInt(0x55)));
+            assert_eq!(value.field("c"), Some(&Value::Int(6)));
+            assert_eq!(value.field(""), None);
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Differences between two layouts of the same record.
+
+use super::{type_layout, Abi, FieldLayout, FieldType, Layout};
+use std::fmt;
+
+/// LayoutDiff reports how the layout of a record changes between two
+/// ABIs, typically the same struct compiled for ILP32 and for LP64.
+///
+/// Pairs hold the value in the first layout and then in the second.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutDiff {
+    /// The struct or union tag.
+    pub name: String,
+    /// `sizeof` the record.
+    pub size: (usize, usize),
+    /// `_Alignof` the record.
+    pub align: (usize, usize),
+    /// Padding after the last field.
+    pub tail_padding: (usize, usize),
+    /// The fields whose placement changes, in declaration order.
+    pub fields: Vec<FieldDiff>,
+    /// Fields only the first layout has.
+    pub removed: Vec<String>,
+    /// Fields only the second layout has.
+    pub added: Vec<String>,
+}
+
+/// FieldDiff reports how the placement of one field changes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDiff {
+    /// The field name.
+    pub name: String,
+    /// `offsetof` the field.
+    pub offset: (usize, usize),
+    /// `sizeof` the field.
+    pub size: (usize, usize),
+    /// Padding inserted before the field.
+    pub padding: (usize, usize),
+    /// The first bit of a bit-field within its storage.
+    pub bit_offset: (usize, usize),
+    /// Changes inside the record type of the field.
+    pub nested: Option<Box<LayoutDiff>>,
+}
+
+impl FieldDiff {
+    fn new(a: &FieldLayout, b: &FieldLayout) -> Option<FieldDiff> {
+        let nested = match (&a.nested, &b.nested) {
+            (Some(a), Some(b)) => Some(Box::new(a.diff(b))).filter(|d| !d.is_empty()),
+            _ => None,
+        };
+        let diff = FieldDiff {
+            name: a.name.clone(),
+            offset: (a.offset, b.offset),
+            size: (a.size, b.size),
+            padding: (a.padding, b.padding),
+            bit_offset: (a.bit_offset, b.bit_offset),
+            nested,
+        };
+        let same = |(a, b): (usize, usize)| a == b;
+        let unchanged = same(diff.offset)
+            && same(diff.size)
+            && same(diff.padding)
+            && same(diff.bit_offset)
+            && diff.nested.is_none();
+        (!unchanged).then_some(diff)
+    }
+}
+
+impl LayoutDiff {
+    /// is_empty reports whether both layouts are identical.
+    pub fn is_empty(&self) -> bool {
+        self.size.0 == self.size.1
+            && self.align.0 == self.align.1
+            && self.tail_padding.0 == self.tail_padding.1
+            && self.fields.is_empty()
+            && self.removed.is_empty()
+            && self.added.is_empty()
+    }
+}
+
+impl Layout {
+    /// diff compares this layout with another layout of the same record,
+    /// matching fields by name.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("entry")
+    ///     .field("tag", CType::Char)
+    ///     .field("value", CType::Long)
+    ///     .field("count", CType::Int);
+    /// let ilp32 = record.layout(DataModel::ILP32).unwrap();
+    /// let lp64 = record.layout(DataModel::LP64).unwrap();
+    /// let diff = ilp32.diff(&lp64);
+    /// assert_eq!(diff.size, (12, 24));
+    /// assert_eq!(diff.fields.len(), 2);
+    /// assert_eq!(diff.fields[0].name, "value");
+    /// assert_eq!(diff.fields[0].padding, (3, 7));
+    /// assert_eq!(diff.fields[1].offset, (8, 16));
+    /// ```
+    pub fn diff(&self, other: &Layout) -> LayoutDiff {
+        let missing = |layout: &Layout, name: &str| !layout.fields.iter().any(|f| f.name == name);
+        LayoutDiff {
+            name: self.name.clone(),
+            size: (self.size, other.size),
+            align: (self.align, other.align),
+            tail_padding: (self.tail_padding, other.tail_padding),
+            fields: self
+                .fields
+                .iter()
+                .filter_map(|a| {
+                    let b = other.fields.iter().find(|b| b.name == a.name)?;
+                    FieldDiff::new(a, b)
+                })
+                .collect(),
+            removed: self
+                .fields
+                .iter()
+                .filter(|f| missing(other, &f.name))
+                .map(|f| f.name.clone())
+                .collect(),
+            added: other
+                .fields
+                .iter()
+                .filter(|f| missing(self, &f.name))
+                .map(|f| f.name.clone())
+                .collect(),
+        }
+    }
+}
+
+impl Layout {
+    /// is_wire_compatible reports whether this layout and another put the
+    /// same bytes in the same places: equal sizes, and fields in the same
+    /// order at equal offsets with equal widths, down into nested records.
+    /// Names and alignments may differ, as neither shows in the bytes.
+    ///
+    /// It ignores byte order, which [`TargetModel::is_wire_compatible`]
+    /// also checks.
+    ///
+    /// [`TargetModel::is_wire_compatible`]: crate::TargetModel::is_wire_compatible
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let sample = Record::new("sample")
+    ///     .field("time", CType::LongLong)
+    ///     .field("value", CType::Int)
+    ///     .field("flags", CType::UShort);
+    /// let mcu = sample.layout(DataModel::ILP32).unwrap();
+    /// let host = sample.layout(DataModel::LP64).unwrap();
+    /// assert!(mcu.is_wire_compatible(&host));
+    ///
+    /// let sample = sample.field("count", CType::Long);
+    /// let mcu = sample.layout(DataModel::ILP32).unwrap();
+    /// let host = sample.layout(DataModel::LP64).unwrap();
+    /// assert!(!mcu.is_wire_compatible(&host));
+    /// ```
+    pub fn is_wire_compatible(&self, other: &Layout) -> bool {
+        let fields = |a: &[FieldLayout], b: &[FieldLayout]| {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_placement(a, b))
+        };
+        self.size == other.size
+            && self.dynamic == other.dynamic
+            && self.flexible == other.flexible
+            && fields(&self.bases, &other.bases)
+            && fields(&self.fields, &other.fields)
+    }
+
+    /// byte_order_matters reports whether the bytes of the record depend
+    /// on the byte order of the target: whether it holds a bit-field or a
+    /// scalar wider than a byte.
+    pub(crate) fn byte_order_matters(&self, abi: &Abi) -> bool {
+        self.dynamic
+            || self.bases.iter().chain(&self.fields).any(|f| {
+                f.bits.is_some()
+                    || match &f.nested {
+                        Some(nested) => nested.byte_order_matters(abi),
+                        None => element_size(&f.ty, abi) > 1,
+                    }
+            })
+    }
+}
+
+/// same_placement reports whether two fields take the same bytes.
+fn same_placement(a: &FieldLayout, b: &FieldLayout) -> bool {
+    let nested = match (&a.nested, &b.nested) {
+        (Some(a), Some(b)) => a.is_wire_compatible(b),
+        (None, None) => true,
+        _ => false,
+    };
+    a.offset == b.offset
+        && a.size == b.size
+        && a.bit_offset == b.bit_offset
+        && a.bits == b.bits
+        && nested
+}
+
+/// element_size returns the size of the scalars an array or scalar field
+/// is made of.
+fn element_size(ty: &FieldType, abi: &Abi) -> usize {
+    match ty {
+        FieldType::Array(element, _) | FieldType::FlexibleArray(element) => {
+            element_size(element, abi)
+        }
+        ty => type_layout(ty, "", abi).map_or(0, |(size, _, _)| size),
+    }
+}
+
+fn change(f: &mut fmt::Formatter<'_>, what: &str, (a, b): (usize, usize)) -> fmt::Result {
+    match a == b {
+        true => Ok(()),
+        false => write!(f, " {} {} -> {}", what, a, b),
+    }
+}
+
+impl LayoutDiff {
+    fn write(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        write!(f, "{:indent$}{}:", "", self.name, indent = indent)?;
+        change(f, "size", self.size)?;
+        change(f, "align", self.align)?;
+        change(f, "tail padding", self.tail_padding)?;
+        writeln!(f)?;
+        for field in &self.fields {
+            write!(f, "{:indent$}  {}:", "", field.name, indent = indent)?;
+            change(f, "offset", field.offset)?;
+            change(f, "size", field.size)?;
+            change(f, "padding", field.padding)?;
+            change(f, "bit offset", field.bit_offset)?;
+            writeln!(f)?;
+            if let Some(nested) = &field.nested {
+                nested.write(f, indent + 4)?;
+            }
+        }
+        for name in &self.removed {
+            writeln!(f, "{:indent$}  {}: removed", "", name, indent = indent)?;
+        }
+        for name in &self.added {
+            writeln!(f, "{:indent$}  {}: added", "", name, indent = indent)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for LayoutDiff {
+    /// Formats the report with one line per changed field.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layout::*;
+    use crate::{CType, DataModel};
+
+    #[test]
+    fn test_diff() {
+        let inner = Record::new("inner")
+            .field("p", CType::Pointer)
+            .field("n", CType::Int);
+        let record = Record::new("outer")
+            .field("a", CType::Int)
+            .field("inner", inner)
+            .field("b", CType::Char);
+        let ilp32 = record.layout(DataModel::ILP32).unwrap();
+        let lp64 = record.layout(DataModel::LP64).unwrap();
+        let diff = ilp32.diff(&lp64);
+        assert_eq!(diff.size, (16, 32));
+        assert_eq!(diff.align, (4, 8));
+        assert_eq!(diff.tail_padding, (3, 7));
+        assert_eq!(diff.fields.len(), 2);
+        let inner = diff.fields[0].nested.as_ref().unwrap();
+        assert_eq!(inner.size, (8, 16));
+        assert_eq!(inner.fields[0].name, "p");
+        assert_eq!(inner.fields[0].size, (4, 8));
+        assert_eq!(inner.fields[1].name, "n");
+        assert_eq!(inner.fields[1].offset, (4, 8));
+        assert_eq!(
+            diff.to_string(),
+            "outer: size 16 -> 32 align 4 -> 8 tail padding 3 -> 7\n  \
+             inner: offset 4 -> 8 size 8 -> 16 padding 0 -> 4\n    \
+             inner: size 8 -> 16 align 4 -> 8 tail padding 0 -> 4\n      \
+             p: size 4 -> 8\n      \
+             n: offset 4 -> 8\n  \
+             b: offset 12 -> 24\n"
+        );
+        assert!(ilp32.diff(&ilp32).is_empty());
+    }
+
+    #[test]
+    fn test_added_removed() {
+        let a = Record::new("s")
+            .field("x", CType::Int)
+            .field("y", CType::Int);
+        let b = Record::new("s")
+            .field("x", CType::Int)
+            .field("z", CType::Int);
+        let diff = a
+            .layout(DataModel::LP64)
+            .unwrap()
+            .diff(&b.layout(DataModel::LP64).unwrap());
+        assert!(diff.fields.is_empty());
+        assert_eq!(diff.removed, vec!["y".to_string()]);
+        assert_eq!(diff.added, vec!["z".to_string()]);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn test_wire_compatible() {
+        let header = Record::new("header")
+            .field("magic", FieldType::array(CType::UChar, 4))
+            .field("length", CType::Int);
+        let record = Record::new("frame")
+            .field("header", header)
+            .bitfield("kind", CType::UInt, 4)
+            .field("data", FieldType::flexible(CType::Short));
+        let ilp32 = record.layout(DataModel::ILP32).unwrap();
+        let lp64 = record.layout(DataModel::LP64).unwrap();
+        assert!(ilp32.is_wire_compatible(&lp64));
+        let llp64 = record.layout(Abi::new(DataModel::LLP64).with_bitfields(BitFieldRules::Msvc));
+        assert!(!ilp32.is_wire_compatible(&llp64.unwrap()));
+        let packed = record.clone().packed().layout(DataModel::ILP32).unwrap();
+        assert!(!ilp32.is_wire_compatible(&packed));
+        let renamed = Record::new("other")
+            .field(
+                "h",
+                Record::new("h")
+                    .field("m", FieldType::array(CType::Char, 4))
+                    .field("n", CType::UInt),
+            )
+            .bitfield("k", CType::Int, 4)
+            .field("d", FieldType::flexible(CType::UShort));
+        assert!(ilp32.is_wire_compatible(&renamed.layout(DataModel::LP64).unwrap()));
+        let abi = Abi::new(DataModel::ILP32);
+        assert!(ilp32.byte_order_matters(&abi));
+        let text = Record::new("text")
+            .field("len", CType::UChar)
+            .field("bytes", FieldType::array(CType::Char, 15));
+        assert!(!text.layout(abi).unwrap().byte_order_matters(&abi));
+    }
+}
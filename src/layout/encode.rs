@@ -0,0 +1,387 @@
+//! Encoding records into target memory.
+
+use super::decode::{bit_span, load_span, Value};
+use super::{FieldLayout, FieldType, Layout};
+use crate::target::{AccessError, TargetModel};
+use crate::{CType, Endianness};
+
+impl Layout {
+    /// encode returns the bytes of an instance of the record holding
+    /// `value`, a [`Value::Record`] as [`Layout::decode`] returns, with the
+    /// padding and the fields missing from `value` zeroed.
+    ///
+    /// Integers are truncated to the width of their field. A trailing
+    /// flexible array makes the result longer than the record.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("frame")
+    ///     .field("kind", CType::UChar)
+    ///     .bitfield("ack", CType::UInt, 1)
+    ///     .field("len", CType::UShort);
+    /// let layout = record.layout(DataModel::ILP32).unwrap();
+    /// let value = Value::Record(vec![
+    ///     ("kind".to_string(), Value::UInt(7)),
+    ///     ("ack".to_string(), Value::Bool(true)),
+    ///     ("len".to_string(), Value::UInt(0x1234)),
+    /// ]);
+    /// let bytes = layout.encode(Platform::LinuxMips, &value).unwrap();
+    /// assert_eq!(bytes, [7, 0x80, 0x12, 0x34]);
+    /// let decoded = layout.decode(Platform::LinuxMips, &bytes).unwrap();
+    /// assert_eq!(decoded.field("ack"), Some(&Value::UInt(1)));
+    /// ```
+    pub fn encode(
+        &self,
+        target: impl Into<TargetModel>,
+        value: &Value,
+    ) -> Result<Vec<u8>, AccessError> {
+        let mut buf = vec![0; self.size + self.flexible_len(value)];
+        self.encode_into(target, value, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// encode_into writes `value` over the instance of the record at the
+    /// start of `buf`, preserving the padding and the fields missing from
+    /// `value`. See [`Layout::encode`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("pair")
+    ///     .field("a", CType::Char)
+    ///     .field("b", CType::Int);
+    /// let layout = record.layout(DataModel::LP64).unwrap();
+    /// let mut buf = [0xaa; 8];
+    /// let value = Value::Record(vec![("b".to_string(), Value::Int(-2))]);
+    /// layout.encode_into(DataModel::LP64, &value, &mut buf).unwrap();
+    /// assert_eq!(buf, [0xaa, 0xaa, 0xaa, 0xaa, 0xfe, 0xff, 0xff, 0xff]);
+    /// ```
+    pub fn encode_into(
+        &self,
+        target: impl Into<TargetModel>,
+        value: &Value,
+        buf: &mut [u8],
+    ) -> Result<(), AccessError> {
+        let needed = self.size + self.flexible_len(value);
+        if buf.len() < needed {
+            return Err(AccessError::ShortBuffer {
+                needed,
+                len: buf.len(),
+            });
+        }
+        self.encode_record(&target.into(), "", value, buf)
+    }
+
+    /// flexible_len returns the number of bytes the elements `value` gives
+    /// the flexible array member take past the end of the record.
+    fn flexible_len(&self, value: &Value) -> usize {
+        let name = match self.fields.last() {
+            Some(field) => &field.name,
+            None => return 0,
+        };
+        match value.field(name) {
+            Some(Value::Array(elements)) => self.allocation_size(elements.len()) - self.size,
+            _ => 0,
+        }
+    }
+
+    /// encode_record writes a record value, the value of the field at
+    /// `path`, at the start of `buf`.
+    fn encode_record(
+        &self,
+        target: &TargetModel,
+        path: &str,
+        value: &Value,
+        buf: &mut [u8],
+    ) -> Result<(), AccessError> {
+        let Value::Record(values) = value else {
+            return Err(AccessError::Mismatch {
+                field: path.to_string(),
+            });
+        };
+        let mut used = Vec::new();
+        self.encode_fields(target, path, values, buf, &mut used)?;
+        match values.iter().find(|(name, _)| !used.contains(name)) {
+            Some((name, _)) => Err(AccessError::UnknownField {
+                name: join(path, name),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// encode_fields writes the fields of the record found in `values`,
+    /// adding their names to `used`.
+    fn encode_fields(
+        &self,
+        target: &TargetModel,
+        path: &str,
+        values: &[(String, Value)],
+        buf: &mut [u8],
+        used: &mut Vec<String>,
+    ) -> Result<(), AccessError> {
+        for base in self.bases.iter() {
+            if let Some(nested) = &base.nested {
+                nested.encode_fields(target, path, values, &mut buf[base.offset..], used)?;
+            }
+        }
+        for field in self.fields.iter() {
+            let at = &mut buf[field.offset..];
+            if field.name.is_empty() {
+                if let (None, Some(nested)) = (field.bits, &field.nested) {
+                    nested.encode_fields(target, path, values, at, used)?;
+                }
+                continue;
+            }
+            let Some((_, value)) = values.iter().find(|(name, _)| *name == field.name) else {
+                continue;
+            };
+            used.push(field.name.clone());
+            let path = join(path, &field.name);
+            let mismatch = || AccessError::Mismatch {
+                field: path.clone(),
+            };
+            match (&field.ty, field.bits, value) {
+                (FieldType::Scalar(ty), Some(bits), value) => {
+                    let value = integer(value).ok_or_else(mismatch)?;
+                    let value = match target.is_signed(*ty) {
+                        true => target.signed_representation.encode(bits, value),
+                        false => value as u128,
+                    };
+                    let (n, shift) = bit_span(target, field.bit_offset, bits);
+                    let mask = ((1 << bits) - 1) << shift;
+                    let raw = load_span(target, &at[..n]);
+                    store_span(target, &mut at[..n], raw & !mask | (value << shift) & mask);
+                }
+                (FieldType::FlexibleArray(element), None, Value::Array(elements)) => {
+                    let stride = self.flexible.map_or(0, |flexible| flexible.stride);
+                    for (i, value) in elements.iter().enumerate() {
+                        let at = &mut at[i * stride..];
+                        encode_type(target, field, &path, element, stride, value, at)?;
+                    }
+                }
+                (ty, None, value) => encode_type(target, field, &path, ty, field.size, value, at)?,
+                _ => return Err(mismatch()),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// encode_type writes `value` as a value of type `ty`, the type of `field`
+/// or of its elements, into the first `size` bytes of `buf`.
+fn encode_type(
+    target: &TargetModel,
+    field: &FieldLayout,
+    path: &str,
+    ty: &FieldType,
+    size: usize,
+    value: &Value,
+    buf: &mut [u8],
+) -> Result<(), AccessError> {
+    let buf = &mut buf[..size];
+    let mismatch = || AccessError::Mismatch {
+        field: path.to_string(),
+    };
+    match (ty, value) {
+        (FieldType::Scalar(CType::Float), Value::Float(x)) => {
+            target.store(buf, (*x as f32).to_bits() as u128)
+        }
+        (FieldType::Scalar(CType::Double), Value::Float(x)) => {
+            target.store(buf, x.to_bits() as u128)
+        }
+        (FieldType::Scalar(CType::Float | CType::Double), _) => return Err(mismatch()),
+        (FieldType::Scalar(CType::Pointer) | FieldType::Pointer(_), Value::Null) => {
+            target.store(buf, target.null_pointer)
+        }
+        (FieldType::Scalar(_) | FieldType::Enum(_) | FieldType::Pointer(_), value) => {
+            target.store(buf, integer(value).ok_or_else(mismatch)? as u128)
+        }
+        (FieldType::Array(element, n), Value::Array(elements)) if elements.len() <= *n => {
+            let stride = size / n;
+            for (i, value) in elements.iter().enumerate() {
+                encode_type(
+                    target,
+                    field,
+                    path,
+                    element,
+                    stride,
+                    value,
+                    &mut buf[i * stride..],
+                )?;
+            }
+        }
+        (FieldType::Record(_) | FieldType::Layout(_), value) => match &field.nested {
+            Some(nested) => nested.encode_record(target, path, value, buf)?,
+            None => return Err(mismatch()),
+        },
+        _ => return Err(mismatch()),
+    }
+    Ok(())
+}
+
+/// store_span stores the bytes holding a bit-field loaded with
+/// [`load_span`].
+fn store_span(target: &TargetModel, bytes: &mut [u8], raw: u128) {
+    let n = bytes.len();
+    for i in 0..n {
+        let byte = (raw >> (8 * i)) as u8;
+        match target.endianness {
+            Endianness::Big => bytes[n - 1 - i] = byte,
+            _ => bytes[i] = byte,
+        }
+    }
+}
+
+/// integer returns the integer an integer or boolean value holds.
+fn integer(value: &Value) -> Option<i128> {
+    match *value {
+        Value::Int(value) => Some(value),
+        Value::UInt(value) => Some(value as i128),
+        Value::Bool(value) => Some(value as i128),
+        _ => None,
+    }
+}
+
+/// join returns the path of the field `name` of the field at `path`.
+fn join(path: &str, name: &str) -> String {
+    match path {
+        "" => name.to_string(),
+        _ => format!("{}.{}", path, name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{Enum, Field, Record};
+    use crate::{DataModel, Platform};
+
+    #[test]
+    fn test_round_trip() {
+        let point = Record::new("point")
+            .field("x", CType::Short)
+            .field("y", CType::Short);
+        let record = Record::new("shape")
+            .field("kind", Enum::new("kind").value("a", -1).value("b", 1))
+            .bitfield("flag", CType::Int, 1)
+            .bitfield("", CType::Int, 3)
+            .bitfield("level", CType::UInt, 5)
+            .field("scale", CType::Double)
+            .with_field(Field::new(
+                "",
+                Record::union("")
+                    .field("f", CType::Float)
+                    .field("p", CType::Pointer),
+            ))
+            .field("pts", FieldType::array(point, 2))
+            .field("tail", FieldType::flexible(CType::UChar));
+        let point = |x, y| {
+            Value::Record(vec![
+                ("x".to_string(), Value::Int(x)),
+                ("y".to_string(), Value::Int(y)),
+            ])
+        };
+        let value = Value::Record(vec![
+            ("kind".to_string(), Value::Int(-1)),
+            ("flag".to_string(), Value::Int(-1)),
+            ("level".to_string(), Value::UInt(17)),
+            ("scale".to_string(), Value::Float(0.5)),
+            ("f".to_string(), Value::Float(2.0)),
+            (
+                "pts".to_string(),
+                Value::Array(vec![point(1, -2), point(3, 4)]),
+            ),
+            ("tail".to_string(), Value::Array(vec![Value::UInt(9); 3])),
+        ]);
+        for &platform in Platform::ALL {
+            let Ok(layout) = record.layout(platform) else {
+                continue;
+            };
+            let bytes = layout.encode(platform, &value).unwrap();
+            assert_eq!(bytes.len(), layout.allocation_size(3));
+            let decoded = layout.decode(platform, &bytes).unwrap();
+            for (name, value) in [
+                ("kind", Value::Int(-1)),
+                ("flag", Value::Int(-1)),
+                ("level", Value::UInt(17)),
+                ("scale", Value::Float(0.5)),
+                ("f", Value::Float(2.0)),
+                ("pts", Value::Array(vec![point(1, -2), point(3, 4)])),
+            ] {
+                assert_eq!(decoded.field(name), Some(&value), "{:?} {}", platform, name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bitfields_preserve_neighbours() {
+        let record = Record::new("flags")
+            .bitfield("a", CType::UInt, 3)
+            .bitfield("b", CType::UInt, 6)
+            .bitfield("c", CType::UInt, 7);
+        let layout = record.layout(DataModel::ILP32).unwrap();
+        let value = Value::Record(vec![("b".to_string(), Value::UInt(0x7f))]);
+        let mut buf = [0xff, 0xff, 0xff, 0xff];
+        layout
+            .encode_into(DataModel::ILP32, &value, &mut buf)
+            .unwrap();
+        // b is truncated to 0x3f and the other bits are left alone.
+        assert_eq!(buf, [0xff; 4]);
+        let mut buf = [0; 4];
+        layout
+            .encode_into(DataModel::ILP32, &value, &mut buf)
+            .unwrap();
+        assert_eq!(u32::from_le_bytes(buf), 0x3f << 3);
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let inner = Record::new("inner").field("x", CType::Int);
+        let record = Record::new("outer")
+            .field("inner", inner)
+            .field("v", FieldType::array(CType::Int, 2));
+        let layout = record.layout(DataModel::LP64).unwrap();
+        let encode = |values: Vec<(&str, Value)>| {
+            let values = values
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect();
+            layout.encode(DataModel::LP64, &Value::Record(values))
+        };
+        assert_eq!(
+            encode(vec![(
+                "inner",
+                Value::Record(vec![("y".to_string(), Value::Int(1))])
+            )]),
+            Err(AccessError::UnknownField {
+                name: "inner.y".to_string()
+            })
+        );
+        assert_eq!(
+            encode(vec![(
+                "inner",
+                Value::Record(vec![("x".to_string(), Value::Float(1.0))])
+            )]),
+            Err(AccessError::Mismatch {
+                field: "inner.x".to_string()
+            })
+        );
+        assert_eq!(
+            encode(vec![("v", Value::Array(vec![Value::Int(1); 3]))]),
+            Err(AccessError::Mismatch {
+                field: "v".to_string()
+            })
+        );
+        assert_eq!(
+            encode(vec![("v", Value::Array(vec![Value::Int(1)]))]),
+            Ok(vec![0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            layout.encode_into(DataModel::LP64, &Value::Record(Vec::new()), &mut [0; 4]),
+            Err(AccessError::ShortBuffer { needed: 12, len: 4 })
+        );
+    }
+}
@@ -0,0 +1,206 @@
+//! Sizing of enumerated types.
+//!
+//! C leaves the integer type behind an enum to the implementation. GCC and
+//! Clang use `unsigned int` or `int` unless a value does not fit, but with
+//! `-fshort-enums`, the default of ARM EABI bare-metal toolchains, or with
+//! `__attribute__((packed))` on the enum they use the smallest type that
+//! holds all values. C++11 and C23 let the declaration fix the type
+//! instead (`enum e : short`).
+
+use super::{Abi, LayoutError};
+use crate::CType;
+
+/// Enum is an enumerated type, described by its values.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::layout::*;
+/// let color = Enum::new("color").value("red", 0).value("blue", 2);
+/// let abi = Abi::new(DataModel::ILP32);
+/// assert_eq!(color.underlying_type(abi), Ok(CType::UInt));
+/// assert_eq!(color.underlying_type(abi.with_short_enums()), Ok(CType::UChar));
+/// assert_eq!(color.clone().packed().underlying_type(abi), Ok(CType::UChar));
+/// assert_eq!(color.underlying(CType::Short).underlying_type(abi), Ok(CType::Short));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Enum {
+    /// The enum tag.
+    pub name: String,
+    /// The enumerators and their values.
+    pub values: Vec<(String, i128)>,
+    /// The fixed underlying type, `enum e : type`.
+    pub underlying: Option<CType>,
+    /// Whether the enum has `__attribute__((packed))`.
+    pub packed: bool,
+}
+
+impl Enum {
+    /// new returns an enum without enumerators.
+    pub fn new(name: &str) -> Enum {
+        Enum {
+            name: name.to_string(),
+            values: Vec::new(),
+            underlying: None,
+            packed: false,
+        }
+    }
+
+    /// value appends an enumerator.
+    pub fn value(mut self, name: &str, value: i128) -> Enum {
+        self.values.push((name.to_string(), value));
+        self
+    }
+
+    /// underlying fixes the underlying type, as C++11 and C23 allow.
+    pub fn underlying(self, ty: CType) -> Enum {
+        Enum {
+            underlying: Some(ty),
+            ..self
+        }
+    }
+
+    /// packed marks the enum `__attribute__((packed))`, giving it the
+    /// smallest type that holds its values.
+    pub fn packed(self) -> Enum {
+        Enum {
+            packed: true,
+            ..self
+        }
+    }
+
+    /// underlying_type returns the integer type the enum is stored as
+    /// under an ABI.
+    ///
+    /// Without a fixed type, an enum without negative values is unsigned.
+    /// Values too large for `int` move it to `long` or `long long`, which
+    /// GCC accepts as an extension.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let e = Enum::new("e").value("min", -1).value("big", 1 << 40);
+    /// assert_eq!(e.underlying_type(DataModel::LP64), Ok(CType::Long));
+    /// assert_eq!(e.underlying_type(DataModel::ILP32), Ok(CType::LongLong));
+    /// assert!(e.underlying(CType::Int).underlying_type(DataModel::LP64).is_err());
+    /// ```
+    pub fn underlying_type(&self, abi: impl Into<Abi>) -> Result<CType, LayoutError> {
+        use CType::*;
+        let abi = abi.into();
+        let min = self.values.iter().map(|v| v.1).min().unwrap_or(0);
+        let max = self.values.iter().map(|v| v.1).max().unwrap_or(0);
+        let fits = |ty: CType| {
+            let bits = abi.size(ty) as u32 * 8;
+            match (bits, signed(ty)) {
+                (0, _) => false,
+                (_, true) => min >= -(1 << (bits - 1)) && max < 1 << (bits - 1),
+                (_, false) => min >= 0 && max < 1 << bits,
+            }
+        };
+        let candidates: &[(CType, CType)] = match abi.short_enums() || self.packed {
+            true => &[
+                (SChar, UChar),
+                (Short, UShort),
+                (Int, UInt),
+                (Long, ULong),
+                (LongLong, ULongLong),
+            ],
+            false => &[(Int, UInt), (Long, ULong), (LongLong, ULongLong)],
+        };
+        let found = match self.underlying {
+            Some(ty) if ty.is_integer() && ty != Bool => Some(ty).filter(|&ty| fits(ty)),
+            Some(_) => None,
+            None => candidates
+                .iter()
+                .map(|&(signed, unsigned)| if min < 0 { signed } else { unsigned })
+                .find(|&ty| fits(ty)),
+        };
+        found.ok_or_else(|| LayoutError::EnumRange {
+            name: self.name.clone(),
+        })
+    }
+}
+
+/// signed reports whether an integer type is signed, taking plain `char`
+/// as signed.
+fn signed(ty: CType) -> bool {
+    use CType::*;
+    matches!(ty, Char | SChar | Short | Int | Long | LongLong)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{FieldType, Record};
+    use crate::DataModel;
+
+    #[test]
+    fn test_default_enums() {
+        let abi = Abi::new(DataModel::LP64);
+        assert_eq!(Enum::new("empty").underlying_type(abi), Ok(CType::UInt));
+        let e = Enum::new("e").value("a", -1).value("b", 1);
+        assert_eq!(e.underlying_type(abi), Ok(CType::Int));
+        let e = Enum::new("e").value("a", 0xffff_ffff);
+        assert_eq!(e.underlying_type(abi), Ok(CType::UInt));
+        let e = Enum::new("e").value("a", 0x1_0000_0000);
+        assert_eq!(e.underlying_type(abi), Ok(CType::ULong));
+        let e = Enum::new("e").value("a", -1).value("b", 0xffff_ffff);
+        assert_eq!(e.underlying_type(abi), Ok(CType::Long));
+        let e = Enum::new("e").value("a", -1).value("b", u64::MAX as i128);
+        assert_eq!(
+            e.underlying_type(abi),
+            Err(LayoutError::EnumRange {
+                name: "e".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_short_enums() {
+        let abi = Abi::new(DataModel::ILP32).with_short_enums();
+        let e = Enum::new("e").value("a", 255);
+        assert_eq!(e.underlying_type(abi), Ok(CType::UChar));
+        let e = Enum::new("e").value("a", -128).value("b", 127);
+        assert_eq!(e.underlying_type(abi), Ok(CType::SChar));
+        let e = Enum::new("e").value("a", 256);
+        assert_eq!(e.underlying_type(abi), Ok(CType::UShort));
+        let e = Enum::new("e").value("a", -1).value("b", 40000);
+        assert_eq!(e.underlying_type(abi), Ok(CType::Int));
+    }
+
+    #[test]
+    fn test_fixed_underlying() {
+        let e = Enum::new("e").value("a", 300).underlying(CType::UChar);
+        assert!(e.underlying_type(DataModel::LP64).is_err());
+        let e = Enum::new("e").value("a", -1).underlying(CType::UInt);
+        assert!(e.underlying_type(DataModel::LP64).is_err());
+        let e = Enum::new("e").underlying(CType::Double);
+        assert!(e.underlying_type(DataModel::LP64).is_err());
+        let e = Enum::new("e").value("a", 1).underlying(CType::Long);
+        assert!(e.underlying_type(DataModel::IP16).is_err());
+        assert_eq!(e.underlying_type(DataModel::LLP64), Ok(CType::Long));
+    }
+
+    #[test]
+    fn test_enum_fields() {
+        let kind = Enum::new("kind").value("a", 0).value("b", 1);
+        let record = Record::new("s")
+            .field("k", kind.clone())
+            .field("c", CType::Char)
+            .field("ks", FieldType::array(kind.clone().packed(), 3));
+        let layout = record.layout(DataModel::LP64).unwrap();
+        assert_eq!((layout.fields[0].size, layout.fields[0].align), (4, 4));
+        assert_eq!(layout.offset_of("ks"), Some(5));
+        assert_eq!(layout.size, 8);
+
+        let abi = Abi::new(DataModel::LP64).with_short_enums();
+        let layout = record.layout(abi).unwrap();
+        assert_eq!(layout.offset_of("c"), Some(1));
+        assert_eq!(layout.size, 5);
+
+        let record = Record::new("s").field("k", kind.underlying(CType::Long));
+        assert!(record.layout(DataModel::IP16).is_err());
+    }
+}
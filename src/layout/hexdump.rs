@@ -0,0 +1,234 @@
+//! Hexdumps of record instances annotated with their fields.
+
+use super::decode::{bit_span, decode_bits, decode_type};
+use super::{FieldType, Layout};
+use crate::target::{AccessError, TargetModel};
+use crate::CType;
+use std::fmt::Write;
+
+/// BYTES_PER_LINE is the number of bytes shown on one line of a hexdump.
+const BYTES_PER_LINE: usize = 8;
+
+/// Row is a region of a hexdump with its label.
+struct Row {
+    offset: usize,
+    len: usize,
+    label: String,
+}
+
+impl Layout {
+    /// hexdump renders `bytes`, an instance of the record on `target`, as a
+    /// hexdump with one region per field, labeled with the field path and
+    /// its decoded value. Padding gets a region of its own, flagged when it
+    /// is not zero, and the members of a union share their bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("msg")
+    ///     .field("kind", CType::Char)
+    ///     .field("len", CType::Int)
+    ///     .field("next", CType::Pointer);
+    /// let layout = record.layout(DataModel::ILP32).unwrap();
+    /// let bytes = [1, 0, 0, 0, 0x10, 0, 0, 0, 0, 0x20, 0, 0];
+    /// let dump = layout.hexdump(Platform::LinuxI686, &bytes).unwrap();
+    /// assert_eq!(
+    ///     dump,
+    ///     "struct msg: size 12, align 4\n\
+    ///      0000  01                       kind = 1\n\
+    ///      0001  00 00 00                 <padding>\n\
+    ///      0004  10 00 00 00              len = 16\n\
+    ///      0008  00 20 00 00              next = 0x2000\n"
+    /// );
+    /// ```
+    pub fn hexdump(
+        &self,
+        target: impl Into<TargetModel>,
+        bytes: &[u8],
+    ) -> Result<String, AccessError> {
+        if bytes.len() < self.size {
+            return Err(AccessError::ShortBuffer {
+                needed: self.size,
+                len: bytes.len(),
+            });
+        }
+        let mut rows = Vec::new();
+        self.rows(&target.into(), 0, "", bytes, &mut rows)?;
+        let end = rows
+            .iter()
+            .map(|row| row.offset + row.len)
+            .fold(self.size, usize::max);
+        let mut covered = vec![false; end];
+        for row in rows.iter() {
+            covered[row.offset..row.offset + row.len].fill(true);
+        }
+        let mut start = 0;
+        while let Some(gap) = covered[start..].iter().position(|&covered| !covered) {
+            let offset = start + gap;
+            let len = covered[offset..]
+                .iter()
+                .position(|&covered| covered)
+                .unwrap_or(end - offset);
+            let label = match bytes[offset..offset + len].iter().all(|&byte| byte == 0) {
+                true => "<padding>",
+                false => "<padding, not zero>",
+            };
+            rows.push(Row {
+                offset,
+                len,
+                label: label.to_string(),
+            });
+            start = offset + len;
+        }
+        rows.sort_by_key(|row| row.offset);
+
+        let mut out = format!(
+            "{} {}: size {}, align {}\n",
+            self.kind.c_keyword(),
+            self.name,
+            self.size,
+            self.align
+        );
+        for row in rows.iter() {
+            let region = &bytes[row.offset..row.offset + row.len];
+            for (i, line) in region.chunks(BYTES_PER_LINE).enumerate() {
+                let hex: Vec<String> = line.iter().map(|byte| format!("{:02x}", byte)).collect();
+                let label = if i == 0 { row.label.as_str() } else { "" };
+                let line = format!(
+                    "{:04x}  {:<width$}  {}",
+                    row.offset + i * BYTES_PER_LINE,
+                    hex.join(" "),
+                    label,
+                    width = BYTES_PER_LINE * 3 - 1
+                );
+                writeln!(out, "{}", line.trim_end()).unwrap();
+            }
+        }
+        Ok(out)
+    }
+
+    /// rows appends a row for each field of the record placed at `offset`,
+    /// with nested records broken into their fields.
+    fn rows(
+        &self,
+        target: &TargetModel,
+        offset: usize,
+        path: &str,
+        bytes: &[u8],
+        rows: &mut Vec<Row>,
+    ) -> Result<(), AccessError> {
+        for base in self.bases.iter() {
+            if let Some(nested) = &base.nested {
+                nested.rows(target, offset + base.offset, path, bytes, rows)?;
+            }
+        }
+        for field in self.fields.iter() {
+            let start = offset + field.offset;
+            let at = &bytes[start..];
+            let name = match path {
+                "" => field.name.clone(),
+                _ => format!("{}.{}", path, field.name),
+            };
+            let (len, label) = match (&field.ty, field.bits, &field.nested) {
+                (_, Some(_), _) if field.name.is_empty() => continue,
+                (FieldType::Scalar(ty), Some(bits), _) => {
+                    let (n, _) = bit_span(target, field.bit_offset, bits);
+                    let value = decode_bits(target, field, *ty, bits, at);
+                    let label =
+                        format!("{} : {} @ bit {} = {}", name, bits, field.bit_offset, value);
+                    (n, label)
+                }
+                (_, Some(_), _) => continue,
+                (FieldType::Record(_) | FieldType::Layout(_), None, Some(nested)) => {
+                    let path = if field.name.is_empty() { path } else { &name };
+                    nested.rows(target, start, path, bytes, rows)?;
+                    continue;
+                }
+                (FieldType::FlexibleArray(element), None, _) => {
+                    let stride = self.flexible.map_or(0, |flexible| flexible.stride);
+                    let len = at.len().checked_div(stride).unwrap_or(0) * stride;
+                    let array = FieldType::Array(element.clone(), len / stride.max(1));
+                    let value = decode_type(target, field, &array, len, at)?;
+                    (len, format!("{} = {}", name, value))
+                }
+                (FieldType::Pointer(_) | FieldType::Scalar(CType::Pointer), None, _) => {
+                    let address = target.load(&at[..field.size]);
+                    match target.is_null(address) {
+                        true => (field.size, format!("{} = NULL", name)),
+                        false => (field.size, format!("{} = {:#x}", name, address)),
+                    }
+                }
+                (ty, None, _) => {
+                    let value = decode_type(target, field, ty, field.size, at)?;
+                    (field.size, format!("{} = {}", name, value))
+                }
+            };
+            rows.push(Row {
+                offset: start,
+                len,
+                label,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layout::*;
+    use crate::{CType, DataModel, Endianness, TargetModel};
+
+    #[test]
+    fn test_hexdump() {
+        let point = Record::new("point")
+            .field("x", CType::Short)
+            .field("y", CType::Short);
+        let record = Record::new("shape")
+            .bitfield("kind", CType::UInt, 4)
+            .bitfield("", CType::UInt, 2)
+            .bitfield("flag", CType::Int, 1)
+            .field("origin", point.clone())
+            .with_field(Field::new(
+                "",
+                Record::union("")
+                    .field("n", CType::Int)
+                    .field("c", CType::UChar),
+            ))
+            .field("pts", FieldType::array(point, 2))
+            .field("scale", CType::Double)
+            .field("tail", FieldType::flexible(CType::UChar));
+        let layout = record.layout(DataModel::LP64).unwrap();
+        let target = TargetModel::new(DataModel::LP64).with_endianness(Endianness::Big);
+        let mut bytes = vec![
+            0x52, 0, 0, 1, 0, 2, 0, 0, 0, 0, 0, 0x2a, 0, 3, 0, 4, 0, 5, 0, 6, 0, 0xff, 0, 0,
+        ];
+        bytes.extend_from_slice(&1.5f64.to_be_bytes());
+        bytes.extend_from_slice(&[9, 8, 7, 6, 5, 4, 3, 2, 1]);
+        let dump = layout.hexdump(target, &bytes).unwrap();
+        let expected = "\
+struct shape: size 32, align 8
+0000  52                       kind : 4 @ bit 0 = 5
+0000  52                       flag : 1 @ bit 6 = -1
+0001  00                       <padding>
+0002  00 01                    origin.x = 1
+0004  00 02                    origin.y = 2
+0006  00 00                    <padding>
+0008  00 00 00 2a              n = 42
+0008  00                       c = 0
+000c  00 03 00 04 00 05 00 06  pts = { { .x = 3, .y = 4 }, { .x = 5, .y = 6 } }
+0014  00 ff 00 00              <padding, not zero>
+0018  3f f8 00 00 00 00 00 00  scale = 1.5
+0020  09 08 07 06 05 04 03 02  tail = { 9, 8, 7, 6, 5, 4, 3, 2, 1 }
+0028  01
+";
+        assert_eq!(dump, expected);
+        assert_eq!(
+            layout.hexdump(target, &bytes[..24]),
+            Err(crate::target::AccessError::ShortBuffer {
+                needed: 32,
+                len: 24
+            })
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! Padding holes in layouts, reported the way pahole does.
+
+use super::{FieldLayout, FieldType, Layout, RecordKind};
+use std::fmt::Write;
+
+/// Hole is a run of padding in a record, in bits so that the unused bits
+/// between bit-fields count too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hole {
+    /// The first padding bit, counted from the start of the record.
+    pub start_bit: usize,
+    /// The number of padding bits.
+    pub bits: usize,
+    /// The field before the hole, or `None` at the start of the record.
+    pub after: Option<String>,
+    /// The field after the hole, or `None` for tail padding.
+    pub before: Option<String>,
+}
+
+impl Hole {
+    /// offset returns the offset in bytes of the first padding bit.
+    pub fn offset(&self) -> usize {
+        self.start_bit / 8
+    }
+
+    /// is_tail reports whether the hole is the padding at the end of the
+    /// record.
+    pub fn is_tail(&self) -> bool {
+        self.before.is_none()
+    }
+}
+
+/// span returns the bits a field occupies, from the start of the record.
+fn span(field: &FieldLayout) -> (usize, usize) {
+    if let FieldType::FlexibleArray(_) = field.ty {
+        return (field.offset * 8, field.offset * 8);
+    }
+    match field.bits {
+        Some(bits) => {
+            let start = field.offset * 8 + field.bit_offset;
+            (start, start + bits)
+        }
+        None => (field.offset * 8, (field.offset + field.size) * 8),
+    }
+}
+
+impl Layout {
+    /// holes returns the padding of the record in order: the gaps between
+    /// fields, the unused bits between bit-fields and the tail padding.
+    /// The members of a union overlap, so a union only has tail padding.
+    /// The bytes before the first base or field of a dynamic class hold
+    /// its virtual table pointer and are not a hole.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("s")
+    ///     .field("c", CType::Char)
+    ///     .field("p", CType::Pointer)
+    ///     .field("n", CType::Int);
+    /// let holes = record.layout(DataModel::LP64).unwrap().holes();
+    /// assert_eq!(holes.len(), 2);
+    /// assert_eq!((holes[0].offset(), holes[0].bits / 8), (1, 7));
+    /// assert_eq!(holes[0].before.as_deref(), Some("p"));
+    /// assert!(holes[1].is_tail());
+    /// ```
+    pub fn holes(&self) -> Vec<Hole> {
+        let mut members: Vec<(&str, (usize, usize))> = self
+            .bases
+            .iter()
+            .chain(&self.fields)
+            .filter(|field| field.bits != Some(0))
+            .map(|field| (field.name.as_str(), span(field)))
+            .collect();
+        if self.kind == RecordKind::Union {
+            members.sort_by_key(|&(_, (_, end))| std::cmp::Reverse(end));
+            members.truncate(1);
+        }
+        let mut holes = Vec::new();
+        let mut end = match (self.dynamic, members.first()) {
+            (true, Some(&(_, (start, _)))) => start,
+            _ => 0,
+        };
+        let mut after = None;
+        for (name, (start, stop)) in members {
+            if start > end {
+                holes.push(Hole {
+                    start_bit: end,
+                    bits: start - end,
+                    after: after.clone(),
+                    before: Some(name.to_string()),
+                });
+            }
+            end = end.max(stop);
+            after = Some(name.to_string());
+        }
+        let size = match self.flexible {
+            Some(flexible) => flexible.offset * 8,
+            None => self.size * 8,
+        };
+        if size > end {
+            holes.push(Hole {
+                start_bit: end,
+                bits: size - end,
+                after,
+                before: None,
+            });
+        }
+        holes
+    }
+
+    /// padding_report describes the record the way pahole does: a line
+    /// for each field with its offset and size, a comment at each hole,
+    /// and totals of the members and the padding.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("s").field("c", CType::Char).field("n", CType::Int);
+    /// let report = record.layout(DataModel::LP64).unwrap().padding_report();
+    /// assert_eq!(
+    ///     report,
+    ///     "struct s {\n\
+    ///     \x20   char c;                         /*     0     1 */\n\
+    ///     \n\
+    ///     \x20   /* XXX 3 bytes hole */\n\
+    ///     \n\
+    ///     \x20   int n;                          /*     4     4 */\n\
+    ///     \n\
+    ///     \x20   /* size: 8, members: 2 */\n\
+    ///     \x20   /* sum members: 5, holes: 1, sum holes: 3 bytes */\n\
+    ///     };\n"
+    /// );
+    /// ```
+    pub fn padding_report(&self) -> String {
+        let holes = self.holes();
+        let mut out = format!("{} {} {{\n", self.kind.c_keyword(), self.name);
+        let members: Vec<&FieldLayout> = self.bases.iter().chain(&self.fields).collect();
+        for field in &members {
+            let start = span(field).0;
+            let hole = holes
+                .iter()
+                .find(|hole| !hole.is_tail() && hole.start_bit + hole.bits == start);
+            if let Some(hole) = hole {
+                let _ = writeln!(out, "\n    /* XXX {} hole */\n", amount(hole.bits));
+            }
+            let declaration = match field.bits {
+                Some(bits) => format!("{}:{};", declaration(&field.ty, &field.name), bits),
+                None => format!("{};", declaration(&field.ty, &field.name)),
+            };
+            let offset = match field.bits {
+                Some(_) => format!("{}:{}", field.offset, field.bit_offset),
+                None => field.offset.to_string(),
+            };
+            let _ = writeln!(
+                out,
+                "    {:<31} /* {:>5} {:>5} */",
+                declaration, offset, field.size
+            );
+        }
+        let sum_members: usize = members
+            .iter()
+            .filter(|field| field.bits.is_none())
+            .map(|field| field.size)
+            .sum();
+        let (tail, inner): (Vec<&Hole>, Vec<&Hole>) = holes.iter().partition(|h| h.is_tail());
+        let _ = writeln!(
+            out,
+            "\n    /* size: {}, members: {} */",
+            self.size,
+            members.len()
+        );
+        let sum_holes: usize = inner.iter().map(|hole| hole.bits).sum();
+        let _ = write!(out, "    /* sum members: {}", sum_members);
+        if !inner.is_empty() {
+            let _ = write!(
+                out,
+                ", holes: {}, sum holes: {}",
+                inner.len(),
+                amount(sum_holes)
+            );
+        }
+        out.push_str(" */\n");
+        if let Some(hole) = tail.first() {
+            let _ = writeln!(out, "    /* padding: {} */", amount(hole.bits));
+        }
+        out.push_str("};\n");
+        out
+    }
+}
+
+/// amount spells a number of bits as bytes where it is whole bytes.
+fn amount(bits: usize) -> String {
+    match (bits / 8, bits % 8) {
+        (1, 0) => "1 byte".to_string(),
+        (bytes, 0) => format!("{} bytes", bytes),
+        _ if bits == 1 => "1 bit".to_string(),
+        _ => format!("{} bits", bits),
+    }
+}
+
+/// declaration spells the declaration of a field of type `ty`.
+fn declaration(ty: &FieldType, name: &str) -> String {
+    match ty {
+        FieldType::Scalar(ty) => format!("{} {}", ty.c_name(), name),
+        FieldType::Array(element, n) => declaration(element, &format!("{}[{}]", name, n)),
+        FieldType::FlexibleArray(element) => declaration(element, &format!("{}[]", name)),
+        FieldType::Pointer(pointee) => declaration(pointee, &format!("*{}", name)),
+        FieldType::Record(record) => {
+            format!("{} {} {}", record.kind.c_keyword(), record.name, name)
+        }
+        FieldType::Layout(layout) => {
+            format!("{} {} {}", layout.kind.c_keyword(), layout.name, name)
+        }
+        FieldType::Enum(e) => format!("enum {} {}", e.name, name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layout::Record;
+    use crate::{CType, DataModel};
+
+    #[test]
+    fn test_holes() {
+        let record = Record::new("flags")
+            .bitfield("a", CType::UInt, 3)
+            .bitfield("", CType::UInt, 0)
+            .bitfield("b", CType::UInt, 4)
+            .field("c", CType::Char);
+        let layout = record.layout(DataModel::LP64).unwrap();
+        let holes = layout.holes();
+        let bits: Vec<_> = holes.iter().map(|h| (h.start_bit, h.bits)).collect();
+        assert_eq!(bits, [(3, 29), (36, 4), (48, 16)]);
+        assert_eq!(holes[0].after.as_deref(), Some("a"));
+        assert!(layout.padding_report().contains("/* XXX 29 bits hole */"));
+        assert!(layout.padding_report().contains("/* padding: 2 bytes */"));
+
+        let union = Record::union("u")
+            .field("c", CType::Char)
+            .field("d", CType::Double);
+        let holes = union.layout(DataModel::ILP32).unwrap().holes();
+        assert!(holes.is_empty());
+        let union = Record::union("u")
+            .field("c", CType::Char)
+            .field("a", crate::layout::FieldType::array(CType::Char, 5))
+            .field("n", CType::Int);
+        let holes = union.layout(DataModel::ILP32).unwrap().holes();
+        assert_eq!(holes.len(), 1);
+        assert_eq!((holes[0].offset(), holes[0].bits), (5, 24));
+
+        let header = Record::new("h")
+            .field("n", CType::Short)
+            .field("data", crate::layout::FieldType::flexible(CType::Int));
+        let holes = header.layout(DataModel::LP64).unwrap().holes();
+        assert_eq!(holes.len(), 1);
+        assert_eq!(holes[0].before.as_deref(), Some("data"));
+    }
+}
@@ -0,0 +1,307 @@
+//! offsetof-style queries on computed layouts.
+
+use super::{Abi, FieldLayout, FieldType, Layout};
+use crate::CType;
+
+/// Member is a resolved path into a layout.
+struct Member<'a> {
+    offset: usize,
+    size: usize,
+    align: usize,
+    ty: &'a FieldType,
+    field: &'a FieldLayout,
+}
+
+impl Layout {
+    /// field returns the top-level field called `name`, looking through
+    /// anonymous struct and union members.
+    pub fn field(&self, name: &str) -> Option<&FieldLayout> {
+        self.lookup(name).map(|(_, field)| field)
+    }
+
+    /// lookup finds a field by name, also inside anonymous members, and
+    /// returns it with the offset of the record it was found in.
+    fn lookup(&self, name: &str) -> Option<(usize, &FieldLayout)> {
+        if let Some(field) = self.fields.iter().find(|f| f.name == name) {
+            return Some((0, field));
+        }
+        self.fields
+            .iter()
+            .filter(|f| f.name.is_empty())
+            .filter_map(|f| {
+                let (offset, field) = f.nested.as_ref()?.lookup(name)?;
+                Some((f.offset + offset, field))
+            })
+            .next()
+    }
+
+    /// resolve walks a path of field names separated by dots, each
+    /// optionally followed by array indices, e.g. `"hdr.entries[2].len"`.
+    fn resolve(&self, path: &str) -> Option<Member<'_>> {
+        let mut layout = self;
+        let mut base = 0;
+        let mut member: Option<Member<'_>> = None;
+        for part in path.split('.') {
+            if let Some(m) = &member {
+                layout = m.field.nested.as_deref()?;
+                base = m.offset;
+            }
+            let (name, mut indices) = match part.find('[') {
+                Some(i) => (&part[..i], &part[i..]),
+                None => (part, ""),
+            };
+            if name.is_empty() {
+                return None;
+            }
+            let (offset, field) = layout.lookup(name)?;
+            if field.bits.is_some() {
+                return None;
+            }
+            let mut m = Member {
+                offset: base + offset + field.offset,
+                size: field.size,
+                align: field.align,
+                ty: &field.ty,
+                field,
+            };
+            while !indices.is_empty() {
+                let close = indices.find(']')?;
+                let index: usize = indices[1..close].trim().parse().ok()?;
+                indices = &indices[close + 1..];
+                match m.ty {
+                    FieldType::Array(element, n) if index < *n => {
+                        let stride = m.size / n;
+                        m.offset += index * stride;
+                        m.size = stride;
+                        m.ty = element;
+                    }
+                    _ => return None,
+                }
+            }
+            member = Some(m);
+        }
+        member
+    }
+
+    /// offset_of returns the offset in bytes of a member named by a path
+    /// such as `"hdr.entries[2].len"`, like C's `offsetof`.
+    ///
+    /// Members of anonymous structs and unions are found by their own
+    /// names. Like `offsetof`, bit-fields have no offset and yield `None`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let entry = Record::new("entry").field("len", CType::Short).field("ptr", CType::Pointer);
+    /// let table = Record::new("table")
+    ///     .field("count", CType::Int)
+    ///     .field("entries", FieldType::array(entry, 4));
+    /// let layout = table.layout(DataModel::LP64).unwrap();
+    /// assert_eq!(layout.offset_of("entries[2].ptr"), Some(48));
+    /// assert_eq!(layout.offset_of("entries[4]"), None);
+    /// assert_eq!(layout.offset_of("missing"), None);
+    /// ```
+    pub fn offset_of(&self, path: &str) -> Option<usize> {
+        self.resolve(path).map(|m| m.offset)
+    }
+
+    /// size_of returns the size in bytes of the member named by `path`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("s").field("names", FieldType::array(FieldType::array(CType::Char, 8), 4));
+    /// let layout = record.layout(DataModel::LP64).unwrap();
+    /// assert_eq!(layout.size_of("names"), Some(32));
+    /// assert_eq!(layout.size_of("names[1]"), Some(8));
+    /// assert_eq!(layout.offset_of("names[1][3]"), Some(11));
+    /// ```
+    pub fn size_of(&self, path: &str) -> Option<usize> {
+        self.resolve(path).map(|m| m.size)
+    }
+
+    /// align_of returns the alignment in bytes of the member named by
+    /// `path` as placed in the record.
+    pub fn align_of(&self, path: &str) -> Option<usize> {
+        self.resolve(path).map(|m| m.align)
+    }
+
+    /// unaligned_fields returns the paths of the scalar members, at any
+    /// depth, whose offset is not a multiple of the alignment `abi` gives
+    /// their type, as happens in packed records. Where
+    /// [`Platform::unaligned_access`](crate::Platform::unaligned_access)
+    /// says so, these need byte-wise copies. Arrays are reported whole and
+    /// bit-fields are skipped.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("hdr")
+    ///     .field("kind", CType::Char)
+    ///     .field("len", CType::Int)
+    ///     .field("id", CType::Short)
+    ///     .packed();
+    /// let layout = record.layout(Platform::SolarisSparc64).unwrap();
+    /// assert_eq!(layout.unaligned_fields(Platform::SolarisSparc64), ["len", "id"]);
+    /// ```
+    pub fn unaligned_fields(&self, abi: impl Into<Abi>) -> Vec<String> {
+        let mut paths = Vec::new();
+        self.find_unaligned(&abi.into(), 0, "", &mut paths);
+        paths
+    }
+
+    /// find_unaligned adds the paths of the misaligned members of the
+    /// record at `base` to `paths`, under `prefix`.
+    fn find_unaligned(&self, abi: &Abi, base: usize, prefix: &str, paths: &mut Vec<String>) {
+        for field in self.bases.iter().chain(self.fields.iter()) {
+            if field.bits.is_some() {
+                continue;
+            }
+            let path = match (prefix, field.name.as_str()) {
+                (_, "") => prefix.to_string(),
+                ("", name) => name.to_string(),
+                (prefix, name) => format!("{}.{}", prefix, name),
+            };
+            let offset = base + field.offset;
+            unaligned(abi, field, &field.ty, field.size, offset, &path, paths);
+        }
+    }
+}
+
+/// unaligned adds `path` to `paths` if the member of type `ty` and `size`
+/// bytes at `offset`, or any part of it, is misaligned.
+fn unaligned(
+    abi: &Abi,
+    field: &FieldLayout,
+    ty: &FieldType,
+    size: usize,
+    offset: usize,
+    path: &str,
+    paths: &mut Vec<String>,
+) {
+    let align = match ty {
+        FieldType::Scalar(ty) => abi.align(*ty),
+        FieldType::Pointer(_) => abi.align(CType::Pointer),
+        FieldType::Enum(_) => size.max(1),
+        FieldType::Array(element, n) => {
+            let stride = size.checked_div(*n).unwrap_or(0);
+            if let (FieldType::Record(_) | FieldType::Layout(_), Some(nested)) =
+                (&**element, &field.nested)
+            {
+                for i in 0..*n {
+                    let path = format!("{}[{}]", path, i);
+                    nested.find_unaligned(abi, offset + i * stride, &path, paths);
+                }
+                return;
+            }
+            let mut element_paths = Vec::new();
+            for i in 0..*n {
+                let at = offset + i * stride;
+                unaligned(abi, field, element, stride, at, path, &mut element_paths);
+            }
+            if !element_paths.is_empty() {
+                paths.push(path.to_string());
+            }
+            return;
+        }
+        FieldType::FlexibleArray(_) => return,
+        FieldType::Record(_) | FieldType::Layout(_) => {
+            if let Some(nested) = &field.nested {
+                nested.find_unaligned(abi, offset, path, paths);
+            }
+            return;
+        }
+    };
+    if !offset.is_multiple_of(align) {
+        paths.push(path.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layout::*;
+    use crate::{CType, DataModel};
+
+    fn event() -> Layout {
+        let point = Record::new("point")
+            .field("x", CType::Int)
+            .field("y", CType::Int);
+        Record::new("event")
+            .field("kind", CType::Char)
+            .field(
+                "",
+                Record::union("")
+                    .field("key", CType::Long)
+                    .field("at", point.clone()),
+            )
+            .field("path", FieldType::array(point, 3))
+            .bitfield("flag", CType::UInt, 1)
+            .layout(DataModel::LP64)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_paths() {
+        let layout = event();
+        assert_eq!(layout.offset_of("kind"), Some(0));
+        assert_eq!(layout.offset_of("key"), Some(8));
+        assert_eq!(layout.offset_of("at.y"), Some(12));
+        assert_eq!(layout.offset_of("path"), Some(16));
+        assert_eq!(layout.offset_of("path[2].y"), Some(36));
+        assert_eq!(layout.size_of("path"), Some(24));
+        assert_eq!(layout.size_of("path[1]"), Some(8));
+        assert_eq!(layout.align_of("key"), Some(8));
+        assert_eq!(layout.field("at").map(|f| f.size), Some(8));
+    }
+
+    #[test]
+    fn test_bad_paths() {
+        let layout = event();
+        assert_eq!(layout.offset_of("flag"), None);
+        assert_eq!(layout.offset_of("kind.x"), None);
+        assert_eq!(layout.offset_of("kind[0]"), None);
+        assert_eq!(layout.offset_of("path[x]"), None);
+        assert_eq!(layout.offset_of("path[3]"), None);
+        assert_eq!(layout.offset_of("path[1"), None);
+        assert_eq!(layout.offset_of(""), None);
+    }
+
+    #[test]
+    fn test_unaligned_fields() {
+        use crate::Platform;
+        let point = Record::new("point")
+            .field("x", CType::Short)
+            .field("y", CType::Int)
+            .packed();
+        let record = Record::new("r")
+            .field("tag", CType::Char)
+            .field("points", FieldType::array(point, 2))
+            .field("ids", FieldType::array(CType::Short, 2))
+            .field("next", CType::Pointer)
+            .bitfield("flag", CType::UInt, 1)
+            .packed();
+        let layout = record.layout(DataModel::LP64).unwrap();
+        assert_eq!(
+            layout.unaligned_fields(DataModel::LP64),
+            [
+                "points[0].x",
+                "points[0].y",
+                "points[1].x",
+                "points[1].y",
+                "ids",
+                "next"
+            ]
+        );
+        assert!(event().unaligned_fields(DataModel::LP64).is_empty());
+
+        let record = Record::new("r")
+            .field("i", CType::Int)
+            .field("ll", CType::LongLong);
+        let layout = record.layout(Platform::LinuxI686).unwrap();
+        assert!(layout.unaligned_fields(Platform::LinuxI686).is_empty());
+        assert_eq!(layout.unaligned_fields(Platform::Windows32), ["ll"]);
+    }
+}
@@ -0,0 +1,228 @@
+//! Cache lines of a record and the false sharing between threads writing
+//! to them.
+
+use super::{Abi, Layout, LayoutError, Record};
+
+/// Writers says which fields each thread writes, as pairs of a thread name
+/// and the names of its fields.
+pub type Writers<'a> = [(&'a str, &'a [&'a str])];
+
+/// CacheLine is one cache line of a record and the fields on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheLine {
+    /// The index of the line, counted from the start of the record.
+    pub index: usize,
+    /// The fields with at least one byte on the line, in order.
+    pub fields: Vec<String>,
+}
+
+/// FalseSharing is a cache line written by more than one thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FalseSharing {
+    /// The index of the line, counted from the start of the record.
+    pub line: usize,
+    /// The written fields on the line with the threads writing each.
+    pub fields: Vec<(String, Vec<String>)>,
+    /// The fields to align to the cache line so that each thread's
+    /// fields start a line of their own.
+    pub fix: Vec<String>,
+}
+
+impl FalseSharing {
+    /// writers returns the threads writing to the line, in the order they
+    /// first appear on it.
+    pub fn writers(&self) -> Vec<&str> {
+        let mut writers: Vec<&str> = Vec::new();
+        for thread in self.fields.iter().flat_map(|(_, threads)| threads) {
+            if !writers.contains(&thread.as_str()) {
+                writers.push(thread);
+            }
+        }
+        writers
+    }
+}
+
+impl Layout {
+    /// cache_lines splits the record into cache lines of `line_size`
+    /// bytes, such as [`Profile::cache_line_size`], assuming it starts a
+    /// line. A field crossing a line boundary is on both lines.
+    ///
+    /// [`Profile::cache_line_size`]: crate::database::Profile::cache_line_size
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("s")
+    ///     .field("a", FieldType::array(CType::Char, 60))
+    ///     .field("n", CType::Long);
+    /// let lines = record.layout(DataModel::ILP32).unwrap().cache_lines(32);
+    /// assert_eq!(lines.len(), 2);
+    /// assert_eq!(lines[1].fields, ["a", "n"]);
+    /// ```
+    pub fn cache_lines(&self, line_size: usize) -> Vec<CacheLine> {
+        let mut lines: Vec<CacheLine> = (0..self.size.div_ceil(line_size))
+            .map(|index| CacheLine {
+                index,
+                fields: Vec::new(),
+            })
+            .collect();
+        for field in self.bases.iter().chain(&self.fields) {
+            if field.size == 0 || field.bits == Some(0) {
+                continue;
+            }
+            let first = field.offset / line_size;
+            let last = (field.offset + field.size - 1) / line_size;
+            for line in &mut lines[first..=last] {
+                line.fields.push(field.name.clone());
+            }
+        }
+        lines
+    }
+
+    /// false_sharing returns the cache lines of `line_size` bytes written
+    /// by more than one of `writers`, where one thread's stores invalidate
+    /// the line in the caches of the others. Each comes with the fields to
+    /// align to the line to separate the threads, which
+    /// [`Record::separate_writers`] applies. Bit-fields count as their
+    /// whole storage unit, since writing one rewrites its neighbours.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("queue")
+    ///     .field("head", CType::Long)
+    ///     .field("tail", CType::Long);
+    /// let writers: &[(&str, &[&str])] = &[("producer", &["tail"]), ("consumer", &["head"])];
+    /// let shared = record.layout(DataModel::LP64).unwrap().false_sharing(64, writers);
+    /// assert_eq!(shared.len(), 1);
+    /// assert_eq!(shared[0].writers(), ["consumer", "producer"]);
+    /// assert_eq!(shared[0].fix, ["tail"]);
+    /// ```
+    pub fn false_sharing(&self, line_size: usize, writers: &Writers) -> Vec<FalseSharing> {
+        let threads_of = |field: &str| -> Vec<String> {
+            writers
+                .iter()
+                .filter(|(_, fields)| fields.contains(&field))
+                .map(|(thread, _)| thread.to_string())
+                .collect()
+        };
+        let mut shared = Vec::new();
+        for line in self.cache_lines(line_size) {
+            let fields: Vec<(String, Vec<String>)> = line
+                .fields
+                .iter()
+                .cloned()
+                .map(|name| {
+                    let threads = threads_of(&name);
+                    (name, threads)
+                })
+                .filter(|(_, threads)| !threads.is_empty())
+                .collect();
+            let mut fix = Vec::new();
+            for pair in fields.windows(2) {
+                let field = self.field(&pair[1].0);
+                let starts_here = field.is_some_and(|f| f.offset / line_size == line.index);
+                if pair[0].1 != pair[1].1 && starts_here {
+                    fix.push(pair[1].0.clone());
+                }
+            }
+            let sharing = FalseSharing {
+                line: line.index,
+                fields,
+                fix,
+            };
+            if sharing.writers().len() > 1 {
+                shared.push(sharing);
+            }
+        }
+        shared
+    }
+}
+
+impl Record {
+    /// separate_writers returns the record with the fields that
+    /// [`Layout::false_sharing`] suggests aligned to `line_size` under
+    /// `abi`, repeating until no cache line is written by two threads or
+    /// no alignment helps.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("queue")
+    ///     .field("head", CType::Long)
+    ///     .field("tail", CType::Long);
+    /// let writers: &[(&str, &[&str])] = &[("producer", &["tail"]), ("consumer", &["head"])];
+    /// let fixed = record.separate_writers(DataModel::LP64, 64, writers).unwrap();
+    /// let layout = fixed.layout(DataModel::LP64).unwrap();
+    /// assert_eq!((layout.fields[1].offset, layout.size), (64, 128));
+    /// assert!(layout.false_sharing(64, writers).is_empty());
+    /// ```
+    pub fn separate_writers(
+        &self,
+        abi: impl Into<Abi>,
+        line_size: usize,
+        writers: &Writers,
+    ) -> Result<Record, LayoutError> {
+        let abi = abi.into();
+        let mut record = self.clone();
+        loop {
+            let layout = record.layout(abi)?;
+            let mut changed = false;
+            for name in layout
+                .false_sharing(line_size, writers)
+                .iter()
+                .flat_map(|sharing| &sharing.fix)
+            {
+                for field in record.fields.iter_mut().filter(|f| &f.name == name) {
+                    if field.aligned.unwrap_or(0) < line_size {
+                        field.aligned = Some(line_size);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                return Ok(record);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database;
+    use crate::layout::Record;
+    use crate::CType;
+
+    #[test]
+    fn test_false_sharing() {
+        let profile = database::find("powerpc64le-unknown-linux-gnu").unwrap();
+        let line = profile.cache_line_size().unwrap();
+        let record = Record::new("stats")
+            .field("hits", CType::Long)
+            .field("misses", CType::Long)
+            .field("lock", CType::Int)
+            .bitfield("dirty", CType::UInt, 1)
+            .bitfield("busy", CType::UInt, 1);
+        let writers: &[(&str, &[&str])] = &[
+            ("reader", &["hits", "misses"]),
+            ("writer", &["lock", "busy"]),
+            ("flusher", &["dirty"]),
+        ];
+        let layout = record.layout(profile.target().abi).unwrap();
+        let shared = layout.false_sharing(line, writers);
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0].fix, ["lock", "dirty", "busy"]);
+        assert_eq!(shared[0].writers(), ["reader", "writer", "flusher"]);
+
+        let fixed = record
+            .separate_writers(profile.target().abi, line, writers)
+            .unwrap();
+        let layout = fixed.layout(profile.target().abi).unwrap();
+        let offsets: Vec<_> = layout.fields.iter().map(|f| f.offset).collect();
+        assert_eq!(offsets, [0, 8, 128, 256, 384]);
+        assert!(layout.false_sharing(line, writers).is_empty());
+    }
+}
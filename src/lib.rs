@@ -13,6 +13,69 @@
 //! ```
 //!
 
+pub mod address;
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+pub mod arith;
+#[cfg(feature = "bindgen")]
+pub mod bindgen;
+pub mod c;
+pub mod calling_convention;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod clayout;
+pub mod compiler;
+pub mod cvalue;
+pub mod database;
+pub mod decl;
+pub mod dump;
+#[cfg(feature = "dwarf")]
+pub mod dwarf;
+pub mod fingerprint;
+pub mod flags;
+pub mod format;
+pub mod hexeditor;
+pub mod json;
+pub mod kaitai;
+pub mod layout;
+pub mod limits;
+pub mod literal;
+pub mod llvm;
+pub mod matrix;
+pub mod models;
+mod native;
+pub mod platform;
+pub mod portable;
+pub mod porting;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod registry;
+pub mod rust;
+mod standard;
+pub mod stdint;
+pub mod target;
+#[cfg(feature = "verify")]
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod y2038;
+
+pub use address::{TargetAddress, TargetPtr};
+pub use clayout::CLayout;
+pub use compiler::Compiler;
+#[cfg(feature = "derive")]
+pub use data_models_derive::CLayout;
+pub use decl::{parse_decl, TypeExpr};
+pub use models::{
+    Ilp32, Ilp64, Ip16, Ip16L32, Llp64, Lp32, Lp64, ModelVisitor, Silp64, StaticModel,
+};
+pub use platform::{FltEvalMethod, LongDouble, Platform, UnalignedAccess};
+pub use target::TargetModel;
+
+// Lets the derive name the crate `::data_models` in its own tests.
+#[cfg(test)]
+extern crate self as data_models;
+
 /// A data model is the choices of bit width of integer types by each platform.
 ///
 /// # Examples
@@ -43,14 +106,14 @@
 /// Four data models found wide acceptance:
 ///
 /// * LP32 or 2/4/4 (int is 16-bit, long and pointer are 32-bit)
-///    M68k mac and Win16 API
+///   M68k mac and Win16 API
 ///
 /// * ILP32 or 4/4/4 (int, long, and pointer are 32-bit);
-///    Win32 API
-///    Unix and Unix-like systems (Linux, Mac OS X)
+///   Win32 API
+///   Unix and Unix-like systems (Linux, Mac OS X)
 ///
 /// * LLP64 or 4/4/8 (int and long are 32-bit, pointer is 64-bit)
-///    Win64 API
+///   Win64 API
 ///
 /// * LP64 or 4/8/8 (int is 32-bit, long and pointer are 64-bit)
 ///   Unix and Unix-like systems (Linux, Mac OS X)
@@ -59,7 +122,8 @@
 /// 1. J. R. Mashey.  The long road to 64 bits. ACM Queue Magazine, 4(8):24–35, 1996.
 /// 2. T. Lauer.  Porting to Win32: A Guide to Making Your Applications Ready for the 32-Bit Future of Windows. Springer, 1996.
 ///
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DataModel {
     //           char,  short, int, long, long long, pointer, example
     /// 16-bit integer and pointer (16-bit PDP-11)
@@ -82,6 +146,91 @@ pub enum DataModel {
     Unknown, //  I'd love to see more platforms here !
 }
 
+/// Era tells whether a data model is still used by new platforms.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// assert_eq!(DataModel::LP64.era(), Era::Current);
+/// assert!(DataModel::LP32.is_historical());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Era {
+    /// Only found on machines and systems of the past.
+    Historical,
+    /// In use by current platforms.
+    Current,
+}
+
+/// Endianness is the order in which a target stores the bytes of an integer.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let layout = DataModel::from_llvm_datalayout("E-p:32:32").unwrap();
+/// assert_eq!(layout.endianness, Endianness::Big);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Endianness {
+    /// Least significant byte first (x86, most ARM and RISC-V).
+    Little,
+    /// Most significant byte first (SPARC, s390x, classic PowerPC and m68k).
+    Big,
+    /// Little-endian 16-bit words with the most significant word first, the
+    /// order of 32-bit `long`s on the PDP-11.
+    PdpMiddle,
+}
+
+/// SignedRepresentation is how a target encodes negative integers. C
+/// allowed all three until C23 settled on two's complement, and machines
+/// like the UNIVAC 1100 and its Unisys successors use ones' complement.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let ones = SignedRepresentation::OnesComplement;
+/// assert_eq!(ones.min(8), -127);
+/// assert_eq!(SignedRepresentation::TwosComplement.min(8), -128);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SignedRepresentation {
+    /// `-x` is `!x + 1`, so there is one more negative value than positive.
+    TwosComplement,
+    /// `-x` is `!x`, leaving a negative zero with all bits set.
+    OnesComplement,
+    /// `-x` sets the sign bit of `x`, leaving a negative zero with only the
+    /// sign bit set.
+    SignMagnitude,
+}
+
+/// CStandard is a revision of the C standard, for the rules that changed
+/// between them.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// assert!(CStandard::C89 < CStandard::C99);
+/// assert!(!CStandard::C89.has(CType::LongLong));
+/// assert!(CStandard::C23.has_bit_int());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CStandard {
+    /// ANSI C, also known as C90.
+    C89,
+    /// C99, which added `long long`.
+    C99,
+    /// C11.
+    C11,
+    /// C17, which only fixed defects of C11.
+    C17,
+    /// C23, which added `_BitInt`, `char8_t` and binary constants.
+    C23,
+}
+
 /// Char represents the `char` C type.
 /// Smallest addressable unit of the machine.
 /// It contains CHAR_BIT bits (typically 8).
@@ -152,12 +301,354 @@ pub enum LongLong {}
 /// assert_eq!(p, 8);
 /// ```
 pub enum Pointer {}
+/// SChar represents the `signed char` C type, which has the size of [`Char`].
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let model = DataModel::LP64;
+/// assert_eq!(model.size_of::<SChar>(), 1);
+/// ```
+pub enum SChar {}
+/// UChar represents the `unsigned char` C type, which has the size of [`Char`].
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let model = DataModel::LP64;
+/// assert_eq!(model.size_of::<UChar>(), 1);
+/// ```
+pub enum UChar {}
+/// UShort represents the `unsigned short` C type, which has the size of [`Short`].
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let model = DataModel::LP64;
+/// assert_eq!(model.size_of::<UShort>(), 2);
+/// ```
+pub enum UShort {}
+/// UInt represents the `unsigned int` C type, which has the size of [`Int`].
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let model = DataModel::LP64;
+/// assert_eq!(model.size_of::<UInt>(), 4);
+/// ```
+pub enum UInt {}
+/// ULong represents the `unsigned long` C type, which has the size of [`Long`].
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let model = DataModel::LP64;
+/// assert_eq!(model.size_of::<ULong>(), 8);
+/// ```
+pub enum ULong {}
+/// ULongLong represents the `unsigned long long` C type, which has the size of [`LongLong`].
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let model = DataModel::LP64;
+/// assert_eq!(model.size_of::<ULongLong>(), 8);
+/// ```
+pub enum ULongLong {}
+
+/// CTypeInfo describes one of the marker types, such as [`Long`], so
+/// generic code can ask about the C type it stands for.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// fn describe<T: CTypeInfo>() -> String {
+///     let sign = if T::SIGNED { "signed" } else { "unsigned" };
+///     format!("{} is {}, at least {} bits", T::C_NAME, sign, T::MIN_BITS)
+/// }
+/// assert_eq!(describe::<ULong>(), "unsigned long is unsigned, at least 32 bits");
+/// assert!(Short::RANK < Long::RANK);
+/// ```
+pub trait CTypeInfo {
+    /// The C type named at runtime.
+    const C_TYPE: CType;
+    /// The C spelling of the type, such as `"long long"`.
+    const C_NAME: &'static str;
+    /// Whether the type is a signed integer type. Plain `char` is signed on
+    /// some targets and unsigned on others, so for [`Char`] this is only a
+    /// placeholder, true as in [`CType::is_signed`]: check
+    /// [`CTypeInfo::PLATFORM_SIGNED`] and take the signedness from
+    /// [`TargetModel::char_signed`].
+    const SIGNED: bool;
+    /// Whether the signedness of the type depends on the target, which is
+    /// only true for [`Char`].
+    const PLATFORM_SIGNED: bool;
+    /// The integer conversion rank, see [`CType::rank`].
+    const RANK: usize;
+    /// The least width in bits the C standard allows, see
+    /// [`CType::min_bits`].
+    const MIN_BITS: usize;
+}
+
+/// markers implements [`CTypeInfo`] and the lookups behind
+/// [`DataModel::size_of`] and [`CType::of`] for the marker types, each of
+/// which is named after its [`CType`].
+macro_rules! markers {
+    ($($marker:ident,)*) => {
+        $(
+            impl CTypeInfo for $marker {
+                const C_TYPE: CType = CType::$marker;
+                const C_NAME: &'static str = CType::$marker.c_name();
+                const SIGNED: bool = CType::$marker.is_signed();
+                const PLATFORM_SIGNED: bool = matches!(CType::$marker, CType::Char);
+                const RANK: usize = CType::$marker.rank();
+                const MIN_BITS: usize = CType::$marker.min_bits();
+            }
+
+            impl SizeOf<$marker> for DataModel {
+                fn size_of(self) -> usize {
+                    self.size(CType::$marker)
+                }
+            }
+
+            impl CTypeOf<$marker> for CType {
+                fn c_type() -> Option<CType> {
+                    Some(CType::$marker)
+                }
+            }
+        )*
+    };
+}
+
+markers! {
+    Char, Short, Int, Long, LongLong, Pointer, SChar, UChar, UShort, UInt, ULong, ULongLong,
+}
+
+/// CType names a C type at runtime, for when the type is only known as a
+/// value rather than as one of the marker types like [`Long`].
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let model = DataModel::LLP64;
+/// assert_eq!(model.size(CType::ULong), 4);
+/// assert_eq!(model.size(CType::Pointer), 8);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CType {
+    /// `_Bool`
+    Bool,
+    /// `char`, whose signedness depends on the platform.
+    Char,
+    /// `signed char`
+    SChar,
+    /// `unsigned char`
+    UChar,
+    /// `short`
+    Short,
+    /// `unsigned short`
+    UShort,
+    /// `int`
+    Int,
+    /// `unsigned int`
+    UInt,
+    /// `long`
+    Long,
+    /// `unsigned long`
+    ULong,
+    /// `long long`
+    LongLong,
+    /// `unsigned long long`
+    ULongLong,
+    /// `void *`
+    Pointer,
+    /// `float`
+    Float,
+    /// `double`
+    Double,
+}
+
+impl CType {
+    /// Every C type, in the order they are declared.
+    pub const ALL: &'static [CType] = &[
+        CType::Bool,
+        CType::Char,
+        CType::SChar,
+        CType::UChar,
+        CType::Short,
+        CType::UShort,
+        CType::Int,
+        CType::UInt,
+        CType::Long,
+        CType::ULong,
+        CType::LongLong,
+        CType::ULongLong,
+        CType::Pointer,
+        CType::Float,
+        CType::Double,
+    ];
+
+    /// c_name is the C spelling of the type, e.g. `"unsigned long"`.
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(CType::ULongLong.c_name(), "unsigned long long");
+    /// ```
+    pub const fn c_name(self) -> &'static str {
+        use CType::*;
+        match self {
+            Bool => "_Bool",
+            Char => "char",
+            SChar => "signed char",
+            UChar => "unsigned char",
+            Short => "short",
+            UShort => "unsigned short",
+            Int => "int",
+            UInt => "unsigned int",
+            Long => "long",
+            ULong => "unsigned long",
+            LongLong => "long long",
+            ULongLong => "unsigned long long",
+            Pointer => "void *",
+            Float => "float",
+            Double => "double",
+        }
+    }
+
+    /// of returns the C type a marker type such as [`ULong`] stands for,
+    /// or `None` for other types.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(CType::of::<ULong>(), Some(CType::ULong));
+    /// assert_eq!(CType::of::<DataModel>(), None);
+    /// ```
+    pub fn of<T>() -> Option<CType> {
+        <CType as CTypeOf<T>>::c_type()
+    }
+
+    /// min_bits is the least width in bits the C standard allows for the
+    /// type: 8 for the character types, 16 for `short` and `int`, 32 for
+    /// `long` and 64 for `long long`. Pointers count as 16, the least
+    /// `SIZE_MAX` allows, `_Bool` as 1 and the floating types as 0.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(CType::Int.min_bits(), 16);
+    /// assert!(8 * DataModel::LP64.size(CType::Long) >= CType::Long.min_bits());
+    /// ```
+    pub const fn min_bits(self) -> usize {
+        use CType::*;
+        match self {
+            Bool => 1,
+            Char | SChar | UChar => 8,
+            Short | UShort | Int | UInt | Pointer => 16,
+            Long | ULong => 32,
+            LongLong | ULongLong => 64,
+            Float | Double => 0,
+        }
+    }
+
+    /// is_integer reports whether the type is one of the integer types,
+    /// which includes `_Bool` and the character types.
+    pub fn is_integer(self) -> bool {
+        !matches!(self, CType::Pointer | CType::Float | CType::Double)
+    }
+}
+
+/// SIZES holds the size in bytes of each C type, in the order of
+/// [`CType::ALL`], for each data model in the order they are declared. A
+/// type the model lacks has size 0.
+#[rustfmt::skip]
+const SIZES: [[u8; 15]; 9] = [
+    // Bool Char SChar UChar Short UShort Int UInt Long ULong LongLong ULongLong Pointer Float Double
+    [1, 1, 1, 1, 0, 0, 2, 2, 0, 0, 0, 0, 2, 4, 8], // IP16
+    [1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 0, 0, 2, 4, 8], // IP16L32
+    [1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 8, 8, 4, 4, 8], // LP32
+    [1, 1, 1, 1, 2, 2, 4, 4, 4, 4, 8, 8, 4, 4, 8], // ILP32
+    [1, 1, 1, 1, 2, 2, 4, 4, 4, 4, 8, 8, 8, 4, 8], // LLP64
+    [1, 1, 1, 1, 2, 2, 4, 4, 8, 8, 8, 8, 8, 4, 8], // LP64
+    [1, 1, 1, 1, 2, 2, 8, 8, 8, 8, 8, 8, 8, 4, 8], // ILP64
+    [1, 1, 1, 1, 8, 8, 8, 8, 8, 8, 8, 8, 8, 4, 8], // SILP64
+    [0; 15],                                       // Unknown
+];
 
 trait SizeOf<T> {
     fn size_of(self) -> usize;
 }
 
 impl DataModel {
+    /// Every data model except [`DataModel::Unknown`], from the oldest.
+    pub const ALL: &'static [DataModel] = &[
+        DataModel::IP16,
+        DataModel::IP16L32,
+        DataModel::LP32,
+        DataModel::ILP32,
+        DataModel::LLP64,
+        DataModel::LP64,
+        DataModel::ILP64,
+        DataModel::SILP64,
+    ];
+
+    /// filter returns the models in [`DataModel::ALL`] for which `predicate`
+    /// holds.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let models = DataModel::filter(|model| model.size(CType::Int) == 8);
+    /// assert_eq!(models, [DataModel::ILP64, DataModel::SILP64]);
+    /// ```
+    pub fn filter(predicate: impl Fn(DataModel) -> bool) -> Vec<DataModel> {
+        DataModel::ALL
+            .iter()
+            .copied()
+            .filter(|&model| predicate(model))
+            .collect()
+    }
+
+    /// with_size returns the models in which `ty` is `size` bytes wide. A
+    /// size of 0 matches no model, not those lacking the type, as in
+    /// [`DataModel::types_with_size`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let models = DataModel::with_size(CType::Short, 8);
+    /// assert_eq!(models, [DataModel::SILP64]);
+    /// ```
+    pub fn with_size(ty: CType, size: usize) -> Vec<DataModel> {
+        DataModel::filter(|model| model.has(ty) && model.size(ty) == size)
+    }
+
+    /// with_pointer_size returns the models whose pointers are `size`
+    /// bytes wide.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let models = DataModel::with_pointer_size(4);
+    /// assert_eq!(models, [DataModel::LP32, DataModel::ILP32]);
+    /// ```
+    pub fn with_pointer_size(size: usize) -> Vec<DataModel> {
+        DataModel::with_size(CType::Pointer, size)
+    }
+
+    /// with_long_size returns the models whose `long` is `size` bytes wide.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let models = DataModel::with_long_size(8);
+    /// assert_eq!(models, [DataModel::LP64, DataModel::ILP64, DataModel::SILP64]);
+    /// ```
+    pub fn with_long_size(size: usize) -> Vec<DataModel> {
+        DataModel::with_size(CType::Long, size)
+    }
+
     /// new tries to guess the data model from the byte size of
     /// int, long, and pointer.
     ///
@@ -168,7 +659,7 @@ impl DataModel {
     /// let p = model.size_of::<Pointer>();
     /// assert_eq!(p, 8);
     /// ```
-    pub fn new(int: usize, long: usize, pointer: usize) -> DataModel {
+    pub const fn new(int: usize, long: usize, pointer: usize) -> DataModel {
         use DataModel::*;
         match (int, long, pointer) {
             (2, 0, 2) => IP16,
@@ -182,7 +673,8 @@ impl DataModel {
         }
     }
     /// size_of will report the size in bytes for one of the types
-    /// defined in this crate.
+    /// defined in this crate, or 0 for other types and for types the
+    /// model lacks, see [`DataModel::has_type`].
     /// # Example
     /// ```
     /// use data_models::*;
@@ -192,77 +684,163 @@ impl DataModel {
     pub fn size_of<T>(self) -> usize {
         <DataModel as SizeOf<T>>::size_of(self)
     }
-}
-
-impl<T, U> SizeOf<T> for U {
-    default fn size_of(self) -> usize {
-        0
+    /// align_of will report the natural alignment in bytes of one of the
+    /// types defined in this crate, which is its size. ABIs that align some
+    /// types more loosely inside records are described by
+    /// [`layout::Abi`].
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let model = DataModel::ILP32;
+    /// assert_eq!(model.align_of::<LongLong>(), 8);
+    /// ```
+    pub fn align_of<T>(self) -> usize {
+        self.size_of::<T>()
     }
-}
-
-impl SizeOf<Char> for DataModel {
-    fn size_of(self) -> usize {
+    /// align reports the natural alignment in bytes of a C type named at
+    /// runtime.
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LP64.align(CType::Pointer), 8);
+    /// ```
+    pub const fn align(self, ty: CType) -> usize {
+        self.size(ty)
+    }
+    /// size reports the size in bytes of a C type named at runtime.
+    /// Unsigned types have the size of their signed counterparts. It is 0
+    /// for a type the model lacks, see [`DataModel::has`].
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let model = DataModel::ILP32;
+    /// assert_eq!(model.size(CType::UInt), 4);
+    /// assert_eq!(model.size(CType::Double), 8);
+    /// ```
+    pub const fn size(self, ty: CType) -> usize {
+        SIZES[self as usize][ty as usize] as usize
+    }
+    /// has reports whether the model has the C type `ty`, telling a type
+    /// the platform lacks, like `long long` on IP16, apart from a size of
+    /// 0 computed by mistake. [`DataModel::Unknown`] has no types.
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert!(DataModel::IP16L32.has(CType::Long));
+    /// assert!(!DataModel::IP16L32.has(CType::LongLong));
+    /// ```
+    pub const fn has(self, ty: CType) -> bool {
+        self.size(ty) != 0
+    }
+    /// has_type reports whether the model has one of the types defined
+    /// in this crate, see [`DataModel::has`].
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert!(DataModel::LP64.has_type::<LongLong>());
+    /// assert!(!DataModel::IP16.has_type::<Long>());
+    /// assert!(!DataModel::LP64.has_type::<String>());
+    /// ```
+    pub fn has_type<T>(self) -> bool {
+        self.size_of::<T>() != 0
+    }
+    /// example_platforms names some platforms that use the model, the most
+    /// familiar first, for showing next to results. The list is empty for
+    /// [`DataModel::Unknown`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LLP64.example_platforms(), ["Win64"]);
+    /// assert_eq!(DataModel::SILP64.example_platforms(), ["Cray UNICOS"]);
+    /// ```
+    pub const fn example_platforms(self) -> &'static [&'static str] {
         use DataModel::*;
         match self {
-            IP16 | IP16L32 | LP32 | ILP32 | LLP64 | LP64 | ILP64 | SILP64 => 1,
-            Unknown => 0,
+            IP16 => &["16-bit PDP-11 Unix"],
+            IP16L32 => &["PDP-11 Unix V7", "MS-DOS small model"],
+            LP32 => &["Win16", "m68k Mac", "MS-DOS large model"],
+            ILP32 => &["Win32", "Linux i386", "Linux armhf", "WebAssembly wasm32"],
+            LLP64 => &["Win64"],
+            LP64 => &["Linux x86-64", "Linux AArch64", "macOS", "FreeBSD amd64"],
+            ILP64 => &["HAL/Fujitsu SPARC64"],
+            SILP64 => &["Cray UNICOS"],
+            Unknown => &[],
         }
     }
-}
-
-impl SizeOf<Short> for DataModel {
-    fn size_of(self) -> usize {
+    /// era tells whether the model is still in use. ILP32, LLP64 and LP64
+    /// are current, and so is [`DataModel::Unknown`], which describes
+    /// platforms yet to be classified.
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::ILP32.era(), Era::Current);
+    /// assert_eq!(DataModel::ILP64.era(), Era::Historical);
+    /// ```
+    pub const fn era(self) -> Era {
         use DataModel::*;
         match self {
-            IP16L32 | LP32 | ILP32 | LLP64 | LP64 | ILP64 => 2,
-            SILP64 => 8,
-            Unknown | IP16 => 0,
+            IP16 | IP16L32 | LP32 | ILP64 | SILP64 => Era::Historical,
+            ILP32 | LLP64 | LP64 | Unknown => Era::Current,
         }
     }
-}
-
-impl SizeOf<Int> for DataModel {
-    fn size_of(self) -> usize {
-        use DataModel::*;
-        match self {
-            IP16 | IP16L32 | LP32 => 2,
-            ILP32 | LLP64 | LP64 => 4,
-            ILP64 | SILP64 => 8,
-            Unknown => 0,
+    /// is_historical reports whether the model is only found on machines
+    /// and systems of the past. See [`DataModel::era`].
+    pub const fn is_historical(self) -> bool {
+        matches!(self.era(), Era::Historical)
+    }
+    /// types_with_size returns the C types that are `size` bytes wide, in
+    /// the order of [`CType::ALL`].
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let types = DataModel::LP64.types_with_size(4);
+    /// assert_eq!(types, [CType::Int, CType::UInt, CType::Float]);
+    /// ```
+    pub fn types_with_size(self, size: usize) -> Vec<CType> {
+        match size {
+            0 => Vec::new(),
+            _ => CType::ALL
+                .iter()
+                .copied()
+                .filter(|&ty| self.size(ty) == size)
+                .collect(),
         }
     }
-}
-
-impl SizeOf<Long> for DataModel {
-    fn size_of(self) -> usize {
-        use DataModel::*;
-        match self {
-            IP16L32 | LP32 | ILP32 | LLP64 => 4,
-            LP64 | ILP64 | SILP64 => 8,
-            Unknown | IP16 => 0,
+    /// types_with_align returns the C types whose natural alignment is
+    /// `align` bytes, in the order of [`CType::ALL`].
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let types = DataModel::ILP32.types_with_align(8);
+    /// assert_eq!(types, [CType::LongLong, CType::ULongLong, CType::Double]);
+    /// ```
+    pub fn types_with_align(self, align: usize) -> Vec<CType> {
+        match align {
+            0 => Vec::new(),
+            _ => CType::ALL
+                .iter()
+                .copied()
+                .filter(|&ty| self.align(ty) == align)
+                .collect(),
         }
     }
 }
 
-impl SizeOf<LongLong> for DataModel {
-    fn size_of(self) -> usize {
-        use DataModel::*;
-        match self {
-            LP32 | ILP32 | LLP64 | LP64 | ILP64 | SILP64 => 8,
-            Unknown | IP16 | IP16L32 => 0,
-        }
+impl<T, U> SizeOf<T> for U {
+    default fn size_of(self) -> usize {
+        0
     }
 }
 
-impl SizeOf<Pointer> for DataModel {
-    fn size_of(self) -> usize {
-        use DataModel::*;
-        match self {
-            IP16 | IP16L32 => 2,
-            LP32 | ILP32 => 4,
-            LLP64 | LP64 | ILP64 | SILP64 => 8,
-            Unknown => 0,
-        }
+/// CTypeOf maps the marker types to the C types they stand for.
+trait CTypeOf<T> {
+    fn c_type() -> Option<CType>;
+}
+
+impl<T, U> CTypeOf<T> for U {
+    default fn c_type() -> Option<CType> {
+        None
     }
 }
 
@@ -386,4 +964,143 @@ mod tests {
         assert_eq!(DataModel::LP64, DataModel::new(4, 8, 8));
         assert_eq!(DataModel::ILP64, DataModel::new(8, 8, 8));
     }
+
+    #[test]
+    fn test_size() {
+        use CType::*;
+        let model = DataModel::LP64;
+        assert_eq!(model.size(UChar), 1);
+        assert_eq!(model.size(UShort), 2);
+        assert_eq!(model.size(UInt), 4);
+        assert_eq!(model.size(ULong), 8);
+        assert_eq!(model.size(ULongLong), 8);
+        assert_eq!(model.size(Float), 4);
+        assert_eq!(DataModel::IP16.size(Long), 0);
+        assert_eq!(DataModel::Unknown.size(Double), 0);
+    }
+
+    #[test]
+    fn test_has() {
+        for &model in DataModel::ALL {
+            assert!(model.has(CType::Int) && model.has_type::<Pointer>());
+        }
+        assert!(!DataModel::IP16.has(CType::Short));
+        assert!(!DataModel::IP16L32.has_type::<ULongLong>());
+        assert!(!DataModel::Unknown.has(CType::Char));
+    }
+
+    #[test]
+    fn test_types_with_size() {
+        use CType::*;
+        assert_eq!(
+            DataModel::LLP64.types_with_size(8),
+            [LongLong, ULongLong, Pointer, Double]
+        );
+        assert_eq!(DataModel::IP16.types_with_size(2), [Int, UInt, Pointer]);
+        assert_eq!(DataModel::SILP64.types_with_align(2), []);
+        assert_eq!(DataModel::IP16.types_with_size(0), []);
+        assert_eq!(DataModel::Unknown.types_with_size(0), []);
+    }
+
+    #[test]
+    fn test_c_type_info() {
+        fn info<T: CTypeInfo>() -> (CType, &'static str, bool, usize, usize) {
+            (T::C_TYPE, T::C_NAME, T::SIGNED, T::RANK, T::MIN_BITS)
+        }
+        assert_eq!(info::<Char>(), (CType::Char, "char", true, 1, 8));
+        assert_eq!(
+            info::<UShort>(),
+            (CType::UShort, "unsigned short", false, 2, 16)
+        );
+        assert_eq!(
+            info::<LongLong>(),
+            (CType::LongLong, "long long", true, 5, 64)
+        );
+        assert_eq!(info::<Pointer>(), (CType::Pointer, "void *", false, 0, 16));
+        fn platform_signed<T: CTypeInfo>() -> bool {
+            T::PLATFORM_SIGNED
+        }
+        assert!(platform_signed::<Char>());
+        assert!(!platform_signed::<SChar>() && !platform_signed::<UChar>());
+        for &model in DataModel::ALL {
+            for &ty in CType::ALL {
+                let size = model.size(ty);
+                assert!(
+                    size == 0 || 8 * size >= ty.min_bits(),
+                    "{:?} {:?}",
+                    model,
+                    ty
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_era() {
+        let current: Vec<_> = DataModel::ALL
+            .iter()
+            .filter(|model| !model.is_historical())
+            .collect();
+        assert_eq!(
+            current,
+            [&DataModel::ILP32, &DataModel::LLP64, &DataModel::LP64]
+        );
+        for &model in DataModel::ALL {
+            assert!(!model.example_platforms().is_empty(), "{:?}", model);
+        }
+        assert!(DataModel::Unknown.example_platforms().is_empty());
+        assert_eq!(DataModel::Unknown.era(), Era::Current);
+    }
+
+    #[test]
+    fn test_filter() {
+        use DataModel::*;
+        assert_eq!(DataModel::with_pointer_size(2), [IP16, IP16L32]);
+        assert_eq!(
+            DataModel::with_pointer_size(8),
+            [LLP64, LP64, ILP64, SILP64]
+        );
+        assert_eq!(DataModel::with_long_size(4), [IP16L32, LP32, ILP32, LLP64]);
+        assert_eq!(DataModel::with_long_size(0), []);
+        assert_eq!(DataModel::with_size(CType::LongLong, 0), []);
+        assert_eq!(DataModel::with_size(CType::Double, 4), []);
+        let affected = DataModel::filter(|model| {
+            !model.is_historical() && model.size(CType::Long) != model.size(CType::Pointer)
+        });
+        assert_eq!(affected, [LLP64]);
+    }
+
+    #[test]
+    fn test_sizes_table() {
+        assert_eq!(SIZES.len(), DataModel::ALL.len() + 1);
+        assert_eq!(SIZES[0].len(), CType::ALL.len());
+        for (i, &model) in DataModel::ALL.iter().enumerate() {
+            assert_eq!(model as usize, i);
+        }
+        for (i, &ty) in CType::ALL.iter().enumerate() {
+            assert_eq!(ty as usize, i);
+        }
+        assert_eq!(DataModel::Unknown as usize, DataModel::ALL.len());
+    }
+
+    #[cfg(feature = "defmt")]
+    #[test]
+    fn test_defmt_format() {
+        fn format<T: defmt::Format>() {}
+        format::<DataModel>();
+        format::<CType>();
+        format::<Platform>();
+        format::<layout::LayoutError>();
+        format::<layout::FieldType>();
+        format::<layout::Layout>();
+        format::<target::AccessError>();
+        format::<literal::LiteralError>();
+        format::<llvm::ParseError>();
+        format::<json::JsonError>();
+        format::<registry::RegistryError>();
+        format::<format::FormatIssue>();
+        format::<decl::DeclError>();
+        format::<decl::TypeExpr>();
+        format::<dump::DumpError>();
+    }
 }
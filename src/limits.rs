@@ -0,0 +1,292 @@
+//! The constants of `limits.h` and `stdint.h`.
+//!
+//! Tools that bake in `INT_MAX` or `SIZE_MAX` usually bake in LP64. The
+//! limits here are computed from a data model, and can be written out as C
+//! macros or Rust constants for a chosen target.
+
+use crate::stdint::StdType;
+use crate::{CType, DataModel, SignedRepresentation, TargetModel};
+
+/// Limit is one of the limit macros, such as `INT_MAX`.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let limits = DataModel::ILP32.limits();
+/// let long_max = limits.iter().find(|limit| limit.name == "LONG_MAX").unwrap();
+/// assert_eq!(long_max.value, i32::MAX as i128);
+/// assert_eq!(long_max.ty, CType::Long);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limit {
+    /// The name of the macro.
+    pub name: &'static str,
+    /// The type of the constant, after the integer promotions.
+    pub ty: CType,
+    /// The value, which i128 holds for every limit up to `ULLONG_MAX`.
+    pub value: i128,
+}
+
+impl Limit {
+    /// c_literal spells the value as a C constant expression of its type,
+    /// e.g. `"4294967295U"` or `"(-2147483647 - 1)"`, as a two's complement
+    /// minimum has no positive counterpart to negate.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let limits = DataModel::LP64.limits();
+    /// let find = |name| limits.iter().find(|limit| limit.name == name).unwrap().c_literal();
+    /// assert_eq!(find("ULONG_MAX"), "18446744073709551615UL");
+    /// assert_eq!(find("INT_MIN"), "(-2147483647 - 1)");
+    /// ```
+    pub fn c_literal(&self) -> String {
+        use CType::*;
+        let suffix = match self.ty {
+            UInt => "U",
+            Long => "L",
+            ULong => "UL",
+            LongLong => "LL",
+            ULongLong => "ULL",
+            _ => "",
+        };
+        match self.value < 0 {
+            true if self.value.unsigned_abs().is_power_of_two() => {
+                format!("(-{}{} - 1)", -(self.value + 1), suffix)
+            }
+            true => format!("(-{}{})", -self.value, suffix),
+            false => format!("{}{}", self.value, suffix),
+        }
+    }
+}
+
+/// LIMITS are the limits of the base types in the order of `limits.h`,
+/// with the type whose minimum or maximum they are.
+const LIMITS: [(&str, CType, bool); 17] = [
+    ("SCHAR_MIN", CType::SChar, false),
+    ("SCHAR_MAX", CType::SChar, true),
+    ("UCHAR_MAX", CType::UChar, true),
+    ("CHAR_MIN", CType::Char, false),
+    ("CHAR_MAX", CType::Char, true),
+    ("SHRT_MIN", CType::Short, false),
+    ("SHRT_MAX", CType::Short, true),
+    ("USHRT_MAX", CType::UShort, true),
+    ("INT_MIN", CType::Int, false),
+    ("INT_MAX", CType::Int, true),
+    ("UINT_MAX", CType::UInt, true),
+    ("LONG_MIN", CType::Long, false),
+    ("LONG_MAX", CType::Long, true),
+    ("ULONG_MAX", CType::ULong, true),
+    ("LLONG_MIN", CType::LongLong, false),
+    ("LLONG_MAX", CType::LongLong, true),
+    ("ULLONG_MAX", CType::ULongLong, true),
+];
+
+/// STDINT_LIMITS are the limits of the pointer-sized and greatest-width
+/// typedefs of `stdint.h`.
+const STDINT_LIMITS: [(&str, StdType, bool); 9] = [
+    ("PTRDIFF_MIN", StdType::PtrDiff, false),
+    ("PTRDIFF_MAX", StdType::PtrDiff, true),
+    ("SIZE_MAX", StdType::Size, true),
+    ("INTPTR_MIN", StdType::IntPtr, false),
+    ("INTPTR_MAX", StdType::IntPtr, true),
+    ("UINTPTR_MAX", StdType::UIntPtr, true),
+    ("INTMAX_MIN", StdType::IntMax, false),
+    ("INTMAX_MAX", StdType::IntMax, true),
+    ("UINTMAX_MAX", StdType::UIntMax, true),
+];
+
+/// limits computes the limits of `model` with plain `char` of the given
+/// signedness and signed integers in the representation `repr`.
+fn limits(model: DataModel, char_signed: bool, repr: SignedRepresentation) -> Vec<Limit> {
+    let mut limits = Vec::new();
+    if model.size(CType::Char) != 0 {
+        limits.push(Limit {
+            name: "CHAR_BIT",
+            ty: CType::Int,
+            value: 8,
+        });
+    }
+    let base = LIMITS.iter().map(|&(name, ty, max)| (name, Some(ty), max));
+    let stdint = STDINT_LIMITS
+        .iter()
+        .map(|&(name, ty, max)| (name, model.resolve(ty), max));
+    for (name, ty, max) in base.chain(stdint) {
+        let ty = match ty {
+            Some(ty) if model.has(ty) => ty,
+            _ => continue,
+        };
+        let bits = 8 * model.size(ty);
+        let signed = match ty {
+            CType::Char => char_signed,
+            _ => ty.is_signed(),
+        };
+        let value = match (signed, max) {
+            (true, true) => repr.max(bits),
+            (true, false) => repr.min(bits),
+            (false, true) => (1 << bits) - 1,
+            (false, false) => 0,
+        };
+        limits.push(Limit {
+            name,
+            ty: model.promote(ty),
+            value,
+        });
+    }
+    limits
+}
+
+impl DataModel {
+    /// limits returns the limit macros of `limits.h`, from `CHAR_BIT` to
+    /// `ULLONG_MAX`, and the `PTRDIFF`, `SIZE`, `INTPTR` and `INTMAX`
+    /// limits of `stdint.h`, leaving out types the model lacks. Plain
+    /// `char` is taken to be signed.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let limits = DataModel::LLP64.limits();
+    /// let value = |name| limits.iter().find(|limit| limit.name == name).map(|limit| limit.value);
+    /// assert_eq!(value("LONG_MAX"), Some(i32::MAX as i128));
+    /// assert_eq!(value("SIZE_MAX"), Some(u64::MAX as i128));
+    /// assert_eq!(DataModel::IP16.limits().iter().find(|limit| limit.name == "LONG_MAX"), None);
+    /// ```
+    pub fn limits(self) -> Vec<Limit> {
+        limits(self, true, self.signed_representation())
+    }
+
+    /// c_limits emits a C header defining the limits as macros named with
+    /// `prefix`, so it can be used next to the host's `limits.h`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let header = DataModel::ILP32.c_limits("ILP32_");
+    /// assert!(header.contains("#define ILP32_LONG_MAX 2147483647L\n"));
+    /// assert!(header.contains("#define ILP32_SIZE_MAX 4294967295U\n"));
+    /// ```
+    pub fn c_limits(self, prefix: &str) -> String {
+        let mut src = format!("/* Limits of the {:?} data model. */\n", self);
+        for limit in self.limits() {
+            src += &format!("#define {}{} {}\n", prefix, limit.name, limit.c_literal());
+        }
+        src
+    }
+
+    /// rust_limits emits Rust source for a module named `module` defining
+    /// the limits as constants of the Rust types matching the model.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let src = DataModel::LP64.rust_limits("limits");
+    /// assert!(src.contains("pub mod limits {"));
+    /// assert!(src.contains("    pub const LONG_MIN: i64 = -9223372036854775808;\n"));
+    /// assert!(src.contains("    pub const UCHAR_MAX: i32 = 255;\n"));
+    /// ```
+    pub fn rust_limits(self, module: &str) -> String {
+        let mut src = format!(
+            "/// Limits of the {:?} data model.\npub mod {} {{\n",
+            self, module
+        );
+        for limit in self.limits() {
+            if let Some(rust) = self.rust_type_of(limit.ty) {
+                src += &format!(
+                    "    pub const {}: {} = {};\n",
+                    limit.name, rust, limit.value
+                );
+            }
+        }
+        src + "}\n"
+    }
+}
+
+impl TargetModel {
+    /// limits returns the limits of the target, with `CHAR_MIN` and
+    /// `CHAR_MAX` following the signedness of its plain `char` and the
+    /// minimums following its signed representation. See
+    /// [`DataModel::limits`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let limits = Platform::LinuxAarch64.target().limits();
+    /// let char_min = limits.iter().find(|limit| limit.name == "CHAR_MIN").unwrap();
+    /// assert_eq!(char_min.value, 0);
+    /// ```
+    pub fn limits(&self) -> Vec<Limit> {
+        limits(
+            self.data_model(),
+            self.char_signed,
+            self.signed_representation,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(limits: &[Limit], name: &str) -> Option<i128> {
+        limits
+            .iter()
+            .find(|limit| limit.name == name)
+            .map(|limit| limit.value)
+    }
+
+    #[test]
+    fn test_limits() {
+        let lp64 = DataModel::LP64.limits();
+        assert_eq!(lp64.len(), 27);
+        assert_eq!(value(&lp64, "CHAR_BIT"), Some(8));
+        assert_eq!(value(&lp64, "CHAR_MIN"), Some(-128));
+        assert_eq!(value(&lp64, "SHRT_MIN"), Some(i16::MIN as i128));
+        assert_eq!(value(&lp64, "USHRT_MAX"), Some(u16::MAX as i128));
+        assert_eq!(value(&lp64, "UINT_MAX"), Some(u32::MAX as i128));
+        assert_eq!(value(&lp64, "LONG_MIN"), Some(i64::MIN as i128));
+        assert_eq!(value(&lp64, "ULLONG_MAX"), Some(u64::MAX as i128));
+        assert_eq!(value(&lp64, "PTRDIFF_MIN"), Some(i64::MIN as i128));
+        assert_eq!(value(&lp64, "UINTMAX_MAX"), Some(u64::MAX as i128));
+
+        let ip16 = DataModel::IP16.limits();
+        assert_eq!(value(&ip16, "INT_MAX"), Some(i16::MAX as i128));
+        assert_eq!(value(&ip16, "SIZE_MAX"), Some(u16::MAX as i128));
+        assert_eq!(value(&ip16, "SHRT_MAX"), None);
+        assert_eq!(value(&ip16, "LLONG_MAX"), None);
+        assert!(DataModel::Unknown.limits().is_empty());
+
+        // USHRT_MAX does not fit a 16-bit int.
+        let ushrt_max = DataModel::LP32
+            .limits()
+            .into_iter()
+            .find(|limit| limit.name == "USHRT_MAX")
+            .unwrap();
+        assert_eq!(ushrt_max.ty, CType::UInt);
+        assert_eq!(ushrt_max.c_literal(), "65535U");
+    }
+
+    #[test]
+    fn test_generated_sources() {
+        let header = DataModel::LP32.c_limits("");
+        assert!(header.contains("#define INT_MIN (-32767 - 1)\n"));
+        assert!(header.contains("#define LONG_MIN (-2147483647L - 1)\n"));
+        assert!(header.contains("#define ULLONG_MAX 18446744073709551615ULL\n"));
+        let src = DataModel::ILP32.rust_limits("c");
+        assert!(src.contains("    pub const CHAR_BIT: i32 = 8;\n"));
+        assert!(src.contains("    pub const SIZE_MAX: u32 = 4294967295;\n"));
+        assert!(src.contains("    pub const INTMAX_MIN: i64 = -9223372036854775808;\n"));
+    }
+
+    #[test]
+    fn test_ones_complement_limits() {
+        let target = TargetModel::new(DataModel::ILP32)
+            .with_signed_representation(SignedRepresentation::OnesComplement);
+        let limits = target.limits();
+        assert_eq!(value(&limits, "SCHAR_MIN"), Some(-127));
+        assert_eq!(value(&limits, "INT_MIN"), Some(-(i32::MAX as i128)));
+        assert_eq!(value(&limits, "INT_MAX"), Some(i32::MAX as i128));
+        assert_eq!(value(&limits, "UINT_MAX"), Some(u32::MAX as i128));
+        let int_min = limits.iter().find(|limit| limit.name == "INT_MIN").unwrap();
+        assert_eq!(int_min.c_literal(), "(-2147483647)");
+    }
+}
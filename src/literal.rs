@@ -0,0 +1,249 @@
+//! The types of integer constants.
+//!
+//! C gives an unsuffixed constant the first type of a list that can hold
+//! its value, so `4000000000` is a `long` on LP64 but a `long long` on
+//! ILP32, and was an `unsigned long` in C89. Octal and hexadecimal
+//! constants may also take unsigned types, which is how `0xffffffff`
+//! silently becomes `unsigned int` where `int` is 32 bits. C23 adds binary
+//! constants like `0b1010` and digit separators like `1'000'000`.
+
+use crate::{CStandard, CType, DataModel};
+use std::error::Error;
+use std::fmt;
+
+/// LiteralError describes why an integer constant has no type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LiteralError {
+    /// The text is not an integer constant, or uses a suffix the standard
+    /// does not have, like `LL` in C89.
+    Malformed {
+        /// The constant as written.
+        literal: String,
+    },
+    /// The value does not fit any of the candidate types.
+    TooLarge {
+        /// The constant as written.
+        literal: String,
+    },
+}
+
+impl fmt::Display for LiteralError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiteralError::Malformed { literal } => {
+                write!(f, "{} is not an integer constant", literal)
+            }
+            LiteralError::TooLarge { literal } => {
+                write!(f, "{} is too large for any integer type", literal)
+            }
+        }
+    }
+}
+
+impl Error for LiteralError {}
+
+/// parse splits an integer constant into its value, whether it is decimal
+/// and its suffix in lowercase. Binary constants and digit separators are
+/// only accepted in C23.
+fn parse(literal: &str, std: CStandard) -> Option<(Option<u128>, bool, String)> {
+    let c23 = std >= CStandard::C23;
+    let bytes = literal.as_bytes();
+    for (i, _) in literal.match_indices('\'') {
+        // A separator goes between two digits, never after the prefix.
+        let between = i > 0 && i + 1 < bytes.len();
+        let digits =
+            between && bytes[i - 1].is_ascii_hexdigit() && bytes[i + 1].is_ascii_hexdigit();
+        if !c23 || !digits || matches!(&literal[..i], "0b" | "0B") {
+            return None;
+        }
+    }
+    let literal = &literal.replace('\'', "");
+    let end = literal
+        .find(|c: char| c != 'x' && c != 'X' && !c.is_ascii_hexdigit())
+        .unwrap_or(literal.len());
+    // Hexadecimal digits can swallow no suffix letter, as they lack u and l.
+    let (number, suffix) = literal.split_at(end);
+    let (digits, radix) = match number.get(..2) {
+        Some("0x") | Some("0X") => (&number[2..], 16),
+        Some("0b") | Some("0B") if c23 => (&number[2..], 2),
+        _ if number.starts_with('0') && number.len() > 1 => (&number[1..], 8),
+        _ => (number, 10),
+    };
+    let valid = match radix {
+        16 => digits.chars().all(|c| c.is_ascii_hexdigit()),
+        8 => digits.chars().all(|c| c.is_digit(8)),
+        2 => digits.chars().all(|c| c.is_digit(2)),
+        _ => digits.chars().all(|c| c.is_ascii_digit()),
+    };
+    let lower = suffix.to_lowercase();
+    let suffixes = ["", "u", "l", "ll", "ul", "lu", "ull", "llu"];
+    if digits.is_empty() || !valid || !suffixes.contains(&lower.as_str()) {
+        return None;
+    }
+    if suffix.contains("lL") || suffix.contains("Ll") {
+        return None;
+    }
+    let value = u128::from_str_radix(digits, radix).ok();
+    Some((value, radix == 10, lower))
+}
+
+impl DataModel {
+    /// literal_type returns the type of the integer constant `literal`,
+    /// written as in C source with its base prefix and suffix, under the
+    /// rules of the standard `std`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LP64.literal_type("4000000000", CStandard::C99), Ok(CType::Long));
+    /// assert_eq!(DataModel::ILP32.literal_type("4000000000", CStandard::C99), Ok(CType::LongLong));
+    /// assert_eq!(DataModel::ILP32.literal_type("4000000000", CStandard::C89), Ok(CType::ULong));
+    /// assert_eq!(DataModel::ILP32.literal_type("0xffffffff", CStandard::C99), Ok(CType::UInt));
+    /// assert_eq!(DataModel::LP32.literal_type("40000u", CStandard::C99), Ok(CType::UInt));
+    /// assert_eq!(DataModel::LP32.literal_type("0b1'0000'0000'0000'0000", CStandard::C23), Ok(CType::Long));
+    /// ```
+    pub fn literal_type(self, literal: &str, std: CStandard) -> Result<CType, LiteralError> {
+        use CType::*;
+        let malformed = || LiteralError::Malformed {
+            literal: literal.to_string(),
+        };
+        let too_large = || LiteralError::TooLarge {
+            literal: literal.to_string(),
+        };
+        let (value, decimal, suffix) = parse(literal, std).ok_or_else(malformed)?;
+        let value = value.ok_or_else(too_large)?;
+        let c89 = std == CStandard::C89;
+        let candidates: &[CType] = match (suffix.as_str(), decimal, c89) {
+            ("", true, true) => &[Int, Long, ULong],
+            ("", true, false) => &[Int, Long, LongLong],
+            ("", false, true) => &[Int, UInt, Long, ULong],
+            ("", false, false) => &[Int, UInt, Long, ULong, LongLong, ULongLong],
+            ("u", _, true) => &[UInt, ULong],
+            ("u", _, false) => &[UInt, ULong, ULongLong],
+            ("l", _, true) => &[Long, ULong],
+            ("l", true, false) => &[Long, LongLong],
+            ("l", false, false) => &[Long, ULong, LongLong, ULongLong],
+            ("ul" | "lu", _, true) => &[ULong],
+            ("ul" | "lu", _, false) => &[ULong, ULongLong],
+            (_, _, true) => return Err(malformed()),
+            ("ll", true, _) => &[LongLong],
+            ("ll", false, _) => &[LongLong, ULongLong],
+            _ => &[ULongLong],
+        };
+        candidates
+            .iter()
+            .copied()
+            .filter(|&ty| self.has(ty))
+            .find(|&ty| value >> (8 * self.size(ty) - ty.is_signed() as usize) == 0)
+            .ok_or_else(too_large)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal_type() {
+        use CType::*;
+        let cases = [
+            ("0", [Int, Int, Int]),
+            ("32767", [Int, Int, Int]),
+            ("32768", [Long, Int, Int]),
+            ("0x8000", [UInt, Int, Int]),
+            ("2147483648", [LongLong, LongLong, Long]),
+            ("0x80000000", [ULong, UInt, UInt]),
+            ("020000000000", [ULong, UInt, UInt]),
+            ("0xffffffffffffffff", [ULongLong, ULongLong, ULong]),
+            ("1U", [UInt, UInt, UInt]),
+            ("4294967296u", [ULongLong, ULongLong, ULong]),
+            ("1l", [Long, Long, Long]),
+            ("0x80000000L", [ULong, ULong, Long]),
+            ("1uL", [ULong, ULong, ULong]),
+            ("1LU", [ULong, ULong, ULong]),
+            ("1ll", [LongLong, LongLong, LongLong]),
+            ("0xffffffffffffffffLL", [ULongLong, ULongLong, ULongLong]),
+            ("1ULL", [ULongLong, ULongLong, ULongLong]),
+            ("1llu", [ULongLong, ULongLong, ULongLong]),
+        ];
+        let models = [DataModel::LP32, DataModel::ILP32, DataModel::LP64];
+        for &(literal, expected) in cases.iter() {
+            for (model, &ty) in models.iter().zip(expected.iter()) {
+                assert_eq!(
+                    model.literal_type(literal, CStandard::C11),
+                    Ok(ty),
+                    "{} {:?}",
+                    literal,
+                    model
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_c89() {
+        use CType::*;
+        let model = DataModel::ILP32;
+        let c89 = |literal| model.literal_type(literal, CStandard::C89);
+        assert_eq!(c89("2147483648"), Ok(ULong));
+        assert_eq!(c89("0x80000000"), Ok(UInt));
+        assert_eq!(c89("2147483648l"), Ok(ULong));
+        assert_eq!(
+            c89("1ll"),
+            Err(LiteralError::Malformed {
+                literal: "1ll".to_string()
+            })
+        );
+        assert_eq!(
+            c89("4294967296"),
+            Err(LiteralError::TooLarge {
+                literal: "4294967296".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_c23() {
+        use CType::*;
+        let model = DataModel::ILP32;
+        let c23 = |literal| model.literal_type(literal, CStandard::C23);
+        assert_eq!(c23("0b11111111111111111111111111111111"), Ok(UInt));
+        assert_eq!(c23("0B101ull"), Ok(ULongLong));
+        assert_eq!(c23("4'294'967'295"), Ok(LongLong));
+        assert_eq!(c23("0xff'ff"), Ok(Int));
+        for literal in ["0b102", "1''0", "10'", "'1", "0x'ff", "0b'1", "1'u", "0b"] {
+            assert!(c23(literal).is_err(), "{}", literal);
+        }
+        for literal in ["0b101", "1'000"] {
+            assert!(
+                model.literal_type(literal, CStandard::C17).is_err(),
+                "{}",
+                literal
+            );
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let model = DataModel::LP64;
+        for literal in ["", "0x", "12a", "09", "1lL", "1uu", "1lul", "-1", "1.0"] {
+            assert_eq!(
+                model.literal_type(literal, CStandard::C99),
+                Err(LiteralError::Malformed {
+                    literal: literal.to_string()
+                }),
+                "{}",
+                literal
+            );
+        }
+        assert!(matches!(
+            model.literal_type("18446744073709551616", CStandard::C99),
+            Err(LiteralError::TooLarge { .. })
+        ));
+        assert!(matches!(
+            DataModel::IP16.literal_type("70000", CStandard::C99),
+            Err(LiteralError::TooLarge { .. })
+        ));
+    }
+}
@@ -0,0 +1,466 @@
+//! LLVM target data layout strings.
+//!
+//! Compilers built on LLVM describe every target with a data layout string
+//! such as `"e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128"`.
+//! It carries the byte order, the pointer width and the alignment of each
+//! integer and floating point width, which is most of what this crate
+//! describes with a [`DataModel`].
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! let layout = DataModel::from_llvm_datalayout("e-m:w-p270:32:32-i64:64-n8:16:32:64-S128").unwrap();
+//! assert_eq!(layout.model, DataModel::LLP64);
+//! assert_eq!(layout.endianness, Endianness::Little);
+//! assert_eq!(layout.integer_align(64), 8);
+//! ```
+//!
+//! Going the other way, a [`DataLayout`] built from a model prints as a data
+//! layout string:
+//!
+//! ```
+//! use data_models::*;
+//! use data_models::llvm::DataLayout;
+//! let layout = DataLayout::new(DataModel::LP64, Endianness::Little);
+//! assert_eq!(layout.to_string(), "e-m:e-i64:64-i128:128-n8:16:32:64-S128");
+//! ```
+
+use crate::{DataModel, Endianness, Pointer};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Mangling is the symbol mangling style named by the `m:` specification.
+///
+/// It is the only part of a data layout string that tells the object format
+/// and therefore separates Windows (LLP64) from Unix (LP64) targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Mangling {
+    /// `m:e` ELF.
+    Elf,
+    /// `m:l` GOFF.
+    Goff,
+    /// `m:m` MIPS.
+    Mips,
+    /// `m:o` Mach-O.
+    MachO,
+    /// `m:x` Windows x86 COFF.
+    WindowsX86Coff,
+    /// `m:w` Windows COFF.
+    WindowsCoff,
+    /// `m:a` XCOFF.
+    XCoff,
+}
+
+impl Mangling {
+    fn code(self) -> char {
+        use Mangling::*;
+        match self {
+            Elf => 'e',
+            Goff => 'l',
+            Mips => 'm',
+            MachO => 'o',
+            WindowsX86Coff => 'x',
+            WindowsCoff => 'w',
+            XCoff => 'a',
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Mangling> {
+        use Mangling::*;
+        Some(match code {
+            "e" => Elf,
+            "l" => Goff,
+            "m" => Mips,
+            "o" => MachO,
+            "x" => WindowsX86Coff,
+            "w" => WindowsCoff,
+            "a" => XCoff,
+            _ => return None,
+        })
+    }
+
+    /// is_windows reports whether the mangling is used by Windows targets.
+    pub fn is_windows(self) -> bool {
+        matches!(self, Mangling::WindowsX86Coff | Mangling::WindowsCoff)
+    }
+}
+
+/// DataLayout is the model/ABI descriptor of an LLVM data layout string.
+///
+/// Sizes and alignments are in bytes like everywhere else in this crate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataLayout {
+    /// The data model implied by the pointer width and mangling.
+    pub model: DataModel,
+    /// Byte order, `e` or `E`.
+    pub endianness: Endianness,
+    /// Size of a pointer in the default address space.
+    pub pointer_size: usize,
+    /// ABI alignment of a pointer in the default address space.
+    pub pointer_align: usize,
+    /// ABI alignment of integers as (width in bits, alignment) sorted by width.
+    pub integer_aligns: Vec<(usize, usize)>,
+    /// ABI alignment of floating point types as (width in bits, alignment)
+    /// sorted by width.
+    pub float_aligns: Vec<(usize, usize)>,
+    /// Native integer widths of the CPU in bits.
+    pub native_integers: Vec<usize>,
+    /// Natural stack alignment, if specified.
+    pub stack_align: Option<usize>,
+    /// Symbol mangling, if specified.
+    pub mangling: Option<Mangling>,
+}
+
+impl Default for DataLayout {
+    /// The layout LLVM assumes for an empty data layout string.
+    fn default() -> DataLayout {
+        DataLayout {
+            model: DataModel::LP64,
+            endianness: Endianness::Little,
+            pointer_size: 8,
+            pointer_align: 8,
+            integer_aligns: vec![(1, 1), (8, 1), (16, 2), (32, 4), (64, 4)],
+            float_aligns: vec![(16, 2), (32, 4), (64, 8), (128, 16)],
+            native_integers: vec![],
+            stack_align: None,
+            mangling: None,
+        }
+    }
+}
+
+/// ParseError describes why a data layout string was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ParseError {
+    /// The specification is not one LLVM defines.
+    UnknownSpec(String),
+    /// A size or alignment is not a number or not a multiple of 8 bits.
+    InvalidNumber(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnknownSpec(spec) => {
+                write!(f, "unknown data layout specification {:?}", spec)
+            }
+            ParseError::InvalidNumber(spec) => write!(f, "invalid size or alignment in {:?}", spec),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+fn bits(spec: &str, value: &str) -> Result<usize, ParseError> {
+    value
+        .parse()
+        .map_err(|_| ParseError::InvalidNumber(spec.to_string()))
+}
+
+fn bytes(spec: &str, value: &str) -> Result<usize, ParseError> {
+    match bits(spec, value)? {
+        b if b % 8 == 0 => Ok(b / 8),
+        _ => Err(ParseError::InvalidNumber(spec.to_string())),
+    }
+}
+
+/// set replaces or inserts the alignment of a width, keeping the table sorted.
+fn set(table: &mut Vec<(usize, usize)>, width: usize, align: usize) {
+    match table.binary_search_by_key(&width, |&(w, _)| w) {
+        Ok(i) => table[i].1 = align,
+        Err(i) => table.insert(i, (width, align)),
+    }
+}
+
+impl DataLayout {
+    /// new builds a best-effort layout for a data model with naturally
+    /// aligned integers.
+    ///
+    /// LLP64 targets get Windows COFF mangling and everything else ELF so
+    /// that ILP32, LLP64 and LP64 survive a round trip through the string;
+    /// models that differ only in `int` or `long` (LP32 vs ILP32) cannot,
+    /// since the string does not describe those types. Adjust the
+    /// fields for targets whose ABI under-aligns types, such as `i64:32` on
+    /// i386 System V.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::llvm::DataLayout;
+    /// let layout = DataLayout::new(DataModel::ILP32, Endianness::Big);
+    /// assert_eq!(layout.to_string(), "E-m:e-p:32:32-i64:64-i128:128-n8:16:32-S64");
+    /// ```
+    pub fn new(model: DataModel, endianness: Endianness) -> DataLayout {
+        let pointer = model.size_of::<Pointer>();
+        let defaults = DataLayout::default();
+        let mut layout = DataLayout {
+            model,
+            endianness,
+            pointer_size: pointer,
+            pointer_align: pointer,
+            native_integers: [8, 16, 32, 64]
+                .iter()
+                .copied()
+                .filter(|&w| w <= pointer * 8)
+                .collect(),
+            stack_align: Some(pointer * 2),
+            mangling: Some(match model {
+                DataModel::LLP64 => Mangling::WindowsCoff,
+                _ => Mangling::Elf,
+            }),
+            ..defaults
+        };
+        set(&mut layout.integer_aligns, 64, 8);
+        set(&mut layout.integer_aligns, 128, 16);
+        layout
+    }
+
+    /// integer_align returns the ABI alignment of an integer of `bits` width.
+    ///
+    /// Like LLVM, a width without its own entry uses the next larger
+    /// specified width, or the largest one if it is wider than all of them.
+    pub fn integer_align(&self, bits: usize) -> usize {
+        self.integer_aligns
+            .iter()
+            .find(|&&(w, _)| w >= bits)
+            .or_else(|| self.integer_aligns.last())
+            .map_or(1, |&(_, a)| a)
+    }
+
+    /// float_align returns the ABI alignment of a floating point type of
+    /// `bits` width, or its size if the layout does not mention it.
+    pub fn float_align(&self, bits: usize) -> usize {
+        self.float_aligns
+            .iter()
+            .find(|&&(w, _)| w == bits)
+            .map_or(bits / 8, |&(_, a)| a)
+    }
+
+    /// infer_model guesses the data model from the pointer width and, for
+    /// 64-bit pointers, from whether the target mangles like Windows.
+    fn infer_model(&self) -> DataModel {
+        let windows = self.mangling.is_some_and(Mangling::is_windows);
+        match self.pointer_size {
+            2 => DataModel::IP16L32,
+            4 => DataModel::ILP32,
+            8 if windows => DataModel::LLP64,
+            8 => DataModel::LP64,
+            _ => DataModel::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for DataLayout {
+    /// Formats the layout as an LLVM data layout string, leaving out the
+    /// specifications that match LLVM's defaults.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let defaults = DataLayout::default();
+        let mut specs = vec![match self.endianness {
+            Endianness::Big => "E".to_string(),
+            _ => "e".to_string(),
+        }];
+        if let Some(mangling) = self.mangling {
+            specs.push(format!("m:{}", mangling.code()));
+        }
+        if (self.pointer_size, self.pointer_align)
+            != (defaults.pointer_size, defaults.pointer_align)
+        {
+            specs.push(format!(
+                "p:{}:{}",
+                self.pointer_size * 8,
+                self.pointer_align * 8
+            ));
+        }
+        for (kind, table, default) in [
+            ('i', &self.integer_aligns, &defaults.integer_aligns),
+            ('f', &self.float_aligns, &defaults.float_aligns),
+        ] {
+            for &(width, align) in table {
+                if !default.contains(&(width, align)) {
+                    specs.push(format!("{}{}:{}", kind, width, align * 8));
+                }
+            }
+        }
+        if !self.native_integers.is_empty() {
+            let widths: Vec<String> = self.native_integers.iter().map(|w| w.to_string()).collect();
+            specs.push(format!("n{}", widths.join(":")));
+        }
+        if let Some(align) = self.stack_align {
+            specs.push(format!("S{}", align * 8));
+        }
+        write!(f, "{}", specs.join("-"))
+    }
+}
+
+impl FromStr for DataLayout {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<DataLayout, ParseError> {
+        let mut layout = DataLayout::default();
+        for spec in s.split('-').filter(|spec| !spec.is_empty()) {
+            let unknown = || ParseError::UnknownSpec(spec.to_string());
+            let mut parts = spec.split(':');
+            let head = parts.next().unwrap_or_default();
+            let fields: Vec<&str> = parts.collect();
+            if head == "ni" {
+                // Non-integral address spaces do not affect C scalar types.
+                continue;
+            }
+            if head.is_empty() || !head.is_char_boundary(1) {
+                return Err(unknown());
+            }
+            let (kind, rest) = head.split_at(1);
+            match kind {
+                "e" if rest.is_empty() => layout.endianness = Endianness::Little,
+                "E" if rest.is_empty() => layout.endianness = Endianness::Big,
+                "m" => {
+                    let code = fields.first().ok_or_else(unknown)?;
+                    layout.mangling = Some(Mangling::from_code(code).ok_or_else(unknown)?);
+                }
+                "S" => layout.stack_align = Some(bytes(spec, rest)?),
+                "p" => {
+                    // Only the default address space describes `void *`.
+                    if !rest.is_empty() && rest != "0" {
+                        continue;
+                    }
+                    layout.pointer_size = bytes(spec, fields.first().ok_or_else(unknown)?)?;
+                    layout.pointer_align = match fields.get(1) {
+                        Some(abi) => bytes(spec, abi)?,
+                        None => layout.pointer_size,
+                    };
+                }
+                "i" | "f" => {
+                    let width = bits(spec, rest)?;
+                    let align = match fields.first() {
+                        Some(abi) => bytes(spec, abi)?,
+                        None => width.div_ceil(8),
+                    };
+                    let table = match kind {
+                        "i" => &mut layout.integer_aligns,
+                        _ => &mut layout.float_aligns,
+                    };
+                    set(table, width, align);
+                }
+                "n" => {
+                    layout.native_integers = std::iter::once(rest)
+                        .chain(fields.iter().copied())
+                        .map(|w| bits(spec, w))
+                        .collect::<Result<_, _>>()?;
+                }
+                // Vector, aggregate, function pointer and address space
+                // specifications do not affect C scalar types.
+                "v" | "a" | "F" | "P" | "A" | "G" => {}
+                _ => return Err(unknown()),
+            }
+        }
+        layout.model = layout.infer_model();
+        Ok(layout)
+    }
+}
+
+impl DataModel {
+    /// from_llvm_datalayout parses an LLVM data layout string into a
+    /// descriptor holding the data model, byte order, pointer width and
+    /// type alignments of the target.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let layout = DataModel::from_llvm_datalayout("E-m:e-p:32:32-i64:64-n32").unwrap();
+    /// assert_eq!(layout.model, DataModel::ILP32);
+    /// assert_eq!(layout.endianness, Endianness::Big);
+    /// assert_eq!(layout.pointer_size, 4);
+    /// ```
+    pub fn from_llvm_datalayout(s: &str) -> Result<DataLayout, ParseError> {
+        s.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_x86_64_linux() {
+        let layout = DataModel::from_llvm_datalayout(
+            "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128",
+        )
+        .unwrap();
+        assert_eq!(layout.model, DataModel::LP64);
+        assert_eq!(layout.endianness, Endianness::Little);
+        assert_eq!(layout.mangling, Some(Mangling::Elf));
+        assert_eq!((layout.pointer_size, layout.pointer_align), (8, 8));
+        assert_eq!(layout.integer_align(32), 4);
+        assert_eq!(layout.integer_align(64), 8);
+        assert_eq!(layout.integer_align(128), 16);
+        assert_eq!(layout.float_align(80), 16);
+        assert_eq!(layout.native_integers, vec![8, 16, 32, 64]);
+        assert_eq!(layout.stack_align, Some(16));
+    }
+
+    #[test]
+    fn test_i386_linux() {
+        let layout = DataModel::from_llvm_datalayout(
+            "e-m:e-p:32:32-p270:32:32-p271:32:32-p272:64:64-i128:128-f64:32:64-f80:32-n8:16:32-S128",
+        )
+        .unwrap();
+        assert_eq!(layout.model, DataModel::ILP32);
+        assert_eq!(layout.pointer_size, 4);
+        // i64 is not specified so LLVM's default 4-byte alignment applies.
+        assert_eq!(layout.integer_align(64), 4);
+        assert_eq!(layout.float_align(64), 4);
+    }
+
+    #[test]
+    fn test_windows_and_avr() {
+        let layout =
+            DataModel::from_llvm_datalayout("e-m:w-p270:32:32-i64:64-n8:16:32:64-S128").unwrap();
+        assert_eq!(layout.model, DataModel::LLP64);
+
+        let layout = DataModel::from_llvm_datalayout(
+            "e-P1-p:16:8-i8:8-i16:8-i32:8-i64:8-f32:8-f64:8-n8-a:8",
+        )
+        .unwrap();
+        assert_eq!(layout.model, DataModel::IP16L32);
+        assert_eq!(layout.pointer_align, 1);
+        assert_eq!(layout.integer_align(32), 1);
+    }
+
+    #[test]
+    fn test_display() {
+        let s = "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128";
+        let layout = DataModel::from_llvm_datalayout(s).unwrap();
+        assert_eq!(
+            layout.to_string(),
+            "e-m:e-i64:64-i128:128-f80:128-n8:16:32:64-S128"
+        );
+        assert_eq!(layout.to_string().parse(), Ok(layout));
+    }
+
+    #[test]
+    fn test_round_trip_models() {
+        use DataModel::*;
+        for &model in &[ILP32, LLP64, LP64] {
+            for &endianness in &[Endianness::Little, Endianness::Big] {
+                let layout = DataLayout::new(model, endianness);
+                assert_eq!(layout.to_string().parse(), Ok(layout));
+            }
+        }
+        let layout = DataLayout::new(LLP64, Endianness::Little);
+        assert_eq!(layout.to_string(), "e-m:w-i64:64-i128:128-n8:16:32:64-S128");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            DataModel::from_llvm_datalayout("e-q:32"),
+            Err(ParseError::UnknownSpec("q:32".to_string()))
+        );
+        assert_eq!(
+            DataModel::from_llvm_datalayout("e-p:33:32"),
+            Err(ParseError::InvalidNumber("p:33:32".to_string()))
+        );
+    }
+}
@@ -0,0 +1,510 @@
+//! The size and alignment of every C type in every data model.
+//!
+//! Documentation tables, spreadsheets and regression baselines all start
+//! from the same matrix, which [`rows`] returns typed and [`csv`] writes
+//! out.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::matrix;
+//! let rows = matrix::rows();
+//! let row = rows
+//!     .iter()
+//!     .find(|row| row.model == DataModel::LLP64 && row.ty == CType::Long)
+//!     .unwrap();
+//! assert_eq!((row.size, row.align), (4, 4));
+//! assert!(matrix::csv().contains("\nLP64,long,8,8\n"));
+//! ```
+//!
+//! [`render_table`] draws the classic comparison of a few models for a
+//! report.
+
+use crate::{CType, DataModel};
+
+/// Row is the size and alignment of one type in one data model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Row {
+    /// The data model.
+    pub model: DataModel,
+    /// The C type.
+    pub ty: CType,
+    /// `sizeof` the type, or 0 if the model lacks it.
+    pub size: usize,
+    /// The natural alignment of the type, or 0 if the model lacks it.
+    pub align: usize,
+}
+
+/// GOLDEN is every `(model, type, size, align)` of [`rows`] written out,
+/// so tests here and downstream check against one dataset that is diffed
+/// from release to release rather than recomputed. Types a model lacks
+/// have size and alignment 0.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::matrix::GOLDEN;
+/// for &(model, ty, size, align) in GOLDEN {
+///     assert_eq!((model.size(ty), model.align(ty)), (size, align));
+/// }
+/// ```
+#[rustfmt::skip]
+pub const GOLDEN: &[(DataModel, CType, usize, usize)] = &[
+    (DataModel::IP16, CType::Bool, 1, 1),
+    (DataModel::IP16, CType::Char, 1, 1),
+    (DataModel::IP16, CType::SChar, 1, 1),
+    (DataModel::IP16, CType::UChar, 1, 1),
+    (DataModel::IP16, CType::Short, 0, 0),
+    (DataModel::IP16, CType::UShort, 0, 0),
+    (DataModel::IP16, CType::Int, 2, 2),
+    (DataModel::IP16, CType::UInt, 2, 2),
+    (DataModel::IP16, CType::Long, 0, 0),
+    (DataModel::IP16, CType::ULong, 0, 0),
+    (DataModel::IP16, CType::LongLong, 0, 0),
+    (DataModel::IP16, CType::ULongLong, 0, 0),
+    (DataModel::IP16, CType::Pointer, 2, 2),
+    (DataModel::IP16, CType::Float, 4, 4),
+    (DataModel::IP16, CType::Double, 8, 8),
+
+    (DataModel::IP16L32, CType::Bool, 1, 1),
+    (DataModel::IP16L32, CType::Char, 1, 1),
+    (DataModel::IP16L32, CType::SChar, 1, 1),
+    (DataModel::IP16L32, CType::UChar, 1, 1),
+    (DataModel::IP16L32, CType::Short, 2, 2),
+    (DataModel::IP16L32, CType::UShort, 2, 2),
+    (DataModel::IP16L32, CType::Int, 2, 2),
+    (DataModel::IP16L32, CType::UInt, 2, 2),
+    (DataModel::IP16L32, CType::Long, 4, 4),
+    (DataModel::IP16L32, CType::ULong, 4, 4),
+    (DataModel::IP16L32, CType::LongLong, 0, 0),
+    (DataModel::IP16L32, CType::ULongLong, 0, 0),
+    (DataModel::IP16L32, CType::Pointer, 2, 2),
+    (DataModel::IP16L32, CType::Float, 4, 4),
+    (DataModel::IP16L32, CType::Double, 8, 8),
+
+    (DataModel::LP32, CType::Bool, 1, 1),
+    (DataModel::LP32, CType::Char, 1, 1),
+    (DataModel::LP32, CType::SChar, 1, 1),
+    (DataModel::LP32, CType::UChar, 1, 1),
+    (DataModel::LP32, CType::Short, 2, 2),
+    (DataModel::LP32, CType::UShort, 2, 2),
+    (DataModel::LP32, CType::Int, 2, 2),
+    (DataModel::LP32, CType::UInt, 2, 2),
+    (DataModel::LP32, CType::Long, 4, 4),
+    (DataModel::LP32, CType::ULong, 4, 4),
+    (DataModel::LP32, CType::LongLong, 8, 8),
+    (DataModel::LP32, CType::ULongLong, 8, 8),
+    (DataModel::LP32, CType::Pointer, 4, 4),
+    (DataModel::LP32, CType::Float, 4, 4),
+    (DataModel::LP32, CType::Double, 8, 8),
+
+    (DataModel::ILP32, CType::Bool, 1, 1),
+    (DataModel::ILP32, CType::Char, 1, 1),
+    (DataModel::ILP32, CType::SChar, 1, 1),
+    (DataModel::ILP32, CType::UChar, 1, 1),
+    (DataModel::ILP32, CType::Short, 2, 2),
+    (DataModel::ILP32, CType::UShort, 2, 2),
+    (DataModel::ILP32, CType::Int, 4, 4),
+    (DataModel::ILP32, CType::UInt, 4, 4),
+    (DataModel::ILP32, CType::Long, 4, 4),
+    (DataModel::ILP32, CType::ULong, 4, 4),
+    (DataModel::ILP32, CType::LongLong, 8, 8),
+    (DataModel::ILP32, CType::ULongLong, 8, 8),
+    (DataModel::ILP32, CType::Pointer, 4, 4),
+    (DataModel::ILP32, CType::Float, 4, 4),
+    (DataModel::ILP32, CType::Double, 8, 8),
+
+    (DataModel::LLP64, CType::Bool, 1, 1),
+    (DataModel::LLP64, CType::Char, 1, 1),
+    (DataModel::LLP64, CType::SChar, 1, 1),
+    (DataModel::LLP64, CType::UChar, 1, 1),
+    (DataModel::LLP64, CType::Short, 2, 2),
+    (DataModel::LLP64, CType::UShort, 2, 2),
+    (DataModel::LLP64, CType::Int, 4, 4),
+    (DataModel::LLP64, CType::UInt, 4, 4),
+    (DataModel::LLP64, CType::Long, 4, 4),
+    (DataModel::LLP64, CType::ULong, 4, 4),
+    (DataModel::LLP64, CType::LongLong, 8, 8),
+    (DataModel::LLP64, CType::ULongLong, 8, 8),
+    (DataModel::LLP64, CType::Pointer, 8, 8),
+    (DataModel::LLP64, CType::Float, 4, 4),
+    (DataModel::LLP64, CType::Double, 8, 8),
+
+    (DataModel::LP64, CType::Bool, 1, 1),
+    (DataModel::LP64, CType::Char, 1, 1),
+    (DataModel::LP64, CType::SChar, 1, 1),
+    (DataModel::LP64, CType::UChar, 1, 1),
+    (DataModel::LP64, CType::Short, 2, 2),
+    (DataModel::LP64, CType::UShort, 2, 2),
+    (DataModel::LP64, CType::Int, 4, 4),
+    (DataModel::LP64, CType::UInt, 4, 4),
+    (DataModel::LP64, CType::Long, 8, 8),
+    (DataModel::LP64, CType::ULong, 8, 8),
+    (DataModel::LP64, CType::LongLong, 8, 8),
+    (DataModel::LP64, CType::ULongLong, 8, 8),
+    (DataModel::LP64, CType::Pointer, 8, 8),
+    (DataModel::LP64, CType::Float, 4, 4),
+    (DataModel::LP64, CType::Double, 8, 8),
+
+    (DataModel::ILP64, CType::Bool, 1, 1),
+    (DataModel::ILP64, CType::Char, 1, 1),
+    (DataModel::ILP64, CType::SChar, 1, 1),
+    (DataModel::ILP64, CType::UChar, 1, 1),
+    (DataModel::ILP64, CType::Short, 2, 2),
+    (DataModel::ILP64, CType::UShort, 2, 2),
+    (DataModel::ILP64, CType::Int, 8, 8),
+    (DataModel::ILP64, CType::UInt, 8, 8),
+    (DataModel::ILP64, CType::Long, 8, 8),
+    (DataModel::ILP64, CType::ULong, 8, 8),
+    (DataModel::ILP64, CType::LongLong, 8, 8),
+    (DataModel::ILP64, CType::ULongLong, 8, 8),
+    (DataModel::ILP64, CType::Pointer, 8, 8),
+    (DataModel::ILP64, CType::Float, 4, 4),
+    (DataModel::ILP64, CType::Double, 8, 8),
+
+    (DataModel::SILP64, CType::Bool, 1, 1),
+    (DataModel::SILP64, CType::Char, 1, 1),
+    (DataModel::SILP64, CType::SChar, 1, 1),
+    (DataModel::SILP64, CType::UChar, 1, 1),
+    (DataModel::SILP64, CType::Short, 8, 8),
+    (DataModel::SILP64, CType::UShort, 8, 8),
+    (DataModel::SILP64, CType::Int, 8, 8),
+    (DataModel::SILP64, CType::UInt, 8, 8),
+    (DataModel::SILP64, CType::Long, 8, 8),
+    (DataModel::SILP64, CType::ULong, 8, 8),
+    (DataModel::SILP64, CType::LongLong, 8, 8),
+    (DataModel::SILP64, CType::ULongLong, 8, 8),
+    (DataModel::SILP64, CType::Pointer, 8, 8),
+    (DataModel::SILP64, CType::Float, 4, 4),
+    (DataModel::SILP64, CType::Double, 8, 8),
+];
+
+/// rows returns a row for each of [`CType::ALL`] in each of
+/// [`DataModel::ALL`], model by model.
+pub fn rows() -> Vec<Row> {
+    let mut rows = Vec::new();
+    for &model in DataModel::ALL {
+        for &ty in CType::ALL {
+            rows.push(Row {
+                model,
+                ty,
+                size: model.size(ty),
+                align: model.align(ty),
+            });
+        }
+    }
+    rows
+}
+
+/// csv writes the [`rows`] as CSV with the header `model,type,size,align`,
+/// naming types as in C.
+pub fn csv() -> String {
+    let mut csv = String::from("model,type,size,align\n");
+    for row in rows() {
+        csv += &format!(
+            "{:?},{},{},{}\n",
+            row.model,
+            row.ty.c_name(),
+            row.size,
+            row.align
+        );
+    }
+    csv
+}
+
+/// invariant_types returns the types with the same size and alignment in
+/// every one of `models`, leaving out types any of them lacks. Structures
+/// shared between processes of those models can use these types freely.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::matrix::invariant_types;
+/// let types = invariant_types(&[DataModel::ILP32, DataModel::LP64]);
+/// assert!(types.contains(&CType::Int));
+/// assert!(types.contains(&CType::LongLong));
+/// assert!(!types.contains(&CType::Long));
+/// assert!(!types.contains(&CType::Pointer));
+/// ```
+pub fn invariant_types(models: &[DataModel]) -> Vec<CType> {
+    CType::ALL
+        .iter()
+        .copied()
+        .filter(|&ty| match models.split_first() {
+            Some((first, rest)) => {
+                let size = first.size(ty);
+                let align = first.align(ty);
+                size != 0
+                    && rest
+                        .iter()
+                        .all(|model| model.size(ty) == size && model.align(ty) == align)
+            }
+            None => true,
+        })
+        .collect()
+}
+
+/// CommonSize is what a set of data models agree on for one type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CommonSize {
+    /// Every model gives the type this size, which is 0 if none has it.
+    Same(usize),
+    /// The models disagree, or only some have the type.
+    Varies,
+}
+
+/// PartialModel is the common denominator of a set of data models: the
+/// size of each type where they agree.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::matrix::CommonSize;
+/// let common = DataModel::common(&[DataModel::ILP32, DataModel::LLP64, DataModel::LP64]);
+/// assert_eq!(common.size(CType::Int), CommonSize::Same(4));
+/// assert_eq!(common.size(CType::Long), CommonSize::Varies);
+/// assert_eq!(common.size(CType::LongLong), CommonSize::Same(8));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PartialModel {
+    sizes: Vec<CommonSize>,
+}
+
+impl PartialModel {
+    /// size returns what the models agree on for `ty`.
+    pub fn size(&self, ty: CType) -> CommonSize {
+        self.sizes[ty as usize]
+    }
+
+    /// iter returns each type with what the models agree on, in the order
+    /// of [`CType::ALL`].
+    pub fn iter(&self) -> impl Iterator<Item = (CType, CommonSize)> + '_ {
+        CType::ALL.iter().copied().zip(self.sizes.iter().copied())
+    }
+}
+
+impl DataModel {
+    /// common returns what `models` agree on for each type. With no
+    /// models, nothing is agreed and every type varies.
+    pub fn common(models: &[DataModel]) -> PartialModel {
+        let sizes = CType::ALL
+            .iter()
+            .map(|&ty| match models.split_first() {
+                Some((first, rest)) if rest.iter().all(|m| m.size(ty) == first.size(ty)) => {
+                    CommonSize::Same(first.size(ty))
+                }
+                _ => CommonSize::Varies,
+            })
+            .collect();
+        PartialModel { sizes }
+    }
+}
+
+/// TableStyle is the markup of a table drawn by [`render_table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TableStyle {
+    /// A GitHub-flavored Markdown table.
+    Markdown,
+    /// A plain text table ruled with `+`, `-` and `|`.
+    Ascii,
+}
+
+/// TABLE_TYPES are the rows of a comparison table; the unsigned types have
+/// the sizes of the signed ones.
+pub(crate) const TABLE_TYPES: [CType; 9] = [
+    CType::Bool,
+    CType::Char,
+    CType::Short,
+    CType::Int,
+    CType::Long,
+    CType::LongLong,
+    CType::Pointer,
+    CType::Float,
+    CType::Double,
+];
+
+/// render_table draws the sizes in bytes of the C types, one row per type,
+/// in a column for each of `models`. Types a model lacks are shown as `-`.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::matrix::{render_table, TableStyle};
+/// let table = render_table(&[DataModel::LLP64, DataModel::LP64], TableStyle::Markdown);
+/// assert!(table.starts_with("| type      | LLP64 | LP64 |\n|-----------|------:|-----:|\n"));
+/// assert!(table.contains("| long      |     4 |    8 |\n"));
+///
+/// let table = render_table(&[DataModel::IP16], TableStyle::Ascii);
+/// assert!(table.starts_with("+-----------+------+\n| type      | IP16 |\n+-----------+------+\n"));
+/// assert!(table.contains("| long      |    - |\n"));
+/// ```
+pub fn render_table(models: &[DataModel], style: TableStyle) -> String {
+    let mut header = vec!["type".to_string()];
+    header.extend(models.iter().map(|model| format!("{:?}", model)));
+    let rows: Vec<Vec<String>> = TABLE_TYPES
+        .iter()
+        .map(|&ty| {
+            let mut row = vec![ty.c_name().to_string()];
+            row.extend(models.iter().map(|model| match model.size(ty) {
+                0 => "-".to_string(),
+                size => size.to_string(),
+            }));
+            row
+        })
+        .collect();
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            let cells = rows.iter().map(|row| row[i].len());
+            cells.chain(Some(header[i].len())).max().unwrap()
+        })
+        .collect();
+    let line = |cells: &[String]| {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(widths.iter())
+            .enumerate()
+            .map(|(i, (cell, &width))| match i {
+                0 => format!(" {:<width$} ", cell, width = width),
+                _ => format!(" {:>width$} ", cell, width = width),
+            })
+            .collect();
+        format!("|{}|\n", cells.join("|"))
+    };
+    let rule = |joint: &str| {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, &width)| match (style, i) {
+                (TableStyle::Markdown, 0) => "-".repeat(width + 2),
+                (TableStyle::Markdown, _) => format!("{}:", "-".repeat(width + 1)),
+                (TableStyle::Ascii, _) => "-".repeat(width + 2),
+            })
+            .collect();
+        format!("{}{}{}\n", joint, cells.join(joint), joint)
+    };
+    let mut table = String::new();
+    match style {
+        TableStyle::Markdown => {
+            table += &line(&header);
+            table += &rule("|");
+            for row in rows.iter() {
+                table += &line(row);
+            }
+        }
+        TableStyle::Ascii => {
+            table += &rule("+");
+            table += &line(&header);
+            table += &rule("+");
+            for row in rows.iter() {
+                table += &line(row);
+            }
+            table += &rule("+");
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix() {
+        let rows = rows();
+        assert_eq!(rows.len(), DataModel::ALL.len() * CType::ALL.len());
+        assert_eq!(
+            rows[0],
+            Row {
+                model: DataModel::IP16,
+                ty: CType::Bool,
+                size: 1,
+                align: 1
+            }
+        );
+        let csv = csv();
+        assert_eq!(csv.lines().count(), rows.len() + 1);
+        assert!(csv.starts_with("model,type,size,align\nIP16,_Bool,1,1\n"));
+        assert!(csv.contains("\nIP16,long,0,0\n"));
+        assert!(csv.contains("\nSILP64,unsigned short,8,8\n"));
+        assert!(csv.ends_with("SILP64,double,8,8\n"));
+    }
+
+    #[test]
+    fn test_golden() {
+        let rows: Vec<_> = rows()
+            .iter()
+            .map(|row| (row.model, row.ty, row.size, row.align))
+            .collect();
+        assert_eq!(rows, GOLDEN);
+    }
+
+    #[test]
+    fn test_invariant_types() {
+        use CType::*;
+        assert_eq!(
+            invariant_types(&[DataModel::LLP64, DataModel::LP64]),
+            [
+                Bool, Char, SChar, UChar, Short, UShort, Int, UInt, LongLong, ULongLong, Pointer,
+                Float, Double
+            ]
+        );
+        assert_eq!(
+            invariant_types(&[DataModel::IP16, DataModel::SILP64]),
+            [Bool, Char, SChar, UChar, Float, Double]
+        );
+        assert_eq!(invariant_types(&[DataModel::Unknown]), []);
+        assert_eq!(invariant_types(&[]), CType::ALL);
+    }
+
+    #[test]
+    fn test_common() {
+        use CommonSize::*;
+        let common = DataModel::common(&[DataModel::IP16, DataModel::IP16L32]);
+        let sizes: Vec<_> = common.iter().map(|(_, size)| size).collect();
+        assert_eq!(
+            sizes,
+            [
+                Same(1),
+                Same(1),
+                Same(1),
+                Same(1),
+                Varies,
+                Varies,
+                Same(2),
+                Same(2),
+                Varies,
+                Varies,
+                Same(0),
+                Same(0),
+                Same(2),
+                Same(4),
+                Same(8)
+            ]
+        );
+        let common = DataModel::common(&[DataModel::LP64]);
+        assert!(common
+            .iter()
+            .all(|(ty, size)| size == Same(DataModel::LP64.size(ty))));
+        assert!(DataModel::common(&[])
+            .iter()
+            .all(|(_, size)| size == Varies));
+    }
+
+    #[test]
+    fn test_render_table() {
+        let models = [DataModel::LP32, DataModel::ILP32, DataModel::SILP64];
+        let markdown = render_table(&models, TableStyle::Markdown);
+        assert_eq!(markdown.lines().count(), TABLE_TYPES.len() + 2);
+        assert!(markdown.contains("| void *    |    4 |     4 |      8 |\n"));
+        assert!(markdown.contains("|-----------|-----:|------:|-------:|\n"));
+        let ascii = render_table(&models, TableStyle::Ascii);
+        assert_eq!(ascii.lines().count(), TABLE_TYPES.len() + 4);
+        assert!(ascii.ends_with(
+            "| double    |    8 |     8 |      8 |\n+-----------+------+-------+--------+\n"
+        ));
+        let empty = render_table(&[], TableStyle::Ascii);
+        assert!(empty.starts_with("+-----------+\n| type      |\n"));
+    }
+}
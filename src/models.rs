@@ -0,0 +1,184 @@
+//! Data models as types, for generic code that knows its model at compile
+//! time.
+//!
+//! Each marker type, such as [`Lp64`], implements [`StaticModel`], whose
+//! associated constants hold the sizes of the C types. Code generic over
+//! `M: StaticModel` is compiled once per model with every size a constant.
+//! [`DataModel::dispatch`] goes from a model known at runtime to the
+//! matching marker type.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! fn read_long<M: StaticModel>(bytes: &[u8]) -> i64 {
+//!     let mut buf = [0; 8];
+//!     buf[..M::LONG].copy_from_slice(&bytes[..M::LONG]);
+//!     i64::from_le_bytes(buf)
+//! }
+//! let bytes = [1, 0, 0, 0, 2, 0, 0, 0];
+//! assert_eq!(read_long::<Llp64>(&bytes), 1);
+//! assert_eq!(read_long::<Lp64>(&bytes), 0x2_0000_0001);
+//! ```
+
+use crate::{CType, CTypeInfo, DataModel};
+
+/// StaticModel is a data model known at compile time. The constants give
+/// the size in bytes of each C type, as [`DataModel::size`] does for
+/// [`StaticModel::MODEL`].
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// assert_eq!(Ilp32::POINTER, 4);
+/// assert_eq!(Lp64::size_of::<Long>(), 8);
+/// assert_eq!(<Llp64 as StaticModel>::MODEL, DataModel::LLP64);
+/// ```
+pub trait StaticModel {
+    /// The data model the type stands for.
+    const MODEL: DataModel;
+    /// The size of `char`.
+    const CHAR: usize = Self::MODEL.size(CType::Char);
+    /// The size of `short`.
+    const SHORT: usize = Self::MODEL.size(CType::Short);
+    /// The size of `int`.
+    const INT: usize = Self::MODEL.size(CType::Int);
+    /// The size of `long`.
+    const LONG: usize = Self::MODEL.size(CType::Long);
+    /// The size of `long long`.
+    const LONG_LONG: usize = Self::MODEL.size(CType::LongLong);
+    /// The size of a pointer.
+    const POINTER: usize = Self::MODEL.size(CType::Pointer);
+    /// The size of `float`.
+    const FLOAT: usize = Self::MODEL.size(CType::Float);
+    /// The size of `double`.
+    const DOUBLE: usize = Self::MODEL.size(CType::Double);
+
+    /// size_of returns the size in bytes of one of the marker types, like
+    /// [`DataModel::size_of`] but folded to a constant.
+    fn size_of<T: CTypeInfo>() -> usize {
+        Self::MODEL.size(T::C_TYPE)
+    }
+
+    /// align_of returns the natural alignment in bytes of one of the
+    /// marker types, like [`DataModel::align_of`].
+    fn align_of<T: CTypeInfo>() -> usize {
+        Self::MODEL.align(T::C_TYPE)
+    }
+}
+
+/// ModelVisitor is code generic over a [`StaticModel`], for running with a
+/// model only known at runtime through [`DataModel::dispatch`].
+pub trait ModelVisitor {
+    /// The result of the visit.
+    type Output;
+    /// visit runs the code for the model `M`.
+    fn visit<M: StaticModel>(self) -> Self::Output;
+}
+
+/// static_models declares a marker type implementing [`StaticModel`] for
+/// each data model.
+macro_rules! static_models {
+    ($($marker:ident => $model:ident,)*) => {
+        $(
+            #[doc = concat!("The ", stringify!($model), " data model as a type, see [`DataModel::", stringify!($model), "`].")]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+            pub struct $marker;
+
+            impl StaticModel for $marker {
+                const MODEL: DataModel = DataModel::$model;
+            }
+
+            impl From<$marker> for DataModel {
+                fn from(_: $marker) -> DataModel {
+                    DataModel::$model
+                }
+            }
+        )*
+
+        impl DataModel {
+            /// dispatch runs `visitor` with the marker type of the model,
+            /// so code written for a [`StaticModel`] can serve a model read
+            /// at runtime. It returns `None` for [`DataModel::Unknown`].
+            ///
+            /// # Example
+            /// ```
+            /// use data_models::*;
+            /// struct PointerSize;
+            /// impl ModelVisitor for PointerSize {
+            ///     type Output = usize;
+            ///     fn visit<M: StaticModel>(self) -> usize {
+            ///         M::POINTER
+            ///     }
+            /// }
+            /// assert_eq!(DataModel::LP32.dispatch(PointerSize), Some(4));
+            /// assert_eq!(DataModel::Unknown.dispatch(PointerSize), None);
+            /// ```
+            pub fn dispatch<V: ModelVisitor>(self, visitor: V) -> Option<V::Output> {
+                match self {
+                    $(DataModel::$model => Some(visitor.visit::<$marker>()),)*
+                    DataModel::Unknown => None,
+                }
+            }
+        }
+    };
+}
+
+static_models! {
+    Ip16 => IP16,
+    Ip16L32 => IP16L32,
+    Lp32 => LP32,
+    Ilp32 => ILP32,
+    Llp64 => LLP64,
+    Lp64 => LP64,
+    Ilp64 => ILP64,
+    Silp64 => SILP64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LongLong, Short};
+
+    struct Sizes;
+
+    impl ModelVisitor for Sizes {
+        type Output = [usize; 8];
+        fn visit<M: StaticModel>(self) -> [usize; 8] {
+            [
+                M::CHAR,
+                M::SHORT,
+                M::INT,
+                M::LONG,
+                M::LONG_LONG,
+                M::POINTER,
+                M::FLOAT,
+                M::DOUBLE,
+            ]
+        }
+    }
+
+    #[test]
+    fn test_static_models() {
+        for &model in DataModel::ALL {
+            let sizes = model.dispatch(Sizes).unwrap();
+            let types = [
+                CType::Char,
+                CType::Short,
+                CType::Int,
+                CType::Long,
+                CType::LongLong,
+                CType::Pointer,
+                CType::Float,
+                CType::Double,
+            ];
+            for (size, ty) in sizes.iter().zip(types.iter()) {
+                assert_eq!(*size, model.size(*ty), "{:?} {:?}", model, ty);
+            }
+        }
+        assert_eq!(DataModel::from(Silp64), DataModel::SILP64);
+        assert_eq!(Silp64::size_of::<Short>(), 8);
+        assert_eq!(Ilp32::align_of::<LongLong>(), 8);
+        const LONG: usize = Llp64::LONG;
+        assert_eq!(LONG, 4);
+    }
+}
@@ -0,0 +1,82 @@
+//! The data model of the host, detected at compile time.
+
+use crate::DataModel;
+use std::mem::size_of;
+use std::os::raw::{c_int, c_long};
+
+impl DataModel {
+    /// NATIVE is the data model of the target this crate is compiled for,
+    /// from the sizes of `c_int`, `c_long` and pointers, or
+    /// [`DataModel::Unknown`] if they match no model.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::NATIVE.size(CType::Long), std::mem::size_of::<std::os::raw::c_long>());
+    /// ```
+    pub const NATIVE: DataModel = DataModel::new(
+        size_of::<c_int>(),
+        size_of::<c_long>(),
+        size_of::<*const u8>(),
+    );
+
+    /// is_native reports whether the model is the one this crate is
+    /// compiled for. It is a `const fn`, so code can branch on it at
+    /// compile time instead of refusing to build like
+    /// [`assert_data_model!`](crate::assert_data_model).
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// const WIDE_LONG: bool = DataModel::LP64.is_native() || DataModel::ILP64.is_native();
+    /// assert_eq!(WIDE_LONG, std::mem::size_of::<std::os::raw::c_long>() == 8);
+    /// ```
+    pub const fn is_native(self) -> bool {
+        self as u8 == DataModel::NATIVE as u8
+    }
+}
+
+/// assert_data_model fails the build unless the crate is compiled for one
+/// of the given data models, for code that only supports those.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// assert_data_model!(ILP32 | LLP64 | LP64);
+/// ```
+///
+/// ```compile_fail
+/// use data_models::*;
+/// assert_data_model!(IP16);
+/// ```
+#[macro_export]
+macro_rules! assert_data_model {
+    ($($model:ident)|+) => {
+        const _: () = assert!(
+            false $(|| $crate::DataModel::$model.is_native())+,
+            concat!("the target data model is not ", stringify!($($model)|+))
+        );
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    assert_data_model!(IP16 | IP16L32 | LP32 | ILP32 | LLP64 | LP64 | ILP64 | SILP64);
+
+    #[test]
+    fn test_native() {
+        assert_ne!(DataModel::NATIVE, DataModel::Unknown);
+        assert_eq!(
+            DataModel::NATIVE.size(crate::CType::Pointer),
+            size_of::<usize>()
+        );
+        let natives = [DataModel::ILP32, DataModel::LLP64, DataModel::LP64]
+            .iter()
+            .filter(|model| model.is_native())
+            .count();
+        assert!(natives <= 1);
+        assert!(!DataModel::Unknown.is_native() || DataModel::NATIVE == DataModel::Unknown);
+    }
+}
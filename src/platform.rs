@@ -0,0 +1,427 @@
+//! Well-known operating system and architecture pairs.
+//!
+//! A [`DataModel`] only fixes the widths of the integer types. Code that
+//! thinks in platforms also needs the byte order, the signedness of plain
+//! `char`, the width of `wchar_t` and the format of `long double`, which
+//! [`Platform`] bundles in one lookup.
+
+use crate::{DataModel, Endianness};
+
+/// Platform is an operating system running on a particular architecture,
+/// with the C ABI its system compiler uses by default.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let platform = Platform::Windows64;
+/// assert_eq!(platform.data_model(), DataModel::LLP64);
+/// assert_eq!(platform.wchar_size(), 2);
+/// assert_eq!(platform.long_double(), LongDouble::Binary64);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Platform {
+    /// Linux on x86-64.
+    LinuxX86_64,
+    /// Linux on x86-64 with the x32 ABI (32-bit pointers).
+    LinuxX32,
+    /// Linux on 32-bit x86.
+    LinuxI686,
+    /// Linux on 64-bit ARM.
+    LinuxAarch64,
+    /// Linux on 32-bit ARM with the hard-float EABI.
+    LinuxArmhf,
+    /// Linux on 64-bit little-endian PowerPC.
+    LinuxPpc64le,
+    /// Linux on IBM Z.
+    LinuxS390x,
+    /// Linux on 64-bit RISC-V.
+    LinuxRiscv64,
+    /// Linux on 32-bit big-endian MIPS.
+    LinuxMips,
+    /// Windows on 32-bit x86.
+    Windows32,
+    /// Windows on x86-64.
+    Windows64,
+    /// Windows on 64-bit ARM.
+    WindowsAarch64,
+    /// macOS on x86-64.
+    MacX86_64,
+    /// macOS on Apple silicon.
+    MacAarch64,
+    /// FreeBSD on x86-64.
+    FreeBsdX86_64,
+    /// AIX on 32-bit POWER.
+    Aix32,
+    /// AIX on 64-bit POWER.
+    Aix64,
+    /// Solaris on 64-bit SPARC.
+    SolarisSparc64,
+}
+
+/// LongDouble is the floating point format behind `long double`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LongDouble {
+    /// Same as `double` (IEEE 754 binary64).
+    Binary64,
+    /// The x87 80-bit extended format, padded to 12 or 16 bytes.
+    X87Extended,
+    /// IEEE 754 binary128.
+    Binary128,
+    /// IBM double-double, a pair of binary64 values.
+    DoubleDouble,
+}
+
+/// FltEvalMethod is the `FLT_EVAL_METHOD` of a target: the precision its
+/// floating point expressions are evaluated in, which can exceed that of
+/// their type. The x87 unit of 32-bit x86 computes everything as `long
+/// double`, so results there depend on when the compiler spills a register.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let method = Platform::LinuxI686.flt_eval_method();
+/// assert_eq!(method, FltEvalMethod::LongDouble);
+/// assert_eq!(method.value(), 2);
+/// assert_eq!(method.float_t_size(12), 12);
+/// assert_eq!(FltEvalMethod::Native.double_t_size(16), 8);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FltEvalMethod {
+    /// `-1`: the precision is not determinable.
+    Indeterminable,
+    /// `0`: each operation is evaluated in the precision of its type.
+    Native,
+    /// `1`: `float` operations are evaluated as `double`.
+    Double,
+    /// `2`: all operations are evaluated as `long double`.
+    LongDouble,
+}
+
+impl FltEvalMethod {
+    /// value returns the value of the `FLT_EVAL_METHOD` macro.
+    pub fn value(self) -> i32 {
+        match self {
+            FltEvalMethod::Indeterminable => -1,
+            FltEvalMethod::Native => 0,
+            FltEvalMethod::Double => 1,
+            FltEvalMethod::LongDouble => 2,
+        }
+    }
+
+    /// float_t_size returns the size of `float_t`, the type `float`
+    /// expressions are evaluated in, given the size of `long double`.
+    pub fn float_t_size(self, long_double_size: usize) -> usize {
+        match self {
+            FltEvalMethod::Indeterminable | FltEvalMethod::Native => 4,
+            FltEvalMethod::Double => 8,
+            FltEvalMethod::LongDouble => long_double_size,
+        }
+    }
+
+    /// double_t_size returns the size of `double_t`, the type `double`
+    /// expressions are evaluated in, given the size of `long double`.
+    pub fn double_t_size(self, long_double_size: usize) -> usize {
+        match self {
+            FltEvalMethod::LongDouble => long_double_size,
+            _ => 8,
+        }
+    }
+}
+
+/// UnalignedAccess is what happens when a program loads or stores a value
+/// at an address that is not a multiple of its alignment.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// assert_eq!(Platform::LinuxX86_64.unaligned_access(), UnalignedAccess::Allowed);
+/// assert!(Platform::SolarisSparc64.unaligned_access().needs_bytewise_access());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum UnalignedAccess {
+    /// The hardware handles misaligned accesses, at most a little slower.
+    Allowed,
+    /// Misaligned accesses trap, and the kernel or firmware emulates them
+    /// hundreds of times slower.
+    FixedUp,
+    /// Misaligned accesses trap and the program gets `SIGBUS`.
+    Fault,
+}
+
+impl UnalignedAccess {
+    /// needs_bytewise_access reports whether code reading misaligned
+    /// fields, as in packed structs, should copy them byte by byte, which
+    /// is what compilers do for `__attribute__((packed))` on such targets.
+    pub fn needs_bytewise_access(self) -> bool {
+        self != UnalignedAccess::Allowed
+    }
+}
+
+impl Platform {
+    /// Every platform known to this crate.
+    pub const ALL: &'static [Platform] = &[
+        Platform::LinuxX86_64,
+        Platform::LinuxX32,
+        Platform::LinuxI686,
+        Platform::LinuxAarch64,
+        Platform::LinuxArmhf,
+        Platform::LinuxPpc64le,
+        Platform::LinuxS390x,
+        Platform::LinuxRiscv64,
+        Platform::LinuxMips,
+        Platform::Windows32,
+        Platform::Windows64,
+        Platform::WindowsAarch64,
+        Platform::MacX86_64,
+        Platform::MacAarch64,
+        Platform::FreeBsdX86_64,
+        Platform::Aix32,
+        Platform::Aix64,
+        Platform::SolarisSparc64,
+    ];
+
+    /// data_model returns the data model of the platform.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Platform::LinuxX32.data_model(), DataModel::ILP32);
+    /// ```
+    pub fn data_model(self) -> DataModel {
+        use Platform::*;
+        match self {
+            LinuxX32 | LinuxI686 | LinuxArmhf | LinuxMips | Windows32 | Aix32 => DataModel::ILP32,
+            Windows64 | WindowsAarch64 => DataModel::LLP64,
+            LinuxX86_64 | LinuxAarch64 | LinuxPpc64le | LinuxS390x | LinuxRiscv64 | MacX86_64
+            | MacAarch64 | FreeBsdX86_64 | Aix64 | SolarisSparc64 => DataModel::LP64,
+        }
+    }
+
+    /// endianness returns the byte order of the platform.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Platform::LinuxS390x.endianness(), Endianness::Big);
+    /// ```
+    pub fn endianness(self) -> Endianness {
+        use Platform::*;
+        match self {
+            LinuxS390x | LinuxMips | Aix32 | Aix64 | SolarisSparc64 => Endianness::Big,
+            _ => Endianness::Little,
+        }
+    }
+
+    /// char_signed reports whether plain `char` is signed.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert!(Platform::LinuxX86_64.char_signed());
+    /// assert!(!Platform::LinuxAarch64.char_signed());
+    /// assert!(Platform::MacAarch64.char_signed());
+    /// ```
+    pub fn char_signed(self) -> bool {
+        use Platform::*;
+        !matches!(
+            self,
+            LinuxAarch64 | LinuxArmhf | LinuxPpc64le | LinuxS390x | LinuxRiscv64 | Aix32 | Aix64
+        )
+    }
+
+    /// wchar_size returns the size of `wchar_t` in bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Platform::LinuxX86_64.wchar_size(), 4);
+    /// assert_eq!(Platform::Aix32.wchar_size(), 2);
+    /// ```
+    pub fn wchar_size(self) -> usize {
+        use Platform::*;
+        match self {
+            Windows32 | Windows64 | WindowsAarch64 | Aix32 => 2,
+            _ => 4,
+        }
+    }
+
+    /// long_double returns the format of `long double`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Platform::LinuxI686.long_double(), LongDouble::X87Extended);
+    /// assert_eq!(Platform::MacAarch64.long_double(), LongDouble::Binary64);
+    /// ```
+    pub fn long_double(self) -> LongDouble {
+        use Platform::*;
+        match self {
+            LinuxX86_64 | LinuxX32 | LinuxI686 | MacX86_64 | FreeBsdX86_64 => {
+                LongDouble::X87Extended
+            }
+            LinuxAarch64 | LinuxS390x | LinuxRiscv64 | SolarisSparc64 => LongDouble::Binary128,
+            LinuxPpc64le => LongDouble::DoubleDouble,
+            LinuxArmhf | LinuxMips | Windows32 | Windows64 | WindowsAarch64 | MacAarch64
+            | Aix32 | Aix64 => LongDouble::Binary64,
+        }
+    }
+
+    /// unaligned_access returns how the platform treats misaligned loads
+    /// and stores of ordinary memory. 32-bit ARM handles single words but
+    /// traps on doubleword and multiple loads, which Linux fixes up.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Platform::MacAarch64.unaligned_access(), UnalignedAccess::Allowed);
+    /// assert_eq!(Platform::LinuxRiscv64.unaligned_access(), UnalignedAccess::FixedUp);
+    /// ```
+    pub fn unaligned_access(self) -> UnalignedAccess {
+        use Platform::*;
+        match self {
+            LinuxArmhf | LinuxRiscv64 | LinuxMips => UnalignedAccess::FixedUp,
+            SolarisSparc64 => UnalignedAccess::Fault,
+            _ => UnalignedAccess::Allowed,
+        }
+    }
+
+    /// triple returns the LLVM target triple of the platform, as passed to
+    /// `clang --target`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Platform::LinuxArmhf.triple(), "armv7-unknown-linux-gnueabihf");
+    /// assert_eq!(Platform::Windows64.triple(), "x86_64-pc-windows-msvc");
+    /// ```
+    pub fn triple(self) -> &'static str {
+        use Platform::*;
+        match self {
+            LinuxX86_64 => "x86_64-unknown-linux-gnu",
+            LinuxX32 => "x86_64-unknown-linux-gnux32",
+            LinuxI686 => "i686-unknown-linux-gnu",
+            LinuxAarch64 => "aarch64-unknown-linux-gnu",
+            LinuxArmhf => "armv7-unknown-linux-gnueabihf",
+            LinuxPpc64le => "powerpc64le-unknown-linux-gnu",
+            LinuxS390x => "s390x-unknown-linux-gnu",
+            LinuxRiscv64 => "riscv64-unknown-linux-gnu",
+            LinuxMips => "mips-unknown-linux-gnu",
+            Windows32 => "i686-pc-windows-msvc",
+            Windows64 => "x86_64-pc-windows-msvc",
+            WindowsAarch64 => "aarch64-pc-windows-msvc",
+            MacX86_64 => "x86_64-apple-darwin",
+            MacAarch64 => "arm64-apple-darwin",
+            FreeBsdX86_64 => "x86_64-unknown-freebsd",
+            Aix32 => "powerpc-ibm-aix",
+            Aix64 => "powerpc64-ibm-aix",
+            SolarisSparc64 => "sparcv9-sun-solaris",
+        }
+    }
+
+    /// long_double_size returns the size of `long double` in bytes,
+    /// including the padding of the x87 format.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Platform::LinuxI686.long_double_size(), 12);
+    /// assert_eq!(Platform::LinuxX86_64.long_double_size(), 16);
+    /// ```
+    pub fn long_double_size(self) -> usize {
+        match (self.long_double(), self) {
+            (LongDouble::X87Extended, Platform::LinuxI686) => 12,
+            (LongDouble::Binary64, _) => 8,
+            _ => 16,
+        }
+    }
+
+    /// flt_eval_method returns the `FLT_EVAL_METHOD` of the platform's
+    /// default compiler options. 32-bit x86 Linux evaluates in the x87
+    /// registers; MSVC uses SSE2 even for 32-bit x86.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Platform::LinuxI686.flt_eval_method(), FltEvalMethod::LongDouble);
+    /// assert_eq!(Platform::Windows32.flt_eval_method(), FltEvalMethod::Native);
+    /// ```
+    pub fn flt_eval_method(self) -> FltEvalMethod {
+        match self {
+            Platform::LinuxI686 => FltEvalMethod::LongDouble,
+            _ => FltEvalMethod::Native,
+        }
+    }
+
+    /// float_t_size returns the size of `float_t`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(Platform::LinuxI686.float_t_size(), 12);
+    /// assert_eq!(Platform::LinuxX86_64.float_t_size(), 4);
+    /// ```
+    pub fn float_t_size(self) -> usize {
+        self.flt_eval_method().float_t_size(self.long_double_size())
+    }
+
+    /// double_t_size returns the size of `double_t`.
+    pub fn double_t_size(self) -> usize {
+        self.flt_eval_method()
+            .double_t_size(self.long_double_size())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_model() {
+        assert_eq!(Platform::LinuxX86_64.data_model(), DataModel::LP64);
+        assert_eq!(Platform::WindowsAarch64.data_model(), DataModel::LLP64);
+        assert_eq!(Platform::Windows32.data_model(), DataModel::ILP32);
+        assert_eq!(Platform::Aix64.data_model(), DataModel::LP64);
+    }
+
+    #[test]
+    fn test_abi_metadata() {
+        assert_eq!(Platform::Aix64.endianness(), Endianness::Big);
+        assert_eq!(Platform::LinuxPpc64le.endianness(), Endianness::Little);
+        assert!(!Platform::LinuxPpc64le.char_signed());
+        assert!(Platform::Windows64.char_signed());
+        assert_eq!(Platform::Aix64.wchar_size(), 4);
+        assert_eq!(
+            Platform::LinuxPpc64le.long_double(),
+            LongDouble::DoubleDouble
+        );
+        assert_eq!(Platform::Windows64.long_double_size(), 8);
+        assert_eq!(Platform::LinuxX32.long_double_size(), 16);
+        assert_eq!(Platform::LinuxAarch64.long_double_size(), 16);
+        assert_eq!(
+            Platform::WindowsAarch64.unaligned_access(),
+            UnalignedAccess::Allowed
+        );
+        assert_eq!(
+            Platform::LinuxMips.unaligned_access(),
+            UnalignedAccess::FixedUp
+        );
+        assert!(!UnalignedAccess::Allowed.needs_bytewise_access());
+        assert!(UnalignedAccess::FixedUp.needs_bytewise_access());
+        assert_eq!(Platform::LinuxI686.double_t_size(), 12);
+        assert_eq!(Platform::LinuxX32.double_t_size(), 8);
+        assert_eq!(FltEvalMethod::Indeterminable.value(), -1);
+        assert_eq!(FltEvalMethod::Double.float_t_size(16), 8);
+    }
+
+    #[test]
+    fn test_all() {
+        for &platform in Platform::ALL {
+            assert_ne!(platform.data_model(), DataModel::Unknown);
+        }
+        assert_eq!(Platform::ALL.len(), 18);
+    }
+}
@@ -0,0 +1,708 @@
+//! Rewriting records so they lay out the same on every target.
+//!
+//! A [`LayoutDiff`](crate::layout::LayoutDiff) shows where a record moves
+//! between data models; [`Layout::portable`] fixes it. Every integer
+//! becomes the exact-width `stdint.h` type wide enough for it on all the
+//! chosen ABIs, pointers become unsigned integers of the widest pointer,
+//! every gap becomes an explicit `_paddingN` member and each run of
+//! bit-fields becomes one unsigned storage unit. The result is emitted as
+//! C and as Rust.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::layout::Record;
+//! let record = Record::new("entry")
+//!     .field("tag", CType::Char)
+//!     .field("value", CType::Long)
+//!     .field("next", CType::Pointer);
+//! let layout = record.layout(DataModel::ILP32).unwrap();
+//! let portable = layout.portable(&[DataModel::ILP32, DataModel::LP64]).unwrap();
+//! assert_eq!(
+//!     portable.c_definition(),
+//!     "#include <stdint.h>\n\
+//!      \n\
+//!      struct entry {\n    \
+//!          char tag;\n    \
+//!          uint8_t _padding0[7];\n    \
+//!          int64_t value;\n    \
+//!          uint64_t next;\n\
+//!      };\n"
+//! );
+//! assert!(portable.rust_definition().contains("    pub value: i64,\n"));
+//! ```
+
+use crate::layout::{Abi, Field, FieldLayout, FieldType, Layout, LayoutError, Record, RecordKind};
+use crate::rust::{ident, RustType};
+use crate::stdint::{StdType, TypeName};
+use crate::{CType, DataModel};
+
+/// PortableRecord is a record rewritten to have the same layout on a set
+/// of ABIs, with every gap filled by an explicit padding member.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format), defmt(bound()))]
+pub struct PortableRecord {
+    /// The struct or union tag.
+    pub name: String,
+    /// Whether the record is a struct or a union.
+    pub kind: RecordKind,
+    /// `sizeof` the record on every ABI.
+    pub size: usize,
+    /// The largest alignment the record has on any of the ABIs.
+    pub align: usize,
+    /// The members in order, padding included.
+    pub fields: Vec<PortableField>,
+}
+
+/// PortableField is a member of a [`PortableRecord`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format), defmt(bound()))]
+pub struct PortableField {
+    /// The member name: the field name, `_paddingN` for padding,
+    /// `_bitfieldN` for the storage of bit-fields and `_anonN` for an
+    /// anonymous member.
+    pub name: String,
+    /// The member type.
+    pub ty: PortableType,
+    /// `offsetof` the member.
+    pub offset: usize,
+    /// For the storage of a run of bit-fields, their names and widths, in
+    /// order from the least significant bit.
+    pub bitfields: Vec<(String, usize)>,
+}
+
+/// PortableType is the type of a [`PortableField`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format), defmt(bound()))]
+pub enum PortableType {
+    /// An exact-width integer, or a `_Bool`, `char`, `float` or `double`,
+    /// which have one size everywhere.
+    Scalar(TypeName),
+    /// A fixed-size array.
+    Array(Box<PortableType>, usize),
+    /// A flexible array member.
+    FlexibleArray(Box<PortableType>),
+    /// A nested record held by value.
+    Record(Box<PortableRecord>),
+}
+
+/// exact returns the exact-width integer of `size` bytes.
+fn exact(size: usize, signed: bool) -> Option<StdType> {
+    use StdType::*;
+    Some(match (size, signed) {
+        (1, true) => Int8,
+        (1, false) => UInt8,
+        (2, true) => Int16,
+        (2, false) => UInt16,
+        (4, true) => Int32,
+        (4, false) => UInt32,
+        (8, true) => Int64,
+        (8, false) => UInt64,
+        _ => return None,
+    })
+}
+
+/// bytes returns an array of `n` `uint8_t`.
+fn bytes(n: usize) -> PortableType {
+    PortableType::Array(Box::new(PortableType::Scalar(StdType::UInt8.into())), n)
+}
+
+/// Placer places the members of a portable record.
+struct Placer<'a> {
+    abis: &'a [Abi],
+    kind: RecordKind,
+    fields: Vec<PortableField>,
+    end: usize,
+    align: usize,
+    paddings: usize,
+    bitfields: usize,
+}
+
+impl Placer<'_> {
+    /// push places a member at the next offset that all the ABIs align it
+    /// to, after explicit padding.
+    fn push(&mut self, name: String, ty: PortableType, size: usize, align: usize) {
+        let offset = match self.kind {
+            RecordKind::Struct => self.end.div_ceil(align) * align,
+            RecordKind::Union => 0,
+        };
+        self.pad(offset);
+        self.fields.push(PortableField {
+            name,
+            ty,
+            offset,
+            bitfields: Vec::new(),
+        });
+        self.end = self.end.max(offset + size);
+        self.align = self.align.max(align);
+    }
+
+    /// pad adds a padding member up to `offset`.
+    fn pad(&mut self, offset: usize) {
+        if offset > self.end {
+            let name = format!("_padding{}", self.paddings);
+            self.paddings += 1;
+            self.fields.push(PortableField {
+                name,
+                ty: bytes(offset - self.end),
+                offset: self.end,
+                bitfields: Vec::new(),
+            });
+            self.end = offset;
+        }
+    }
+
+    /// flush places the storage unit of a run of bit-fields: the smallest
+    /// unsigned integer holding all their bits, or bytes past 64 bits.
+    fn flush(&mut self, run: &mut Vec<(String, usize)>) -> Result<(), LayoutError> {
+        if run.is_empty() {
+            return Ok(());
+        }
+        let bits: usize = run.iter().map(|(_, bits)| bits).sum();
+        let size = bits.div_ceil(8).next_power_of_two();
+        let (ty, size, align) = match exact(size, false) {
+            Some(ty) => {
+                let align = self.max_align(&run[0].0, ty.into(), CType::UInt)?;
+                (PortableType::Scalar(ty.into()), size, align)
+            }
+            None => (bytes(bits.div_ceil(8)), bits.div_ceil(8), 1),
+        };
+        let name = format!("_bitfield{}", self.bitfields);
+        self.bitfields += 1;
+        self.push(name, ty, size, align);
+        self.fields.last_mut().unwrap().bitfields = std::mem::take(run);
+        Ok(())
+    }
+
+    /// max_align returns the largest alignment of `ty` on the ABIs, or an
+    /// error naming `field` of type `from` if one of them lacks it.
+    fn max_align(&self, field: &str, ty: TypeName, from: CType) -> Result<usize, LayoutError> {
+        let mut align = 1;
+        for abi in self.abis {
+            match abi.model().resolve(ty) {
+                Some(base) => align = align.max(abi.align(base)),
+                None => {
+                    return Err(LayoutError::MissingType {
+                        field: field.to_string(),
+                        ty: from,
+                    })
+                }
+            }
+        }
+        Ok(align)
+    }
+
+    /// scalar returns the portable type of a field of C type `ty`, `size`
+    /// bytes and aligned to `align` in the layout, with its size and
+    /// alignment.
+    fn scalar(
+        &self,
+        field: &str,
+        ty: CType,
+        signed: bool,
+        sizes: impl Fn(&Abi) -> Result<usize, LayoutError>,
+        (size, align): (usize, usize),
+    ) -> Result<(PortableType, usize, usize), LayoutError> {
+        let missing = || LayoutError::MissingType {
+            field: field.to_string(),
+            ty,
+        };
+        let mut size = size;
+        for abi in self.abis {
+            match sizes(abi)? {
+                0 => return Err(missing()),
+                n => size = size.max(n),
+            }
+        }
+        let name = match ty {
+            CType::Bool | CType::Char | CType::Float | CType::Double => TypeName::C(ty),
+            _ => TypeName::Std(exact(size, signed).ok_or_else(missing)?),
+        };
+        let align = align.max(self.max_align(field, name, ty)?);
+        Ok((PortableType::Scalar(name), size, align))
+    }
+
+    /// field_type returns the portable type of `ty`, the type of `field` or
+    /// of its elements, with its size and alignment. `size` is the size of
+    /// `ty` in the layout, or 0 if unknown.
+    fn field_type(
+        &self,
+        field: &FieldLayout,
+        ty: &FieldType,
+        size: usize,
+        name: &str,
+    ) -> Result<(PortableType, usize, usize), LayoutError> {
+        let here = (size, field.align);
+        match ty {
+            FieldType::Scalar(ty) => {
+                let signed = ty.is_signed();
+                self.scalar(&field.name, *ty, signed, |abi| Ok(abi.size(*ty)), here)
+            }
+            FieldType::Pointer(_) => {
+                let sizes = |abi: &Abi| Ok(abi.size(CType::Pointer));
+                self.scalar(&field.name, CType::Pointer, false, sizes, here)
+            }
+            FieldType::Enum(e) => {
+                let signed = e.values.iter().any(|&(_, value)| value < 0)
+                    || e.underlying.is_some_and(CType::is_signed);
+                let sizes = |abi: &Abi| Ok(abi.size(e.underlying_type(*abi)?));
+                self.scalar(&field.name, CType::Int, signed, sizes, here)
+            }
+            FieldType::Array(element, n) => {
+                let size = size.checked_div(*n).unwrap_or(0);
+                let (element, size, align) = self.field_type(field, element, size, name)?;
+                Ok((PortableType::Array(Box::new(element), *n), size * n, align))
+            }
+            FieldType::FlexibleArray(element) => {
+                let (element, _, align) = self.field_type(field, element, 0, name)?;
+                Ok((PortableType::FlexibleArray(Box::new(element)), 0, align))
+            }
+            FieldType::Record(_) | FieldType::Layout(_) => {
+                let nested = match field.nested.as_deref() {
+                    Some(nested) => nested.portable_record(name, self.abis)?,
+                    None => PortableRecord {
+                        name: name.to_string(),
+                        kind: RecordKind::Struct,
+                        size: 0,
+                        align: 1,
+                        fields: Vec::new(),
+                    },
+                };
+                let (size, align) = (nested.size, nested.align);
+                Ok((PortableType::Record(Box::new(nested)), size, align))
+            }
+        }
+    }
+}
+
+impl Layout {
+    /// portable rewrites the record so that it has the same layout on each
+    /// of the ABIs. The layout contributes its fields and their widths, so
+    /// one computed for a packed record is unpacked.
+    ///
+    /// The fields keep their order. Each integer becomes the exact-width
+    /// type of its largest size, keeping its signedness, and each pointer
+    /// or enum an integer of its largest size; `_Bool`, `char`, `float`
+    /// and `double` stay as they are. Each field goes at the first offset
+    /// that every ABI aligns its type to, any gap before it becomes a
+    /// `_paddingN` byte array, and so does the tail up to a multiple of
+    /// the largest alignment. A trailing flexible array is moved to that
+    /// multiple instead, as nothing may follow it.
+    ///
+    /// Consecutive bit-fields of a struct share one `_bitfieldN` storage
+    /// unit, the smallest unsigned integer holding all their bits, which
+    /// the program packs from the least significant bit. Nested records
+    /// are rewritten the same way, and named after the record and the
+    /// field holding them if they have no tag.
+    ///
+    /// It fails if one of the ABIs lacks a type, like a 64-bit integer on
+    /// IP16.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let record = Record::new("sample")
+    ///     .field("count", CType::Int)
+    ///     .field("total", CType::LongLong)
+    ///     .bitfield("kind", CType::UInt, 3)
+    ///     .bitfield("valid", CType::UInt, 1);
+    /// let i686 = Abi::new(DataModel::ILP32).with_max_align(4);
+    /// let portable = record.layout(i686).unwrap().portable(&[Abi::new(DataModel::LP64)]).unwrap();
+    /// assert_eq!((portable.size, portable.align), (24, 8));
+    /// assert_eq!(portable.fields[1].name, "_padding0");
+    /// assert_eq!(portable.fields[3].bitfields, vec![("kind".to_string(), 3), ("valid".to_string(), 1)]);
+    /// ```
+    pub fn portable<A: Into<Abi> + Copy>(&self, abis: &[A]) -> Result<PortableRecord, LayoutError> {
+        let abis: Vec<Abi> = abis.iter().map(|&abi| abi.into()).collect();
+        let name = match self.name.as_str() {
+            "" => "anon",
+            name => name,
+        };
+        self.portable_record(name, &abis)
+    }
+
+    /// portable_record rewrites the record under the name `name`.
+    fn portable_record(&self, name: &str, abis: &[Abi]) -> Result<PortableRecord, LayoutError> {
+        let name = match self.name.as_str() {
+            "" => name,
+            tag => tag,
+        };
+        let mut placer = Placer {
+            abis,
+            kind: self.kind,
+            fields: Vec::new(),
+            end: 0,
+            align: 1,
+            paddings: 0,
+            bitfields: 0,
+        };
+        let (mut run, mut anons) = (Vec::new(), 0);
+        for field in &self.fields {
+            if let Some(bits) = field.bits {
+                if bits == 0 || self.kind == RecordKind::Union {
+                    placer.flush(&mut run)?;
+                }
+                if bits > 0 {
+                    run.push((field.name.clone(), bits));
+                }
+                continue;
+            }
+            placer.flush(&mut run)?;
+            let member = match field.name.as_str() {
+                "" => {
+                    anons += 1;
+                    format!("_anon{}", anons - 1)
+                }
+                name => name.to_string(),
+            };
+            let nested = format!("{}_{}", name, member.trim_start_matches('_'));
+            let (ty, size, align) = placer.field_type(field, &field.ty, field.size, &nested)?;
+            if let PortableType::FlexibleArray(_) = ty {
+                let align = placer.align.max(align);
+                placer.pad(placer.end.div_ceil(align) * align);
+            }
+            placer.push(member, ty, size, align);
+        }
+        placer.flush(&mut run)?;
+        let size = placer.end.div_ceil(placer.align) * placer.align;
+        let flexible = matches!(
+            placer.fields.last(),
+            Some(PortableField {
+                ty: PortableType::FlexibleArray(_),
+                ..
+            })
+        );
+        if !flexible {
+            match self.kind {
+                RecordKind::Struct => placer.pad(size),
+                RecordKind::Union if placer.end < size => {
+                    placer.push(format!("_padding{}", placer.paddings), bytes(size), size, 1)
+                }
+                RecordKind::Union => {}
+            }
+        }
+        Ok(PortableRecord {
+            name: name.to_string(),
+            kind: self.kind,
+            size,
+            align: placer.align,
+            fields: placer.fields,
+        })
+    }
+}
+
+impl PortableType {
+    /// c_parts returns the C type and the declarator suffix of a
+    /// member, like `("int32_t", "[4]")`.
+    fn c_parts(&self) -> (String, String) {
+        match self {
+            PortableType::Scalar(ty) => (ty.c_name().to_string(), String::new()),
+            PortableType::Array(element, n) => {
+                let (ty, suffix) = element.c_parts();
+                (ty, format!("[{}]{}", n, suffix))
+            }
+            PortableType::FlexibleArray(element) => {
+                let (ty, suffix) = element.c_parts();
+                (ty, format!("[]{}", suffix))
+            }
+            PortableType::Record(record) => (
+                format!("{} {}", record.kind.c_keyword(), record.name),
+                String::new(),
+            ),
+        }
+    }
+
+    /// rust_parts returns the Rust spelling of the type and its alignment
+    /// on the host, assuming each primitive is aligned to its size.
+    fn rust_parts(&self) -> (String, usize) {
+        match self {
+            PortableType::Scalar(TypeName::Std(ty)) => {
+                let size = ty.width().unwrap_or(8) / 8;
+                let rust = RustType::integer(size, ty.is_signed()).unwrap_or(RustType::U8);
+                (rust.to_string(), size)
+            }
+            PortableType::Scalar(TypeName::C(ty)) => match ty {
+                CType::Bool => ("bool".to_string(), 1),
+                CType::Float => ("f32".to_string(), 4),
+                CType::Double => ("f64".to_string(), 8),
+                _ => ("u8".to_string(), 1),
+            },
+            PortableType::Array(element, n) => {
+                let (element, align) = element.rust_parts();
+                (format!("[{}; {}]", element, n), align)
+            }
+            PortableType::FlexibleArray(element) => {
+                let (element, align) = element.rust_parts();
+                (format!("[{}; 0]", element), align)
+            }
+            PortableType::Record(record) => (record.name.clone(), record.rust_align()),
+        }
+    }
+
+    /// field_type spells the type in the base types of a data model.
+    fn field_type(&self, model: DataModel) -> Option<FieldType> {
+        Some(match self {
+            PortableType::Scalar(ty) => FieldType::Scalar(model.resolve(*ty)?),
+            PortableType::Array(element, n) => FieldType::array(element.field_type(model)?, *n),
+            PortableType::FlexibleArray(element) => FieldType::flexible(element.field_type(model)?),
+            PortableType::Record(record) => FieldType::Record(record.record(model)?),
+        })
+    }
+}
+
+impl PortableRecord {
+    /// records returns the record and the records nested in it, each once,
+    /// innermost first.
+    fn records<'a>(&'a self, out: &mut Vec<&'a PortableRecord>) {
+        for field in &self.fields {
+            let mut ty = &field.ty;
+            while let PortableType::Array(element, _) | PortableType::FlexibleArray(element) = ty {
+                ty = element;
+            }
+            if let PortableType::Record(record) = ty {
+                record.records(out);
+            }
+        }
+        if !out.iter().any(|record| record.name == self.name) {
+            out.push(self);
+        }
+    }
+
+    /// c_definition emits the C definition of the record, after those of
+    /// the records nested in it. The storage of bit-fields is commented
+    /// with the bit-fields it holds.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::Record;
+    /// let record = Record::new("flags").bitfield("a", CType::UInt, 3).bitfield("b", CType::UInt, 2);
+    /// let portable = record.layout(DataModel::LP64).unwrap().portable(&[DataModel::ILP32]).unwrap();
+    /// assert!(portable.c_definition().contains("    uint8_t _bitfield0; /* a:3, b:2 */\n"));
+    /// ```
+    pub fn c_definition(&self) -> String {
+        let mut records = Vec::new();
+        self.records(&mut records);
+        let mut src = String::from("#include <stdint.h>\n");
+        for record in records {
+            src += &format!("\n{} {} {{\n", record.kind.c_keyword(), record.name);
+            for field in &record.fields {
+                let (ty, suffix) = field.ty.c_parts();
+                src += &format!("    {} {}{};", ty, field.name, suffix);
+                if !field.bitfields.is_empty() {
+                    src += &format!(" /* {} */", bit_list(&field.bitfields));
+                }
+                src += "\n";
+            }
+            src += "};\n";
+        }
+        src
+    }
+
+    /// rust_definition emits Rust source for `#[repr(C)]` types with the
+    /// layout of the record, after those of the records nested in it. A
+    /// record is `packed` where the host would align a member more than its
+    /// offset allows. Plain `char` becomes `u8`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::Record;
+    /// let record = Record::new("pair").field("a", CType::Short).field("b", CType::Long);
+    /// let portable = record.layout(DataModel::LP64).unwrap().portable(&[DataModel::LLP64]).unwrap();
+    /// assert_eq!(
+    ///     portable.rust_definition(),
+    ///     "#[repr(C)]\n#[derive(Clone, Copy)]\n#[allow(non_camel_case_types)]\n\
+    ///      pub struct pair {\n    pub a: i16,\n    pub _padding0: [u8; 6],\n    pub b: i64,\n}\n"
+    /// );
+    /// ```
+    pub fn rust_definition(&self) -> String {
+        let mut records = Vec::new();
+        self.records(&mut records);
+        let defs: Vec<_> = records
+            .into_iter()
+            .map(|record| {
+                let repr = match record.rust_align() {
+                    1 if record.rust_natural() > 1 => "C, packed",
+                    _ => "C",
+                };
+                let mut body = String::new();
+                for field in &record.fields {
+                    if !field.bitfields.is_empty() {
+                        body += &format!("    /// {}\n", bit_list(&field.bitfields));
+                    }
+                    let (ty, _) = field.ty.rust_parts();
+                    body += &format!("    pub {}: {},\n", ident(&field.name), ty);
+                }
+                format!(
+                    "#[repr({})]\n#[derive(Clone, Copy)]\n#[allow(non_camel_case_types)]\npub {} {} {{\n{}}}\n",
+                    repr,
+                    record.kind.c_keyword(),
+                    record.name,
+                    body
+                )
+            })
+            .collect();
+        defs.join("\n")
+    }
+
+    /// rust_natural returns the alignment of the Rust type on the host
+    /// without `packed`.
+    fn rust_natural(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|field| field.ty.rust_parts().1)
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// rust_align returns the alignment of the Rust type on the host: 1 if
+    /// it has to be `packed` to keep its offsets and size.
+    fn rust_align(&self) -> usize {
+        let natural = self.rust_natural();
+        let aligned = self.size.is_multiple_of(natural)
+            && self
+                .fields
+                .iter()
+                .all(|field| field.offset.is_multiple_of(field.ty.rust_parts().1));
+        match aligned {
+            true => natural,
+            false => 1,
+        }
+    }
+
+    /// record spells the record in the base types of a data model, so it
+    /// can be laid out there, or returns `None` if the model lacks one of
+    /// its types. The storage of bit-fields becomes an ordinary field.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::Record;
+    /// let record = Record::new("entry").field("tag", CType::Char).field("value", CType::Long);
+    /// let layout = record.layout(DataModel::LP64).unwrap();
+    /// let portable = layout.portable(&[DataModel::LP64, DataModel::LLP64]).unwrap();
+    /// let lp64 = portable.record(DataModel::LP64).unwrap().layout(DataModel::LP64).unwrap();
+    /// let llp64 = portable.record(DataModel::LLP64).unwrap().layout(DataModel::LLP64).unwrap();
+    /// assert!(llp64.is_wire_compatible(&lp64));
+    /// assert_eq!(llp64.fields[2].offset, layout.fields[1].offset);
+    /// ```
+    pub fn record(&self, model: DataModel) -> Option<Record> {
+        let mut record = match self.kind {
+            RecordKind::Struct => Record::new(&self.name),
+            RecordKind::Union => Record::union(&self.name),
+        };
+        for field in &self.fields {
+            record = record.with_field(Field::new(&field.name, field.ty.field_type(model)?));
+        }
+        Some(record)
+    }
+}
+
+/// bit_list lists bit-fields as `name:width`.
+fn bit_list(bitfields: &[(String, usize)]) -> String {
+    bitfields
+        .iter()
+        .map(|(name, bits)| format!("{}:{}", name, bits))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::BitFieldRules;
+
+    #[test]
+    fn test_portable() {
+        let inner = Record::new("")
+            .field("id", CType::UShort)
+            .field("ptr", FieldType::pointer(CType::Char));
+        let record = Record::new("message")
+            .field("kind", CType::Char)
+            .field("length", CType::Long)
+            .field("header", inner)
+            .bitfield("urgent", CType::UInt, 1)
+            .bitfield("retries", CType::UInt, 3)
+            .field("scale", CType::Double)
+            .field("body", FieldType::flexible(CType::Short));
+        let abis = [
+            Abi::new(DataModel::ILP32).with_max_align(4),
+            Abi::new(DataModel::LP64),
+            Abi::new(DataModel::LLP64).with_bitfields(BitFieldRules::Msvc),
+        ];
+        let portable = record.layout(abis[0]).unwrap().portable(&abis).unwrap();
+        assert_eq!((portable.size, portable.align), (48, 8));
+        let expected = "\
+#include <stdint.h>
+
+struct message_header {
+    uint16_t id;
+    uint8_t _padding0[6];
+    uint64_t ptr;
+};
+
+struct message {
+    char kind;
+    uint8_t _padding0[7];
+    int64_t length;
+    struct message_header header;
+    uint8_t _bitfield0; /* urgent:1, retries:3 */
+    uint8_t _padding1[7];
+    double scale;
+    int16_t body[];
+};
+";
+        assert_eq!(portable.c_definition(), expected);
+        let reference = portable
+            .record(DataModel::LP64)
+            .unwrap()
+            .layout(abis[1])
+            .unwrap();
+        for &abi in &abis {
+            let layout = portable.record(abi.model()).unwrap().layout(abi).unwrap();
+            assert!(layout.is_wire_compatible(&reference), "{:?}", abi);
+        }
+        let rust = portable.rust_definition();
+        assert!(rust.contains("pub struct message_header {\n    pub id: u16,\n"));
+        assert!(rust.contains("    /// urgent:1, retries:3\n    pub _bitfield0: u8,\n"));
+        assert!(rust.contains("    pub body: [i16; 0],\n}\n"));
+        let ip16 = Abi::new(DataModel::IP16);
+        assert!(record.layout(abis[1]).unwrap().portable(&[ip16]).is_err());
+    }
+
+    #[test]
+    fn test_portable_union_packed() {
+        let record = Record::union("value")
+            .field("i", CType::Int)
+            .field("bytes", FieldType::array(CType::UChar, 3));
+        let portable = record
+            .layout(DataModel::ILP32)
+            .unwrap()
+            .portable(&[DataModel::ILP32])
+            .unwrap();
+        assert_eq!(portable.fields.len(), 2);
+        assert_eq!(portable.fields[1].ty, bytes(3));
+        let packed = Record::new("p")
+            .field("c", CType::Char)
+            .field("n", CType::Int)
+            .packed();
+        let portable = packed
+            .layout(DataModel::LP64)
+            .unwrap()
+            .portable(&[DataModel::LP64])
+            .unwrap();
+        assert_eq!(portable.size, 8);
+        assert_eq!(portable.fields[2].offset, 4);
+        let i686 = Abi::new(DataModel::ILP32).with_max_align(4);
+        let record = Record::new("q")
+            .field("n", CType::Int)
+            .field("ll", CType::LongLong);
+        let portable = record.layout(i686).unwrap().portable(&[i686]).unwrap();
+        assert_eq!(portable.size, 12);
+        assert!(portable
+            .rust_definition()
+            .starts_with("#[repr(C, packed)]\n"));
+    }
+}
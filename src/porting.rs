@@ -0,0 +1,429 @@
+//! What changes when code moves from one data model to another.
+//!
+//! Porting from Win32 to Win64 or from ILP32 to LP64 Unix comes down to
+//! the same checklist: which types change size, where a pointer stops
+//! fitting in the integer it is stored in, and whether `size_t` grows.
+//! [`report`] draws it up for any two models, or any two ABIs, so a CI
+//! job can post it.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::porting::{self, Issue};
+//! let report = porting::report(DataModel::ILP32, DataModel::LP64);
+//! assert!(report.issues.contains(&Issue::SizeChanged { ty: CType::Long, from: 4, to: 8 }));
+//! assert!(report.issues.contains(&Issue::PointerNoLongerFits { ty: CType::Int }));
+//! assert!(report.to_markdown().contains("- [ ] `long` grows from 4 to 8 bytes\n"));
+//! ```
+//!
+//! [`audit_pointer_fields`] goes through the records of a code base for
+//! the fields such a port truncates.
+
+use crate::layout::{Abi, FieldType, Record};
+use crate::matrix::TABLE_TYPES;
+use crate::{CType, DataModel};
+use std::fmt;
+
+/// Issue is one difference to check when porting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Issue {
+    /// An integer or floating type changes size. A size of 0 means the
+    /// model lacks the type.
+    SizeChanged {
+        /// The type.
+        ty: CType,
+        /// The size before.
+        from: usize,
+        /// The size after.
+        to: usize,
+    },
+    /// A type keeps its size but is aligned differently in records, which
+    /// moves the fields after it.
+    AlignChanged {
+        /// The type.
+        ty: CType,
+        /// The alignment before.
+        from: usize,
+        /// The alignment after.
+        to: usize,
+    },
+    /// Pointers change width, and `size_t` and `ptrdiff_t` with them.
+    SizeTChanged {
+        /// The size before.
+        from: usize,
+        /// The size after.
+        to: usize,
+    },
+    /// A pointer stored in the integer type no longer survives the round
+    /// trip.
+    PointerNoLongerFits {
+        /// The type, `int` or `long`.
+        ty: CType,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Issue::SizeChanged { ty, from: 0, to } => {
+                write!(f, "`{}` is new, {} bytes", ty.c_name(), to)
+            }
+            Issue::SizeChanged { ty, from, to: 0 } => {
+                write!(f, "`{}` ({} bytes) does not exist", ty.c_name(), from)
+            }
+            Issue::SizeChanged { ty, from, to } => {
+                let verb = if to > from { "grows" } else { "shrinks" };
+                write!(
+                    f,
+                    "`{}` {} from {} to {} bytes",
+                    ty.c_name(),
+                    verb,
+                    from,
+                    to
+                )
+            }
+            Issue::AlignChanged { ty, from, to } => write!(
+                f,
+                "`{}` is aligned to {} bytes instead of {} in records",
+                ty.c_name(),
+                to,
+                from
+            ),
+            Issue::SizeTChanged { from, to } => write!(
+                f,
+                "pointers, `size_t` and `ptrdiff_t` change from {} to {} bytes",
+                from, to
+            ),
+            Issue::PointerNoLongerFits { ty } => write!(
+                f,
+                "a pointer no longer fits in `{}`; store it in `intptr_t`",
+                ty.c_name()
+            ),
+        }
+    }
+}
+
+/// Report is the checklist for porting between two models.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Report {
+    /// The model ported from.
+    pub from: DataModel,
+    /// The model ported to.
+    pub to: DataModel,
+    /// The differences, pointer width first, then sizes, alignments and
+    /// pointer round trips in the order of the types.
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    /// is_clean reports whether there is nothing to check.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// to_markdown renders the report as a Markdown task list under a
+    /// heading, for a CI comment.
+    pub fn to_markdown(&self) -> String {
+        let mut src = format!("### Porting from {:?} to {:?}\n\n", self.from, self.to);
+        if self.is_clean() {
+            return src + "No differences.\n";
+        }
+        for issue in &self.issues {
+            src += &format!("- [ ] {}\n", issue);
+        }
+        src
+    }
+}
+
+/// report lists the differences between two data models, or two ABIs when
+/// alignment rules such as the 4-byte `double` of i386 matter. Unsigned
+/// types are covered by their signed counterparts.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::porting::{self, Issue};
+/// let report = porting::report(Platform::LinuxI686, Platform::LinuxX32);
+/// assert_eq!(report.issues, [Issue::AlignChanged { ty: CType::LongLong, from: 4, to: 8 },
+///                            Issue::AlignChanged { ty: CType::Double, from: 4, to: 8 }]);
+/// assert!(porting::report(DataModel::LP64, DataModel::LP64).is_clean());
+/// ```
+pub fn report(from: impl Into<Abi>, to: impl Into<Abi>) -> Report {
+    let (from, to) = (from.into(), to.into());
+    let mut issues = Vec::new();
+    let pointer = (from.size(CType::Pointer), to.size(CType::Pointer));
+    if pointer.0 != pointer.1 {
+        issues.push(Issue::SizeTChanged {
+            from: pointer.0,
+            to: pointer.1,
+        });
+    }
+    for &ty in TABLE_TYPES.iter().filter(|&&ty| ty != CType::Pointer) {
+        let (size_from, size_to) = (from.size(ty), to.size(ty));
+        if size_from != size_to {
+            issues.push(Issue::SizeChanged {
+                ty,
+                from: size_from,
+                to: size_to,
+            });
+        }
+    }
+    for &ty in TABLE_TYPES.iter() {
+        let (align_from, align_to) = (from.align(ty), to.align(ty));
+        if from.size(ty) == to.size(ty) && from.size(ty) != 0 && align_from != align_to {
+            issues.push(Issue::AlignChanged {
+                ty,
+                from: align_from,
+                to: align_to,
+            });
+        }
+    }
+    for &ty in [CType::Int, CType::Long].iter() {
+        if from.model().int_can_hold_pointer(ty) && !to.model().int_can_hold_pointer(ty) {
+            issues.push(Issue::PointerNoLongerFits { ty });
+        }
+    }
+    Report {
+        from: from.model(),
+        to: to.model(),
+        issues,
+    }
+}
+
+/// Reason is why a field may truncate pointers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Reason {
+    /// The type could hold a pointer on the model ported from.
+    HeldPointer,
+    /// The field is named like a pointer, e.g. `handle` or `user_data`.
+    PointerName,
+}
+
+/// Finding is a field too narrow for a pointer on the model ported to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Finding {
+    /// The outermost record.
+    pub record: String,
+    /// The path of the field within it, such as `io.handle` or `slots[]`.
+    pub field: String,
+    /// The integer type of the field.
+    pub ty: CType,
+    /// The size of the type on the model ported to.
+    pub size: usize,
+    /// The size of a pointer on the model ported to.
+    pub pointer_size: usize,
+    /// Why the field is suspect.
+    pub reason: Reason,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let why = match self.reason {
+            Reason::HeldPointer => "it could hold one before",
+            Reason::PointerName => "its name suggests it holds one",
+        };
+        write!(
+            f,
+            "{}.{}: `{}` is {} bytes but pointers are {}, and {}",
+            self.record,
+            self.field,
+            self.ty.c_name(),
+            self.size,
+            self.pointer_size,
+            why
+        )
+    }
+}
+
+/// POINTER_NAMES are the words in field names that usually mean the
+/// field holds an address.
+const POINTER_NAMES: [&str; 10] = [
+    "ptr",
+    "pointer",
+    "addr",
+    "handle",
+    "cookie",
+    "ctx",
+    "context",
+    "userdata",
+    "user_data",
+    "hwnd",
+];
+
+/// audit_pointer_fields returns the integer fields of `records`, nested
+/// ones included, that cannot hold a pointer on `to` although their type
+/// held one on `from`, or although they are named like a pointer. Bit-fields
+/// are left out.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::layout::Record;
+/// use data_models::porting::{audit_pointer_fields, Reason};
+/// let window = Record::new("window").field("handle", CType::Long).field("id", CType::Int);
+/// let findings = audit_pointer_fields(&[window], DataModel::LP64, DataModel::LLP64);
+/// assert_eq!(findings.len(), 1);
+/// assert_eq!(findings[0].field, "handle");
+/// assert_eq!(findings[0].reason, Reason::HeldPointer);
+/// ```
+pub fn audit_pointer_fields(records: &[Record], from: DataModel, to: DataModel) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for record in records {
+        for field in record.fields.iter().filter(|field| field.bits.is_none()) {
+            audit(
+                &mut findings,
+                &record.name,
+                &field.name,
+                &field.ty,
+                from,
+                to,
+            );
+        }
+    }
+    findings
+}
+
+/// audit adds the findings for a field of type `ty` at `path`.
+fn audit(
+    findings: &mut Vec<Finding>,
+    record: &str,
+    path: &str,
+    ty: &FieldType,
+    from: DataModel,
+    to: DataModel,
+) {
+    match ty {
+        FieldType::Scalar(ty) if ty.is_integer() && *ty != CType::Bool => {
+            if to.int_can_hold_pointer(*ty) || to.size(*ty) == 0 {
+                return;
+            }
+            let name = path.rsplit('.').next().unwrap_or(path).to_lowercase();
+            let reason = if from.int_can_hold_pointer(*ty) {
+                Reason::HeldPointer
+            } else if POINTER_NAMES.iter().any(|word| name.contains(word)) {
+                Reason::PointerName
+            } else {
+                return;
+            };
+            findings.push(Finding {
+                record: record.to_string(),
+                field: path.to_string(),
+                ty: *ty,
+                size: to.size(*ty),
+                pointer_size: to.size(CType::Pointer),
+                reason,
+            });
+        }
+        FieldType::Array(ty, _) | FieldType::FlexibleArray(ty) => {
+            audit(findings, record, &format!("{}[]", path), ty, from, to)
+        }
+        FieldType::Record(nested) => {
+            for field in nested.fields.iter().filter(|field| field.bits.is_none()) {
+                let path = format!("{}.{}", path, field.name);
+                audit(findings, record, &path, &field.ty, from, to);
+            }
+        }
+        FieldType::Layout(nested) => {
+            for field in nested.fields.iter().filter(|field| field.bits.is_none()) {
+                let path = format!("{}.{}", path, field.name);
+                audit(findings, record, &path, &field.ty, from, to);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report() {
+        use CType::*;
+        let report = report(DataModel::ILP32, DataModel::LLP64);
+        assert_eq!(
+            report.issues,
+            [
+                Issue::SizeTChanged { from: 4, to: 8 },
+                Issue::PointerNoLongerFits { ty: Int },
+                Issue::PointerNoLongerFits { ty: Long },
+            ]
+        );
+        assert_eq!(
+            report.to_markdown(),
+            "### Porting from ILP32 to LLP64\n\n\
+             - [ ] pointers, `size_t` and `ptrdiff_t` change from 4 to 8 bytes\n\
+             - [ ] a pointer no longer fits in `int`; store it in `intptr_t`\n\
+             - [ ] a pointer no longer fits in `long`; store it in `intptr_t`\n"
+        );
+        let report = super::report(DataModel::IP16, DataModel::IP16L32);
+        assert_eq!(
+            report.issues,
+            [
+                Issue::SizeChanged {
+                    ty: Short,
+                    from: 0,
+                    to: 2
+                },
+                Issue::SizeChanged {
+                    ty: Long,
+                    from: 0,
+                    to: 4
+                },
+            ]
+        );
+        assert_eq!(report.issues[0].to_string(), "`short` is new, 2 bytes");
+        let report = super::report(DataModel::SILP64, DataModel::LP64);
+        assert_eq!(
+            report.issues[0].to_string(),
+            "`short` shrinks from 8 to 2 bytes"
+        );
+        let report = super::report(DataModel::LP32, DataModel::IP16);
+        assert!(report.issues.contains(&Issue::SizeChanged {
+            ty: LongLong,
+            from: 8,
+            to: 0
+        }));
+        assert_eq!(
+            super::report(DataModel::LP64, DataModel::LP64).to_markdown(),
+            "### Porting from LP64 to LP64\n\nNo differences.\n"
+        );
+    }
+
+    #[test]
+    fn test_audit_pointer_fields() {
+        use CType::*;
+        let io = Record::new("io")
+            .field("fd", Int)
+            .field("user_data", UInt)
+            .bitfield("addr_bits", ULong, 3);
+        let request = Record::new("request")
+            .field("io", io)
+            .field("slots", FieldType::array(ULong, 4))
+            .field("context", FieldType::pointer(Int))
+            .field("cookie", ULongLong)
+            .field("flags", Long);
+        let findings = audit_pointer_fields(&[request], DataModel::LP64, DataModel::LLP64);
+        let fields: Vec<_> = findings
+            .iter()
+            .map(|finding| (finding.field.as_str(), finding.reason))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("io.user_data", Reason::PointerName),
+                ("slots[]", Reason::HeldPointer),
+                ("flags", Reason::HeldPointer),
+            ]
+        );
+        assert_eq!(
+            findings[0].to_string(),
+            "request.io.user_data: `unsigned int` is 4 bytes but pointers are 8, \
+             and its name suggests it holds one"
+        );
+        let lp64 = Record::new("r").field("handle", Long);
+        assert!(audit_pointer_fields(&[lp64], DataModel::ILP32, DataModel::LP64).is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+//! Python bindings, built with pyo3 as the `data_models` extension module.
+//!
+//! ```text
+//! cargo rustc --release --features python,pyo3/extension-module --crate-type cdylib
+//! cp target/release/libdata_models.so data_models.so
+//! ```
+//!
+//! ```python
+//! import data_models
+//! data_models.size_of("LLP64", "long")                  # 4
+//! data_models.from_triple("aarch64-unknown-linux-gnu")  # 'LP64'
+//! data_models.layout("LP64", ["char", ("void *", 2)])   # (24, 8, [0, 8])
+//! ```
+//!
+//! Types are C type names such as `unsigned long` or `char *`, read with
+//! [`parse_decl`].
+
+use crate::layout::{FieldType, Record};
+use crate::{parse_decl, DataModel, Platform, TypeExpr};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// data_model finds a data model by name, such as `LP64`.
+fn data_model(name: &str) -> PyResult<DataModel> {
+    DataModel::ALL
+        .iter()
+        .copied()
+        .find(|model| format!("{:?}", model) == name)
+        .ok_or_else(|| PyValueError::new_err(format!("unknown data model {:?}", name)))
+}
+
+/// type_expr reads a C type name.
+fn type_expr(ty: &str) -> PyResult<TypeExpr> {
+    parse_decl(ty).map_err(|err| PyValueError::new_err(format!("{:?}: {}", ty, err)))
+}
+
+/// models returns the names of the data models.
+#[pyfunction]
+fn models() -> Vec<String> {
+    DataModel::ALL.iter().map(|m| format!("{:?}", m)).collect()
+}
+
+/// size_of returns the size in bytes of a C type, or 0 if the model lacks
+/// it.
+#[pyfunction]
+fn size_of(model: &str, ctype: &str) -> PyResult<usize> {
+    Ok(type_expr(ctype)?.size(data_model(model)?).unwrap_or(0))
+}
+
+/// align_of returns the alignment in bytes of a C type, or 0 if the model
+/// lacks it.
+#[pyfunction]
+fn align_of(model: &str, ctype: &str) -> PyResult<usize> {
+    Ok(type_expr(ctype)?.align(data_model(model)?).unwrap_or(0))
+}
+
+/// from_triple returns the data model of a known target triple.
+#[pyfunction]
+fn from_triple(triple: &str) -> Option<String> {
+    Platform::ALL
+        .iter()
+        .find(|platform| platform.triple() == triple)
+        .map(|platform| format!("{:?}", platform.data_model()))
+}
+
+/// PyField is a field of [`layout`]: a C type, or a (C type, count) pair
+/// for an array.
+#[derive(FromPyObject)]
+enum PyField {
+    Scalar(String),
+    Array(String, usize),
+}
+
+/// layout returns the size, alignment and field offsets of a struct, or
+/// of a union if `union` is true.
+#[pyfunction]
+#[pyo3(signature = (model, fields, union = false))]
+fn layout(model: &str, fields: Vec<PyField>, union: bool) -> PyResult<(usize, usize, Vec<usize>)> {
+    let mut record = match union {
+        true => Record::union("record"),
+        false => Record::new("record"),
+    };
+    for (i, field) in fields.iter().enumerate() {
+        let (ctype, count) = match field {
+            PyField::Scalar(ctype) => (ctype, None),
+            PyField::Array(ctype, count) => (ctype, Some(*count)),
+        };
+        let ty = type_expr(ctype)?
+            .field_type()
+            .ok_or_else(|| PyValueError::new_err(format!("{:?} is not a field type", ctype)))?;
+        let ty = match count {
+            Some(n) => FieldType::array(ty, n),
+            None => ty,
+        };
+        record = record.field(&format!("f{}", i), ty);
+    }
+    let layout = record
+        .layout(data_model(model)?)
+        .map_err(|err| PyValueError::new_err(err.to_string()))?;
+    let offsets = layout.fields.iter().map(|field| field.offset).collect();
+    Ok((layout.size, layout.align, offsets))
+}
+
+/// data_models is the Python module.
+#[pymodule]
+fn data_models(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(models, m)?)?;
+    m.add_function(wrap_pyfunction!(self::size_of, m)?)?;
+    m.add_function(wrap_pyfunction!(self::align_of, m)?)?;
+    m.add_function(wrap_pyfunction!(from_triple, m)?)?;
+    m.add_function(wrap_pyfunction!(layout, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(models()[0], "IP16");
+        assert_eq!(size_of("LLP64", "long").unwrap(), 4);
+        assert_eq!(size_of("LP64", "unsigned long *").unwrap(), 8);
+        assert_eq!(size_of("IP16", "long").unwrap(), 0);
+        assert_eq!(align_of("ILP32", "double").unwrap(), 8);
+        assert!(size_of("LP65", "long").is_err());
+        assert!(size_of("LP64", "long double").is_err());
+        assert_eq!(
+            from_triple("aarch64-unknown-linux-gnu"),
+            Some("LP64".to_string())
+        );
+        assert_eq!(from_triple("vax"), None);
+        let fields = vec![
+            PyField::Scalar("char".into()),
+            PyField::Array("void *".into(), 2),
+        ];
+        assert_eq!(layout("LP64", fields, false).unwrap(), (24, 8, vec![0, 8]));
+        let fields = vec![PyField::Scalar("long".into())];
+        assert!(layout("IP16", fields, true).is_err());
+    }
+}
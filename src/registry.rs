@@ -0,0 +1,513 @@
+//! Named targets loaded from a TOML file.
+//!
+//! An in-house chip rarely needs a new data model; it needs an existing one
+//! with its own byte order, `char` signedness or alignment rules. A
+//! [`Registry`] reads such profiles from a TOML file, one table per
+//! profile, with the keys of the [JSON descriptions](crate::json). A
+//! profile can start from a built-in [`Platform`] or an earlier profile
+//! named by `base`:
+//!
+//! ```toml
+//! # Our DSP: ILP32, big-endian, doubles aligned to 4 bytes.
+//! [acme-dsp]
+//! data_model = "ILP32"
+//! endianness = "big"
+//! char_signed = false
+//! alignments = { "long long" = 4, double = 4 }
+//!
+//! [acme-dsp-packed]
+//! base = "acme-dsp"
+//! pack = 1
+//!
+//! [acme-arm]
+//! base = "LinuxArmhf"
+//! long_double = "binary64"
+//! ```
+//!
+//! The TOML accepted is the subset these files need: tables, comments,
+//! strings, integers, booleans and inline tables.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::registry::Registry;
+//! let registry = Registry::from_toml("[acme]\ndata_model = \"LP64\"\nendianness = \"big\"\n").unwrap();
+//! let acme = registry.get("acme").unwrap();
+//! assert_eq!(acme.endianness, Endianness::Big);
+//! assert_eq!(registry.get("LinuxX86_64"), Some(Platform::LinuxX86_64.target()));
+//! ```
+//!
+//! Plugin-style tools can instead [`register`] targets by name as they
+//! start and [`lookup`] the names users give them later. The registry
+//! behind these is kept per thread, or shared by the whole process with
+//! the `sync` feature.
+
+use crate::json::{self, Json, JsonError};
+use crate::{Platform, TargetModel};
+#[cfg(not(feature = "sync"))]
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+#[cfg(feature = "sync")]
+use std::sync::{Mutex, PoisonError};
+
+/// Registry is a set of named targets.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Registry {
+    profiles: Vec<(String, TargetModel)>,
+}
+
+/// RegistryError describes why a registry file was rejected.
+#[derive(Debug)]
+pub enum RegistryError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The line is not TOML this crate understands.
+    Syntax {
+        /// The line number, from 1.
+        line: usize,
+    },
+    /// A profile is defined twice.
+    Duplicate(String),
+    /// The `base` of a profile is neither a platform nor an earlier profile.
+    UnknownBase {
+        /// The profile.
+        profile: String,
+        /// Its base.
+        base: String,
+    },
+    /// A profile does not describe a target.
+    Profile {
+        /// The profile.
+        profile: String,
+        /// What is wrong with it.
+        error: JsonError,
+    },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Io(err) => write!(f, "cannot read the registry: {}", err),
+            RegistryError::Syntax { line } => write!(f, "invalid TOML on line {}", line),
+            RegistryError::Duplicate(profile) => {
+                write!(f, "profile {:?} is defined twice", profile)
+            }
+            RegistryError::UnknownBase { profile, base } => {
+                write!(f, "profile {:?} has an unknown base {:?}", profile, base)
+            }
+            RegistryError::Profile { profile, error } => {
+                write!(f, "profile {:?}: {}", profile, error)
+            }
+        }
+    }
+}
+
+impl Error for RegistryError {}
+
+/// RegistryError logs like its `Display`, without the text of the I/O
+/// error.
+#[cfg(feature = "defmt")]
+impl defmt::Format for RegistryError {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            RegistryError::Io(_) => defmt::write!(f, "cannot read the registry"),
+            RegistryError::Syntax { line } => {
+                defmt::write!(f, "invalid TOML on line {=usize}", line)
+            }
+            RegistryError::Duplicate(profile) => {
+                defmt::write!(f, "profile {=str} is defined twice", profile)
+            }
+            RegistryError::UnknownBase { profile, base } => {
+                defmt::write!(
+                    f,
+                    "profile {=str} has an unknown base {=str}",
+                    profile,
+                    base
+                )
+            }
+            RegistryError::Profile { profile, error } => {
+                defmt::write!(f, "profile {=str}: {}", profile, error)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for RegistryError {
+    fn from(err: io::Error) -> RegistryError {
+        RegistryError::Io(err)
+    }
+}
+
+impl Registry {
+    /// new returns a registry with no profiles, which still finds the
+    /// built-in platforms.
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    /// load reads the profiles of the TOML file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Registry, RegistryError> {
+        Registry::from_toml(&fs::read_to_string(path)?)
+    }
+
+    /// from_toml reads the profiles of a TOML document.
+    pub fn from_toml(src: &str) -> Result<Registry, RegistryError> {
+        let mut registry = Registry::new();
+        for (name, keys) in parse(src)? {
+            if registry.profiles.iter().any(|(n, _)| *n == name) {
+                return Err(RegistryError::Duplicate(name));
+            }
+            let base = match keys.iter().find(|(key, _)| key == "base") {
+                None => None,
+                Some((_, Json::String(base))) => match registry.get(base) {
+                    Some(target) => Some(target),
+                    None => {
+                        return Err(RegistryError::UnknownBase {
+                            profile: name,
+                            base: base.clone(),
+                        })
+                    }
+                },
+                Some(_) => {
+                    return Err(RegistryError::Profile {
+                        profile: name,
+                        error: JsonError::Invalid("base".to_string()),
+                    })
+                }
+            };
+            match json::describe(&keys, base) {
+                Ok(target) => registry.insert(&name, target),
+                Err(error) => {
+                    return Err(RegistryError::Profile {
+                        profile: name,
+                        error,
+                    })
+                }
+            }
+        }
+        Ok(registry)
+    }
+
+    /// insert adds a profile, replacing one of the same name.
+    pub fn insert(&mut self, name: &str, target: TargetModel) {
+        match self.profiles.iter_mut().find(|(n, _)| n == name) {
+            Some(profile) => profile.1 = target,
+            None => self.profiles.push((name.to_string(), target)),
+        }
+    }
+
+    /// get returns the profile called `name`, or else the target of the
+    /// platform of that name, e.g. `"LinuxX86_64"`.
+    pub fn get(&self, name: &str) -> Option<TargetModel> {
+        let profile = self.profiles.iter().find(|(n, _)| n == name);
+        match profile {
+            Some(&(_, target)) => Some(target),
+            None => Platform::ALL
+                .iter()
+                .find(|platform| format!("{:?}", platform) == name)
+                .map(|platform| platform.target()),
+        }
+    }
+
+    /// names returns the names of the profiles, in the order they were
+    /// added.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+#[cfg(not(feature = "sync"))]
+thread_local! {
+    static GLOBAL: RefCell<Registry> = RefCell::new(Registry::new());
+}
+
+#[cfg(feature = "sync")]
+static GLOBAL: Mutex<Registry> = Mutex::new(Registry {
+    profiles: Vec::new(),
+});
+
+/// with_global runs `f` on the registry behind [`register`] and [`lookup`].
+fn with_global<R>(f: impl FnOnce(&mut Registry) -> R) -> R {
+    #[cfg(not(feature = "sync"))]
+    return GLOBAL.with(|registry| f(&mut registry.borrow_mut()));
+    #[cfg(feature = "sync")]
+    return f(&mut GLOBAL.lock().unwrap_or_else(PoisonError::into_inner));
+}
+
+/// register adds a named target to the global registry, replacing one of
+/// the same name. Without the `sync` feature, the registry belongs to the
+/// calling thread.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::registry::{lookup, register};
+/// let dsp = TargetModel::new(DataModel::ILP32).with_endianness(Endianness::Big);
+/// register("vendor_dsp_x", dsp);
+/// assert_eq!(lookup("vendor_dsp_x"), Some(dsp));
+/// assert_eq!(lookup("LinuxX86_64"), Some(Platform::LinuxX86_64.target()));
+/// assert_eq!(lookup("vendor_dsp_y"), None);
+/// ```
+pub fn register(name: &str, target: TargetModel) {
+    with_global(|registry| registry.insert(name, target))
+}
+
+/// lookup returns the target registered as `name`, or else the target of
+/// the platform of that name. See [`Registry::get`].
+pub fn lookup(name: &str) -> Option<TargetModel> {
+    with_global(|registry| registry.get(name))
+}
+
+/// Table is a TOML table: its name and its keys in order.
+type Table = (String, Vec<(String, Json)>);
+
+/// parse reads the tables of a TOML document in order.
+fn parse(src: &str) -> Result<Vec<Table>, RegistryError> {
+    let mut tables: Vec<Table> = Vec::new();
+    for (i, line) in src.lines().enumerate() {
+        let syntax = RegistryError::Syntax { line: i + 1 };
+        let mut cursor = Cursor { rest: line };
+        if cursor.end() {
+            continue;
+        }
+        if cursor.eat('[') {
+            cursor.space();
+            let name = cursor.key().ok_or(syntax)?;
+            cursor.space();
+            if !cursor.eat(']') || !cursor.end() {
+                return Err(RegistryError::Syntax { line: i + 1 });
+            }
+            tables.push((name, Vec::new()));
+            continue;
+        }
+        let pair = cursor.pair().filter(|_| cursor.end());
+        match (pair, tables.last_mut()) {
+            (Some(pair), Some((_, keys))) => keys.push(pair),
+            _ => return Err(syntax),
+        }
+    }
+    Ok(tables)
+}
+
+/// Cursor reads the TOML values on one line.
+struct Cursor<'a> {
+    rest: &'a str,
+}
+
+impl Cursor<'_> {
+    /// space skips whitespace.
+    fn space(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    /// end reports whether only whitespace and a comment are left.
+    fn end(&mut self) -> bool {
+        self.space();
+        self.rest.is_empty() || self.rest.starts_with('#')
+    }
+
+    /// eat consumes `c` if it comes next.
+    fn eat(&mut self, c: char) -> bool {
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// key reads a bare or quoted key.
+    fn key(&mut self) -> Option<String> {
+        if self.rest.starts_with('"') || self.rest.starts_with('\'') {
+            return self.string();
+        }
+        let end = self
+            .rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '-')
+            .unwrap_or(self.rest.len());
+        let (key, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(key.to_string()).filter(|key| !key.is_empty())
+    }
+
+    /// pair reads `key = value`.
+    fn pair(&mut self) -> Option<(String, Json)> {
+        let key = self.key()?;
+        self.space();
+        if !self.eat('=') {
+            return None;
+        }
+        self.space();
+        Some((key, self.value()?))
+    }
+
+    /// value reads a string, integer, boolean or inline table.
+    fn value(&mut self) -> Option<Json> {
+        if self.rest.starts_with('"') || self.rest.starts_with('\'') {
+            return self.string().map(Json::String);
+        }
+        if self.eat('{') {
+            let mut table = Vec::new();
+            self.space();
+            if self.eat('}') {
+                return Some(Json::Object(table));
+            }
+            loop {
+                self.space();
+                table.push(self.pair()?);
+                self.space();
+                if self.eat('}') {
+                    return Some(Json::Object(table));
+                }
+                if !self.eat(',') {
+                    return None;
+                }
+            }
+        }
+        let end = self
+            .rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(self.rest.len());
+        let (word, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        let digits = word.replace('_', "");
+        let (digits, radix) = match digits.get(..2) {
+            Some("0x") => (&digits[2..], 16),
+            Some("0o") => (&digits[2..], 8),
+            Some("0b") => (&digits[2..], 2),
+            _ => (digits.as_str(), 10),
+        };
+        match word {
+            "true" => Some(Json::Bool(true)),
+            "false" => Some(Json::Bool(false)),
+            _ => u128::from_str_radix(digits, radix)
+                .ok()
+                .map(|n| Json::Number(n.to_string())),
+        }
+    }
+
+    /// string reads a basic `"..."` or literal `'...'` string.
+    fn string(&mut self) -> Option<String> {
+        if self.eat('\'') {
+            let end = self.rest.find('\'')?;
+            let s = self.rest[..end].to_string();
+            self.rest = &self.rest[end + 1..];
+            return Some(s);
+        }
+        if !self.eat('"') {
+            return None;
+        }
+        let mut s = String::new();
+        let mut chars = self.rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &self.rest[i + 1..];
+                    return Some(s);
+                }
+                '\\' => s.push(match chars.next()?.1 {
+                    '"' => '"',
+                    '\\' => '\\',
+                    'n' => '\n',
+                    't' => '\t',
+                    _ => return None,
+                }),
+                c => s.push(c),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataModel, Endianness, LongDouble};
+
+    const REGISTRY: &str = r#"
+# In-house targets.
+[acme-dsp]
+data_model = "ILP32"   # the DSP
+endianness = 'big'
+char_signed = false
+alignments = { "long long" = 4, double = 4 }
+
+["acme-dsp-packed"]
+base = "acme-dsp"
+pack = 0x1
+
+[acme-arm]
+base = "LinuxArmhf"
+long_double = "binary64"
+null_pointer = 4_294_967_295
+"#;
+
+    #[test]
+    fn test_registry() {
+        let registry = Registry::from_toml(REGISTRY).unwrap();
+        let names: Vec<_> = registry.names().collect();
+        assert_eq!(names, ["acme-dsp", "acme-dsp-packed", "acme-arm"]);
+        let dsp = registry.get("acme-dsp").unwrap();
+        assert_eq!(dsp.data_model(), DataModel::ILP32);
+        assert_eq!(dsp.endianness, Endianness::Big);
+        assert!(!dsp.char_signed);
+        assert_eq!(dsp.abi.max_align(), Some(4));
+        let packed = registry.get("acme-dsp-packed").unwrap();
+        assert_eq!(packed.abi.pack(), Some(1));
+        assert_eq!(packed.endianness, Endianness::Big);
+        let arm = registry.get("acme-arm").unwrap();
+        assert_eq!(arm.long_double, LongDouble::Binary64);
+        assert_eq!(arm.null_pointer, u32::MAX as u128);
+        assert!(!arm.char_signed);
+        assert_eq!(registry.get("acme"), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |src| Registry::from_toml(src).unwrap_err();
+        assert!(matches!(err("x = 1"), RegistryError::Syntax { line: 1 }));
+        assert!(matches!(
+            err("[a]\n\nx = [1]"),
+            RegistryError::Syntax { line: 3 }
+        ));
+        assert!(matches!(err("[a] b"), RegistryError::Syntax { line: 1 }));
+        assert!(matches!(
+            err("[a]\nbase = \"LinuxX86_64\"\n[a]\nbase = \"a\""),
+            RegistryError::Duplicate(name) if name == "a"
+        ));
+        assert!(matches!(
+            err("[a]\nbase = \"b\""),
+            RegistryError::UnknownBase { base, .. } if base == "b"
+        ));
+        assert!(matches!(
+            err("[a]\nendianness = \"big\""),
+            RegistryError::Profile {
+                error: JsonError::Missing(_),
+                ..
+            }
+        ));
+        assert!(matches!(
+            Registry::load("/nonexistent/registry.toml"),
+            Err(RegistryError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_register() {
+        let target = TargetModel::new(DataModel::LP32).with_char_signed(false);
+        register("test_register", target);
+        assert_eq!(lookup("test_register"), Some(target));
+        register("test_register", TargetModel::new(DataModel::IP16));
+        assert_eq!(
+            lookup("test_register").map(|target| target.data_model()),
+            Some(DataModel::IP16)
+        );
+        let elsewhere = std::thread::spawn(|| lookup("test_register").is_some());
+        assert_eq!(elsewhere.join().unwrap(), cfg!(feature = "sync"));
+    }
+}
@@ -0,0 +1,776 @@
+//! Mapping C types to the Rust types with the same representation.
+//!
+//! On the host, `std::os::raw` (and `libc`) already alias `c_long` and
+//! friends to the right primitive. For a foreign target the alias has to be
+//! picked from that target's data model instead: `c_long` is `i32` on LLP64
+//! but `i64` on LP64.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! use data_models::rust::RustType;
+//! assert_eq!(DataModel::LLP64.rust_type_of(CType::Long), Some(RustType::I32));
+//! assert_eq!(DataModel::LP64.rust_type_of(CType::Long), Some(RustType::I64));
+//! assert_eq!(CType::Long.rust_raw_name(), "c_long");
+//! ```
+//!
+//! [`DataModel::rust_type_aliases`] writes the whole mapping out as Rust
+//! source, for tools that model a foreign target rather than the host, and
+//! [`Layout::rust_definition`] does the same for a record.
+
+use crate::layout::{FieldLayout, FieldType, Layout, RecordKind};
+use crate::{CType, DataModel, TargetModel};
+use std::fmt;
+
+/// RustType is a Rust primitive type a C type can be represented as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RustType {
+    /// `bool`
+    Bool,
+    /// `i8`
+    I8,
+    /// `u8`
+    U8,
+    /// `i16`
+    I16,
+    /// `u16`
+    U16,
+    /// `i32`
+    I32,
+    /// `u32`
+    U32,
+    /// `i64`
+    I64,
+    /// `u64`
+    U64,
+    /// `f32`
+    F32,
+    /// `f64`
+    F64,
+}
+
+impl RustType {
+    /// integer returns the Rust integer of `size` bytes and the given
+    /// signedness, if there is one.
+    pub fn integer(size: usize, signed: bool) -> Option<RustType> {
+        use RustType::*;
+        Some(match (size, signed) {
+            (1, true) => I8,
+            (1, false) => U8,
+            (2, true) => I16,
+            (2, false) => U16,
+            (4, true) => I32,
+            (4, false) => U32,
+            (8, true) => I64,
+            (8, false) => U64,
+            _ => return None,
+        })
+    }
+
+    /// is_signed reports whether the type is a signed integer or a float.
+    pub fn is_signed(self) -> bool {
+        use RustType::*;
+        matches!(self, I8 | I16 | I32 | I64 | F32 | F64)
+    }
+
+    /// name is the Rust spelling of the type, e.g. `"i64"`.
+    pub fn name(self) -> &'static str {
+        use RustType::*;
+        match self {
+            Bool => "bool",
+            I8 => "i8",
+            U8 => "u8",
+            I16 => "i16",
+            U16 => "u16",
+            I32 => "i32",
+            U32 => "u32",
+            I64 => "i64",
+            U64 => "u64",
+            F32 => "f32",
+            F64 => "f64",
+        }
+    }
+}
+
+impl fmt::Display for RustType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl CType {
+    /// rust_raw_name is the name of the type in `std::os::raw` and `libc`,
+    /// e.g. `"c_long"`.
+    ///
+    /// Pointers have no such alias; they are named `"uintptr_t"` after the
+    /// `libc` integer that holds them.
+    pub fn rust_raw_name(self) -> &'static str {
+        use CType::*;
+        match self {
+            Bool => "bool",
+            Char => "c_char",
+            SChar => "c_schar",
+            UChar => "c_uchar",
+            Short => "c_short",
+            UShort => "c_ushort",
+            Int => "c_int",
+            UInt => "c_uint",
+            Long => "c_long",
+            ULong => "c_ulong",
+            LongLong => "c_longlong",
+            ULongLong => "c_ulonglong",
+            Pointer => "uintptr_t",
+            Float => "c_float",
+            Double => "c_double",
+        }
+    }
+}
+
+impl DataModel {
+    /// rust_type_of returns the Rust primitive with the same size and
+    /// signedness as a C type on this model, or `None` when the model does
+    /// not have the type (`long` on IP16).
+    ///
+    /// Pointers map to the unsigned integer of their width, which is how
+    /// foreign addresses are held on the host. Plain `char` is taken to be
+    /// signed as on x86; [`TargetModel::rust_type_of`] knows the targets
+    /// where it is unsigned, like ARM, PowerPC and s390x.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::rust::RustType;
+    /// let model = DataModel::ILP32;
+    /// assert_eq!(model.rust_type_of(CType::ULongLong), Some(RustType::U64));
+    /// assert_eq!(model.rust_type_of(CType::Pointer), Some(RustType::U32));
+    /// assert_eq!(DataModel::IP16.rust_type_of(CType::Long), None);
+    /// ```
+    pub fn rust_type_of(self, ty: CType) -> Option<RustType> {
+        use CType::*;
+        let size = self.size(ty);
+        match ty {
+            _ if size == 0 => None,
+            Bool => Some(RustType::Bool),
+            Float => Some(RustType::F32),
+            Double => Some(RustType::F64),
+            Char | SChar | Short | Int | Long | LongLong => RustType::integer(size, true),
+            UChar | UShort | UInt | ULong | ULongLong | Pointer => RustType::integer(size, false),
+        }
+    }
+
+    /// rust_type_name is the name of the Rust primitive for one of the
+    /// marker types, such as [`LongLong`](crate::LongLong), for code
+    /// generators that only need the spelling. See
+    /// [`DataModel::rust_type_of`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LP64.rust_type_name::<LongLong>(), Some("i64"));
+    /// assert_eq!(LongLong::C_NAME, "long long");
+    /// assert_eq!(DataModel::IP16.rust_type_name::<Long>(), None);
+    /// ```
+    pub fn rust_type_name<T>(self) -> Option<&'static str> {
+        CType::of::<T>()
+            .and_then(|ty| self.rust_type_of(ty))
+            .map(RustType::name)
+    }
+}
+
+impl TargetModel {
+    /// rust_type_of returns the Rust primitive with the same size and
+    /// signedness as a C type on the target, mapping plain `char` to `u8`
+    /// where it is unsigned. See [`DataModel::rust_type_of`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::rust::RustType;
+    /// assert_eq!(Platform::LinuxX86_64.target().rust_type_of(CType::Char), Some(RustType::I8));
+    /// assert_eq!(Platform::LinuxAarch64.target().rust_type_of(CType::Char), Some(RustType::U8));
+    /// ```
+    pub fn rust_type_of(&self, ty: CType) -> Option<RustType> {
+        let model = self.data_model();
+        match ty {
+            CType::Char => RustType::integer(model.size(ty), self.char_signed),
+            _ => model.rust_type_of(ty),
+        }
+    }
+}
+
+/// The pointer-sized integer aliases of `stdint.h` and `stddef.h`, with
+/// whether they are signed.
+const POINTER_ALIASES: [(&str, bool); 5] = [
+    ("intptr_t", true),
+    ("uintptr_t", false),
+    ("size_t", false),
+    ("ssize_t", true),
+    ("ptrdiff_t", true),
+];
+
+impl DataModel {
+    /// rust_type_aliases emits Rust source for a module named `module`
+    /// defining the `std::os::raw` aliases (`c_int`, `c_long`, ...) and the
+    /// pointer-sized integers (`uintptr_t`, `size_t`, ...) of this model.
+    ///
+    /// Types the model does not have are left out.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let src = DataModel::LLP64.rust_type_aliases("ctypes");
+    /// assert!(src.contains("pub mod ctypes {"));
+    /// assert!(src.contains("    pub type c_long = i32;"));
+    /// assert!(src.contains("    pub type size_t = u64;"));
+    /// ```
+    pub fn rust_type_aliases(self, module: &str) -> String {
+        TargetModel::new(self).rust_type_aliases(module)
+    }
+}
+
+impl TargetModel {
+    /// rust_type_aliases emits Rust source for a module of the type aliases
+    /// of the target, with `c_char` following the signedness of its plain
+    /// `char`. See [`DataModel::rust_type_aliases`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let src = Platform::LinuxArmhf.target().rust_type_aliases("armhf");
+    /// assert!(src.contains("    pub type c_char = u8;\n"));
+    /// assert!(src.contains("    pub type c_schar = i8;\n"));
+    /// ```
+    pub fn rust_type_aliases(&self, module: &str) -> String {
+        use CType::*;
+        let model = self.data_model();
+        let mut src = format!(
+            "/// C types of the {:?} data model.\n#[allow(non_camel_case_types)]\npub mod {} {{\n",
+            model, module
+        );
+        let types = [
+            Char, SChar, UChar, Short, UShort, Int, UInt, Long, ULong, LongLong, ULongLong, Float,
+            Double,
+        ];
+        for &ty in types.iter() {
+            if let Some(rust) = self.rust_type_of(ty) {
+                src += &format!("    pub type {} = {};\n", ty.rust_raw_name(), rust);
+            }
+        }
+        let pointer = model.size(Pointer);
+        for &(name, signed) in POINTER_ALIASES.iter() {
+            if let Some(rust) = RustType::integer(pointer, signed) {
+                src += &format!("    pub type {} = {};\n", name, rust);
+            }
+        }
+        src + "}\n"
+    }
+}
+
+/// Keywords that have to be written as raw identifiers to name a field.
+const KEYWORDS: [&str; 45] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where",
+];
+
+/// ident spells a C identifier as a Rust one.
+pub(crate) fn ident(name: &str) -> String {
+    match KEYWORDS.contains(&name) {
+        true => format!("r#{}", name),
+        false => name.to_string(),
+    }
+}
+
+/// Definition is one generated Rust type: its name, its source and its
+/// alignment on the host.
+struct Definition {
+    name: String,
+    src: String,
+    align: usize,
+}
+
+/// pow2_divisor returns the largest power of two dividing `offset`, or
+/// `usize::MAX` for offset 0.
+fn pow2_divisor(offset: usize) -> usize {
+    match offset {
+        0 => usize::MAX,
+        _ => 1 << offset.trailing_zeros(),
+    }
+}
+
+impl Layout {
+    /// rust_definition emits Rust source for a `#[repr(C)]` type with the
+    /// layout of the record, using the fixed-width types of `target` for
+    /// its scalar fields, after the definitions of the records nested in it.
+    ///
+    /// Every gap is filled by an explicit `_paddingN: [u8; n]` field, so the
+    /// offsets do not depend on the host inserting the same padding as the
+    /// target. Where the target aligns a field less than the host aligns
+    /// its Rust type (`long long` on i686, packed records) the type is
+    /// `packed`; where it aligns the record more, it is `align(N)`. Plain
+    /// `char` follows the signedness of the target. The
+    /// bytes of consecutive bit-fields become a single opaque
+    /// `_bitfieldN: [u8; n]` field. Nested records without a tag are named
+    /// after the record and the field holding them, and anonymous members
+    /// become `_anonN` fields.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::Record;
+    /// let record = Record::new("entry").field("key", CType::Int).field("value", CType::Long);
+    /// let src = record.layout(DataModel::LLP64).unwrap().rust_definition(DataModel::LLP64);
+    /// assert!(src.contains("pub struct entry {\n    pub key: i32,\n    pub value: i32,\n}\n"));
+    /// ```
+    pub fn rust_definition(&self, target: impl Into<TargetModel>) -> String {
+        let mut defs = Vec::new();
+        let name = match self.name.as_str() {
+            "" => "anon",
+            name => name,
+        };
+        self.rust_record(name, &target.into(), &mut defs);
+        let srcs: Vec<_> = defs.into_iter().map(|def| def.src).collect();
+        srcs.join("\n")
+    }
+
+    /// rust_record appends the definition of the record named `name`, and
+    /// of the records nested in it, to `defs` and returns its alignment on
+    /// the host.
+    fn rust_record(&self, name: &str, target: &TargetModel, defs: &mut Vec<Definition>) -> usize {
+        if let Some(def) = defs.iter().find(|def| def.name == name) {
+            return def.align;
+        }
+        let mut members = Vec::new();
+        let (mut end, mut natural, mut pack) = (0, 1, usize::MAX);
+        let (mut paddings, mut bitfields, mut anons) = (0, 0, 0);
+        let mut bits: Option<(usize, usize)> = None;
+        for field in self.fields.iter() {
+            if let Some(width) = field.bits {
+                if width > 0 {
+                    let start = field.offset + field.bit_offset / 8;
+                    let stop = field.offset + (field.bit_offset + width).div_ceil(8);
+                    bits = match (bits, self.kind) {
+                        (Some((from, to)), RecordKind::Struct) if start <= to => {
+                            Some((from, to.max(stop)))
+                        }
+                        (Some((from, to)), _) => {
+                            let decl = format!("_bitfield{}: [u8; {}]", bitfields, to - from);
+                            members.push((from, decl, to - from));
+                            bitfields += 1;
+                            Some((start, stop))
+                        }
+                        (None, _) => Some((start, stop)),
+                    };
+                }
+                continue;
+            }
+            let rust = match field.name.as_str() {
+                "" => {
+                    anons += 1;
+                    format!("_anon{}", anons - 1)
+                }
+                name => ident(name),
+            };
+            let nested_name = match field.nested.as_deref() {
+                Some(nested) if !nested.name.is_empty() => nested.name.clone(),
+                _ => format!(
+                    "{}_{}",
+                    name,
+                    rust.trim_start_matches("r#").trim_start_matches('_')
+                ),
+            };
+            let (ty, align) =
+                rust_field_type(field, &field.ty, field.size, &nested_name, target, defs);
+            if self.kind == RecordKind::Struct && align > pow2_divisor(field.offset) {
+                pack = pack.min(pow2_divisor(field.offset));
+            }
+            natural = natural.max(align);
+            members.push((field.offset, format!("{}: {}", rust, ty), field.size));
+        }
+        if let Some((from, to)) = bits {
+            let decl = format!("_bitfield{}: [u8; {}]", bitfields, to - from);
+            members.push((from, decl, to - from));
+        }
+        if self.kind == RecordKind::Struct {
+            members.sort_by_key(|member| member.0);
+        }
+
+        let mut body = String::new();
+        for (offset, member, size) in members {
+            if self.kind == RecordKind::Struct && offset > end {
+                body += &format!("    pub _padding{}: [u8; {}],\n", paddings, offset - end);
+                paddings += 1;
+            }
+            body += &format!("    pub {},\n", member);
+            end = end.max(offset + size);
+        }
+        if end < self.size {
+            let padding = match self.kind {
+                RecordKind::Struct => self.size - end,
+                RecordKind::Union => self.size,
+            };
+            body += &format!("    pub _padding{}: [u8; {}],\n", paddings, padding);
+        }
+
+        if natural > self.align {
+            pack = pack.min(self.align);
+        }
+        let (repr, align) = if pack < natural {
+            (format!("C, packed({})", pack), pack)
+        } else if self.align > natural {
+            (format!("C, align({})", self.align), self.align)
+        } else {
+            ("C".to_string(), natural)
+        };
+        let keyword = match self.kind {
+            RecordKind::Struct => "struct",
+            RecordKind::Union => "union",
+        };
+        let src = format!(
+            "#[repr({})]\n#[derive(Clone, Copy)]\n#[allow(non_camel_case_types)]\npub {} {} {{\n{}}}\n",
+            repr, keyword, name, body
+        );
+        defs.push(Definition {
+            name: name.to_string(),
+            src,
+            align,
+        });
+        align
+    }
+}
+
+/// rust_field_type returns the Rust spelling of `ty`, the type of `field`
+/// or of its elements, and its alignment on the host. `size` is the size of
+/// `ty` on the target, or 0 if unknown. A record type is defined as `name`
+/// unless it has a tag.
+fn rust_field_type(
+    field: &FieldLayout,
+    ty: &FieldType,
+    size: usize,
+    name: &str,
+    target: &TargetModel,
+    defs: &mut Vec<Definition>,
+) -> (String, usize) {
+    let model = target.data_model();
+    match ty {
+        FieldType::Scalar(ty) => {
+            let rust = target.rust_type_of(*ty).unwrap_or(RustType::U8);
+            (rust.to_string(), model.size(*ty).max(1))
+        }
+        FieldType::Enum(e) => {
+            // The field size tells whether short enums were in effect.
+            let ty = e.underlying_type(model).unwrap_or(CType::Int);
+            let signed = e.values.iter().any(|&(_, value)| value < 0);
+            match (e.underlying, RustType::integer(size, signed)) {
+                (None, Some(rust)) => (rust.to_string(), size),
+                _ => {
+                    let rust = model.rust_type_of(ty).unwrap_or(RustType::U8);
+                    (rust.to_string(), model.size(ty).max(1))
+                }
+            }
+        }
+        FieldType::Array(element, n) => {
+            let size = size.checked_div(*n).unwrap_or(0);
+            let (element, align) = rust_field_type(field, element, size, name, target, defs);
+            (format!("[{}; {}]", element, n), align)
+        }
+        FieldType::FlexibleArray(element) => {
+            let (element, align) = rust_field_type(field, element, 0, name, target, defs);
+            (format!("[{}; 0]", element), align)
+        }
+        FieldType::Pointer(_) => {
+            let rust = model.rust_type_of(CType::Pointer).unwrap_or(RustType::U8);
+            (rust.to_string(), model.size(CType::Pointer).max(1))
+        }
+        FieldType::Record(_) | FieldType::Layout(_) => match field.nested.as_deref() {
+            Some(nested) => (name.to_string(), nested.rust_record(name, target, defs)),
+            None => (name.to_string(), 1),
+        },
+    }
+}
+
+/// Integer is the integer of `SIZE` bytes with the given signedness, for
+/// [`model_types!`](crate::model_types) to select through [`IntegerType`].
+#[doc(hidden)]
+pub struct Integer<const SIZE: usize, const SIGNED: bool>;
+
+/// IntegerType names the Rust primitive of an [`Integer`].
+#[doc(hidden)]
+pub trait IntegerType {
+    type Type;
+}
+
+/// Missing stands in for a C type the model lacks, such as `long long` on
+/// IP16. It has no values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Missing {}
+
+macro_rules! integer_types {
+    ($($size:literal => $signed:ty, $unsigned:ty;)*) => {
+        $(
+            impl IntegerType for Integer<$size, true> {
+                type Type = $signed;
+            }
+
+            impl IntegerType for Integer<$size, false> {
+                type Type = $unsigned;
+            }
+        )*
+    };
+}
+
+integer_types! {
+    0 => Missing, Missing;
+    1 => i8, u8;
+    2 => i16, u16;
+    4 => i32, u32;
+    8 => i64, u64;
+}
+
+/// NATIVE_CHAR_SIGNED reports whether plain `char` is signed on the target
+/// this crate is compiled for.
+#[doc(hidden)]
+pub const NATIVE_CHAR_SIGNED: bool = std::os::raw::c_char::MIN != 0;
+
+/// model_types defines `target_` type aliases, `target_long` and so on, of
+/// the fixed-width Rust primitives for the C types of a data model, without
+/// going through `std::os::raw`. Without an argument the model is the one
+/// being compiled for, [`DataModel::NATIVE`], and `target_char` follows the
+/// signedness of its plain `char`; a named model takes plain `char` to be
+/// signed. Types the model lacks are aliases of [`Missing`].
+///
+/// The aliases are `target_char`, `target_schar`, `target_uchar`,
+/// `target_short`, `target_ushort`, `target_int`, `target_uint`,
+/// `target_long`, `target_ulong`, `target_longlong`, `target_ulonglong`,
+/// `target_float`, `target_double`, and the pointer-sized `target_intptr`,
+/// `target_uintptr`, `target_size`, `target_ssize` and `target_ptrdiff`.
+///
+/// # Example
+/// ```
+/// mod win64 {
+///     data_models::model_types!(LLP64);
+/// }
+/// mod host {
+///     data_models::model_types!();
+/// }
+/// assert_eq!(std::mem::size_of::<win64::target_long>(), 4);
+/// assert_eq!(std::mem::size_of::<win64::target_size>(), 8);
+/// let max: win64::target_ulonglong = u64::MAX;
+/// assert_eq!(
+///     std::mem::size_of::<host::target_long>(),
+///     std::mem::size_of::<std::os::raw::c_long>()
+/// );
+/// ```
+#[macro_export]
+macro_rules! model_types {
+    () => {
+        $crate::model_types!(@types $crate::DataModel::NATIVE, $crate::rust::NATIVE_CHAR_SIGNED);
+    };
+    ($model:ident) => {
+        $crate::model_types!(@types $crate::DataModel::$model, true);
+    };
+    (@types $model:expr, $char_signed:expr) => {
+        $crate::model_types!(@alias $model, target_char, Char, $char_signed);
+        $crate::model_types!(@alias $model, target_schar, SChar, true);
+        $crate::model_types!(@alias $model, target_uchar, UChar, false);
+        $crate::model_types!(@alias $model, target_short, Short, true);
+        $crate::model_types!(@alias $model, target_ushort, UShort, false);
+        $crate::model_types!(@alias $model, target_int, Int, true);
+        $crate::model_types!(@alias $model, target_uint, UInt, false);
+        $crate::model_types!(@alias $model, target_long, Long, true);
+        $crate::model_types!(@alias $model, target_ulong, ULong, false);
+        $crate::model_types!(@alias $model, target_longlong, LongLong, true);
+        $crate::model_types!(@alias $model, target_ulonglong, ULongLong, false);
+        $crate::model_types!(@alias $model, target_intptr, Pointer, true);
+        $crate::model_types!(@alias $model, target_uintptr, Pointer, false);
+        $crate::model_types!(@alias $model, target_size, Pointer, false);
+        $crate::model_types!(@alias $model, target_ssize, Pointer, true);
+        $crate::model_types!(@alias $model, target_ptrdiff, Pointer, true);
+        #[allow(non_camel_case_types)]
+        pub type target_float = f32;
+        #[allow(non_camel_case_types)]
+        pub type target_double = f64;
+    };
+    (@alias $model:expr, $name:ident, $ty:ident, $signed:expr) => {
+        #[allow(non_camel_case_types)]
+        pub type $name = <$crate::rust::Integer<
+            { $model.size($crate::CType::$ty) },
+            { $signed },
+        > as $crate::rust::IntegerType>::Type;
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Platform;
+
+    #[allow(dead_code)]
+    mod ip16 {
+        model_types!(IP16);
+    }
+
+    #[allow(dead_code)]
+    mod lp64 {
+        model_types!(LP64);
+    }
+
+    #[test]
+    fn test_model_types() {
+        use std::mem::size_of;
+        assert_eq!(size_of::<ip16::target_int>(), 2);
+        assert_eq!(size_of::<ip16::target_size>(), 2);
+        assert_eq!(size_of::<ip16::target_long>(), 0);
+        assert_eq!(size_of::<lp64::target_long>(), 8);
+        let char_min: lp64::target_char = -128;
+        let uint_max: lp64::target_uint = u32::MAX;
+        assert_eq!((char_min, uint_max), (i8::MIN, u32::MAX));
+        let ptrdiff: lp64::target_ptrdiff = -1;
+        assert_eq!(ptrdiff, -1i64);
+    }
+
+    #[test]
+    fn test_rust_type_of() {
+        use CType::*;
+        use RustType::*;
+        assert_eq!(DataModel::LP64.rust_type_of(Char), Some(I8));
+        assert_eq!(DataModel::LP64.rust_type_of(UChar), Some(U8));
+        assert_eq!(DataModel::LP64.rust_type_of(ULong), Some(U64));
+        assert_eq!(DataModel::LLP64.rust_type_of(ULong), Some(U32));
+        assert_eq!(DataModel::LP32.rust_type_of(Int), Some(I16));
+        assert_eq!(DataModel::SILP64.rust_type_of(Short), Some(I64));
+        assert_eq!(DataModel::IP16.rust_type_of(Pointer), Some(U16));
+        assert_eq!(
+            DataModel::LP64.rust_type_of(CType::Bool),
+            Some(RustType::Bool)
+        );
+        assert_eq!(DataModel::LP64.rust_type_of(Double), Some(F64));
+        assert_eq!(DataModel::Unknown.rust_type_of(Int), None);
+
+        let target = Platform::LinuxPpc64le.target();
+        assert_eq!(target.rust_type_of(Char), Some(U8));
+        assert_eq!(target.rust_type_of(SChar), Some(I8));
+        assert_eq!(target.rust_type_of(Long), Some(I64));
+        assert_eq!(
+            TargetModel::new(DataModel::Unknown).rust_type_of(Char),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rust_type_aliases() {
+        let src = DataModel::LP64.rust_type_aliases("lp64");
+        assert!(src.starts_with("/// C types of the LP64 data model.\n"));
+        assert!(src.contains("    pub type c_char = i8;\n"));
+        assert!(src.contains("    pub type c_ulong = u64;\n"));
+        assert!(src.contains("    pub type c_double = f64;\n"));
+        assert!(src.contains("    pub type ssize_t = i64;\n"));
+        assert!(src.ends_with("}\n"));
+
+        let src = Platform::LinuxS390x.target().rust_type_aliases("s390x");
+        assert!(src.contains("    pub type c_char = u8;\n"));
+        assert!(src.contains("    pub type c_long = i64;\n"));
+
+        let src = DataModel::IP16.rust_type_aliases("pdp11");
+        assert!(!src.contains("c_long"));
+        assert!(src.contains("    pub type uintptr_t = u16;\n"));
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(RustType::U16.to_string(), "u16");
+        assert_eq!(CType::ULongLong.rust_raw_name(), "c_ulonglong");
+        assert_eq!(
+            DataModel::LLP64.rust_type_name::<crate::ULong>(),
+            Some("u32")
+        );
+        assert_eq!(
+            DataModel::ILP32.rust_type_name::<crate::Pointer>(),
+            Some("u32")
+        );
+        assert_eq!(DataModel::LP64.rust_type_name::<DataModel>(), None);
+        assert_eq!(
+            <crate::Pointer as crate::CTypeInfo>::C_NAME,
+            CType::Pointer.c_name()
+        );
+    }
+
+    #[test]
+    fn test_rust_definition() {
+        use crate::layout::{Field, Record};
+        let record = Record::new("node")
+            .field("kind", CType::Char)
+            .bitfield("flag", CType::UInt, 1)
+            .bitfield("mode", CType::UInt, 12)
+            .field("type", CType::Short)
+            .with_field(Field::new(
+                "",
+                Record::union("")
+                    .field("i", CType::Int)
+                    .field("p", CType::Pointer),
+            ));
+        let src = record
+            .layout(DataModel::LP64)
+            .unwrap()
+            .rust_definition(DataModel::LP64);
+        let expected = "\
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[allow(non_camel_case_types)]
+pub union node_anon0 {
+    pub i: i32,
+    pub p: u64,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[allow(non_camel_case_types)]
+pub struct node {
+    pub kind: i8,
+    pub _bitfield0: [u8; 2],
+    pub _padding0: [u8; 1],
+    pub r#type: i16,
+    pub _padding1: [u8; 2],
+    pub _anon0: node_anon0,
+}
+";
+        assert_eq!(src, expected);
+    }
+
+    #[test]
+    fn test_rust_definition_repr() {
+        use crate::layout::{Abi, Enum, Record};
+        let record = Record::new("s")
+            .field("c", CType::Char)
+            .field("ll", CType::LongLong);
+        let layout = record.layout(Platform::LinuxI686).unwrap();
+        let src = layout.rust_definition(DataModel::ILP32);
+        assert!(src.starts_with("#[repr(C, packed(4))]\n"));
+        assert!(src.contains("    pub _padding0: [u8; 3],\n    pub ll: i64,\n"));
+
+        let record = Record::new("s").aligned(16).field("c", CType::Char);
+        let layout = record.layout(DataModel::LP64).unwrap();
+        let src = layout.rust_definition(DataModel::LP64);
+        assert!(src.starts_with("#[repr(C, align(16))]\n"));
+        assert!(src.contains("    pub _padding0: [u8; 15],\n"));
+
+        let kind = Enum::new("kind").value("a", -1);
+        let record = Record::new("s")
+            .field("k", kind.clone())
+            .field("ks", FieldType::array(kind.packed(), 2));
+        let abi = Abi::new(DataModel::LP64);
+        let src = record.layout(abi).unwrap().rust_definition(DataModel::LP64);
+        assert!(src.contains("    pub k: i32,\n    pub ks: [i8; 2],\n"));
+        let abi = abi.with_short_enums();
+        let src = record.layout(abi).unwrap().rust_definition(DataModel::LP64);
+        assert!(src.contains("    pub k: i8,\n"));
+
+        let record = Record::new("s").field("c", CType::Char);
+        let layout = record.layout(Platform::LinuxAarch64).unwrap();
+        let src = layout.rust_definition(Platform::LinuxAarch64);
+        assert!(src.contains("    pub c: u8,\n"));
+    }
+}
@@ -0,0 +1,128 @@
+//! The types each revision of the C standard provides.
+
+use crate::{CStandard, CType, DataModel};
+
+impl CStandard {
+    /// Every revision of the standard, from the oldest.
+    pub const ALL: &'static [CStandard] = &[
+        CStandard::C89,
+        CStandard::C99,
+        CStandard::C11,
+        CStandard::C17,
+        CStandard::C23,
+    ];
+
+    /// name returns the usual name of the revision, like `"C99"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            CStandard::C89 => "C89",
+            CStandard::C99 => "C99",
+            CStandard::C11 => "C11",
+            CStandard::C17 => "C17",
+            CStandard::C23 => "C23",
+        }
+    }
+
+    /// std_flag returns the GCC and Clang option selecting the revision.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(CStandard::C17.std_flag(), "-std=c17");
+    /// ```
+    pub fn std_flag(self) -> &'static str {
+        match self {
+            CStandard::C89 => "-std=c89",
+            CStandard::C99 => "-std=c99",
+            CStandard::C11 => "-std=c11",
+            CStandard::C17 => "-std=c17",
+            CStandard::C23 => "-std=c23",
+        }
+    }
+
+    /// has reports whether the revision has the type `ty`. `_Bool`, `long
+    /// long` and `unsigned long long` came with C99.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert!(CStandard::C99.has(CType::Bool));
+    /// assert!(!CStandard::C89.has(CType::ULongLong));
+    /// ```
+    pub fn has(self, ty: CType) -> bool {
+        match ty {
+            CType::Bool | CType::LongLong | CType::ULongLong => self >= CStandard::C99,
+            _ => true,
+        }
+    }
+
+    /// has_char8_t reports whether `<uchar.h>` declares `char8_t`, an
+    /// `unsigned char` for UTF-8 code units, which came with C23.
+    pub fn has_char8_t(self) -> bool {
+        self >= CStandard::C23
+    }
+
+    /// has_bit_int reports whether the revision has the bit-precise
+    /// integer types `_BitInt(N)`, which came with C23.
+    pub fn has_bit_int(self) -> bool {
+        self >= CStandard::C23
+    }
+}
+
+impl DataModel {
+    /// types_in returns the C types the model has under the revision
+    /// `std`, in the order of [`CType::ALL`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::LP64.types_in(CStandard::C89).len(), 12);
+    /// assert_eq!(DataModel::LP64.types_in(CStandard::C99).len(), 15);
+    /// ```
+    pub fn types_in(self, std: CStandard) -> Vec<CType> {
+        CType::ALL
+            .iter()
+            .copied()
+            .filter(|&ty| self.has(ty) && std.has(ty))
+            .collect()
+    }
+
+    /// bit_int_max_width returns the least value of `BITINT_MAXWIDTH`
+    /// C23 allows, the width of `unsigned long long`, or `None` before
+    /// C23 or where the model lacks `long long`. Compilers may allow
+    /// wider `_BitInt`s; Clang allows 8388608 bits.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert_eq!(DataModel::ILP32.bit_int_max_width(CStandard::C23), Some(64));
+    /// assert_eq!(DataModel::ILP32.bit_int_max_width(CStandard::C17), None);
+    /// ```
+    pub fn bit_int_max_width(self, std: CStandard) -> Option<usize> {
+        match 8 * self.size(CType::ULongLong) {
+            0 => None,
+            _ if !std.has_bit_int() => None,
+            bits => Some(bits),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standards() {
+        assert!(CStandard::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+        let names: Vec<_> = CStandard::ALL.iter().map(|std| std.name()).collect();
+        assert_eq!(names, ["C89", "C99", "C11", "C17", "C23"]);
+        assert!(!CStandard::C17.has_char8_t());
+        assert!(CStandard::C23.has_char8_t());
+        assert!(CStandard::C89.has(CType::Pointer));
+        assert!(!DataModel::ILP32
+            .types_in(CStandard::C89)
+            .contains(&CType::Bool));
+        assert_eq!(DataModel::IP16.types_in(CStandard::C23).len(), 9);
+        assert_eq!(DataModel::IP16.bit_int_max_width(CStandard::C23), None);
+    }
+}
@@ -0,0 +1,242 @@
+//! The standard typedefs of `stddef.h`, `stdint.h` and POSIX.
+//!
+//! Which base type a typedef names is up to the C library, which usually
+//! follows the data model. The resolutions here pick the first base type of
+//! the right width in rank order, which is what glibc and the Windows CRT
+//! do: `size_t` is `unsigned int` on ILP32, `unsigned long` on LP64 and
+//! `unsigned long long` on LLP64. [`Platform::resolve`] knows the C
+//! libraries that differ, like macOS making `int64_t` a `long long`.
+
+use crate::{CType, DataModel, Platform};
+
+/// StdType is one of the standard integer typedefs.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::stdint::StdType;
+/// assert_eq!(DataModel::LLP64.resolve(StdType::Size), Some(CType::ULongLong));
+/// assert_eq!(StdType::Size.c_name(), "size_t");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StdType {
+    /// `size_t`
+    Size,
+    /// `ssize_t`
+    SSize,
+    /// `ptrdiff_t`
+    PtrDiff,
+    /// `intmax_t`
+    IntMax,
+    /// `uintmax_t`
+    UIntMax,
+    /// `intptr_t`
+    IntPtr,
+    /// `uintptr_t`
+    UIntPtr,
+    /// `int8_t`
+    Int8,
+    /// `uint8_t`
+    UInt8,
+    /// `int16_t`
+    Int16,
+    /// `uint16_t`
+    UInt16,
+    /// `int32_t`
+    Int32,
+    /// `uint32_t`
+    UInt32,
+    /// `int64_t`
+    Int64,
+    /// `uint64_t`
+    UInt64,
+}
+
+impl StdType {
+    /// c_name is the name of the typedef, e.g. `"size_t"`.
+    pub fn c_name(self) -> &'static str {
+        use StdType::*;
+        match self {
+            Size => "size_t",
+            SSize => "ssize_t",
+            PtrDiff => "ptrdiff_t",
+            IntMax => "intmax_t",
+            UIntMax => "uintmax_t",
+            IntPtr => "intptr_t",
+            UIntPtr => "uintptr_t",
+            Int8 => "int8_t",
+            UInt8 => "uint8_t",
+            Int16 => "int16_t",
+            UInt16 => "uint16_t",
+            Int32 => "int32_t",
+            UInt32 => "uint32_t",
+            Int64 => "int64_t",
+            UInt64 => "uint64_t",
+        }
+    }
+
+    /// is_signed reports whether the typedef names a signed type.
+    pub fn is_signed(self) -> bool {
+        use StdType::*;
+        matches!(
+            self,
+            SSize | PtrDiff | IntMax | IntPtr | Int8 | Int16 | Int32 | Int64
+        )
+    }
+
+    /// width returns the width in bits of an exact-width type like
+    /// `int32_t`, or `None` for the other typedefs.
+    pub fn width(self) -> Option<usize> {
+        use StdType::*;
+        match self {
+            Int8 | UInt8 => Some(8),
+            Int16 | UInt16 => Some(16),
+            Int32 | UInt32 => Some(32),
+            Int64 | UInt64 => Some(64),
+            _ => None,
+        }
+    }
+}
+
+/// TypeName is a C integer or floating type spelled either as a base type
+/// or as a standard typedef.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::stdint::{StdType, TypeName};
+/// assert_eq!(TypeName::from(CType::Long).c_name(), "long");
+/// assert_eq!(TypeName::from(StdType::PtrDiff).c_name(), "ptrdiff_t");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TypeName {
+    /// A base type.
+    C(CType),
+    /// A standard typedef.
+    Std(StdType),
+}
+
+impl TypeName {
+    /// c_name is how the type is spelled in C.
+    pub fn c_name(self) -> &'static str {
+        match self {
+            TypeName::C(ty) => ty.c_name(),
+            TypeName::Std(ty) => ty.c_name(),
+        }
+    }
+}
+
+impl From<CType> for TypeName {
+    fn from(ty: CType) -> TypeName {
+        TypeName::C(ty)
+    }
+}
+
+impl From<StdType> for TypeName {
+    fn from(ty: StdType) -> TypeName {
+        TypeName::Std(ty)
+    }
+}
+
+impl DataModel {
+    /// resolve returns the base type a type name stands for on the model,
+    /// or `None` if the model has no base type for it.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::stdint::StdType;
+    /// assert_eq!(DataModel::ILP32.resolve(StdType::Size), Some(CType::UInt));
+    /// assert_eq!(DataModel::LP64.resolve(StdType::PtrDiff), Some(CType::Long));
+    /// assert_eq!(DataModel::ILP32.resolve(StdType::IntMax), Some(CType::LongLong));
+    /// assert_eq!(DataModel::LP64.resolve(CType::Short), Some(CType::Short));
+    /// ```
+    pub fn resolve(self, ty: impl Into<TypeName>) -> Option<CType> {
+        use CType::*;
+        let ty = match ty.into() {
+            TypeName::C(ty) if !self.has(ty) => return None,
+            TypeName::C(ty) => return Some(ty),
+            TypeName::Std(ty) => ty,
+        };
+        let size = match ty {
+            StdType::IntMax | StdType::UIntMax => [Int, Long, LongLong]
+                .iter()
+                .map(|&ty| self.size(ty))
+                .max()
+                .unwrap_or(0),
+            _ => match ty.width() {
+                Some(width) => width / 8,
+                None => self.size(Pointer),
+            },
+        };
+        let base = [SChar, Short, Int, Long, LongLong]
+            .iter()
+            .copied()
+            .filter(|&base| ty.width().is_some() || base.rank() >= Int.rank())
+            .find(|&base| size != 0 && self.size(base) == size)?;
+        match ty.is_signed() {
+            true => Some(base),
+            false => Some(base.to_unsigned()),
+        }
+    }
+}
+
+impl Platform {
+    /// resolve returns the base type a type name stands for in the C
+    /// library of the platform. See [`DataModel::resolve`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::stdint::StdType;
+    /// assert_eq!(Platform::LinuxX86_64.resolve(StdType::Int64), Some(CType::Long));
+    /// assert_eq!(Platform::MacX86_64.resolve(StdType::Int64), Some(CType::LongLong));
+    /// ```
+    pub fn resolve(self, ty: impl Into<TypeName>) -> Option<CType> {
+        let ty = ty.into();
+        match (self, ty) {
+            (Platform::MacX86_64 | Platform::MacAarch64, TypeName::Std(StdType::Int64)) => {
+                Some(CType::LongLong)
+            }
+            (Platform::MacX86_64 | Platform::MacAarch64, TypeName::Std(StdType::UInt64)) => {
+                Some(CType::ULongLong)
+            }
+            _ => self.data_model().resolve(ty),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        use CType::*;
+        use StdType::*;
+        let cases = [
+            (DataModel::IP16, [UInt, Int, Int, Int]),
+            (DataModel::LP32, [ULong, Long, Long, LongLong]),
+            (DataModel::ILP32, [UInt, Int, Int, LongLong]),
+            (DataModel::LLP64, [ULongLong, LongLong, LongLong, LongLong]),
+            (DataModel::LP64, [ULong, Long, Long, Long]),
+            (DataModel::ILP64, [UInt, Int, Int, Int]),
+        ];
+        for &(model, expected) in cases.iter() {
+            let resolved = [Size, SSize, PtrDiff, IntMax].map(|ty| model.resolve(ty));
+            assert_eq!(resolved, expected.map(Some), "{:?}", model);
+            assert_eq!(model.resolve(UIntMax), Some(expected[3].to_unsigned()));
+        }
+        assert_eq!(DataModel::LP64.resolve(Int8), Some(SChar));
+        assert_eq!(DataModel::LP64.resolve(UInt16), Some(UShort));
+        assert_eq!(DataModel::LP32.resolve(Int32), Some(Long));
+        assert_eq!(DataModel::ILP32.resolve(UInt64), Some(ULongLong));
+        assert_eq!(DataModel::LP64.resolve(Int64), Some(Long));
+        assert_eq!(DataModel::LLP64.resolve(IntPtr), Some(LongLong));
+        assert_eq!(DataModel::SILP64.resolve(Int16), None);
+        assert_eq!(DataModel::IP16.resolve(Long), None);
+        assert_eq!(DataModel::Unknown.resolve(Size), None);
+    }
+}
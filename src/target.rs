@@ -0,0 +1,664 @@
+//! Everything about a target that decides how its C values look in memory.
+//!
+//! A [`DataModel`] fixes the widths of the integer types, but reading or
+//! writing target memory also needs the byte order, the signedness of plain
+//! `char` and the alignment rules of the target's compiler. [`TargetModel`]
+//! carries them together, and reads and writes integers the way the target
+//! stores them.
+//!
+//! # Example
+//! ```
+//! use data_models::*;
+//! let target = Platform::LinuxS390x.target();
+//! let bytes = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe];
+//! assert_eq!(target.read::<Long>(&bytes), Ok(-2));
+//! assert_eq!(target.read_as(CType::ULong, &bytes), Ok(0xffff_ffff_ffff_fffe));
+//!
+//! let mut buf = [0; 4];
+//! Platform::Windows64.target().write::<Long>(&mut buf, -2).unwrap();
+//! assert_eq!(buf, [0xfe, 0xff, 0xff, 0xff]);
+//! ```
+
+use crate::arith::CastResult;
+use crate::layout::{Abi, LayoutError, Record};
+use crate::{CType, DataModel, Endianness, LongDouble, Platform, SignedRepresentation};
+use std::error::Error;
+use std::fmt;
+
+mod reader;
+
+pub use reader::{TargetReader, TargetWriter};
+
+/// TargetModel describes a target: its data model and record layout rules,
+/// its byte order, whether plain `char` is signed, how negative integers
+/// are represented and what a null pointer looks like.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let target = TargetModel::new(DataModel::LP64).with_endianness(Endianness::Big);
+/// assert_eq!(target.data_model(), DataModel::LP64);
+/// assert_eq!(target.endianness, Endianness::Big);
+/// assert!(target.char_signed);
+///
+/// let target = Platform::LinuxAarch64.target();
+/// assert_eq!(target.endianness, Endianness::Little);
+/// assert!(!target.char_signed);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TargetModel {
+    /// The data model and the alignment rules of records.
+    pub abi: Abi,
+    /// The byte order of integers.
+    pub endianness: Endianness,
+    /// Whether plain `char` is signed.
+    pub char_signed: bool,
+    /// The representation of signed integers.
+    pub signed_representation: SignedRepresentation,
+    /// The bits of a null pointer, which are all zero on nearly every
+    /// target but not on some historical and segmented ones.
+    pub null_pointer: u128,
+    /// The format of `long double`.
+    pub long_double: LongDouble,
+}
+
+impl TargetModel {
+    /// new returns a little-endian target of a data model with signed
+    /// `char`, naturally aligned types, the signed representation of the
+    /// model and a `long double` that is a `double`.
+    pub fn new(model: DataModel) -> TargetModel {
+        TargetModel {
+            abi: Abi::new(model),
+            endianness: Endianness::Little,
+            char_signed: true,
+            signed_representation: model.signed_representation(),
+            null_pointer: 0,
+            long_double: LongDouble::Binary64,
+        }
+    }
+
+    /// with_endianness sets the byte order.
+    pub fn with_endianness(self, endianness: Endianness) -> TargetModel {
+        TargetModel { endianness, ..self }
+    }
+
+    /// with_char_signed sets whether plain `char` is signed.
+    pub fn with_char_signed(self, char_signed: bool) -> TargetModel {
+        TargetModel {
+            char_signed,
+            ..self
+        }
+    }
+
+    /// with_signed_representation sets how negative integers are encoded.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let univac = TargetModel::new(DataModel::ILP32)
+    ///     .with_signed_representation(SignedRepresentation::OnesComplement);
+    /// assert_eq!(univac.read_as(CType::Int, &[0xfe, 0xff, 0xff, 0xff]), Ok(-1));
+    /// ```
+    pub fn with_signed_representation(self, repr: SignedRepresentation) -> TargetModel {
+        TargetModel {
+            signed_representation: repr,
+            ..self
+        }
+    }
+
+    /// with_null_pointer sets the bits of a null pointer.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let target = TargetModel::new(DataModel::ILP32).with_null_pointer(0xffff_ffff);
+    /// assert!(target.is_null(0xffff_ffff));
+    /// assert!(!target.is_null(0));
+    /// assert!(!target.null_is_zero());
+    /// ```
+    pub fn with_null_pointer(self, null_pointer: u128) -> TargetModel {
+        TargetModel {
+            null_pointer,
+            ..self
+        }
+    }
+
+    /// null_is_zero reports whether a null pointer has all bits zero, so
+    /// zeroed memory holds null pointers.
+    pub fn null_is_zero(&self) -> bool {
+        self.null_pointer == 0
+    }
+
+    /// is_null reports whether the bits `raw` of a pointer loaded from
+    /// target memory are a null pointer.
+    pub fn is_null(&self, raw: u128) -> bool {
+        let bits = 8 * self.data_model().size(CType::Pointer);
+        let mask = u128::MAX >> (128 - bits.clamp(1, 128));
+        raw & mask == self.null_pointer & mask
+    }
+
+    /// with_long_double sets the format of `long double`.
+    pub fn with_long_double(self, long_double: LongDouble) -> TargetModel {
+        TargetModel {
+            long_double,
+            ..self
+        }
+    }
+
+    /// with_abi sets the record layout rules, and with them the data model.
+    pub fn with_abi(self, abi: Abi) -> TargetModel {
+        TargetModel { abi, ..self }
+    }
+
+    /// char_is_signed reports whether plain `char` is signed on the target,
+    /// which decides how a `char` decodes and which Rust type `c_char` is.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert!(Platform::LinuxX86_64.target().char_is_signed());
+    /// assert!(!Platform::LinuxS390x.target().char_is_signed());
+    /// ```
+    pub fn char_is_signed(&self) -> bool {
+        self.char_signed
+    }
+
+    /// data_model returns the data model of the target.
+    pub fn data_model(&self) -> DataModel {
+        self.abi.model()
+    }
+
+    /// is_wire_compatible reports whether a record written by this target
+    /// reads back unchanged on another, as shared memory or a file: both
+    /// lay it out with the same bytes in the same places, and both store
+    /// them in the same byte order, unless the record holds only bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// let sample = Record::new("sample")
+    ///     .field("time", CType::LongLong)
+    ///     .field("value", CType::Int);
+    /// let mcu = TargetModel::new(DataModel::ILP32);
+    /// let host = Platform::LinuxX86_64.target();
+    /// assert_eq!(mcu.is_wire_compatible(&host, &sample), Ok(true));
+    ///
+    /// let mcu = mcu.with_endianness(Endianness::Big);
+    /// assert_eq!(mcu.is_wire_compatible(&host, &sample), Ok(false));
+    ///
+    /// let name = Record::new("name").field("text", FieldType::array(CType::Char, 16));
+    /// assert_eq!(mcu.is_wire_compatible(&host, &name), Ok(true));
+    /// ```
+    pub fn is_wire_compatible(
+        &self,
+        other: &TargetModel,
+        record: &Record,
+    ) -> Result<bool, LayoutError> {
+        let layout = record.layout(self.abi)?;
+        if !layout.is_wire_compatible(&record.layout(other.abi)?) {
+            return Ok(false);
+        }
+        Ok(self.endianness == other.endianness || !layout.byte_order_matters(&self.abi))
+    }
+}
+
+impl TargetModel {
+    /// read_as reads an integer of type `ty` from the start of `bytes`,
+    /// decoding signed types in the signed representation of the target.
+    ///
+    /// Pointers read as unsigned integers and plain `char` follows the
+    /// signedness of the target.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let target = TargetModel::new(DataModel::LP64);
+    /// assert_eq!(target.read_as(CType::Short, &[0x00, 0x80]), Ok(-32768));
+    /// assert_eq!(target.read_as(CType::UShort, &[0x00, 0x80]), Ok(32768));
+    /// assert!(target.read_as(CType::Int, &[0x00, 0x80]).is_err());
+    /// ```
+    pub fn read_as(&self, ty: CType, bytes: &[u8]) -> Result<i128, AccessError> {
+        let size = self.access_size(ty, bytes.len())?;
+        let value = self.load(&bytes[..size]);
+        Ok(match self.is_signed(ty) {
+            true => self.signed_representation.decode(8 * size, value),
+            false => value as i128,
+        })
+    }
+
+    /// write_as writes `value` as an integer of type `ty` to the start of
+    /// `buf`. Values that do not fit the type are truncated to its width,
+    /// like a conversion to an unsigned type in C, or wrapped as the signed
+    /// representation of the target does for signed types.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let target = TargetModel::new(DataModel::ILP32).with_endianness(Endianness::Big);
+    /// let mut buf = [0; 4];
+    /// target.write_as(CType::UInt, &mut buf, 0x0102_0304).unwrap();
+    /// assert_eq!(buf, [1, 2, 3, 4]);
+    /// ```
+    pub fn write_as(&self, ty: CType, buf: &mut [u8], value: i128) -> Result<(), AccessError> {
+        let size = self.access_size(ty, buf.len())?;
+        let raw = match self.is_signed(ty) {
+            true => self.signed_representation.encode(8 * size, value),
+            false => value as u128,
+        };
+        self.store(&mut buf[..size], raw);
+        Ok(())
+    }
+
+    /// load returns the unsigned integer stored in `bytes` in the byte
+    /// order of the target.
+    pub(crate) fn load(&self, bytes: &[u8]) -> u128 {
+        let size = bytes.len();
+        (0..size).rev().fold(0, |value, i| {
+            value << 8 | bytes[self.byte_index(size, i)] as u128
+        })
+    }
+
+    /// store writes the low bytes of `value` to `buf` in the byte order of
+    /// the target.
+    pub(crate) fn store(&self, buf: &mut [u8], value: u128) {
+        let size = buf.len();
+        for i in 0..size {
+            buf[self.byte_index(size, i)] = (value >> (8 * i)) as u8;
+        }
+    }
+
+    /// read reads an integer of one of the marker types, such as
+    /// [`Long`](crate::Long), from the start of `bytes`. See [`TargetModel::read_as`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let bytes = [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0];
+    /// assert_eq!(Platform::LinuxX86_64.target().read::<Long>(&bytes), Ok(0xffff_ffff));
+    /// assert_eq!(Platform::Windows64.target().read::<Long>(&bytes), Ok(-1));
+    /// ```
+    pub fn read<T>(&self, bytes: &[u8]) -> Result<i128, AccessError> {
+        let ty = CType::of::<T>().ok_or(AccessError::MissingType)?;
+        self.read_as(ty, bytes)
+    }
+
+    /// write writes `value` as one of the marker types, such as
+    /// [`Long`](crate::Long), to the start of `buf`. See [`TargetModel::write_as`].
+    pub fn write<T>(&self, buf: &mut [u8], value: i128) -> Result<(), AccessError> {
+        let ty = CType::of::<T>().ok_or(AccessError::MissingType)?;
+        self.write_as(ty, buf, value)
+    }
+
+    /// range returns the least and greatest values of the integer type
+    /// `ty` on the target, or `None` if the model lacks it or it is not an
+    /// integer type.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let target = TargetModel::new(DataModel::LP64);
+    /// assert_eq!(target.range(CType::Short), Some((-32768, 32767)));
+    /// let target = target.with_signed_representation(SignedRepresentation::SignMagnitude);
+    /// assert_eq!(target.range(CType::Short), Some((-32767, 32767)));
+    /// assert_eq!(target.range(CType::UShort), Some((0, 65535)));
+    /// ```
+    pub fn range(&self, ty: CType) -> Option<(i128, i128)> {
+        let bits = 8 * self.data_model().size(ty);
+        match ty {
+            _ if bits == 0 || !ty.is_integer() => None,
+            CType::Bool => Some((0, 1)),
+            _ if self.is_signed(ty) => {
+                let repr = self.signed_representation;
+                Some((repr.min(bits), repr.max(bits)))
+            }
+            _ => Some((0, (1 << bits) - 1)),
+        }
+    }
+
+    /// wrap_as converts `value` to the integer or pointer type `ty` as
+    /// [`DataModel::wrap_as`] does, wrapping signed types the way the
+    /// signed representation of the target does.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let target = TargetModel::new(DataModel::ILP32);
+    /// assert_eq!(target.wrap_as(CType::Int, 1 << 31), Some(-(1 << 31)));
+    /// let target = target.with_signed_representation(SignedRepresentation::OnesComplement);
+    /// assert_eq!(target.wrap_as(CType::Int, 1 << 31), Some(-(1 << 31) + 1));
+    /// ```
+    pub fn wrap_as(&self, ty: CType, value: i128) -> Option<i128> {
+        let model = self.data_model();
+        let bits = 8 * model.size(ty);
+        match ty {
+            _ if bits == 0 => None,
+            CType::Char if !self.char_signed => model.wrap_as(CType::UChar, value),
+            _ if self.is_signed(ty) => Some(self.signed_representation.wrap(bits, value)),
+            _ => model.wrap_as(ty, value),
+        }
+    }
+
+    /// cast converts `value` of type `from` to type `to` as C does on the
+    /// target, and tells whether the standard defines the result. The
+    /// value is first wrapped to `from` if it is an integer type; floating
+    /// values are given by their integral part. Conversions to an integer
+    /// type of another signedness follow the target's representation of
+    /// negative integers and its plain `char`. It returns `None` if the
+    /// model lacks one of the types.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::arith::CastResult;
+    /// use CType::*;
+    /// let target = TargetModel::new(DataModel::ILP32).with_char_signed(false);
+    /// assert_eq!(target.cast(-1, Int, Char), Some(CastResult::Converted(255)));
+    /// assert_eq!(target.cast(1 << 25 | 1, Int, Float), Some(CastResult::Rounded(1 << 25)));
+    /// let ones = target.with_signed_representation(SignedRepresentation::OnesComplement);
+    /// assert_eq!(ones.cast(0xffff_fffe, UInt, Int), Some(CastResult::ImplementationDefined(-1)));
+    /// ```
+    pub fn cast(&self, value: i128, from: CType, to: CType) -> Option<CastResult> {
+        let model = self.data_model();
+        let floating = |ty| matches!(ty, CType::Float | CType::Double);
+        let value = match floating(from) {
+            _ if !model.has(from) => return None,
+            true => value,
+            false => self.wrap_as(from, value)?,
+        };
+        if floating(to) {
+            let rounded = match model.size(to) {
+                0 => return None,
+                4 => value as f32 as i128,
+                _ => value as f64 as i128,
+            };
+            return Some(match rounded == value {
+                true => CastResult::Exact(value),
+                false => CastResult::Rounded(rounded),
+            });
+        }
+        let result = self.wrap_as(to, value)?;
+        let pointer = from == CType::Pointer || to == CType::Pointer;
+        Some(match result {
+            _ if result == value => CastResult::Exact(value),
+            _ if to == CType::Bool => CastResult::Converted(result),
+            _ if floating(from) => CastResult::Undefined,
+            _ if pointer || self.is_signed(to) => CastResult::ImplementationDefined(result),
+            _ => CastResult::Converted(result),
+        })
+    }
+
+    /// is_signed reports whether values of an integer type are signed on
+    /// the target.
+    pub(crate) fn is_signed(&self, ty: CType) -> bool {
+        match ty {
+            CType::Char => self.char_signed,
+            _ => ty.is_signed(),
+        }
+    }
+
+    /// access_size returns the size of `ty` if it can be read from or
+    /// written to a buffer of `len` bytes.
+    fn access_size(&self, ty: CType, len: usize) -> Result<usize, AccessError> {
+        if !ty.is_integer() && ty != CType::Pointer {
+            return Err(AccessError::NotInteger { ty });
+        }
+        match self.data_model().size(ty) {
+            0 => Err(AccessError::MissingType),
+            size if size > len => Err(AccessError::ShortBuffer { needed: size, len }),
+            size => Ok(size),
+        }
+    }
+
+    /// byte_index returns where the `i`th least significant byte of a
+    /// `size` byte integer is stored.
+    ///
+    /// PDP-11 words are little-endian but a `long` stores its high word
+    /// first, so 0x0A0B0C0D is laid out as 0B 0A 0D 0C. Longer integers
+    /// continue the pattern with the most significant word first.
+    fn byte_index(&self, size: usize, i: usize) -> usize {
+        match self.endianness {
+            Endianness::Little => i,
+            Endianness::Big => size - 1 - i,
+            Endianness::PdpMiddle if size == 1 => i,
+            Endianness::PdpMiddle => (size / 2 - 1 - i / 2) * 2 + i % 2,
+        }
+    }
+}
+
+/// AccessError describes why an integer cannot be read or written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccessError {
+    /// The data model does not have the type, like `long` on IP16.
+    MissingType,
+    /// The type is not an integer or pointer type.
+    NotInteger {
+        /// The requested type.
+        ty: CType,
+    },
+    /// The buffer is shorter than the type.
+    ShortBuffer {
+        /// The size of the type.
+        needed: usize,
+        /// The length of the buffer.
+        len: usize,
+    },
+    /// The value given for a field does not fit its type, like an array
+    /// for an `int`.
+    Mismatch {
+        /// The field.
+        field: String,
+    },
+    /// A record value names a field the record does not have.
+    UnknownField {
+        /// The name of the field.
+        name: String,
+    },
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessError::MissingType => write!(f, "the data model lacks the type"),
+            AccessError::NotInteger { ty } => write!(f, "{:?} is not an integer type", ty),
+            AccessError::ShortBuffer { needed, len } => {
+                write!(
+                    f,
+                    "buffer of {} bytes is shorter than {} bytes",
+                    len, needed
+                )
+            }
+            AccessError::Mismatch { field } => {
+                write!(f, "value does not match the type of field {}", field)
+            }
+            AccessError::UnknownField { name } => write!(f, "no field called {}", name),
+        }
+    }
+}
+
+impl Error for AccessError {}
+
+impl From<DataModel> for TargetModel {
+    fn from(model: DataModel) -> TargetModel {
+        TargetModel::new(model)
+    }
+}
+
+impl From<Platform> for TargetModel {
+    fn from(platform: Platform) -> TargetModel {
+        platform.target()
+    }
+}
+
+impl From<TargetModel> for Abi {
+    fn from(target: TargetModel) -> Abi {
+        target.abi
+    }
+}
+
+impl Platform {
+    /// target returns the description of the platform as a [`TargetModel`].
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// let target = Platform::LinuxS390x.target();
+    /// assert_eq!(target.data_model(), DataModel::LP64);
+    /// assert_eq!(target.endianness, Endianness::Big);
+    /// ```
+    pub fn target(self) -> TargetModel {
+        TargetModel {
+            abi: self.abi(),
+            endianness: self.endianness(),
+            char_signed: self.char_signed(),
+            signed_representation: SignedRepresentation::TwosComplement,
+            null_pointer: 0,
+            long_double: self.long_double(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::FieldType;
+    use crate::{CType, Char, Int, Long, LongLong, Pointer, Short};
+
+    #[test]
+    fn test_signed_representation() {
+        let target = TargetModel::new(DataModel::LP64)
+            .with_signed_representation(SignedRepresentation::SignMagnitude);
+        let mut buf = [0; 2];
+        target.write::<Short>(&mut buf, -5).unwrap();
+        assert_eq!(buf, [0x05, 0x80]);
+        assert_eq!(target.read::<Short>(&buf), Ok(-5));
+        assert_eq!(target.read::<Short>(&[0, 0x80]), Ok(0));
+        target.write_as(CType::UShort, &mut buf, -5).unwrap();
+        assert_eq!(buf, [0xfb, 0xff]);
+        assert_eq!(target.wrap_as(CType::Short, 0x8001), Some(1));
+        assert_eq!(target.wrap_as(CType::UShort, -1), Some(0xffff));
+        assert_eq!(target.range(CType::Char), Some((-127, 127)));
+        assert_eq!(target.range(CType::Double), None);
+        assert_eq!(
+            Platform::LinuxAarch64.target().wrap_as(CType::Char, -1),
+            Some(255)
+        );
+    }
+
+    #[test]
+    fn test_platform_targets() {
+        for &platform in Platform::ALL {
+            let target = platform.target();
+            assert_eq!(target.data_model(), platform.data_model());
+            assert_eq!(target.endianness, platform.endianness());
+            assert_eq!(target.char_signed, platform.char_signed());
+        }
+        let target = TargetModel::from(Platform::LinuxI686);
+        assert_eq!(target.abi.align(CType::LongLong), 4);
+    }
+
+    #[test]
+    fn test_read_write() {
+        let little = TargetModel::new(DataModel::LP64);
+        let big = little.with_endianness(Endianness::Big);
+        let bytes = [0x80, 1, 2, 3, 4, 5, 6, 0x87, 0xaa];
+        assert_eq!(little.read::<Char>(&bytes), Ok(-128));
+        assert_eq!(little.with_char_signed(false).read::<Char>(&bytes), Ok(128));
+        assert_eq!(little.read::<Int>(&bytes), Ok(0x0302_0180));
+        assert_eq!(big.read::<Int>(&bytes), Ok(-0x7ffe_fdfd));
+        assert_eq!(big.read::<Pointer>(&bytes), Ok(0x8001_0203_0405_0687));
+        assert_eq!(little.read::<LongLong>(&bytes), Ok(-0x78f9_fafb_fcfd_fe80));
+        assert_eq!(little.read_as(CType::Bool, &bytes), Ok(0x80));
+
+        for &target in &[little, big] {
+            let mut buf = [0; 8];
+            for &value in &[0, 1, -1, i64::MIN as i128, i64::MAX as i128] {
+                target.write::<Long>(&mut buf, value).unwrap();
+                assert_eq!(target.read::<Long>(&buf), Ok(value));
+            }
+            target.write::<Short>(&mut buf, 0x12345).unwrap();
+            assert_eq!(target.read::<Short>(&buf), Ok(0x2345));
+        }
+    }
+
+    #[test]
+    fn test_access_errors() {
+        let target = TargetModel::new(DataModel::IP16);
+        assert_eq!(target.read::<Long>(&[0; 8]), Err(AccessError::MissingType));
+        assert_eq!(
+            target.read::<String>(&[0; 8]),
+            Err(AccessError::MissingType)
+        );
+        assert_eq!(
+            target.read_as(CType::Double, &[0; 8]),
+            Err(AccessError::NotInteger { ty: CType::Double })
+        );
+        assert_eq!(
+            target.write::<Int>(&mut [0; 1], 0),
+            Err(AccessError::ShortBuffer { needed: 2, len: 1 })
+        );
+    }
+
+    #[test]
+    fn test_pdp_endian() {
+        let pdp11 = TargetModel::new(DataModel::IP16L32).with_endianness(Endianness::PdpMiddle);
+        assert_eq!(pdp11.read::<Char>(&[0x80]), Ok(-128));
+        assert_eq!(pdp11.read::<Int>(&[1, 2]), Ok(0x0201));
+        assert_eq!(
+            pdp11.read::<Long>(&[0x0b, 0x0a, 0x0d, 0x0c]),
+            Ok(0x0a0b_0c0d)
+        );
+        assert_eq!(pdp11.read::<Long>(&[0x80, 0xff, 0, 0]), Ok(-0x80_0000));
+
+        let mut buf = [0; 4];
+        pdp11.write::<Long>(&mut buf, 0x0102_0304).unwrap();
+        assert_eq!(buf, [2, 1, 4, 3]);
+        for &value in &[0, -1, 1 << 16, i32::MIN as i128, i32::MAX as i128] {
+            pdp11.write::<Long>(&mut buf, value).unwrap();
+            assert_eq!(pdp11.read::<Long>(&buf), Ok(value));
+        }
+
+        let target = TargetModel::new(DataModel::ILP32).with_endianness(Endianness::PdpMiddle);
+        let mut buf = [0; 8];
+        target
+            .write::<LongLong>(&mut buf, 0x0102_0304_0506_0708)
+            .unwrap();
+        assert_eq!(buf, [2, 1, 4, 3, 6, 5, 8, 7]);
+    }
+
+    #[test]
+    fn test_layout_with_target() {
+        let record = Record::new("s")
+            .field("c", CType::Char)
+            .field("ll", CType::LongLong);
+        let target = TargetModel::new(DataModel::ILP32)
+            .with_endianness(Endianness::PdpMiddle)
+            .with_char_signed(false);
+        assert_eq!(record.layout(target).unwrap().size, 16);
+        let target = target.with_abi(Abi::new(DataModel::ILP32).with_max_align(4));
+        assert_eq!(record.layout(target).unwrap().size, 12);
+    }
+
+    #[test]
+    fn test_wire_compatible() {
+        let record = Record::new("s")
+            .field("c", CType::Char)
+            .field("ll", CType::LongLong);
+        let host = Platform::LinuxX86_64.target();
+        let armhf = Platform::LinuxArmhf.target();
+        assert_eq!(armhf.is_wire_compatible(&host, &record), Ok(true));
+        let i686 = Platform::LinuxI686.target();
+        assert_eq!(i686.is_wire_compatible(&host, &record), Ok(false));
+        let mips = Platform::LinuxMips.target();
+        assert_eq!(mips.is_wire_compatible(&armhf, &record), Ok(false));
+        let bytes = Record::new("b").field("c", FieldType::array(CType::UChar, 3));
+        assert_eq!(mips.is_wire_compatible(&armhf, &bytes), Ok(true));
+        let flags = bytes.bitfield("f", CType::UChar, 1);
+        assert_eq!(mips.is_wire_compatible(&armhf, &flags), Ok(false));
+        let long = Record::new("l").field("n", CType::Long);
+        assert!(TargetModel::new(DataModel::IP16)
+            .is_wire_compatible(&host, &long)
+            .is_err());
+    }
+}
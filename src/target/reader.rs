@@ -0,0 +1,298 @@
+//! Sequential reads and writes of C values in target memory.
+
+use super::{AccessError, TargetModel};
+use crate::CType;
+
+/// TargetReader reads C values one after the other from target memory,
+/// like walking a core dump or a serialized struct.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::target::TargetReader;
+/// // struct { char tag; long len; void *data; } on 32-bit big-endian MIPS
+/// let bytes = [7, 0, 0, 0, 0, 0, 0, 42, 0x80, 0, 0x10, 0];
+/// let mut reader = TargetReader::new(Platform::LinuxMips.target(), &bytes);
+/// assert_eq!(reader.read_char(), Ok(7));
+/// reader.align_to(CType::Long).unwrap();
+/// assert_eq!(reader.read_long(), Ok(42));
+/// assert_eq!(reader.read_pointer(), Ok(0x8000_1000));
+/// assert_eq!(reader.remaining(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TargetReader<'a> {
+    target: TargetModel,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> TargetReader<'a> {
+    /// new returns a reader at the start of `bytes`.
+    pub fn new(target: TargetModel, bytes: &'a [u8]) -> TargetReader<'a> {
+        TargetReader {
+            target,
+            bytes,
+            pos: 0,
+        }
+    }
+
+    /// position returns the offset of the next read from the start.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// remaining returns the number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    /// skip advances `n` bytes.
+    pub fn skip(&mut self, n: usize) -> Result<(), AccessError> {
+        self.read_bytes(n).map(|_| ())
+    }
+
+    /// align_to advances to the next offset aligned for `ty` inside a
+    /// record of the target.
+    pub fn align_to(&mut self, ty: CType) -> Result<(), AccessError> {
+        let align = self.target.abi.align(ty);
+        self.skip(self.pos.next_multiple_of(align) - self.pos)
+    }
+
+    /// read_bytes reads the next `n` bytes as they are.
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], AccessError> {
+        if n > self.remaining() {
+            return Err(AccessError::ShortBuffer {
+                needed: n,
+                len: self.remaining(),
+            });
+        }
+        let bytes = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    /// read reads the next value of type `ty`. See
+    /// [`TargetModel::read_as`].
+    pub fn read(&mut self, ty: CType) -> Result<i128, AccessError> {
+        let value = self.target.read_as(ty, &self.bytes[self.pos..])?;
+        self.pos += self.target.data_model().size(ty);
+        Ok(value)
+    }
+
+    /// read_char reads the next `char`.
+    pub fn read_char(&mut self) -> Result<i128, AccessError> {
+        self.read(CType::Char)
+    }
+
+    /// read_short reads the next `short`.
+    pub fn read_short(&mut self) -> Result<i128, AccessError> {
+        self.read(CType::Short)
+    }
+
+    /// read_int reads the next `int`.
+    pub fn read_int(&mut self) -> Result<i128, AccessError> {
+        self.read(CType::Int)
+    }
+
+    /// read_long reads the next `long`.
+    pub fn read_long(&mut self) -> Result<i128, AccessError> {
+        self.read(CType::Long)
+    }
+
+    /// read_long_long reads the next `long long`.
+    pub fn read_long_long(&mut self) -> Result<i128, AccessError> {
+        self.read(CType::LongLong)
+    }
+
+    /// read_pointer reads the next pointer as an address.
+    pub fn read_pointer(&mut self) -> Result<i128, AccessError> {
+        self.read(CType::Pointer)
+    }
+}
+
+/// TargetWriter writes C values one after the other into target memory.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::target::TargetWriter;
+/// let mut buf = [0xaa; 16];
+/// let mut writer = TargetWriter::new(Platform::LinuxX86_64.target(), &mut buf);
+/// writer.write_int(-1).unwrap();
+/// writer.align_to(CType::Pointer).unwrap();
+/// writer.write_pointer(0x1000).unwrap();
+/// assert_eq!(writer.position(), 16);
+/// assert_eq!(buf[..8], [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]);
+/// ```
+#[derive(Debug)]
+pub struct TargetWriter<'a> {
+    target: TargetModel,
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> TargetWriter<'a> {
+    /// new returns a writer at the start of `buf`.
+    pub fn new(target: TargetModel, buf: &'a mut [u8]) -> TargetWriter<'a> {
+        TargetWriter {
+            target,
+            buf,
+            pos: 0,
+        }
+    }
+
+    /// position returns the offset of the next write from the start.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// remaining returns the number of bytes left to write.
+    pub fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// align_to advances to the next offset aligned for `ty` inside a
+    /// record of the target, zeroing the padding.
+    pub fn align_to(&mut self, ty: CType) -> Result<(), AccessError> {
+        let align = self.target.abi.align(ty);
+        let padding = self.pos.next_multiple_of(align) - self.pos;
+        self.write_bytes(&vec![0; padding])
+    }
+
+    /// write_bytes writes `bytes` as they are.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), AccessError> {
+        if bytes.len() > self.remaining() {
+            return Err(AccessError::ShortBuffer {
+                needed: bytes.len(),
+                len: self.remaining(),
+            });
+        }
+        self.buf[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+        Ok(())
+    }
+
+    /// write writes `value` as the next value of type `ty`. See
+    /// [`TargetModel::write_as`].
+    pub fn write(&mut self, ty: CType, value: i128) -> Result<(), AccessError> {
+        self.target.write_as(ty, &mut self.buf[self.pos..], value)?;
+        self.pos += self.target.data_model().size(ty);
+        Ok(())
+    }
+
+    /// write_char writes the next `char`.
+    pub fn write_char(&mut self, value: i128) -> Result<(), AccessError> {
+        self.write(CType::Char, value)
+    }
+
+    /// write_short writes the next `short`.
+    pub fn write_short(&mut self, value: i128) -> Result<(), AccessError> {
+        self.write(CType::Short, value)
+    }
+
+    /// write_int writes the next `int`.
+    pub fn write_int(&mut self, value: i128) -> Result<(), AccessError> {
+        self.write(CType::Int, value)
+    }
+
+    /// write_long writes the next `long`.
+    pub fn write_long(&mut self, value: i128) -> Result<(), AccessError> {
+        self.write(CType::Long, value)
+    }
+
+    /// write_long_long writes the next `long long`.
+    pub fn write_long_long(&mut self, value: i128) -> Result<(), AccessError> {
+        self.write(CType::LongLong, value)
+    }
+
+    /// write_pointer writes the next pointer from an address.
+    pub fn write_pointer(&mut self, value: i128) -> Result<(), AccessError> {
+        self.write(CType::Pointer, value)
+    }
+}
+
+impl TargetModel {
+    /// reader returns a [`TargetReader`] at the start of `bytes`.
+    pub fn reader<'a>(&self, bytes: &'a [u8]) -> TargetReader<'a> {
+        TargetReader::new(*self, bytes)
+    }
+
+    /// writer returns a [`TargetWriter`] at the start of `buf`.
+    pub fn writer<'a>(&self, buf: &'a mut [u8]) -> TargetWriter<'a> {
+        TargetWriter::new(*self, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DataModel, Platform};
+
+    #[test]
+    fn test_round_trip() {
+        for &platform in Platform::ALL {
+            let target = platform.target();
+            let mut buf = [0; 64];
+            let mut writer = target.writer(&mut buf);
+            writer.write_char(-3).unwrap();
+            writer.align_to(CType::Short).unwrap();
+            writer.write_short(-300).unwrap();
+            writer.align_to(CType::Long).unwrap();
+            writer.write_long(-70000).unwrap();
+            writer.align_to(CType::LongLong).unwrap();
+            writer.write_long_long(i64::MIN as i128).unwrap();
+            writer.align_to(CType::Pointer).unwrap();
+            writer.write_pointer(0xdead).unwrap();
+            let end = writer.position();
+
+            let mut reader = target.reader(&buf);
+            let char = if target.char_signed { -3 } else { 253 };
+            assert_eq!(reader.read_char(), Ok(char));
+            reader.align_to(CType::Short).unwrap();
+            assert_eq!(reader.read_short(), Ok(-300));
+            reader.align_to(CType::Long).unwrap();
+            assert_eq!(reader.read_long(), Ok(-70000));
+            reader.align_to(CType::LongLong).unwrap();
+            assert_eq!(reader.read_long_long(), Ok(i64::MIN as i128));
+            reader.align_to(CType::Pointer).unwrap();
+            assert_eq!(reader.read_pointer(), Ok(0xdead));
+            assert_eq!(reader.position(), end);
+        }
+    }
+
+    #[test]
+    fn test_alignment_profile() {
+        let bytes = [0; 16];
+        let mut reader = Platform::LinuxI686.target().reader(&bytes);
+        reader.read_int().unwrap();
+        reader.align_to(CType::LongLong).unwrap();
+        assert_eq!(reader.position(), 4);
+        let mut reader = TargetModel::new(DataModel::ILP32).reader(&bytes);
+        reader.read_int().unwrap();
+        reader.align_to(CType::LongLong).unwrap();
+        assert_eq!(reader.position(), 8);
+    }
+
+    #[test]
+    fn test_short_reads() {
+        let bytes = [1, 2, 3];
+        let mut reader = TargetModel::new(DataModel::LP64).reader(&bytes);
+        assert_eq!(reader.read_short(), Ok(0x0201));
+        assert_eq!(
+            reader.read_int(),
+            Err(AccessError::ShortBuffer { needed: 4, len: 1 })
+        );
+        assert_eq!(reader.position(), 2);
+        assert_eq!(
+            reader.align_to(CType::Int),
+            Err(AccessError::ShortBuffer { needed: 2, len: 1 })
+        );
+        assert_eq!(reader.read_bytes(1), Ok(&[3][..]));
+
+        let mut buf = [0; 2];
+        let mut writer = TargetModel::new(DataModel::IP16).writer(&mut buf);
+        writer.write_int(1).unwrap();
+        assert!(writer.write_char(1).is_err());
+        assert_eq!(writer.write_long(1), Err(AccessError::MissingType));
+    }
+}
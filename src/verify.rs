@@ -0,0 +1,259 @@
+//! Check the predictions of this crate against a real C compiler.
+//!
+//! A [`Probe`] feeds the [`c_verification_unit`] of a target to a C
+//! compiler and reports each assertion the compiler rejects, so a build
+//! script can refuse to build on a toolchain that disagrees with the
+//! tables:
+//!
+//! ```no_run
+//! // build.rs
+//! use data_models::*;
+//! use data_models::verify::Probe;
+//!
+//! fn main() {
+//!     if let Err(err) = Probe::from_env().check(&Platform::LinuxArmhf.target()) {
+//!         panic!("{}", err);
+//!     }
+//! }
+//! ```
+//!
+//! Only the compiler's front end runs, so a cross compiler works without
+//! the target to run on. It has to accept C11 from standard input, as GCC
+//! and Clang do.
+//!
+//! [`c_verification_unit`]: TargetModel::c_verification_unit
+
+use crate::{DataModel, TargetModel};
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Probe is a C compiler command to check predictions with.
+///
+/// # Example
+/// ```
+/// use data_models::verify::Probe;
+/// let probe = Probe::new("clang").with_arg("--target=aarch64-unknown-linux-gnu");
+/// assert_eq!(probe.compiler, "clang");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Probe {
+    /// The compiler to run.
+    pub compiler: String,
+    /// Extra arguments, such as `--target` or `-m32`.
+    pub args: Vec<String>,
+}
+
+/// Mismatch is a prediction the compiler disagrees with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Mismatch {
+    /// The predicted fact, e.g. `"sizeof(long) == 4"`.
+    pub check: String,
+    /// The compiler's error for it.
+    pub message: String,
+}
+
+/// VerifyError describes why a check did not pass.
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The compiler could not be run.
+    Spawn {
+        /// The compiler command.
+        compiler: String,
+        /// The error running it.
+        error: std::io::Error,
+    },
+    /// The compiler failed on something other than the assertions, such
+    /// as a missing header.
+    Compile {
+        /// The output of the compiler.
+        output: String,
+    },
+    /// The compiler rejected some of the predictions.
+    Mismatches(Vec<Mismatch>),
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Spawn { compiler, error } => {
+                write!(f, "cannot run the C compiler {}: {}", compiler, error)
+            }
+            VerifyError::Compile { output } => {
+                write!(f, "the C compiler failed:\n{}", output)
+            }
+            VerifyError::Mismatches(mismatches) => {
+                writeln!(f, "the C compiler disagrees with data_models:")?;
+                for mismatch in mismatches {
+                    writeln!(f, "  expected {}: {}", mismatch.check, mismatch.message)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for VerifyError {}
+
+/// VerifyError logs like its `Display`, without the text of the I/O error.
+#[cfg(feature = "defmt")]
+impl defmt::Format for VerifyError {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            VerifyError::Spawn { compiler, .. } => {
+                defmt::write!(f, "cannot run the C compiler {=str}", compiler)
+            }
+            VerifyError::Compile { output } => {
+                defmt::write!(f, "the C compiler failed:\n{=str}", output)
+            }
+            VerifyError::Mismatches(mismatches) => {
+                defmt::write!(
+                    f,
+                    "the C compiler disagrees with data_models: {}",
+                    mismatches
+                )
+            }
+        }
+    }
+}
+
+impl Probe {
+    /// new returns a probe running `compiler`.
+    pub fn new(compiler: &str) -> Probe {
+        Probe {
+            compiler: compiler.to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    /// from_env returns the probe a build script would use: the compiler
+    /// in `TARGET_CC` or `CC`, or `cc`, with the arguments in `CFLAGS`.
+    pub fn from_env() -> Probe {
+        let compiler = env::var("TARGET_CC")
+            .or_else(|_| env::var("CC"))
+            .unwrap_or_else(|_| "cc".to_string());
+        let flags = env::var("CFLAGS").unwrap_or_default();
+        Probe {
+            compiler,
+            args: flags.split_whitespace().map(str::to_string).collect(),
+        }
+    }
+
+    /// with_arg adds an argument for the compiler.
+    pub fn with_arg(mut self, arg: &str) -> Probe {
+        self.args.push(arg.to_string());
+        self
+    }
+
+    /// check compiles the verification unit of `target` and returns the
+    /// predictions the compiler rejects.
+    pub fn check(&self, target: &TargetModel) -> Result<(), VerifyError> {
+        self.compile(&target.c_verification_unit())
+    }
+
+    /// check_model compiles the verification unit of `model`, which leaves
+    /// out the signedness of plain `char`. See [`Probe::check`].
+    pub fn check_model(&self, model: DataModel) -> Result<(), VerifyError> {
+        self.compile(&model.c_verification_unit())
+    }
+
+    /// compile runs the compiler on `src` and maps the lines it rejects
+    /// back to the assertions on them.
+    fn compile(&self, src: &str) -> Result<(), VerifyError> {
+        let spawn = |error| VerifyError::Spawn {
+            compiler: self.compiler.clone(),
+            error,
+        };
+        let mut child = Command::new(&self.compiler)
+            .args(&self.args)
+            .args(["-std=c11", "-fsyntax-only", "-x", "c", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(spawn)?;
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(src.as_bytes())
+            .map_err(spawn)?;
+        let output = child.wait_with_output().map_err(spawn)?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        let lines: Vec<&str> = src.lines().collect();
+        let mut mismatches: Vec<Mismatch> = Vec::new();
+        for message in stderr.lines().filter(|line| line.contains("error")) {
+            let check = error_line(message)
+                .and_then(|n| lines.get(n - 1))
+                .and_then(|line| line.strip_prefix("_Static_assert("))
+                .and_then(|line| line.rsplit_once(", \""));
+            if let Some((check, _)) = check {
+                if !mismatches.iter().any(|mismatch| mismatch.check == check) {
+                    mismatches.push(Mismatch {
+                        check: check.to_string(),
+                        message: message.to_string(),
+                    });
+                }
+            }
+        }
+        match mismatches.is_empty() {
+            true => Err(VerifyError::Compile { output: stderr }),
+            false => Err(VerifyError::Mismatches(mismatches)),
+        }
+    }
+}
+
+/// error_line returns the line number of a compiler diagnostic about the
+/// standard input, `<stdin>:12:5: error: ...`.
+fn error_line(message: &str) -> Option<usize> {
+    let rest = message.strip_prefix("<stdin>:")?;
+    let end = rest.find(':')?;
+    rest[..end].parse().ok().filter(|&n| n > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_line() {
+        let gcc = "<stdin>:12:1: error: static assertion failed: \"sizeof(long)\"";
+        assert_eq!(error_line(gcc), Some(12));
+        assert_eq!(error_line("In file included from <stdin>:1:"), None);
+        assert_eq!(error_line("<stdin>:0:1: error"), None);
+    }
+
+    #[test]
+    fn test_spawn_error() {
+        let err = Probe::new("/nonexistent/cc")
+            .check_model(DataModel::LP64)
+            .unwrap_err();
+        assert!(matches!(err, VerifyError::Spawn { .. }));
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    #[test]
+    fn test_check() {
+        use crate::Platform;
+        let probe = Probe::new("cc");
+        if Command::new("cc").arg("--version").output().is_err() {
+            return;
+        }
+        probe.check(&Platform::LinuxX86_64.target()).unwrap();
+        let err = probe.check(&Platform::Windows64.target()).unwrap_err();
+        let mismatches = match err {
+            VerifyError::Mismatches(mismatches) => mismatches,
+            err => panic!("{}", err),
+        };
+        let checks: Vec<&str> = mismatches.iter().map(|m| m.check.as_str()).collect();
+        assert!(checks.contains(&"sizeof(long) == 4"));
+        assert!(checks.contains(&"LONG_MAX == 2147483647L"));
+        assert!(!checks.contains(&"sizeof(int) == 4"));
+    }
+}
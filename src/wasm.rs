@@ -0,0 +1,137 @@
+//! WebAssembly bindings, built with wasm-bindgen, for tools running in a
+//! browser.
+//!
+//! ```text
+//! cargo rustc --release --target wasm32-unknown-unknown --features wasm --lib --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/data_models.wasm
+//! ```
+//!
+//! ```js
+//! import init, { sizeOf, fromTriple, layout } from "./pkg/data_models.js";
+//! await init();
+//! sizeOf("LLP64", "long");                          // 4
+//! fromTriple("aarch64-unknown-linux-gnu");          // "LP64"
+//! const l = layout("LP64", ["char", "void *[2]"]);  // l.size 24, l.align 8, l.offsets [0, 8]
+//! ```
+//!
+//! Types are C type names such as `unsigned long` or `void *[2]`, read
+//! with [`parse_decl`]. Errors are thrown as strings.
+
+use crate::layout::Record;
+use crate::{parse_decl, DataModel, Platform, TypeExpr};
+use wasm_bindgen::prelude::*;
+
+/// data_model finds a data model by name, such as `LP64`.
+fn data_model(name: &str) -> Result<DataModel, String> {
+    DataModel::ALL
+        .iter()
+        .copied()
+        .find(|model| format!("{:?}", model) == name)
+        .ok_or_else(|| format!("unknown data model {:?}", name))
+}
+
+/// type_expr reads a C type name.
+fn type_expr(ty: &str) -> Result<TypeExpr, String> {
+    parse_decl(ty).map_err(|err| format!("{:?}: {}", ty, err))
+}
+
+/// models returns the names of the data models.
+#[wasm_bindgen]
+pub fn models() -> Vec<String> {
+    DataModel::ALL.iter().map(|m| format!("{:?}", m)).collect()
+}
+
+/// size_of returns the size in bytes of a C type, or 0 if the model lacks
+/// it.
+#[wasm_bindgen(js_name = sizeOf)]
+pub fn size_of(model: &str, ctype: &str) -> Result<usize, String> {
+    Ok(type_expr(ctype)?.size(data_model(model)?).unwrap_or(0))
+}
+
+/// align_of returns the alignment in bytes of a C type, or 0 if the model
+/// lacks it.
+#[wasm_bindgen(js_name = alignOf)]
+pub fn align_of(model: &str, ctype: &str) -> Result<usize, String> {
+    Ok(type_expr(ctype)?.align(data_model(model)?).unwrap_or(0))
+}
+
+/// from_triple returns the data model of a known target triple.
+#[wasm_bindgen(js_name = fromTriple)]
+pub fn from_triple(triple: &str) -> Option<String> {
+    Platform::ALL
+        .iter()
+        .find(|platform| platform.triple() == triple)
+        .map(|platform| format!("{:?}", platform.data_model()))
+}
+
+/// RecordLayout is the result of [`layout`].
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordLayout {
+    /// `sizeof` the record.
+    pub size: usize,
+    /// `_Alignof` the record.
+    pub align: usize,
+    offsets: Vec<usize>,
+}
+
+#[wasm_bindgen]
+impl RecordLayout {
+    /// offsets returns the offset of each field.
+    #[wasm_bindgen(getter)]
+    pub fn offsets(&self) -> Vec<usize> {
+        self.offsets.clone()
+    }
+}
+
+/// layout returns the layout of a struct, or of a union if `union` is
+/// true, with a field of each C type in `ctypes`.
+#[wasm_bindgen]
+pub fn layout(model: &str, ctypes: Vec<String>, union: bool) -> Result<RecordLayout, String> {
+    let mut record = match union {
+        true => Record::union("record"),
+        false => Record::new("record"),
+    };
+    for (i, ctype) in ctypes.iter().enumerate() {
+        let ty = type_expr(ctype)?
+            .field_type()
+            .ok_or_else(|| format!("{:?} is not a field type", ctype))?;
+        record = record.field(&format!("f{}", i), ty);
+    }
+    let layout = record
+        .layout(data_model(model)?)
+        .map_err(|err| err.to_string())?;
+    Ok(RecordLayout {
+        size: layout.size,
+        align: layout.align,
+        offsets: layout.fields.iter().map(|field| field.offset).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bindings() {
+        assert_eq!(models().len(), DataModel::ALL.len());
+        assert_eq!(size_of("LLP64", "long"), Ok(4));
+        assert_eq!(align_of("ILP32", "long long"), Ok(8));
+        assert_eq!(size_of("IP16", "long"), Ok(0));
+        assert!(size_of("LP64", "struct s").is_err());
+        assert_eq!(
+            from_triple("aarch64-unknown-linux-gnu").as_deref(),
+            Some("LP64")
+        );
+        let ctypes = vec!["char".to_string(), "void *[2]".to_string()];
+        let record = layout("LP64", ctypes, false).unwrap();
+        assert_eq!(
+            (record.size, record.align, record.offsets()),
+            (24, 8, vec![0, 8])
+        );
+        assert_eq!(
+            layout("LP64", vec!["void".to_string()], true),
+            Err("\"void\" is not a field type".to_string())
+        );
+    }
+}
@@ -0,0 +1,340 @@
+//! The year 2038 problem.
+//!
+//! A signed 32-bit `time_t` counts seconds since 1970 only up to
+//! 2038-01-19 03:14:07 UTC. Every 64-bit platform moved to a 64-bit
+//! `time_t`, but 32-bit Linux kept the old one by default: glibc 2.34 and
+//! later offer a second ABI with a 64-bit `time_t` when a program is built
+//! with `-D_TIME_BITS=64`, so the same image can mix both. On-disk and wire
+//! formats that store `time_t` fields overflow with it.
+
+use crate::layout::{FieldLayout, FieldType, Layout};
+use crate::{CType, DataModel, Platform};
+
+/// TIME_FIELDS are the names of the `time_t` members of the structs of the
+/// C library and POSIX, such as `tv_sec` of `struct timespec` and
+/// `st_mtime` of `struct stat`.
+pub const TIME_FIELDS: &[&str] = &[
+    "tv_sec",
+    "st_atime",
+    "st_mtime",
+    "st_ctime",
+    "actime",
+    "modtime",
+    "ut_time",
+    "msg_stime",
+    "msg_rtime",
+    "msg_ctime",
+    "sem_otime",
+    "sem_ctime",
+    "shm_atime",
+    "shm_dtime",
+    "shm_ctime",
+];
+
+/// TIME_TYPES are the types of the C library and POSIX that hold a
+/// `time_t`, and so change size with it.
+pub const TIME_TYPES: &[&str] = &[
+    "time_t",
+    "struct timespec",
+    "struct timeval",
+    "struct itimerspec",
+    "struct itimerval",
+    "struct utimbuf",
+    "struct stat",
+    "struct utmp",
+    "struct msqid_ds",
+    "struct semid_ds",
+    "struct shmid_ds",
+];
+
+/// Y2038_LIMIT is the last second a signed 32-bit `time_t` can hold,
+/// 2038-01-19 03:14:07 UTC.
+pub const Y2038_LIMIT: i128 = i32::MAX as i128;
+
+/// TimeAbi is how a C library sizes `time_t`.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// use data_models::y2038::TimeAbi;
+/// assert_eq!(Platform::LinuxX86_64.time_abi(), TimeAbi::Time64);
+/// assert_eq!(Platform::LinuxArmhf.time_abi(), TimeAbi::Dual);
+/// assert_eq!(Platform::Aix32.time_abi(), TimeAbi::Time32);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimeAbi {
+    /// `time_t` is 32 bits and overflows in 2038.
+    Time32,
+    /// `time_t` is 64 bits.
+    Time64,
+    /// `time_t` is 32 bits unless the program is built with
+    /// `-D_TIME_BITS=64`, as with glibc 2.34 and later on 32-bit Linux.
+    Dual,
+}
+
+impl TimeAbi {
+    /// time_size returns the size of `time_t` in bytes, with or without
+    /// `_TIME_BITS=64`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::y2038::TimeAbi;
+    /// assert_eq!(TimeAbi::Dual.time_size(false), 4);
+    /// assert_eq!(TimeAbi::Dual.time_size(true), 8);
+    /// assert_eq!(TimeAbi::Time32.time_size(true), 4);
+    /// ```
+    pub fn time_size(self, time_bits_64: bool) -> usize {
+        match (self, time_bits_64) {
+            (TimeAbi::Time64, _) | (TimeAbi::Dual, true) => 8,
+            _ => 4,
+        }
+    }
+}
+
+/// Y2038Risk is a type or field too narrow to hold times after 2038.
+///
+/// # Example
+/// ```
+/// use data_models::*;
+/// let risks = Platform::LinuxI686.y2038_risks();
+/// assert_eq!(risks[0].name, "time_t");
+/// assert!(risks[0].fixed_by_time_bits);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Y2038Risk {
+    /// The type name, or the dotted path of the field.
+    pub name: String,
+    /// The offset of the field in its record, or 0 for a type.
+    pub offset: usize,
+    /// The size of the time value in bytes.
+    pub size: usize,
+    /// The last second since 1970 the value can hold.
+    pub last_second: i128,
+    /// Whether building with `-D_TIME_BITS=64` widens the value. Fields of
+    /// a computed layout are reported as they are laid out, so this is
+    /// false for them.
+    pub fixed_by_time_bits: bool,
+}
+
+/// last_second returns the last second since 1970 an integer of `size`
+/// bytes can hold.
+fn last_second(size: usize, signed: bool) -> i128 {
+    let bits = (8 * size).min(127) - signed as usize;
+    (1 << bits) - 1
+}
+
+impl DataModel {
+    /// time_abi returns the size of `time_t` the model implies: a `long` on
+    /// Unix, which is 32 bits on ILP32 and smaller models, and 64 bits on
+    /// LLP64 Windows.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::y2038::TimeAbi;
+    /// assert_eq!(DataModel::ILP32.time_abi(), TimeAbi::Time32);
+    /// assert_eq!(DataModel::LLP64.time_abi(), TimeAbi::Time64);
+    /// ```
+    pub fn time_abi(self) -> TimeAbi {
+        match self.size(CType::Long).max(self.size(CType::Pointer)) {
+            0..=4 => TimeAbi::Time32,
+            _ => TimeAbi::Time64,
+        }
+    }
+}
+
+impl Platform {
+    /// time_abi returns how the C library of the platform sizes `time_t`.
+    /// 32-bit Windows and x32 Linux already use a 64-bit `time_t`, while
+    /// the other 32-bit Linux ports have both ABIs.
+    pub fn time_abi(self) -> TimeAbi {
+        use Platform::*;
+        match self {
+            LinuxI686 | LinuxArmhf | LinuxMips => TimeAbi::Dual,
+            Aix32 => TimeAbi::Time32,
+            _ => TimeAbi::Time64,
+        }
+    }
+
+    /// y2038_risks returns the [`TIME_TYPES`] that overflow in 2038 on the
+    /// platform by default, which is none of them on platforms with a
+    /// 64-bit `time_t`.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// assert!(Platform::LinuxAarch64.y2038_risks().is_empty());
+    /// let risks = Platform::Aix32.y2038_risks();
+    /// assert!(risks.iter().any(|risk| risk.name == "struct timespec"));
+    /// assert!(risks.iter().all(|risk| !risk.fixed_by_time_bits));
+    /// ```
+    pub fn y2038_risks(self) -> Vec<Y2038Risk> {
+        let abi = self.time_abi();
+        let size = abi.time_size(false);
+        if size >= 8 {
+            return Vec::new();
+        }
+        TIME_TYPES
+            .iter()
+            .map(|&name| Y2038Risk {
+                name: name.to_string(),
+                offset: 0,
+                size,
+                last_second: last_second(size, true),
+                fixed_by_time_bits: abi == TimeAbi::Dual,
+            })
+            .collect()
+    }
+}
+
+impl Layout {
+    /// y2038_risks returns the integer fields named in `time_fields`, at
+    /// any depth, that are too narrow to hold a signed 64-bit time. Pass
+    /// [`TIME_FIELDS`] for the members of the standard structs, along with
+    /// the names of the time fields of the record itself.
+    ///
+    /// # Example
+    /// ```
+    /// use data_models::*;
+    /// use data_models::layout::*;
+    /// use data_models::y2038::{TIME_FIELDS, Y2038_LIMIT};
+    /// let timespec = Record::new("timespec")
+    ///     .field("tv_sec", CType::Long)
+    ///     .field("tv_nsec", CType::Long);
+    /// let entry = Record::new("entry")
+    ///     .field("id", CType::Int)
+    ///     .field("created", timespec)
+    ///     .field("expires", CType::UInt);
+    /// let names = [TIME_FIELDS, &["expires"]].concat();
+    ///
+    /// let layout = entry.layout(Platform::LinuxArmhf).unwrap();
+    /// let risks = layout.y2038_risks(&names);
+    /// assert_eq!(risks.len(), 2);
+    /// assert_eq!((risks[0].name.as_str(), risks[0].offset), ("created.tv_sec", 4));
+    /// assert_eq!(risks[0].last_second, Y2038_LIMIT);
+    /// assert_eq!(risks[1].name, "expires");
+    ///
+    /// let layout = entry.layout(Platform::LinuxX86_64).unwrap();
+    /// assert_eq!(layout.y2038_risks(&names).len(), 1);
+    /// ```
+    pub fn y2038_risks(&self, time_fields: &[&str]) -> Vec<Y2038Risk> {
+        let mut risks = Vec::new();
+        self.find_y2038_risks(time_fields, 0, "", &mut risks);
+        risks
+    }
+
+    /// find_y2038_risks adds the risks of the record at `base` to `risks`,
+    /// naming them under `prefix`.
+    fn find_y2038_risks(
+        &self,
+        time_fields: &[&str],
+        base: usize,
+        prefix: &str,
+        risks: &mut Vec<Y2038Risk>,
+    ) {
+        for field in self.bases.iter().chain(self.fields.iter()) {
+            let path = match (prefix, field.name.as_str()) {
+                (_, "") => prefix.to_string(),
+                ("", name) => name.to_string(),
+                (prefix, name) => format!("{}.{}", prefix, name),
+            };
+            field_risks(field, time_fields, base + field.offset, &path, risks);
+        }
+    }
+}
+
+/// field_risks adds the risks of `field`, placed at `offset`, to `risks`.
+fn field_risks(
+    field: &FieldLayout,
+    time_fields: &[&str],
+    offset: usize,
+    path: &str,
+    risks: &mut Vec<Y2038Risk>,
+) {
+    match &field.ty {
+        FieldType::Scalar(ty) if ty.is_integer() => {
+            let signed = ty.is_signed();
+            let last = last_second(field.size, signed);
+            if time_fields.contains(&field.name.as_str()) && last < i64::MAX as i128 {
+                risks.push(Y2038Risk {
+                    name: path.to_string(),
+                    offset,
+                    size: field.size,
+                    last_second: last,
+                    fixed_by_time_bits: false,
+                });
+            }
+        }
+        FieldType::Array(_, n) => {
+            if let Some(nested) = &field.nested {
+                let stride = field.size / (*n).max(1);
+                for i in 0..*n {
+                    let path = format!("{}[{}]", path, i);
+                    nested.find_y2038_risks(time_fields, offset + i * stride, &path, risks);
+                }
+            }
+        }
+        _ => {
+            if let Some(nested) = &field.nested {
+                nested.find_y2038_risks(time_fields, offset, path, risks);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Record;
+
+    #[test]
+    fn test_time_abi() {
+        assert_eq!(DataModel::IP16.time_abi(), TimeAbi::Time32);
+        assert_eq!(DataModel::LP64.time_abi(), TimeAbi::Time64);
+        assert_eq!(Platform::LinuxX32.time_abi(), TimeAbi::Time64);
+        assert_eq!(Platform::Windows32.time_abi(), TimeAbi::Time64);
+        assert_eq!(Platform::LinuxMips.time_abi(), TimeAbi::Dual);
+        for &platform in Platform::ALL {
+            let risks = platform.y2038_risks();
+            match platform.time_abi() {
+                TimeAbi::Time64 => assert!(risks.is_empty()),
+                _ => assert_eq!(risks.len(), TIME_TYPES.len()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_layout_risks() {
+        let timeval = Record::new("timeval")
+            .field("tv_sec", CType::Long)
+            .field("tv_usec", CType::Long);
+        let stat = Record::new("stat")
+            .field("st_size", CType::Long)
+            .field("st_mtime", CType::Long)
+            .field("times", crate::layout::FieldType::array(timeval, 2));
+        let layout = stat.layout(DataModel::ILP32).unwrap();
+        let risks = layout.y2038_risks(TIME_FIELDS);
+        let found: Vec<_> = risks
+            .iter()
+            .map(|risk| (risk.name.as_str(), risk.offset))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("st_mtime", 4),
+                ("times[0].tv_sec", 8),
+                ("times[1].tv_sec", 16)
+            ]
+        );
+        let layout = stat.layout(DataModel::LP64).unwrap();
+        assert!(layout.y2038_risks(TIME_FIELDS).is_empty());
+
+        let record = Record::new("r").field("tv_sec", CType::UInt);
+        let risks = record
+            .layout(DataModel::LP64)
+            .unwrap()
+            .y2038_risks(&["tv_sec"]);
+        assert_eq!(risks[0].last_second, u32::MAX as i128);
+    }
+}